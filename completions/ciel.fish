@@ -1,149 +1,583 @@
-function __ciel_find_ciel_workdir
-    set cur (readlink -f $PWD)
-    while test "$cur" != "/"
-        if test -d "$cur/.ciel"
-            echo "$cur"
-            break
-        end
-        set cur (dirname $cur)
-    end
-end
-
-function __ciel_list_instances
-    set workdir (__ciel_find_ciel_workdir)
-    if ! test -d "$workdir/".ciel/container/instances
-        return
-    end
-    find "$workdir/".ciel/container/instances -maxdepth 1 -mindepth 1 -type d -printf '%f\tInstance\n'
-end
-
-function __ciel_list_packages
-    set workdir (__ciel_find_ciel_workdir)
-    if ! test -d "$workdir/"TREE
-        return
-    end
-    find "$workdir/TREE/groups/" -maxdepth 1 -mindepth 1 -type f -printf 'groups/%f\n'
-    if string match -q -- "*/*" "$current"
-        return
-    end
-    find "$workdir/TREE" -maxdepth 2 -mindepth 2 -type d -not -path "TREE/.git" -printf '%f\n'
-end
-
-function __ciel_list_plugins
-    set ciel_path (readlink -f (command -v ciel))
-    set ciel_plugin_dir (dirname $ciel_path)"/../libexec/ciel-plugin"
-    find "$ciel_plugin_dir" -maxdepth 1 -mindepth 1 -type f -printf '%f\t-Ciel plugin-\n' | cut -d'-' -f2-
-end
-
 complete -c ciel -n "__fish_use_subcommand" -s C -d 'Set the CIEL! working directory' -r
-complete -c ciel -n "__fish_use_subcommand" -s b -l batch -d 'Batch mode, no input required'
-complete -c ciel -n "__fish_use_subcommand" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_use_subcommand" -s V -l version -d 'Print version information'
+complete -c ciel -n "__fish_use_subcommand" -l limit-rate -d 'Limit the download rate (bytes per second, e.g. 2M)' -r
+complete -c ciel -n "__fish_use_subcommand" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_use_subcommand" -l offline -d 'Forbid any network access (cached OS tarballs are still used)'
+complete -c ciel -n "__fish_use_subcommand" -l insecure -d 'Do not verify the signatures of the release manifest and the OS tarballs'
+complete -c ciel -n "__fish_use_subcommand" -l no-proxy -d 'Ignore the proxy configuration and the proxy environment variables'
+complete -c ciel -n "__fish_use_subcommand" -l no-space-check -d 'Do not check the free disk space before loading the OS, updating and building'
+complete -c ciel -n "__fish_use_subcommand" -s b -l batch -d 'Batch mode, never prompt (fail if any input is required)'
+complete -c ciel -n "__fish_use_subcommand" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_use_subcommand" -s V -l version -d 'Print version'
 complete -c ciel -n "__fish_use_subcommand" -f -a "version" -d 'Display the version of CIEL!'
+complete -c ciel -n "__fish_use_subcommand" -f -a "archs" -d 'List the supported architectures and their tarball variants'
 complete -c ciel -n "__fish_use_subcommand" -f -a "init" -d 'Initialize the work directory'
+complete -c ciel -n "__fish_use_subcommand" -f -a "load-os" -d 'Unpack OS tarball or fetch the latest BuildKit from the repository'
+complete -c ciel -n "__fish_use_subcommand" -f -a "verify-dist" -d 'Verify the base OS against the file lists and checksums of the installed packages'
 complete -c ciel -n "__fish_use_subcommand" -f -a "update-os" -d 'Update the OS in the container'
+complete -c ciel -n "__fish_use_subcommand" -f -a "pack-os" -d 'Pack the base OS into a compressed read-only image (run `ciel unpack-os` before updating it)'
+complete -c ciel -n "__fish_use_subcommand" -f -a "unpack-os" -d 'Unpack the base OS image back into a directory'
 complete -c ciel -n "__fish_use_subcommand" -f -a "load-tree" -d 'Clone package tree from the link provided or AOSC OS ABBS main repository'
 complete -c ciel -n "__fish_use_subcommand" -f -a "update-tree" -d 'Update the existing ABBS tree (fetch only) and optionally switch to a different branch'
+complete -c ciel -n "__fish_use_subcommand" -f -a "tree" -d 'Manage the ABBS tree checkout (TREE)'
+complete -c ciel -n "__fish_use_subcommand" -f -a "topic" -d 'Work on a topic: switch TREE to its branch, install the packages from its repository and build into OUTPUT-<topic>'
 complete -c ciel -n "__fish_use_subcommand" -f -a "new" -d 'Create a new CIEL workspace'
 complete -c ciel -n "__fish_use_subcommand" -f -a "list" -d 'List all the instances under the specified working directory'
 complete -c ciel -n "__fish_use_subcommand" -f -a "add" -d 'Add a new instance'
+complete -c ciel -n "__fish_use_subcommand" -f -a "template" -d 'Instance templates (stored in .ciel/templates)'
+complete -c ciel -n "__fish_use_subcommand" -f -a "layer" -d 'Shared read-only layers between the base OS and the instances (stored in .ciel/container/layers)'
+complete -c ciel -n "__fish_use_subcommand" -f -a "port" -d 'Forward ports of the host to the services in the instances (the instances get their own network)'
+complete -c ciel -n "__fish_use_subcommand" -f -a "remote" -d 'Remote machines to build on over SSH (`ciel build --on`)'
+complete -c ciel -n "__fish_use_subcommand" -f -a "farm" -d 'Build the packages on the remote machines, for each of their architectures'
+complete -c ciel -n "__fish_use_subcommand" -f -a "snapshot" -d 'Named snapshots of the instance state (stored in .ciel/data/snapshots)'
+complete -c ciel -n "__fish_use_subcommand" -f -a "clone" -d 'Duplicate an instance into a new instance'
+complete -c ciel -n "__fish_use_subcommand" -f -a "export" -d 'Export an instance as a portable bundle'
+complete -c ciel -n "__fish_use_subcommand" -f -a "oci" -d 'OCI images of the instances, for Docker/Podman-based CI'
+complete -c ciel -n "__fish_use_subcommand" -f -a "import" -d 'Import an instance from a bundle'
 complete -c ciel -n "__fish_use_subcommand" -f -a "del" -d 'Remove an instance'
 complete -c ciel -n "__fish_use_subcommand" -f -a "shell" -d 'Start an interactive shell'
+complete -c ciel -n "__fish_use_subcommand" -f -a "run-ephemeral" -d 'Run the command in a temporary instance created from the base OS, removed afterwards'
 complete -c ciel -n "__fish_use_subcommand" -f -a "run" -d 'Lower-level version of \'shell\', without login environment, without sourcing ~/.bash_profile'
 complete -c ciel -n "__fish_use_subcommand" -f -a "config" -d 'Configure system and toolchain for building interactively'
 complete -c ciel -n "__fish_use_subcommand" -f -a "commit" -d 'Commit changes onto the shared underlying OS'
+complete -c ciel -n "__fish_use_subcommand" -f -a "diff" -d 'Show the files added, modified and deleted in the instance (relative to the base OS)'
+complete -c ciel -n "__fish_use_subcommand" -f -a "recover" -d 'Recover the workspace after a crash (stale mounts, dead containers, etc.)'
 complete -c ciel -n "__fish_use_subcommand" -f -a "doctor" -d 'Diagnose problems (hopefully)'
 complete -c ciel -n "__fish_use_subcommand" -f -a "build" -d 'Build the packages using the specified instance'
+complete -c ciel -n "__fish_use_subcommand" -f -a "fetch" -d 'Download the sources of the packages into the shared source cache'
+complete -c ciel -n "__fish_use_subcommand" -f -a "ci" -d 'Build the packages non-interactively for CI runners (GitHub Actions, GitLab CI)'
+complete -c ciel -n "__fish_use_subcommand" -f -a "du" -d 'Show the disk usage of the base OS, the instances, the output and the cache directories'
+complete -c ciel -n "__fish_use_subcommand" -f -a "gc" -d 'Remove the data left behind in the workspace: incomplete imports, data of the removed instances, dangling machines, partial downloads, old logs and the unused package cache lock'
+complete -c ciel -n "__fish_use_subcommand" -f -a "list-builds" -d 'List the recent builds with their duration, CPU time and peak memory usage'
+complete -c ciel -n "__fish_use_subcommand" -f -a "status" -d 'Compare the package versions in TREE against the output repository and the remote repository'
+complete -c ciel -n "__fish_use_subcommand" -f -a "search" -d 'Search the packages in TREE and show whether they are built in the output repository'
+complete -c ciel -n "__fish_use_subcommand" -f -a "log" -d 'Show the most recent build log of the specified package'
+complete -c ciel -n "__fish_use_subcommand" -f -a "logs" -d 'Show the output of the build in progress (or the last build) in the specified instance'
 complete -c ciel -n "__fish_use_subcommand" -f -a "rollback" -d 'Rollback all or specified instance'
 complete -c ciel -n "__fish_use_subcommand" -f -a "down" -d 'Shutdown and unmount all or one instance'
 complete -c ciel -n "__fish_use_subcommand" -f -a "stop" -d 'Shuts down an instance'
+complete -c ciel -n "__fish_use_subcommand" -f -a "checkpoint" -d 'Save the state of a running instance and stop it (requires CRIU)'
+complete -c ciel -n "__fish_use_subcommand" -f -a "restore" -d 'Resume an instance from its checkpoint'
 complete -c ciel -n "__fish_use_subcommand" -f -a "mount" -d 'Mount all or specified instance'
 complete -c ciel -n "__fish_use_subcommand" -f -a "farewell" -d 'Remove everything related to CIEL!'
 complete -c ciel -n "__fish_use_subcommand" -f -a "repo" -d 'Local repository operations'
+complete -c ciel -n "__fish_use_subcommand" -f -a "cache" -d 'Shared compiler cache (ccache) and package cache operations'
+complete -c ciel -n "__fish_use_subcommand" -f -a "mirror" -d 'AOSC OS mirror selection'
+complete -c ciel -n "__fish_use_subcommand" -f -a "daemon" -d 'Serve the REST API for controlling Ciel remotely'
+complete -c ciel -n "__fish_use_subcommand" -f -a "tui" -d 'Show the interactive dashboard of the instances and builds'
+complete -c ciel -n "__fish_use_subcommand" -f -a "completion" -d 'Print the shell completion script'
+complete -c ciel -n "__fish_use_subcommand" -f -a "_complete" -d 'Print the completion candidates (used by the completion scripts)'
 complete -c ciel -n "__fish_use_subcommand" -f -a "clean" -d 'Clean all the output directories and source cache directories'
 complete -c ciel -n "__fish_use_subcommand" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
-complete -c ciel -n "__fish_seen_subcommand_from version" -s h -l help -d 'Print help information'
+complete -c ciel -n "__fish_seen_subcommand_from version" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from version" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from archs" -l refresh -d 'Fetch the list from the release manifest first'
+complete -c ciel -n "__fish_seen_subcommand_from archs" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from archs" -s h -l help -d 'Print help'
 complete -c ciel -n "__fish_seen_subcommand_from init" -l upgrade -d 'Upgrade Ciel workspace from an older version'
-complete -c ciel -n "__fish_seen_subcommand_from init" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from load-os" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from update-os" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from load-tree" -s h -l help -d 'Print help information'
+complete -c ciel -n "__fish_seen_subcommand_from init" -s n -l dry-run -d 'Only print the changes planned for the upgrade'
+complete -c ciel -n "__fish_seen_subcommand_from init" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from init" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from load-os" -s a -l arch -d 'Specify the target architecture for fetching OS tarball' -r
+complete -c ciel -n "__fish_seen_subcommand_from load-os" -s s -l source -d 'Download the OS tarball using HTTP, metalink or BitTorrent (requires aria2c, falls back to HTTP)' -r -f -a "{http	'',metalink	'',torrent	''}"
+complete -c ciel -n "__fish_seen_subcommand_from load-os" -l stream -d 'Extract the tarball while downloading it (no disk space needed for the tarball, not cached)'
+complete -c ciel -n "__fish_seen_subcommand_from load-os" -l verify -d 'Verify the base OS against the package manifests after loading'
+complete -c ciel -n "__fish_seen_subcommand_from load-os" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from load-os" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from verify-dist" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from verify-dist" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from update-os" -s i -d 'Update the specified instance only (using its configuration overrides)' -r
+complete -c ciel -n "__fish_seen_subcommand_from update-os" -l force-use-apt -d 'Use apt to update-os'
+complete -c ciel -n "__fish_seen_subcommand_from update-os" -l dist-only -d 'Update the base OS in place without creating and booting an instance (quicker)'
+complete -c ciel -n "__fish_seen_subcommand_from update-os" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from update-os" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from pack-os" -s f -l format -d 'Format of the image' -r -f -a "{erofs	'',squashfs	''}"
+complete -c ciel -n "__fish_seen_subcommand_from pack-os" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from pack-os" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from unpack-os" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from unpack-os" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from load-tree" -s b -l branch -d 'Branch to switch to (defaults to the branch in the workspace configuration)' -r
+complete -c ciel -n "__fish_seen_subcommand_from load-tree" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from load-tree" -s h -l help -d 'Print help'
 complete -c ciel -n "__fish_seen_subcommand_from update-tree" -s r -l rebase -d 'Rebase the specified branch from the updated upstream' -r
-complete -c ciel -n "__fish_seen_subcommand_from update-tree" -s h -l help -d 'Print help information'
+complete -c ciel -n "__fish_seen_subcommand_from update-tree" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from update-tree" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch; and not __fish_seen_subcommand_from help" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch; and not __fish_seen_subcommand_from help" -f -a "clone" -d 'Clone the ABBS tree into the workspace'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch; and not __fish_seen_subcommand_from help" -f -a "update" -d 'Fetch the remote and fast-forward the current branch of the tree'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch; and not __fish_seen_subcommand_from help" -f -a "switch-branch" -d 'Fetch the remote and switch the tree to another (topic) branch, keeping the local changes'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and __fish_seen_subcommand_from clone" -s b -l branch -d 'Branch to switch to (defaults to the branch in the workspace configuration)' -r
+complete -c ciel -n "__fish_seen_subcommand_from tree; and __fish_seen_subcommand_from clone" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and __fish_seen_subcommand_from clone" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and __fish_seen_subcommand_from update" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and __fish_seen_subcommand_from update" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and __fish_seen_subcommand_from switch-branch" -s r -l rebase -d 'Rebase the branch onto the specified upstream (e.g. origin/stable)' -r
+complete -c ciel -n "__fish_seen_subcommand_from tree; and __fish_seen_subcommand_from switch-branch" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and __fish_seen_subcommand_from switch-branch" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch; and not __fish_seen_subcommand_from help" -f -a "clone" -d 'Clone the ABBS tree into the workspace'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch; and not __fish_seen_subcommand_from help" -f -a "update" -d 'Fetch the remote and fast-forward the current branch of the tree'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch; and not __fish_seen_subcommand_from help" -f -a "switch-branch" -d 'Fetch the remote and switch the tree to another (topic) branch, keeping the local changes'
+complete -c ciel -n "__fish_seen_subcommand_from tree; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from topic" -l off -d 'Stop working on the topic, switching TREE back to the default branch'
+complete -c ciel -n "__fish_seen_subcommand_from topic" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from topic" -s h -l help -d 'Print help'
 complete -c ciel -n "__fish_seen_subcommand_from new" -l from-tarball -d 'Create a new workspace from the specified tarball' -r
-complete -c ciel -n "__fish_seen_subcommand_from new" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from list" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from add" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from del" -s h -l help -d 'Print help information'
+complete -c ciel -n "__fish_seen_subcommand_from new" -s a -l arch -d 'Create a new workspace for specified architecture' -r
+complete -c ciel -n "__fish_seen_subcommand_from new" -s i -l instance -d 'Create the named instance after initialization (instead of asking)' -r
+complete -c ciel -n "__fish_seen_subcommand_from new" -l rootless -d 'Create a workspace that can be used without root privileges (requires fuse-overlayfs and podman)'
+complete -c ciel -n "__fish_seen_subcommand_from new" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from new" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from list" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from list" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from add" -s t -l template -d 'Create the instance using the template (see `ciel template`)' -r
+complete -c ciel -n "__fish_seen_subcommand_from add" -l tmpfs-size -d 'Size limit of the tmpfs (e.g. 16G or 50%, defaults to half of the memory)' -r
+complete -c ciel -n "__fish_seen_subcommand_from add" -l kernel -d 'Kernel image to boot (defaults to the newest kernel in /boot of the instance)' -r
+complete -c ciel -n "__fish_seen_subcommand_from add" -l tmpfs -d 'Keep the changes on tmpfs, discarding them when the instance is stopped (overlay backend only)'
+complete -c ciel -n "__fish_seen_subcommand_from add" -l vm -d 'Run the instance in a virtual machine (systemd-vmspawn), for the builds depending on the kernel'
+complete -c ciel -n "__fish_seen_subcommand_from add" -l provision -d 'Install the build essentials (`provision.packages`) and apply the maintainer identity, the default with `provision.auto`'
+complete -c ciel -n "__fish_seen_subcommand_from add" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from add" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from template; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from template; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from template; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "save" -d 'Save the instance configuration and the provisioning steps as a template'
+complete -c ciel -n "__fish_seen_subcommand_from template; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the templates'
+complete -c ciel -n "__fish_seen_subcommand_from template; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "remove" -d 'Remove the template'
+complete -c ciel -n "__fish_seen_subcommand_from template; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from save" -l install -d 'Extra packages to install into the new instances' -r
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from save" -l user -d 'Users to create in the new instances' -r
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from save" -l script -d 'Shell script to run in the new instances after the packages are installed' -r
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from save" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from save" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from list" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from list" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from remove" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from remove" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "save" -d 'Save the instance configuration and the provisioning steps as a template'
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the templates'
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "remove" -d 'Remove the template'
+complete -c ciel -n "__fish_seen_subcommand_from template; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "create" -d 'Create a shared layer (empty, or from the changes of an instance)'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "attach" -d 'Stack the shared layer on the base OS of the instance'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "detach" -d 'Remove the shared layer from the instance'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the shared layers'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "remove" -d 'Remove the shared layer (must not be attached to any instance)'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from create" -s i -l instance -d 'Move the changes of this instance into the layer (the layer is attached to the instance)' -r
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from create" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from create" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from attach" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from attach" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from detach" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from detach" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from list" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from list" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from remove" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from remove" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "create" -d 'Create a shared layer (empty, or from the changes of an instance)'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "attach" -d 'Stack the shared layer on the base OS of the instance'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "detach" -d 'Remove the shared layer from the instance'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the shared layers'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "remove" -d 'Remove the shared layer (must not be attached to any instance)'
+complete -c ciel -n "__fish_seen_subcommand_from layer; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from port; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from port; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from port; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "add" -d 'Forward the port of the host to the instance'
+complete -c ciel -n "__fish_seen_subcommand_from port; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "remove" -d 'Stop forwarding the port of the host to the instance'
+complete -c ciel -n "__fish_seen_subcommand_from port; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the forwarded ports'
+complete -c ciel -n "__fish_seen_subcommand_from port; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from port; and __fish_seen_subcommand_from add" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from port; and __fish_seen_subcommand_from add" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from port; and __fish_seen_subcommand_from remove" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from port; and __fish_seen_subcommand_from remove" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from port; and __fish_seen_subcommand_from list" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from port; and __fish_seen_subcommand_from list" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from port; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "add" -d 'Forward the port of the host to the instance'
+complete -c ciel -n "__fish_seen_subcommand_from port; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "remove" -d 'Stop forwarding the port of the host to the instance'
+complete -c ciel -n "__fish_seen_subcommand_from port; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the forwarded ports'
+complete -c ciel -n "__fish_seen_subcommand_from port; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "add" -d 'Add a remote machine running ciel'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "remove" -d 'Remove the remote machine'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the remote machines'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from add" -s p -l port -d 'SSH port' -r
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from add" -l path -d 'Workspace on the remote machine, relative to the home directory unless absolute (default: ciel)' -r
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from add" -s i -d 'Instance to build in on the remote machine' -r
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from add" -l arch -d 'Architecture of the base OS on the remote machine (for the build farm)' -r
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from add" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from add" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from remove" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from remove" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from list" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from list" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "add" -d 'Add a remote machine running ciel'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "remove" -d 'Remove the remote machine'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the remote machines'
+complete -c ciel -n "__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from farm" -s b -l builders -d 'Remote machines to build on (default: all)' -r
+complete -c ciel -n "__fish_seen_subcommand_from farm" -l with-deps -d 'Also build the in-tree dependencies of the packages that are not in the list'
+complete -c ciel -n "__fish_seen_subcommand_from farm" -l revdeps -d 'Also rebuild the packages in the tree depending on the packages'
+complete -c ciel -n "__fish_seen_subcommand_from farm" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from farm" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "create" -d 'Save the current state of the instance as a snapshot'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "restore" -d 'Restore the instance to the snapshot (the current state is discarded)'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the snapshots'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "remove" -d 'Remove the snapshot'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from create" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from create" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from restore" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from restore" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from list" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from list" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from remove" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from remove" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "create" -d 'Save the current state of the instance as a snapshot'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "restore" -d 'Restore the instance to the snapshot (the current state is discarded)'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the snapshots'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "remove" -d 'Remove the snapshot'
+complete -c ciel -n "__fish_seen_subcommand_from snapshot; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from clone" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from clone" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from export" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from export" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from help" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from help" -f -a "export" -d 'Export the instance as an OCI image (the base OS and the changes of the instance as the layers)'
+complete -c ciel -n "__fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from oci; and __fish_seen_subcommand_from export" -s o -l output -d 'Directory of the OCI image layout (default: oci-<instance>)' -r
+complete -c ciel -n "__fish_seen_subcommand_from oci; and __fish_seen_subcommand_from export" -s t -l tag -d 'Tag of the image in the layout' -r
+complete -c ciel -n "__fish_seen_subcommand_from oci; and __fish_seen_subcommand_from export" -l push -d 'Push the image with skopeo, e.g. docker://registry.example.org/aosc/buildenv:latest' -r
+complete -c ciel -n "__fish_seen_subcommand_from oci; and __fish_seen_subcommand_from export" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from oci; and __fish_seen_subcommand_from export" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from oci; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from help" -f -a "export" -d 'Export the instance as an OCI image (the base OS and the changes of the instance as the layers)'
+complete -c ciel -n "__fish_seen_subcommand_from oci; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from import" -s n -l name -d 'Name of the imported instance (defaults to the original name)' -r
+complete -c ciel -n "__fish_seen_subcommand_from import" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from import" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from del" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from del" -s h -l help -d 'Print help'
 complete -c ciel -n "__fish_seen_subcommand_from shell" -s i -d 'Instance to be used' -r
-complete -c ciel -n "__fish_seen_subcommand_from shell" -s h -l help -d 'Print help information'
+complete -c ciel -n "__fish_seen_subcommand_from shell" -s w -l workdir -d 'Working directory in the container (defaults to the home directory with --user)' -r
+complete -c ciel -n "__fish_seen_subcommand_from shell" -s u -l user -d 'Run as the user instead of root (created if missing, using the UID of the same user on the host)' -r
+complete -c ciel -n "__fish_seen_subcommand_from shell" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from shell" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from run-ephemeral" -s o -l output -d 'Write the output of the command to the file' -r
+complete -c ciel -n "__fish_seen_subcommand_from run-ephemeral" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from run-ephemeral" -s h -l help -d 'Print help'
 complete -c ciel -n "__fish_seen_subcommand_from run" -s i -d 'Instance to run command in' -r
-complete -c ciel -n "__fish_seen_subcommand_from run" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from config" -s g -d 'Configure base system instead of an instance'
-complete -c ciel -n "__fish_seen_subcommand_from config" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from commit" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from doctor" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from build" -s c -l resume -d 'Continue from a Ciel checkpoint' -r
+complete -c ciel -n "__fish_seen_subcommand_from run" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from run" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from edit; and not __fish_seen_subcommand_from help" -s i -d 'Instance to be configured' -r
+complete -c ciel -n "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from edit; and not __fish_seen_subcommand_from help" -s g -d 'Configure base system instead of an instance'
+complete -c ciel -n "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from edit; and not __fish_seen_subcommand_from help" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from edit; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from edit; and not __fish_seen_subcommand_from help" -f -a "edit" -d 'Edit the workspace configuration file (.ciel/config.toml)'
+complete -c ciel -n "__fish_seen_subcommand_from config; and not __fish_seen_subcommand_from edit; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from config; and __fish_seen_subcommand_from edit" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from config; and __fish_seen_subcommand_from edit" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from config; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from edit; and not __fish_seen_subcommand_from help" -f -a "edit" -d 'Edit the workspace configuration file (.ciel/config.toml)'
+complete -c ciel -n "__fish_seen_subcommand_from config; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from edit; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from commit" -s i -d 'Instance to be committed' -r
+complete -c ciel -n "__fish_seen_subcommand_from commit" -l path -d 'Only commit the changes under these paths (in the instance)' -r
+complete -c ciel -n "__fish_seen_subcommand_from commit" -s p -l interactive -d 'Choose the changes to commit one by one (similar to `git add -p`)'
+complete -c ciel -n "__fish_seen_subcommand_from commit" -s l -l list -d 'List the changes without committing them'
+complete -c ciel -n "__fish_seen_subcommand_from commit" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from commit" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from diff" -s i -r
+complete -c ciel -n "__fish_seen_subcommand_from diff" -s c -l content -d 'Also show the content diffs of the text files'
+complete -c ciel -n "__fish_seen_subcommand_from diff" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from diff" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from recover" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from recover" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from doctor" -l fix -d 'Automatically fix the safe issues'
+complete -c ciel -n "__fish_seen_subcommand_from doctor" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from doctor" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from build" -s i -d 'Instance to build in' -r
+complete -c ciel -n "__fish_seen_subcommand_from build" -l cross -d 'Cross-compile the packages for the specified architecture using the AOSC OS cross toolchains' -r
+complete -c ciel -n "__fish_seen_subcommand_from build" -l branch -d 'Put the packages into the named output repository (OUTPUT-<name>) and build against it' -r
+complete -c ciel -n "__fish_seen_subcommand_from build" -l cpus -d 'Limit the CPU time of the instance to the specified number of CPUs' -r
+complete -c ciel -n "__fish_seen_subcommand_from build" -l memory -d 'Limit the memory usage of the instance (e.g. 16G)' -r
+complete -c ciel -n "__fish_seen_subcommand_from build" -l retries -d 'Retry the builds failing because of network errors (e.g. when fetching the sources) up to the specified number of times' -r
+complete -c ciel -n "__fish_seen_subcommand_from build" -s c -l resume -d 'Continue from a Ciel checkpoint, or from the last failure if no checkpoint is specified' -r
 complete -c ciel -n "__fish_seen_subcommand_from build" -l stage-select -d 'Select the starting point for a build' -r
+complete -c ciel -n "__fish_seen_subcommand_from build" -s j -l parallel -d 'Build the packages concurrently using the specified number of instances' -r
+complete -c ciel -n "__fish_seen_subcommand_from build" -l changed-since -d 'Build the packages whose spec or defines changed in TREE since the Git revision' -r
+complete -c ciel -n "__fish_seen_subcommand_from build" -l verify-reproducible -d 'Build the package twice (the second time in a fresh instance) and compare the produced packages' -r
+complete -c ciel -n "__fish_seen_subcommand_from build" -l archs -d 'Build the packages for each of the architectures (comma-separated), in a workspace of their own' -r
+complete -c ciel -n "__fish_seen_subcommand_from build" -l on -d 'Build on the remote machine (see `ciel remote`) and pull back the output and the logs' -r
 complete -c ciel -n "__fish_seen_subcommand_from build" -s g -d 'Fetch source packages only'
 complete -c ciel -n "__fish_seen_subcommand_from build" -s x -l offline -d 'Disable network in the container during the build'
+complete -c ciel -n "__fish_seen_subcommand_from build" -l isolate-network -d 'Build without network access (except the local repository) to verify that nothing is downloaded during the build'
+complete -c ciel -n "__fish_seen_subcommand_from build" -l with-deps -d 'Also build the in-tree dependencies of the packages that are not in the list'
+complete -c ciel -n "__fish_seen_subcommand_from build" -l revdeps -d 'Also rebuild the packages in the tree depending on the packages (e.g. after ABI-breaking updates)'
+complete -c ciel -n "__fish_seen_subcommand_from build" -l html-report -d 'Also generate an HTML build report in the output directory'
 complete -c ciel -n "__fish_seen_subcommand_from build" -s 2 -l stage2 -d 'Use stage 2 mode instead of the regular build mode'
-complete -c ciel -n "__fish_seen_subcommand_from build" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from rollback" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from down" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from stop" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from mount" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from farewell" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from help" -f -a "refresh" -d 'Refresh the repository'
-complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from help" -f -a "init" -d 'Initialize the repository'
-complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from help" -f -a "deinit" -d 'Uninitialize the repository'
-complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
-complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from refresh" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from init" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from deinit" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from help" -f -a "refresh" -d 'Refresh the repository'
-complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from help" -f -a "init" -d 'Initialize the repository'
-complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from help" -f -a "deinit" -d 'Uninitialize the repository'
-complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
-complete -c ciel -n "__fish_seen_subcommand_from clean" -s h -l help -d 'Print help information'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "version" -d 'Display the version of CIEL!'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "init" -d 'Initialize the work directory'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "load-os" -d 'Unpack OS tarball or fetch the latest BuildKit from the repository'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "update-os" -d 'Update the OS in the container'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "load-tree" -d 'Clone package tree from the link provided or AOSC OS ABBS main repository'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "update-tree" -d 'Update the existing ABBS tree (fetch only) and optionally switch to a different branch'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "new" -d 'Create a new CIEL workspace'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List all the instances under the specified working directory'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "add" -d 'Add a new instance'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "del" -d 'Remove an instance'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "shell" -d 'Start an interactive shell'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "run" -d 'Lower-level version of \'shell\', without login environment, without sourcing ~/.bash_profile'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "config" -d 'Configure system and toolchain for building interactively'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "commit" -d 'Commit changes onto the shared underlying OS'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "doctor" -d 'Diagnose problems (hopefully)'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "build" -d 'Build the packages using the specified instance'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "rollback" -d 'Rollback all or specified instance'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "down" -d 'Shutdown and unmount all or one instance'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "stop" -d 'Shuts down an instance'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "mount" -d 'Mount all or specified instance'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "farewell" -d 'Remove everything related to CIEL!'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "repo" -d 'Local repository operations'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "clean" -d 'Clean all the output directories and source cache directories'
-complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
-complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit" -f -a "refresh" -d 'Refresh the repository'
-complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit" -f -a "init" -d 'Initialize the repository'
-complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit" -f -a "deinit" -d 'Uninitialize the repository'
-# Enhanced completions
-complete -xc ciel -n "__fish_seen_subcommand_from build" -a "(__ciel_list_packages)"
-complete -xc ciel -n "__fish_seen_subcommand_from build" -s i -d 'Instance to build in' -a "(__ciel_list_instances)"
-complete -xc ciel -n "__fish_seen_subcommand_from run" -s i -d 'Instance to run command in' -a "(__ciel_list_instances)"
-complete -xc ciel -n "__fish_seen_subcommand_from config" -s i -d 'Instance to be configured' -a "(__ciel_list_instances)"
-complete -xc ciel -n "__fish_seen_subcommand_from commit" -s i -d 'Instance to be committed' -a "(__ciel_list_instances)"
-complete -xc ciel -n "__fish_seen_subcommand_from build" -s i -d 'Instance to build in' -a "(__ciel_list_instances)"
-complete -xc ciel -n "__fish_seen_subcommand_from rollback" -s i -d 'Instance to be rolled back' -a "(__ciel_list_instances)"
-complete -xc ciel -n "__fish_seen_subcommand_from down" -s i -d 'Instance to be un-mounted' -a "(__ciel_list_instances)"
-complete -xc ciel -n "__fish_seen_subcommand_from stop" -s i -d 'Instance to be stopped' -a "(__ciel_list_instances)"
-complete -xc ciel -n "__fish_seen_subcommand_from mount" -s i -d 'Instance to be mounted' -a "(__ciel_list_instances)"
-complete -xc ciel -n "__fish_seen_subcommand_from load-os" -a "(__fish_complete_suffix tar.xz)"
-complete -c ciel -n "__fish_use_subcommand" -f -a "(__ciel_list_plugins)"
+complete -c ciel -n "__fish_seen_subcommand_from build" -l strict -d 'Fail the builds when the QA checks find problems in the produced packages'
+complete -c ciel -n "__fish_seen_subcommand_from build" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from build" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from fetch" -s i -d 'Instance to fetch the sources other than tarballs (e.g. Git repositories) in' -r
+complete -c ciel -n "__fish_seen_subcommand_from fetch" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from fetch" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from ci" -s i -d 'Instance to build in (created if missing)' -r
+complete -c ciel -n "__fish_seen_subcommand_from ci" -l format -d 'Log format (detected from the CI environment by default)' -r -f -a "{auto	'',github	'',gitlab	'',plain	''}"
+complete -c ciel -n "__fish_seen_subcommand_from ci" -l cross -d 'Cross-compile the packages for the specified architecture' -r
+complete -c ciel -n "__fish_seen_subcommand_from ci" -l retries -d 'Retry the builds failing because of network errors up to the specified number of times' -r
+complete -c ciel -n "__fish_seen_subcommand_from ci" -l changed-since -d 'Build the packages changed in TREE since the Git revision' -r
+complete -c ciel -n "__fish_seen_subcommand_from ci" -l offline -d 'Disable network in the container during the build'
+complete -c ciel -n "__fish_seen_subcommand_from ci" -l isolate-network -d 'Build without network access (except the local repository)'
+complete -c ciel -n "__fish_seen_subcommand_from ci" -l stage2 -d 'Use stage 2 mode instead of the regular build mode'
+complete -c ciel -n "__fish_seen_subcommand_from ci" -l strict -d 'Fail the builds when the QA checks find problems in the produced packages'
+complete -c ciel -n "__fish_seen_subcommand_from ci" -l html-report -d 'Also generate an HTML build report in the output directory'
+complete -c ciel -n "__fish_seen_subcommand_from ci" -l with-deps -d 'Also build the in-tree dependencies of the packages'
+complete -c ciel -n "__fish_seen_subcommand_from ci" -l revdeps -d 'Also rebuild the packages depending on the packages'
+complete -c ciel -n "__fish_seen_subcommand_from ci" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from ci" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from du" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from du" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from gc" -l log-age -d 'Remove the build logs older than this (in days)' -r
+complete -c ciel -n "__fish_seen_subcommand_from gc" -s n -l dry-run -d 'Only show what would be removed'
+complete -c ciel -n "__fish_seen_subcommand_from gc" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from gc" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from list-builds" -s n -l limit -d 'Number of builds to list' -r
+complete -c ciel -n "__fish_seen_subcommand_from list-builds" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from list-builds" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from status" -l outdated -d 'Only list the packages whose version in TREE is neither built nor in the remote repository'
+complete -c ciel -n "__fish_seen_subcommand_from status" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from status" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from search" -l outdated -d 'Only list the packages not built yet or older in the output repository'
+complete -c ciel -n "__fish_seen_subcommand_from search" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from search" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from log" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from log" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from logs" -s f -l follow -d 'Keep printing the output until the build finishes'
+complete -c ciel -n "__fish_seen_subcommand_from logs" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from logs" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from rollback" -s i -d 'Instance to be rolled back' -r
+complete -c ciel -n "__fish_seen_subcommand_from rollback" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from rollback" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from down" -s i -d 'Instance to be un-mounted' -r
+complete -c ciel -n "__fish_seen_subcommand_from down" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from down" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from stop" -s i -d 'Instance to be stopped' -r
+complete -c ciel -n "__fish_seen_subcommand_from stop" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from stop" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from checkpoint" -s i -d 'Instance to be checkpointed' -r
+complete -c ciel -n "__fish_seen_subcommand_from checkpoint" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from checkpoint" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from restore" -s i -d 'Instance to be restored' -r
+complete -c ciel -n "__fish_seen_subcommand_from restore" -l discard -d 'Discard the checkpoint instead of restoring it'
+complete -c ciel -n "__fish_seen_subcommand_from restore" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from restore" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from mount" -s i -d 'Instance to be mounted' -r
+complete -c ciel -n "__fish_seen_subcommand_from mount" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from mount" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from farewell" -s y -l yes -d 'Delete the workspace without confirmation (required in batch mode)'
+complete -c ciel -n "__fish_seen_subcommand_from farewell" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from farewell" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -l branch -d 'Use the named output repository (OUTPUT-<name>)' -r
+complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the output repositories in the workspace'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "refresh" -d 'Refresh the repository'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "init" -d 'Initialize the repository'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "deinit" -d 'Uninitialize the repository'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "sign" -d 'Sign the repository using the configured key'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "prune" -d 'Remove old packages according to the retention policy (defaults to the workspace configuration)'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "serve" -d 'Serve the repository over HTTP'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from list" -l branch -d 'Use the named output repository (OUTPUT-<name>)' -r
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from list" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from list" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from refresh" -l branch -d 'Use the named output repository (OUTPUT-<name>)' -r
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from refresh" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from refresh" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from init" -l branch -d 'Use the named output repository (OUTPUT-<name>)' -r
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from init" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from init" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from deinit" -l branch -d 'Use the named output repository (OUTPUT-<name>)' -r
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from deinit" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from deinit" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from sign" -l branch -d 'Use the named output repository (OUTPUT-<name>)' -r
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from sign" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from sign" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from prune" -l keep -d 'Number of versions to keep for each package' -r
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from prune" -l max-age -d 'Remove the packages older than the specified number of days (the latest version is always kept)' -r
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from prune" -l branch -d 'Use the named output repository (OUTPUT-<name>)' -r
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from prune" -l dbg-latest-only -d 'Keep only the latest version of the debug symbol packages'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from prune" -s n -l dry-run -d 'Only list the packages to be removed'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from prune" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from prune" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from serve" -s p -l port -d 'Port to listen on' -r
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from serve" -l bind -d 'Address to listen on' -r
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from serve" -l branch -d 'Use the named output repository (OUTPUT-<name>)' -r
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from serve" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from serve" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the output repositories in the workspace'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "refresh" -d 'Refresh the repository'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "init" -d 'Initialize the repository'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "deinit" -d 'Uninitialize the repository'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "sign" -d 'Sign the repository using the configured key'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "prune" -d 'Remove old packages according to the retention policy (defaults to the workspace configuration)'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "serve" -d 'Serve the repository over HTTP'
+complete -c ciel -n "__fish_seen_subcommand_from repo; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from help" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from help" -f -a "stats" -d 'Show the statistics of the compiler cache'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from help" -f -a "clear" -d 'Remove all the cached objects'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from help" -f -a "prune" -d 'Remove old packages from the shared package cache (apt archives)'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from stats" -s i -d 'Instance to be used' -r
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from stats" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from stats" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from clear" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from clear" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from prune" -l keep -d 'Number of versions to keep for each package' -r
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from prune" -l max-age -d 'Also remove the packages downloaded more than the specified number of days ago (the latest version is always kept)' -r
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from prune" -s a -l all -d 'Remove all the cached packages'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from prune" -s n -l dry-run -d 'Only list the packages to be removed'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from prune" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from prune" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from help" -f -a "stats" -d 'Show the statistics of the compiler cache'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from help" -f -a "clear" -d 'Remove all the cached objects'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from help" -f -a "prune" -d 'Remove old packages from the shared package cache (apt archives)'
+complete -c ciel -n "__fish_seen_subcommand_from cache; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto; and not __fish_seen_subcommand_from help" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the known mirrors'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto; and not __fish_seen_subcommand_from help" -f -a "bench" -d 'Rank the mirrors by latency and throughput'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto; and not __fish_seen_subcommand_from help" -f -a "set" -d 'Use the specified mirror'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto; and not __fish_seen_subcommand_from help" -f -a "auto" -d 'Use the fastest mirror'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from list" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from list" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from bench" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from bench" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from set" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from set" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from auto" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from auto" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List the known mirrors'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto; and not __fish_seen_subcommand_from help" -f -a "bench" -d 'Rank the mirrors by latency and throughput'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto; and not __fish_seen_subcommand_from help" -f -a "set" -d 'Use the specified mirror'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto; and not __fish_seen_subcommand_from help" -f -a "auto" -d 'Use the fastest mirror'
+complete -c ciel -n "__fish_seen_subcommand_from mirror; and __fish_seen_subcommand_from help; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from daemon" -s l -l listen -d 'Listen on the TCP address (e.g. 127.0.0.1:8080), requires a token' -r
+complete -c ciel -n "__fish_seen_subcommand_from daemon" -l socket -d 'Listen on the unix socket' -r
+complete -c ciel -n "__fish_seen_subcommand_from daemon" -l token -d 'Token required in the Authorization header (Bearer)' -r
+complete -c ciel -n "__fish_seen_subcommand_from daemon" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from daemon" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from tui" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from tui" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from completion" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from completion" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from _complete" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from _complete" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from clean" -l json -d 'Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)'
+complete -c ciel -n "__fish_seen_subcommand_from clean" -s h -l help -d 'Print help'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "version" -d 'Display the version of CIEL!'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "archs" -d 'List the supported architectures and their tarball variants'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "init" -d 'Initialize the work directory'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "load-os" -d 'Unpack OS tarball or fetch the latest BuildKit from the repository'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "verify-dist" -d 'Verify the base OS against the file lists and checksums of the installed packages'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "update-os" -d 'Update the OS in the container'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "pack-os" -d 'Pack the base OS into a compressed read-only image (run `ciel unpack-os` before updating it)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "unpack-os" -d 'Unpack the base OS image back into a directory'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "load-tree" -d 'Clone package tree from the link provided or AOSC OS ABBS main repository'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "update-tree" -d 'Update the existing ABBS tree (fetch only) and optionally switch to a different branch'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "tree" -d 'Manage the ABBS tree checkout (TREE)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "topic" -d 'Work on a topic: switch TREE to its branch, install the packages from its repository and build into OUTPUT-<topic>'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "new" -d 'Create a new CIEL workspace'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "list" -d 'List all the instances under the specified working directory'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "add" -d 'Add a new instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "template" -d 'Instance templates (stored in .ciel/templates)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "layer" -d 'Shared read-only layers between the base OS and the instances (stored in .ciel/container/layers)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "port" -d 'Forward ports of the host to the services in the instances (the instances get their own network)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "remote" -d 'Remote machines to build on over SSH (`ciel build --on`)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "farm" -d 'Build the packages on the remote machines, for each of their architectures'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "snapshot" -d 'Named snapshots of the instance state (stored in .ciel/data/snapshots)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "clone" -d 'Duplicate an instance into a new instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "export" -d 'Export an instance as a portable bundle'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "oci" -d 'OCI images of the instances, for Docker/Podman-based CI'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "import" -d 'Import an instance from a bundle'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "del" -d 'Remove an instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "shell" -d 'Start an interactive shell'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "run-ephemeral" -d 'Run the command in a temporary instance created from the base OS, removed afterwards'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "run" -d 'Lower-level version of \'shell\', without login environment, without sourcing ~/.bash_profile'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "config" -d 'Configure system and toolchain for building interactively'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "commit" -d 'Commit changes onto the shared underlying OS'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "diff" -d 'Show the files added, modified and deleted in the instance (relative to the base OS)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "recover" -d 'Recover the workspace after a crash (stale mounts, dead containers, etc.)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "doctor" -d 'Diagnose problems (hopefully)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "build" -d 'Build the packages using the specified instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "fetch" -d 'Download the sources of the packages into the shared source cache'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "ci" -d 'Build the packages non-interactively for CI runners (GitHub Actions, GitLab CI)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "du" -d 'Show the disk usage of the base OS, the instances, the output and the cache directories'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "gc" -d 'Remove the data left behind in the workspace: incomplete imports, data of the removed instances, dangling machines, partial downloads, old logs and the unused package cache lock'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "list-builds" -d 'List the recent builds with their duration, CPU time and peak memory usage'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "status" -d 'Compare the package versions in TREE against the output repository and the remote repository'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "search" -d 'Search the packages in TREE and show whether they are built in the output repository'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "log" -d 'Show the most recent build log of the specified package'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "logs" -d 'Show the output of the build in progress (or the last build) in the specified instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "rollback" -d 'Rollback all or specified instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "down" -d 'Shutdown and unmount all or one instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "stop" -d 'Shuts down an instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "checkpoint" -d 'Save the state of a running instance and stop it (requires CRIU)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "restore" -d 'Resume an instance from its checkpoint'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "mount" -d 'Mount all or specified instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "farewell" -d 'Remove everything related to CIEL!'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "repo" -d 'Local repository operations'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "cache" -d 'Shared compiler cache (ccache) and package cache operations'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "mirror" -d 'AOSC OS mirror selection'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "daemon" -d 'Serve the REST API for controlling Ciel remotely'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "tui" -d 'Show the interactive dashboard of the instances and builds'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "completion" -d 'Print the shell completion script'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "_complete" -d 'Print the completion candidates (used by the completion scripts)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "clean" -d 'Clean all the output directories and source cache directories'
+complete -c ciel -n "__fish_seen_subcommand_from help; and not __fish_seen_subcommand_from version; and not __fish_seen_subcommand_from archs; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from load-os; and not __fish_seen_subcommand_from verify-dist; and not __fish_seen_subcommand_from update-os; and not __fish_seen_subcommand_from pack-os; and not __fish_seen_subcommand_from unpack-os; and not __fish_seen_subcommand_from load-tree; and not __fish_seen_subcommand_from update-tree; and not __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from topic; and not __fish_seen_subcommand_from new; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from farm; and not __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from export; and not __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from import; and not __fish_seen_subcommand_from del; and not __fish_seen_subcommand_from shell; and not __fish_seen_subcommand_from run-ephemeral; and not __fish_seen_subcommand_from run; and not __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from commit; and not __fish_seen_subcommand_from diff; and not __fish_seen_subcommand_from recover; and not __fish_seen_subcommand_from doctor; and not __fish_seen_subcommand_from build; and not __fish_seen_subcommand_from fetch; and not __fish_seen_subcommand_from ci; and not __fish_seen_subcommand_from du; and not __fish_seen_subcommand_from gc; and not __fish_seen_subcommand_from list-builds; and not __fish_seen_subcommand_from status; and not __fish_seen_subcommand_from search; and not __fish_seen_subcommand_from log; and not __fish_seen_subcommand_from logs; and not __fish_seen_subcommand_from rollback; and not __fish_seen_subcommand_from down; and not __fish_seen_subcommand_from stop; and not __fish_seen_subcommand_from checkpoint; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from mount; and not __fish_seen_subcommand_from farewell; and not __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from daemon; and not __fish_seen_subcommand_from tui; and not __fish_seen_subcommand_from completion; and not __fish_seen_subcommand_from _complete; and not __fish_seen_subcommand_from clean; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch" -f -a "clone" -d 'Clone the ABBS tree into the workspace'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch" -f -a "update" -d 'Fetch the remote and fast-forward the current branch of the tree'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from tree; and not __fish_seen_subcommand_from clone; and not __fish_seen_subcommand_from update; and not __fish_seen_subcommand_from switch-branch" -f -a "switch-branch" -d 'Fetch the remote and switch the tree to another (topic) branch, keeping the local changes'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove" -f -a "save" -d 'Save the instance configuration and the provisioning steps as a template'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove" -f -a "list" -d 'List the templates'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from template; and not __fish_seen_subcommand_from save; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove" -f -a "remove" -d 'Remove the template'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove" -f -a "create" -d 'Create a shared layer (empty, or from the changes of an instance)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove" -f -a "attach" -d 'Stack the shared layer on the base OS of the instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove" -f -a "detach" -d 'Remove the shared layer from the instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove" -f -a "list" -d 'List the shared layers'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from layer; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from attach; and not __fish_seen_subcommand_from detach; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove" -f -a "remove" -d 'Remove the shared layer (must not be attached to any instance)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list" -f -a "add" -d 'Forward the port of the host to the instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list" -f -a "remove" -d 'Stop forwarding the port of the host to the instance'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from port; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list" -f -a "list" -d 'List the forwarded ports'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list" -f -a "add" -d 'Add a remote machine running ciel'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list" -f -a "remove" -d 'Remove the remote machine'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from list" -f -a "list" -d 'List the remote machines'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove" -f -a "create" -d 'Save the current state of the instance as a snapshot'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove" -f -a "restore" -d 'Restore the instance to the snapshot (the current state is discarded)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove" -f -a "list" -d 'List the snapshots'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from snapshot; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from restore; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from remove" -f -a "remove" -d 'Remove the snapshot'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from oci; and not __fish_seen_subcommand_from export" -f -a "export" -d 'Export the instance as an OCI image (the base OS and the changes of the instance as the layers)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from config; and not __fish_seen_subcommand_from edit" -f -a "edit" -d 'Edit the workspace configuration file (.ciel/config.toml)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve" -f -a "list" -d 'List the output repositories in the workspace'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve" -f -a "refresh" -d 'Refresh the repository'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve" -f -a "init" -d 'Initialize the repository'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve" -f -a "deinit" -d 'Uninitialize the repository'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve" -f -a "sign" -d 'Sign the repository using the configured key'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve" -f -a "prune" -d 'Remove old packages according to the retention policy (defaults to the workspace configuration)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from repo; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from refresh; and not __fish_seen_subcommand_from init; and not __fish_seen_subcommand_from deinit; and not __fish_seen_subcommand_from sign; and not __fish_seen_subcommand_from prune; and not __fish_seen_subcommand_from serve" -f -a "serve" -d 'Serve the repository over HTTP'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune" -f -a "stats" -d 'Show the statistics of the compiler cache'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune" -f -a "clear" -d 'Remove all the cached objects'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from cache; and not __fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from clear; and not __fish_seen_subcommand_from prune" -f -a "prune" -d 'Remove old packages from the shared package cache (apt archives)'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto" -f -a "list" -d 'List the known mirrors'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto" -f -a "bench" -d 'Rank the mirrors by latency and throughput'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto" -f -a "set" -d 'Use the specified mirror'
+complete -c ciel -n "__fish_seen_subcommand_from help; and __fish_seen_subcommand_from mirror; and not __fish_seen_subcommand_from list; and not __fish_seen_subcommand_from bench; and not __fish_seen_subcommand_from set; and not __fish_seen_subcommand_from auto" -f -a "auto" -d 'Use the fastest mirror'
+
+complete -c ciel -s i -x -a "(ciel _complete instances 2>/dev/null)"
+complete -c ciel -n "__fish_seen_subcommand_from build" -f -a "(ciel _complete packages (commandline -ct) 2>/dev/null)"
@@ -0,0 +1,93 @@
+//! ABI comparison of the shared libraries between two builds of a package,
+//! based on the symbols exported in their dynamic symbol tables
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+/// Exported symbols of each shared library in a package
+pub(super) type LibrarySymbols = HashMap<String, HashSet<String>>;
+
+/// Symbols removed from a shared library
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct AbiBreak {
+    pub library: String,
+    pub removed: Vec<String>,
+}
+
+/// Name of the shared library up to its major version (`libfoo.so.1.2.3` -> `libfoo.so.1`),
+/// the libraries are compared between the builds by this name. `None` for the other files.
+pub(super) fn library_name(path: &Path) -> Option<String> {
+    // separate debug symbols shipped in the -dbg packages
+    if path.starts_with("usr/lib/debug") {
+        return None;
+    }
+    let file_name = path.file_name()?.to_str()?;
+    if !file_name.starts_with("lib") {
+        return None;
+    }
+    let (base, version) = file_name.split_once(".so")?;
+    if version.is_empty() {
+        return Some(file_name.to_string());
+    }
+    let major = version.strip_prefix('.')?.split('.').next()?;
+    if major.parse::<u32>().is_err() {
+        return None;
+    }
+
+    Some(format!("{}.so.{}", base, major))
+}
+
+/// Symbols exported by the previous build but not by the current one,
+/// the libraries not in both builds are not compared (see the soname check)
+pub(super) fn removed_symbols(
+    previous: &LibrarySymbols,
+    current: &LibrarySymbols,
+) -> Vec<AbiBreak> {
+    let mut breaks = current
+        .iter()
+        .filter_map(|(library, symbols)| {
+            let mut removed = previous
+                .get(library)?
+                .difference(symbols)
+                .cloned()
+                .collect::<Vec<_>>();
+            if removed.is_empty() {
+                return None;
+            }
+            removed.sort();
+            Some(AbiBreak {
+                library: library.clone(),
+                removed,
+            })
+        })
+        .collect::<Vec<_>>();
+    breaks.sort_by(|a, b| a.library.cmp(&b.library));
+
+    breaks
+}
+
+#[test]
+fn test_removed_symbols() {
+    assert_eq!(
+        library_name(Path::new("usr/lib/libfoo.so.1.2.3")).as_deref(),
+        Some("libfoo.so.1")
+    );
+    assert_eq!(
+        library_name(Path::new("usr/lib/libbar.so")).as_deref(),
+        Some("libbar.so")
+    );
+    assert!(library_name(Path::new("usr/lib/debug/usr/lib/libfoo.so.1.debug")).is_none());
+    assert!(library_name(Path::new("usr/bin/foo")).is_none());
+    let symbols = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<HashSet<_>>();
+    let previous = LibrarySymbols::from([("libfoo.so.1".to_string(), symbols(&["foo", "bar"]))]);
+    let current = LibrarySymbols::from([("libfoo.so.1".to_string(), symbols(&["foo", "baz"]))]);
+    assert_eq!(
+        removed_symbols(&previous, &current),
+        vec![AbiBreak {
+            library: "libfoo.so.1".to_string(),
+            removed: vec!["bar".to_string()],
+        }]
+    );
+}
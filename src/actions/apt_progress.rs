@@ -0,0 +1,205 @@
+//! Progress of the package operations in the instances: the output of apt/oma is parsed
+//! and rendered as a progress bar, instead of being streamed over the terminal
+
+use anyhow::Result;
+use console::style;
+use indicatif::{ProgressBar, ProgressStyle};
+use nix::{fcntl::OFlag, unistd::pipe2};
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{common::CIEL_DATA_DIR, info, warn};
+
+use super::{
+    cache::run_apt_script,
+    retry::{classify_output, report_failure, FailureKind},
+};
+
+/// Lines of output shown when the operation fails
+const FAILURE_TAIL_LINES: usize = 20;
+
+#[derive(Debug, PartialEq, Eq)]
+enum AptEvent {
+    /// Number of steps of the operation, from the summary of apt
+    Total(u64),
+    /// A step (unpacking, setting up or removing a package) with its description
+    Step(String),
+    /// Other activity, e.g. a download
+    Activity(String),
+    /// Warnings and errors, printed above the progress bar
+    Notice(String),
+    Other,
+}
+
+/// The number at the beginning of `<n> <what>`
+fn leading_count(text: &str, what: &str) -> Option<u64> {
+    text.trim().strip_suffix(what)?.trim().parse().ok()
+}
+
+fn parse_apt_line(line: &str) -> AptEvent {
+    let line = line.trim_end();
+    if line.starts_with("E:")
+        || line.starts_with("W:")
+        || line.starts_with("Err:")
+        || line.starts_with("ERROR")
+        || line.starts_with("WARNING")
+    {
+        return AptEvent::Notice(line.to_string());
+    }
+    // "3 upgraded, 1 newly installed, 2 to remove and 0 not upgraded."
+    if line.contains(" upgraded, ") && line.contains(" newly installed") {
+        let mut total = 0;
+        for part in line.trim_end_matches('.').split(", ") {
+            let part = part.split(" and ").next().unwrap_or(part);
+            if let Some(count) = leading_count(part, "upgraded") {
+                total += count * 2;
+            } else if let Some(count) = leading_count(part, "newly installed") {
+                total += count * 2;
+            } else if let Some(count) = leading_count(part, "to remove") {
+                total += count;
+            }
+        }
+        return AptEvent::Total(total);
+    }
+    for prefix in ["Unpacking ", "Setting up ", "Removing "] {
+        if line.starts_with(prefix) {
+            return AptEvent::Step(line.trim_end_matches(" ...").to_string());
+        }
+    }
+    // oma prints its downloads as "Downloading <package>" without the progress bar
+    if line.starts_with("Get:")
+        || line.starts_with("Hit:")
+        || line.starts_with("Fetched ")
+        || line.starts_with("Downloading ")
+    {
+        return AptEvent::Activity(line.to_string());
+    }
+
+    AptEvent::Other
+}
+
+/// With `echo`, the output is also printed as is (when there is no terminal to draw the progress on)
+fn render_events<R: BufRead>(
+    reader: R,
+    log: &mut File,
+    bar: &ProgressBar,
+    echo: bool,
+) -> VecDeque<String> {
+    let mut tail = VecDeque::with_capacity(FAILURE_TAIL_LINES);
+    for line in reader.split(b'\n') {
+        let line = match line {
+            Ok(line) => String::from_utf8_lossy(&line).to_string(),
+            Err(_) => break,
+        };
+        log.write_all(line.as_bytes()).ok();
+        log.write_all(b"\n").ok();
+        if echo {
+            eprintln!("{}", line);
+        }
+        // apt may redraw the line with carriage returns
+        let line = line.rsplit('\r').next().unwrap_or_default().to_string();
+        match parse_apt_line(&line) {
+            AptEvent::Total(total) => {
+                bar.set_style(
+                    ProgressStyle::default_bar()
+                        .template("{spinner:.green} [{bar:25.cyan/blue}] {pos}/{len} (eta {eta}) {wide_msg}")
+                        .unwrap(),
+                );
+                bar.set_length(total.max(1));
+                bar.set_position(0);
+            }
+            AptEvent::Step(step) => {
+                bar.inc(1);
+                bar.set_message(step);
+            }
+            AptEvent::Activity(activity) => bar.set_message(activity),
+            AptEvent::Notice(notice) => bar.println(notice),
+            AptEvent::Other => (),
+        }
+        if tail.len() == FAILURE_TAIL_LINES {
+            tail.pop_front();
+        }
+        tail.push_back(line);
+    }
+
+    tail
+}
+
+/// Run the apt/oma script in the instance, rendering its progress on the terminal.
+/// The full output is written to `.ciel/data/apt-<instance>.log`, which is kept if the script fails.
+/// Without a terminal, the output is streamed as is.
+/// Returns the exit status and the cause of the failure (if it failed).
+pub(super) fn run_apt_script_with_progress(
+    instance: &str,
+    script: &str,
+) -> Result<(i32, Option<FailureKind>)> {
+    let echo = !console::user_attended_stderr();
+    fs::create_dir_all(CIEL_DATA_DIR)?;
+    let log_path = Path::new(CIEL_DATA_DIR).join(format!("apt-{}.log", instance));
+    let mut log = File::create(&log_path)?;
+    // the pipe must not be inherited by the container itself, or the reader will never see EOF
+    let (reader, writer) = pipe2(OFlag::O_CLOEXEC)?;
+    let reader = BufReader::new(File::from(reader));
+    let writer = File::from(writer);
+    let bar = if echo {
+        ProgressBar::hidden()
+    } else {
+        let bar = ProgressBar::new_spinner().with_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} {wide_msg}")
+                .unwrap(),
+        );
+        bar.set_message("Preparing ...");
+        bar.enable_steady_tick(Duration::from_millis(100));
+        bar
+    };
+    let renderer = {
+        let bar = bar.clone();
+        std::thread::spawn(move || render_events(reader, &mut log, &bar, echo))
+    };
+    let status = run_apt_script(instance, script, Some(&writer));
+    drop(writer);
+    let tail = renderer.join().unwrap_or_default();
+    bar.finish_and_clear();
+    let status = status?;
+    if status == 0 {
+        fs::remove_file(&log_path).ok();
+        return Ok((0, None));
+    }
+    let failure = classify_output(&Vec::from(tail.clone()).join("\n"));
+    if !echo {
+        warn!(
+            "Package operation failed with status {}, last output:",
+            status
+        );
+        for line in tail.iter() {
+            eprintln!("    {}", line);
+        }
+    }
+    report_failure(instance, failure);
+    info!("The full output is kept in {}.", log_path.display());
+
+    Ok((status, Some(failure)))
+}
+
+#[test]
+fn test_parse_apt_line() {
+    assert_eq!(
+        parse_apt_line("3 upgraded, 1 newly installed, 2 to remove and 0 not upgraded."),
+        AptEvent::Total(10)
+    );
+    assert_eq!(
+        parse_apt_line("Unpacking bash (5.2.15) over (5.2.12) ..."),
+        AptEvent::Step("Unpacking bash (5.2.15) over (5.2.12)".to_string())
+    );
+    assert_eq!(
+        parse_apt_line("E: Unable to locate package foo"),
+        AptEvent::Notice("E: Unable to locate package foo".to_string())
+    );
+    assert_eq!(parse_apt_line("Reading package lists..."), AptEvent::Other);
+}
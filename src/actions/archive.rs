@@ -0,0 +1,173 @@
+//! Instance export and import (portable .tar.zst bundles)
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use rand::random;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io,
+    path::{Component, Path},
+    process::{Command, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    common::{create_spinner, extract_tarball, is_instance_exists, CIEL_INST_DIR},
+    info, warn,
+};
+
+use super::container::container_down;
+
+const METADATA_FILE: &str = "ciel-instance.json";
+/// Prefix of the temporary directories used when importing (cleaned up by `ciel recover`)
+pub(super) const IMPORT_TEMP_PREFIX: &str = ".import-";
+const ARCHIVE_FORMAT_VERSION: usize = 1;
+/// Layers included in the bundle (configuration layer and the overlay upper layer)
+const EXPORTED_LAYERS: &[&str] = &["config.toml", "layers/local", "layers/diff"];
+
+/// Metadata stored in the bundle
+#[derive(Debug, Serialize, Deserialize)]
+struct InstanceMetadata {
+    format: usize,
+    instance: String,
+    ciel_version: String,
+    created: u64,
+}
+
+/// Make sure the instance name is a plain directory name (e.g. not `../foo` or a hidden one)
+fn validate_instance_name(name: &str) -> Result<()> {
+    let mut components = Path::new(name).components();
+    if name.starts_with('.')
+        || !matches!(components.next(), Some(Component::Normal(_)))
+        || components.next().is_some()
+    {
+        bail!("Invalid instance name: {}", name);
+    }
+
+    Ok(())
+}
+
+/// Bundle the instance filesystem changes and configuration into a .tar.zst file
+pub fn export_instance(instance: &str, path: &Path) -> Result<()> {
+    if !is_instance_exists(instance) {
+        bail!("Instance {} does not exist.", instance);
+    }
+    let inst_dir = Path::new(CIEL_INST_DIR).join(instance);
+    if inst_dir.join("layers/snapshot").exists() || inst_dir.join("layers/clone").exists() {
+        bail!("Only instances using the overlay filesystem backend can be exported.");
+    }
+    // make sure the filesystem is in a consistent state
+    container_down(instance)?;
+    info!("{}: exporting to {}...", instance, path.display());
+    let metadata = InstanceMetadata {
+        format: ARCHIVE_FORMAT_VERSION,
+        instance: instance.to_string(),
+        ciel_version: env!("CARGO_PKG_VERSION").to_string(),
+        created: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    };
+    fs::write(
+        inst_dir.join(METADATA_FILE),
+        serde_json::to_vec_pretty(&metadata)?,
+    )?;
+    let layers = EXPORTED_LAYERS
+        .iter()
+        .filter(|x| inst_dir.join(x).exists())
+        .collect::<Vec<_>>();
+    let spinner = create_spinner("Exporting the instance...", 200);
+    // the tar crate does not preserve xattrs (used by overlayfs for opaque directories)
+    let result = (|| -> Result<()> {
+        let mut tar = Command::new("tar")
+            .args([
+                "--xattrs",
+                "--xattrs-include=*",
+                "--numeric-owner",
+                "-cpf",
+                "-",
+                "-C",
+            ])
+            .arg(&inst_dir)
+            .arg(METADATA_FILE)
+            .args(&layers)
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("Unable to execute tar: {}", e))?;
+        let mut stream = tar
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("Unable to read from tar"))?;
+        let mut encoder = zstd::stream::write::Encoder::new(File::create(path)?, 0)?;
+        io::copy(&mut stream, &mut encoder)?;
+        encoder.finish()?;
+        let status = tar.wait()?;
+        if !status.success() {
+            bail!("tar exited with error: {}", status);
+        }
+
+        Ok(())
+    })();
+    spinner.finish_and_clear();
+    fs::remove_file(inst_dir.join(METADATA_FILE)).ok();
+    if let Err(e) = result {
+        fs::remove_file(path).ok();
+        return Err(e);
+    }
+    info!("{}: instance exported.", instance);
+
+    Ok(())
+}
+
+/// Import an instance from a bundle created by `export_instance`
+pub fn import_instance(path: &Path, name: Option<&str>) -> Result<String> {
+    let bundle = File::open(path)?;
+    let temp =
+        Path::new(CIEL_INST_DIR).join(format!("{}{:x}", IMPORT_TEMP_PREFIX, random::<u32>()));
+    fs::create_dir_all(&temp)?;
+    let spinner = create_spinner("Importing the instance...", 200);
+    let result = extract_tarball(bundle, &temp);
+    spinner.finish_and_clear();
+    let result = result.and_then(|_| {
+        let metadata: InstanceMetadata = serde_json::from_reader(
+            File::open(temp.join(METADATA_FILE))
+                .map_err(|_| anyhow!("{} is not a Ciel instance bundle.", path.display()))?,
+        )?;
+        if metadata.format > ARCHIVE_FORMAT_VERSION {
+            bail!(
+                "This bundle was created by a newer version of Ciel ({}).",
+                metadata.ciel_version
+            );
+        }
+        let instance = name.unwrap_or(&metadata.instance).to_string();
+        validate_instance_name(&instance)?;
+        if is_instance_exists(&instance) {
+            bail!("Instance {} already exists.", instance);
+        }
+        fs::remove_file(temp.join(METADATA_FILE))?;
+        fs::rename(&temp, Path::new(CIEL_INST_DIR).join(&instance))?;
+
+        Ok(instance)
+    });
+    if result.is_err() {
+        fs::remove_dir_all(&temp).ok();
+    }
+    let instance = result?;
+    info!("{}: instance imported.", instance);
+    warn!(
+        "{}: changes in the instance are now applied on top of the OS in this workspace.",
+        instance
+    );
+
+    Ok(instance)
+}
+
+#[test]
+fn test_validate_instance_name() {
+    assert!(validate_instance_name("main").is_ok());
+    assert!(validate_instance_name("main-1.2").is_ok());
+    assert!(validate_instance_name("").is_err());
+    assert!(validate_instance_name("..").is_err());
+    assert!(validate_instance_name(".import-1234").is_err());
+    assert!(validate_instance_name("../main").is_err());
+    assert!(validate_instance_name("/main").is_err());
+    assert!(validate_instance_name("foo/bar").is_err());
+}
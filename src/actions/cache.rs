@@ -0,0 +1,160 @@
+//! Shared compiler cache (ccache) and package cache (apt archives) management
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use indicatif::HumanBytes;
+use nix::{
+    errno::Errno,
+    fcntl::{Flock, FlockArg},
+};
+use std::{
+    fs::{self, File, OpenOptions},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    config, info,
+    repo::{collect_all_packages, find_prunable_packages, PrunePolicy},
+    warn,
+};
+
+use super::{
+    container::{run_in_container, run_in_container_with_output},
+    APT_CACHE_HOST_DIR, CCACHE_HOST_DIR,
+};
+
+/// Lock file in the shared package cache. apt locks the cache itself and gives up immediately
+/// when another instance is using it, so ciel serializes the apt operations with this lock.
+const APT_CACHE_LOCK: &str = ".ciel-lock";
+
+/// Show the statistics (e.g. hit rates) of the shared ccache using the specified instance
+pub fn cache_stats(instance: &str) -> Result<i32> {
+    let conf =
+        config::read_config().map_err(|_| anyhow!("Please configure this workspace first!"))?;
+    if !conf.ccache {
+        warn!("ccache is not enabled, enable it using `ciel config -g`.");
+    }
+    let cache_dir = format!("CCACHE_DIR={}", config::CCACHE_DIR);
+
+    run_in_container(instance, &["env", &cache_dir, "ccache", "--show-stats"])
+}
+
+/// Remove all the cached objects in the shared ccache
+pub fn cache_clear() -> Result<()> {
+    let cache_dir = Path::new(CCACHE_HOST_DIR);
+    if !cache_dir.is_dir() {
+        info!("ccache directory is empty.");
+        return Ok(());
+    }
+    for entry in fs::read_dir(cache_dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            fs::remove_dir_all(path)?;
+        } else {
+            fs::remove_file(path)?;
+        }
+    }
+    info!("ccache has been cleared.");
+
+    Ok(())
+}
+
+/// Lock the shared package cache, waiting for the apt operations in the other instances to finish
+pub(super) fn lock_apt_cache() -> Result<Flock<File>> {
+    fs::create_dir_all(APT_CACHE_HOST_DIR)?;
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(Path::new(APT_CACHE_HOST_DIR).join(APT_CACHE_LOCK))?;
+    let file = match Flock::lock(file, FlockArg::LockExclusiveNonblock) {
+        Ok(lock) => return Ok(lock),
+        Err((file, Errno::EWOULDBLOCK)) => file,
+        Err((_, e)) => bail!("Unable to lock the package cache: {}", e),
+    };
+    info!("Waiting for the other instances to release the package cache...");
+
+    Flock::lock(file, FlockArg::LockExclusive)
+        .map_err(|(_, e)| anyhow!("Unable to lock the package cache: {}", e))
+}
+
+/// Returns true if the lock file of the shared package cache exists and no apt operation is
+/// holding it
+pub(super) fn is_apt_cache_lock_unused() -> bool {
+    File::open(Path::new(APT_CACHE_HOST_DIR).join(APT_CACHE_LOCK))
+        .is_ok_and(|file| Flock::lock(file, FlockArg::LockExclusiveNonblock).is_ok())
+}
+
+/// Remove the lock file of the shared package cache if no apt operation is holding it,
+/// returns true if it was removed
+pub(super) fn remove_unused_apt_cache_lock() -> Result<bool> {
+    let path = Path::new(APT_CACHE_HOST_DIR).join(APT_CACHE_LOCK);
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(_) => return Ok(false),
+    };
+    match Flock::lock(file, FlockArg::LockExclusiveNonblock) {
+        // removed while locked, so that nobody acquires the old file in the meantime
+        Ok(_lock) => {
+            fs::remove_file(&path)?;
+            Ok(true)
+        }
+        Err(_) => Ok(false),
+    }
+}
+
+/// Run the apt (or oma) script in the instance while holding the lock of the shared package cache
+pub(super) fn run_apt_script(instance: &str, script: &str, output: Option<&File>) -> Result<i32> {
+    let _lock = lock_apt_cache()?;
+
+    run_in_container_with_output(instance, &["/bin/bash", "-ec", script], output)
+}
+
+/// Remove packages from the shared package cache: all of them with `all`, otherwise the versions
+/// older than the latest `keep` versions and the ones downloaded more than `max_age` days ago
+pub fn apt_cache_prune(keep: usize, max_age: Option<u64>, all: bool, dry_run: bool) -> Result<()> {
+    let cache_dir = Path::new(APT_CACHE_HOST_DIR);
+    if !cache_dir.is_dir() {
+        info!("Package cache is empty.");
+        return Ok(());
+    }
+    let _lock = lock_apt_cache()?;
+    let prunable: Vec<PathBuf> = if all {
+        collect_all_packages(cache_dir)?
+            .into_iter()
+            .map(|x| x.into_path())
+            .collect()
+    } else {
+        let policy = PrunePolicy {
+            keep_versions: Some(keep),
+            max_age: max_age.map(|x| Duration::from_secs(x * 24 * 60 * 60)),
+            dbg_latest_only: false,
+        };
+        find_prunable_packages(cache_dir, &policy)?
+    };
+    let mut size = 0;
+    for path in prunable.iter() {
+        size += fs::metadata(path).map(|x| x.len()).unwrap_or(0);
+        if dry_run {
+            println!("{}", path.display());
+        } else {
+            fs::remove_file(path)?;
+        }
+    }
+    if dry_run {
+        info!(
+            "{} package(s) ({}) would be removed.",
+            prunable.len(),
+            HumanBytes(size)
+        );
+    } else {
+        info!(
+            "Removed {} package(s) from the package cache, {} freed.",
+            prunable.len(),
+            HumanBytes(size)
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+//! Checkpoint and restore of the running instances (using CRIU)
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    backend::get_container_backend, common::CIEL_DATA_DIR, info, machine::inspect_instance,
+};
+
+use super::container::{get_instance_ns_name, mount_fs};
+
+pub(super) const CHECKPOINT_DIR: &str = "checkpoints";
+
+/// Directory holding the checkpoint of the instance
+fn checkpoint_directory(instance: &str) -> PathBuf {
+    Path::new(CIEL_DATA_DIR).join(CHECKPOINT_DIR).join(instance)
+}
+
+/// Whether the instance has a checkpoint waiting to be restored
+pub fn has_checkpoint(instance: &str) -> bool {
+    checkpoint_directory(instance).is_dir()
+}
+
+/// Remove the checkpoint of the instance (if any)
+pub fn discard_checkpoint(instance: &str) -> Result<()> {
+    let directory = checkpoint_directory(instance);
+    if directory.is_dir() {
+        fs::remove_dir_all(directory)?;
+    }
+
+    Ok(())
+}
+
+/// Save the state of the running instance (including the builds in progress) and stop it,
+/// so that the host can be rebooted and the instance restored afterwards
+pub fn checkpoint_container(instance: &str) -> Result<()> {
+    which::which("criu").map_err(|_| anyhow!("criu is required for checkpointing."))?;
+    let ns_name = get_instance_ns_name(instance)?;
+    let inst = inspect_instance(instance, &ns_name)?;
+    if !inst.started {
+        bail!("{}: instance is not running.", instance);
+    }
+    if has_checkpoint(instance) {
+        bail!(
+            "{}: instance already has a checkpoint, restore or discard it first.",
+            instance
+        );
+    }
+    let directory = checkpoint_directory(instance);
+    info!("{}: checkpointing...", instance);
+    if let Err(e) = get_container_backend(&ns_name).checkpoint(&ns_name, &directory) {
+        // keep the logs for troubleshooting
+        let log = directory.join("criu.log");
+        if log.is_file() {
+            fs::rename(
+                &log,
+                Path::new(CIEL_DATA_DIR).join(format!("criu-{}.log", instance)),
+            )?;
+        }
+        discard_checkpoint(instance)?;
+        return Err(e);
+    }
+    info!(
+        "{}: checkpoint saved, use `ciel restore -i {}` to resume.",
+        instance, instance
+    );
+
+    Ok(())
+}
+
+/// Resume the instance from its checkpoint, mounting the filesystem first if needed
+pub fn restore_container(instance: &str) -> Result<()> {
+    if !has_checkpoint(instance) {
+        bail!("{}: instance has no checkpoint.", instance);
+    }
+    which::which("criu").map_err(|_| anyhow!("criu is required for restoring."))?;
+    let ns_name = get_instance_ns_name(instance)?;
+    let inst = inspect_instance(instance, &ns_name)?;
+    if inst.started {
+        bail!("{}: instance is already running.", instance);
+    }
+    if !inst.mounted {
+        mount_fs(instance)?;
+    }
+    info!("{}: restoring...", instance);
+    get_container_backend(&ns_name).restore(&ns_name, &checkpoint_directory(instance))?;
+    discard_checkpoint(instance)?;
+    info!("{}: instance restored.", instance);
+
+    Ok(())
+}
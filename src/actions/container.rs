@@ -1,25 +1,56 @@
-use anyhow::{anyhow, Result};
-use console::{style, user_attended};
-use dialoguer::{theme::ColorfulTheme, Confirm, Input};
+use anyhow::{anyhow, bail, Result};
+use console::{style, Color};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
 use git2::Repository;
-use nix::unistd::sync;
+use nix::fcntl::OFlag;
+use nix::unistd::{pipe2, sync};
 use rand::random;
 use std::{
     ffi::OsStr,
     fs,
+    io::{Read, Write},
+    os::unix::fs::FileTypeExt,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use crate::{
     actions::{ensure_host_sanity, OMA_UPDATE_SCRIPT},
+    backend::BackendKind,
     common::*,
-    config, error, info,
+    config::{self, WatchdogConfig},
+    error, info,
     machine::{self, get_container_ns_name, inspect_instance, spawn_container},
-    network::download_file_progress,
+    network::{
+        self, cache_tarball, download_file_multi_source, download_file_progress, ensure_online,
+        find_cached_tarball, strip_multi_source_suffix,
+    },
     overlayfs, warn,
 };
 
-use super::{for_each_instance, APT_UPDATE_SCRIPT};
+use super::{
+    apt_progress::run_apt_script_with_progress,
+    cache::lock_apt_cache,
+    diff::filter_changes,
+    for_each_instance,
+    hooks::{run_hook, Hook},
+    provenance::record_dist_source,
+    watchdog::{Watchdog, WATCHDOG_STATUS},
+    APT_CACHE_HOST_DIR, APT_UPDATE_SCRIPT,
+};
+
+/// Switches to the user (`$1`, creating it with the UID `$2` if missing) and the working directory (`$3`),
+/// then executes the command
+const SHELL_WRAPPER: &str = r#"user="$1"; uid="$2"; dir="$3"; shift 3
+if [ -n "$user" ]; then
+    if ! id -u "$user" > /dev/null 2>&1; then
+        { [ -n "$uid" ] && useradd -m -u "$uid" "$user"; } || useradd -m "$user" || exit 1
+    fi
+    home="$(getent passwd "$user" | cut -d: -f6)"
+    exec runuser -u "$user" -- env HOME="$home" USER="$user" LOGNAME="$user" /bin/sh -c 'cd -- "${1:-$HOME}" || exit 1; shift; exec "$@"' sh "$dir" "$@"
+fi
+if [ -n "$dir" ]; then cd -- "$dir" || exit 1; fi
+exec "$@""#;
 
 /// Get the branch name of the workspace TREE repository
 #[inline]
@@ -33,9 +64,79 @@ pub fn get_branch_name() -> Result<String> {
         .to_owned())
 }
 
+/// Name of the output repository selected using `--branch`, overrides the TREE branch name
+#[inline]
+pub fn get_output_branch() -> Option<String> {
+    std::env::var("CIEL_OUTPUT_BRANCH")
+        .ok()
+        .filter(|x| !x.is_empty())
+}
+
+/// Select the named output repository (`OUTPUT-<name>`) for the following operations
+pub fn set_output_branch(name: &str) -> Result<()> {
+    if name.is_empty()
+        || name.starts_with('.')
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        bail!("Invalid output repository name: {}", name);
+    }
+    std::env::set_var("CIEL_OUTPUT_BRANCH", name);
+
+    Ok(())
+}
+
+/// Limit the resources of the instances started afterwards (e.g. `ciel build --cpus 8 --memory 16G`),
+/// overriding the limits in the configuration
+pub fn set_resource_limits(cpus: Option<u32>, memory: Option<&str>) -> Result<()> {
+    if let Some(cpus) = cpus {
+        if cpus == 0 {
+            bail!("The number of CPUs must be positive.");
+        }
+        std::env::set_var("CIEL_CPUS", cpus.to_string());
+    }
+    if let Some(memory) = memory {
+        let digits = memory.trim_end_matches(|c: char| "KMGTkmgt%".contains(c));
+        if digits.is_empty()
+            || memory.len() - digits.len() > 1
+            || !digits.chars().all(|c| c.is_ascii_digit())
+        {
+            bail!("Invalid memory limit: {} (e.g. 16G or 50%)", memory);
+        }
+        std::env::set_var("CIEL_MEMORY", memory.to_uppercase());
+    }
+
+    Ok(())
+}
+
+/// Resource limits of the instance, from the configuration and the command line
+fn get_resource_limits(instance: &str) -> (config::ResourceLimits, bool) {
+    let mut limits = config::read_instance_config(instance)
+        .map(|c| c.resources)
+        .unwrap_or_default();
+    let cpus = std::env::var("CIEL_CPUS").ok();
+    let memory = std::env::var("CIEL_MEMORY").ok();
+    let from_command_line = cpus.is_some() || memory.is_some();
+    limits.merge(config::ResourceLimits {
+        cpu_quota: cpus.map(|x| format!("{}00%", x)),
+        memory_max: memory,
+        ..Default::default()
+    });
+
+    (limits, from_command_line)
+}
+
 /// Determine the output directory name
 #[inline]
 pub fn get_output_directory(sep_mount: bool) -> String {
+    if let Some(branch) = get_output_branch() {
+        return format!("OUTPUT-{}", branch);
+    }
+    // the packages built for a topic are kept apart
+    if let Some(topic) = config::read_config().ok().and_then(|c| c.topic) {
+        return format!("OUTPUT-{}", topic);
+    }
     if sep_mount {
         format!(
             "OUTPUT-{}",
@@ -46,15 +147,19 @@ pub fn get_output_directory(sep_mount: bool) -> String {
     }
 }
 
-fn commit(instance: &str) -> Result<()> {
+/// Commit the upper layer, or only the changes at `selected` paths
+fn commit(instance: &str, selected: Option<&[PathBuf]>) -> Result<()> {
     get_instance_ns_name(instance)?;
     info!("Un-mounting all the instances...");
     // Un-mount all the instances
     for_each_instance(&container_down)?;
     info!("{}: committing instance...", instance);
     let spinner = create_spinner("Committing upper layer...", 200);
-    let man = &mut *overlayfs::get_overlayfs_manager(instance)?;
-    man.commit()?;
+    let man = &mut *overlayfs::get_layer_manager(instance)?;
+    match selected {
+        Some(paths) => man.commit_selected(paths)?,
+        None => man.commit()?,
+    }
     sync();
     spinner.finish_and_clear();
 
@@ -66,7 +171,7 @@ fn rollback(instance: &str) -> Result<()> {
     get_instance_ns_name(instance)?;
     info!("{}: rolling back instance...", instance);
     let spinner = create_spinner("Removing upper layer...", 200);
-    let man = &mut *overlayfs::get_overlayfs_manager(instance)?;
+    let man = &mut *overlayfs::get_layer_manager(instance)?;
     man.rollback()?;
     sync();
     spinner.finish_and_clear();
@@ -74,16 +179,17 @@ fn rollback(instance: &str) -> Result<()> {
     Ok(())
 }
 
-/// Remove everything in the current workspace
-pub fn farewell(path: &Path) -> Result<()> {
-    if !user_attended() {
-        eprintln!("DELETE THIS CIEL WORKSPACE?");
-        info!("Not controlled by an user. Automatically confirmed.");
+/// Remove everything in the current workspace, `confirmed` skips the confirmation prompts
+pub fn farewell(path: &Path, confirmed: bool) -> Result<()> {
+    if confirmed {
+        info!("Deletion confirmed with --yes.");
         // Un-mount all the instances
         for_each_instance(&container_down)?;
+        overlayfs::unmount_dist_image()?;
         fs::remove_dir_all(path.join(".ciel"))?;
         return Ok(());
     }
+    ensure_interactive("please confirm the deletion with `ciel farewell --yes`")?;
     let theme = ColorfulTheme::default();
     let delete = Confirm::with_theme(&theme)
         .with_prompt("DELETE THIS CIEL WORKSPACE?")
@@ -110,47 +216,198 @@ pub fn farewell(path: &Path) -> Result<()> {
     info!("Un-mounting all the instances...");
     // Un-mount all the instances
     for_each_instance(&container_down)?;
+    overlayfs::unmount_dist_image()?;
     fs::remove_dir_all(path.join(".ciel"))?;
 
     Ok(())
 }
 
+/// Pack the base OS into a compressed read-only image, mounted as the base layer of the instances
+pub fn pack_os(format: overlayfs::DistImageFormat) -> Result<()> {
+    info!("Shutting down instance(s) before packing the base OS...");
+    for_each_instance(&container_down)?;
+    let spinner = create_spinner("Packing the base OS...", 200);
+    overlayfs::pack_dist(format)?;
+    spinner.finish_and_clear();
+    info!("Base OS packed into {} image.", format.name());
+
+    Ok(())
+}
+
+/// Unpack the base OS image back into a directory, so that it can be updated or committed to
+pub fn unpack_os() -> Result<()> {
+    info!("Shutting down instance(s) before unpacking the base OS...");
+    for_each_instance(&container_down)?;
+    let spinner = create_spinner("Unpacking the base OS image...", 200);
+    overlayfs::unpack_dist()?;
+    spinner.finish_and_clear();
+    info!("Base OS unpacked.");
+
+    Ok(())
+}
+
 /// Download the OS tarball and then extract it for use as the base layer
-pub fn load_os(url: &str, sha256: Option<String>, tarball: bool) -> Result<()> {
-    info!("Downloading base OS rootfs...");
-    let path = Path::new(url);
+/// With `stream`, the tarball is extracted while it is being downloaded instead of being saved first
+pub fn load_os(url: &str, sha256: Option<String>, tarball: bool, stream: bool) -> Result<()> {
+    let sha256 = fetch_os(url, sha256, tarball, stream)?;
+    if let Err(e) = record_dist_source(url, sha256) {
+        warn!("Unable to record the source of the base OS: {}", e);
+    }
+
+    run_hook(Hook::PostLoadOs, None, &[])
+}
+
+/// Returns the SHA-256 checksum of the tarball if known
+fn fetch_os(
+    url: &str,
+    sha256: Option<String>,
+    tarball: bool,
+    stream: bool,
+) -> Result<Option<String>> {
+    if let Some(cached) = sha256.as_deref().and_then(find_cached_tarball) {
+        info!("Using cached base OS rootfs {}", cached.display());
+        let total = fs::metadata(&cached)?.len();
+        extract_system_rootfs(&cached, total, tarball)?;
+        return Ok(sha256);
+    }
+    let multi_source = strip_multi_source_suffix(url);
+    let path = Path::new(multi_source.unwrap_or(url));
+    if stream && !path.is_file() {
+        return stream_os(multi_source.unwrap_or(url), sha256.as_deref(), tarball).map(Some);
+    }
     let filename = path
         .file_name()
         .ok_or_else(|| anyhow!("Unable to convert path to string"))?
         .to_str()
         .ok_or_else(|| anyhow!("Unable to decode path string"))?;
     let is_local_file = path.is_file();
-    let total = if !is_local_file {
-        download_file_progress(url, filename)?
+    if !is_local_file {
+        ensure_online("Downloading the base OS rootfs")?;
+        info!("Downloading base OS rootfs...");
+    }
+    let total = if let Some(multi_source) = multi_source.filter(|_| !is_local_file) {
+        download_file_multi_source(url, filename, sha256.as_deref()).or_else(|e| {
+            // the descriptor itself may be unavailable
+            warn!("Multi-source download failed: {}", e);
+            download_file_progress(multi_source, filename, sha256.as_deref())
+        })?
+    } else if !is_local_file {
+        // checksum is verified after the download is completed
+        download_file_progress(url, filename, sha256.as_deref())?
     } else {
         let tarball = fs::File::open(path)?;
         tarball.metadata()?.len()
     };
-    if let Some(sha256) = sha256 {
-        info!("Verifying tarball checksum...");
-        let tarball = fs::File::open(Path::new(filename))?;
-        let checksum = sha256sum(tarball)?;
-        if sha256 == checksum {
-            info!("Checksum verified.");
+
+    if is_local_file {
+        extract_system_rootfs(&PathBuf::from(path), total, tarball)?;
+    } else {
+        // the checksum comes from the signed manifest, otherwise the tarball is verified itself
+        if sha256.is_none() {
+            if let Err(e) =
+                network::verify_downloaded_file(multi_source.unwrap_or(url), Path::new(filename))
+            {
+                fs::remove_file(filename).ok();
+                return Err(e);
+            }
+        }
+        if let Some(sha256) = &sha256 {
+            if let Err(e) = cache_tarball(Path::new(filename), sha256) {
+                warn!("Unable to save the tarball to the cache: {}", e);
+            }
+        }
+        extract_system_rootfs(Path::new(filename), total, tarball)?;
+    }
+
+    Ok(sha256)
+}
+
+/// Extract the tarball while downloading it, the checksum is computed on the fly
+/// and the extracted base OS is discarded if it does not match. Returns the checksum.
+fn stream_os(url: &str, sha256: Option<&str>, tarball: bool) -> Result<String> {
+    if !tarball {
+        bail!("Only tarballs can be extracted while downloading, not squashfs images.");
+    }
+    ensure_online("Downloading the base OS rootfs")?;
+    info!("Downloading and extracting base OS rootfs...");
+    let (mut reader, total) = network::open_download_stream(url)?;
+    // the checksum comes from the signed manifest, otherwise the tarball is verified itself
+    let mut verifier = None;
+    if sha256.is_none() {
+        if network::is_insecure() {
+            warn!("Signature verification skipped (--insecure).");
         } else {
-            return Err(anyhow!(
+            let (tee, stream_verifier) = network::verify_stream(url, reader)?;
+            reader = tee;
+            verifier = Some(stream_verifier);
+        }
+    }
+    let checksum = extract_system_rootfs_stream(reader, total)?;
+    // the base OS has been replaced, remove it if it turns out to be untrustworthy
+    let discard = |e: anyhow::Error| -> Result<String> {
+        fs::remove_dir_all(CIEL_DIST_DIR).ok();
+        fs::create_dir_all(CIEL_DIST_DIR)?;
+        Err(anyhow!("{}, the extracted base OS has been removed.", e))
+    };
+    if let Some(verifier) = verifier {
+        if let Err(e) = verifier.finish() {
+            return discard(e);
+        }
+        info!("Signature verified.");
+    }
+    if let Some(sha256) = sha256 {
+        if sha256 != checksum {
+            return discard(anyhow!(
                 "Checksum mismatch: expected {} but got {}",
                 sha256,
                 checksum
             ));
         }
+        info!("Checksum verified.");
     }
 
-    if is_local_file {
-        extract_system_rootfs(&PathBuf::from(path), total, tarball)?;
-    } else {
-        extract_system_rootfs(Path::new(filename), total, tarball)?;
+    Ok(checksum)
+}
+
+/// Update the base layer incrementally from an rsync source (a rsync:// URL or a local directory),
+/// only the changed files are transferred instead of the whole tarball
+pub fn sync_os(source: &str) -> Result<()> {
+    which::which("rsync").map_err(|_| anyhow!("rsync is required for incremental updates."))?;
+    if source.contains("://") {
+        ensure_online("Synchronizing the base OS")?;
     }
+    overlayfs::ensure_dist_writable("synchronizing the base OS")?;
+    info!("Shutting down instance(s) before updating the base OS...");
+    for_each_instance(&container_down)?;
+    fs::create_dir_all(CIEL_DIST_DIR)?;
+    info!("Synchronizing base OS rootfs from {}...", source);
+    // trailing slashes: synchronize the contents instead of the directory itself
+    let status = std::process::Command::new("rsync")
+        .args([
+            "-aHAX",
+            "--numeric-ids",
+            "--delete",
+            "--info=progress2",
+            &format!("{}/", source.trim_end_matches('/')),
+            &format!("{}/", CIEL_DIST_DIR),
+        ])
+        .status()
+        .map_err(|e| anyhow!("Unable to execute rsync: {}", e))?;
+    if !status.success() {
+        bail!("rsync exited with error: {}", status);
+    }
+    if let Err(e) = crate::qemu::setup_emulation(Path::new(CIEL_DIST_DIR)) {
+        warn!("Unable to set up QEMU user-mode emulation: {}", e);
+    }
+    // the configuration in the base layer has been overwritten
+    if let Ok(c) = config::read_config() {
+        config::apply_config(CIEL_DIST_DIR, &c)?;
+    }
+    if let Err(e) = record_dist_source(source, None) {
+        warn!("Unable to record the source of the base OS: {}", e);
+    }
+    info!("Base OS updated.");
+    warn!("Please rollback all your instances for the changes to take effect!");
 
     Ok(())
 }
@@ -167,9 +424,10 @@ pub fn config_os(instance: Option<&str>) -> Result<()> {
     }
     let path;
     if let Some(instance) = instance {
-        let man = &mut *overlayfs::get_overlayfs_manager(instance)?;
+        let man = &mut *overlayfs::get_layer_manager(instance)?;
         path = man.get_config_layer()?;
     } else {
+        overlayfs::ensure_dist_writable("configuring the base OS")?;
         path = PathBuf::from(CIEL_DIST_DIR);
     }
     if let Ok(c) = config {
@@ -180,11 +438,7 @@ pub fn config_os(instance: Option<&str>) -> Result<()> {
             for_each_instance(&container_down)?;
         }
         config::apply_config(path, &c)?;
-        fs::create_dir_all(CIEL_DATA_DIR)?;
-        fs::write(
-            Path::new(CIEL_DATA_DIR).join("config.toml"),
-            c.save_config()?,
-        )?;
+        config::write_config(&c)?;
         info!("Configurations applied.");
         let volatile_changed = if let Some(prev_voltile) = prev_volatile {
             prev_voltile != c.volatile_mount
@@ -197,11 +451,7 @@ pub fn config_os(instance: Option<&str>) -> Result<()> {
         }
         warn!(
             "Please rollback {} for the new config to take effect!",
-            if let Some(inst) = instance {
-                inst
-            } else {
-                "all your instances"
-            }
+            instance.unwrap_or("all your instances")
         );
     } else {
         return Err(anyhow!("Could not recognize the configuration."));
@@ -210,10 +460,75 @@ pub fn config_os(instance: Option<&str>) -> Result<()> {
     Ok(())
 }
 
+/// Edit the workspace configuration file and then apply it
+pub fn edit_config() -> Result<()> {
+    overlayfs::ensure_dist_writable("configuring the base OS")?;
+    let c = config::edit_config()?;
+    info!("Shutting down instance(s) before applying config...");
+    for_each_instance(&container_down)?;
+    config::apply_config(CIEL_DIST_DIR, &c)?;
+    config::write_config(&c)?;
+    info!("Configurations applied.");
+    warn!("Please rollback all your instances for the new config to take effect!");
+
+    Ok(())
+}
+
+/// Apply the configuration overrides of the instance (if any) to its configuration layer
+pub fn apply_instance_config(instance: &str) -> Result<()> {
+    if !config::has_instance_config(instance) {
+        return Ok(());
+    }
+    let c = config::read_instance_config(instance)?;
+    if let Some(branch) = &c.branch {
+        let current = get_branch_name()?;
+        if *branch != current {
+            bail!(
+                "{} tracks the {} branch, but TREE is on {}. Please run `ciel update-tree {}` first.",
+                instance,
+                branch,
+                current,
+                branch
+            );
+        }
+    }
+    // the configuration layer must not be modified while mounted
+    container_down(instance)?;
+    let man = &mut *overlayfs::get_layer_manager(instance)?;
+    config::apply_config(man.get_config_layer()?, &c)?;
+    info!("{}: instance configuration applied.", instance);
+
+    Ok(())
+}
+
+/// Check the free space required for updating the OS (`free-space.update`)
+fn check_update_space() -> Result<()> {
+    let config = config::read_config()
+        .map(|c| c.free_space)
+        .unwrap_or_default();
+
+    ensure_free_space(config.update_requirement()?, "updating the OS")
+}
+
+/// Update the OS in the instance only (using the configuration of the instance)
+pub fn update_instance(instance: &str) -> Result<()> {
+    check_update_space()?;
+    apply_instance_config(instance)?;
+    mount_fs(instance)?;
+    info!("{}: updating OS...", instance);
+    let status = super::packaging::update_instance_os(instance, None);
+    if status != 0 {
+        bail!("{}: failed to update the OS (status: {})", instance, status);
+    }
+    info!("{}: OS updated.", instance);
+
+    Ok(())
+}
+
 /// Mount the filesystem of the instance
 pub fn mount_fs(instance: &str) -> Result<()> {
     let config = config::read_config()?;
-    let man = &mut *overlayfs::get_overlayfs_manager(instance)?;
+    let man = &mut *overlayfs::get_layer_manager(instance)?;
     man.set_volatile(config.volatile_mount)?;
     machine::mount_layers(man, instance)?;
     info!("{}: filesystem mounted.", instance);
@@ -223,7 +538,7 @@ pub fn mount_fs(instance: &str) -> Result<()> {
 
 /// Un-mount the filesystem of the container
 pub fn unmount_fs(instance: &str) -> Result<()> {
-    let man = &mut *overlayfs::get_overlayfs_manager(instance)?;
+    let man = &mut *overlayfs::get_layer_manager(instance)?;
     let target = std::env::current_dir()?.join(instance);
     let mut retry = 0usize;
     while man.is_mounted(&target)? {
@@ -267,7 +582,7 @@ pub fn remove_mount(instance: &str) -> Result<()> {
     Ok(())
 }
 
-fn get_instance_ns_name(instance: &str) -> Result<String> {
+pub(super) fn get_instance_ns_name(instance: &str) -> Result<String> {
     if !is_instance_exists(instance) {
         error!("Instance `{}` does not exist.", instance);
         info!(
@@ -281,20 +596,93 @@ fn get_instance_ns_name(instance: &str) -> Result<String> {
     get_container_ns_name(instance, legacy)
 }
 
+/// Options passing the devices into the container: bind-mounted and allowed by the device policy,
+/// for the directories (e.g. `/dev/dri`) each device node in them is allowed
+fn device_options(devices: &[String]) -> Result<Vec<String>> {
+    let mut options = Vec::new();
+    for device in devices {
+        let path = Path::new(device);
+        if !path.starts_with("/dev") || device.contains(':') {
+            bail!("Invalid device: {}", device);
+        }
+        let metadata =
+            fs::metadata(path).map_err(|e| anyhow!("Unable to pass {}: {}", device, e))?;
+        options.push(format!("--bind={}", device));
+        let nodes = if metadata.is_dir() {
+            fs::read_dir(path)?
+                .filter_map(|x| x.ok())
+                .filter(|x| {
+                    x.file_type()
+                        .is_ok_and(|x| x.is_char_device() || x.is_block_device())
+                })
+                .map(|x| x.path().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        } else {
+            vec![device.clone()]
+        };
+        for node in nodes {
+            options.push(format!("--property=DeviceAllow={} rwm", node));
+        }
+    }
+
+    Ok(options)
+}
+
 /// Start the container/instance, also mounting the container filesystem prior to the action
 pub fn start_container(instance: &str) -> Result<String> {
     let ns_name = get_instance_ns_name(instance)?;
     let inst = inspect_instance(instance, &ns_name)?;
     let (mut extra_options, mounts) = ensure_host_sanity()?;
+    let remote_compile = config::read_config().is_ok_and(|c| c.remote_compile.enabled());
     if std::env::var("CIEL_OFFLINE").is_ok() {
-        // FIXME: does not work with current version of systemd
-        // add the offline option (private-network means don't share the host network)
-        extra_options.push("--private-network".to_string());
-        info!("{}: network disconnected.", instance);
+        if remote_compile {
+            // compile jobs are sent to the remote machines, the network is required
+            warn!(
+                "{}: remote compilation is enabled, network will not be disconnected.",
+                instance
+            );
+        } else {
+            // FIXME: does not work with current version of systemd
+            // add the offline option (private-network means don't share the host network)
+            extra_options.push("--private-network".to_string());
+            info!("{}: network disconnected.", instance);
+        }
+    }
+    let (limits, from_command_line) = get_resource_limits(instance);
+    if inst.started && from_command_line {
+        warn!(
+            "{}: instance is already running, stop it first to apply the resource limits.",
+            instance
+        );
+    }
+    extra_options.extend(
+        limits
+            .properties()
+            .into_iter()
+            .map(|x| format!("--property={}", x)),
+    );
+    let (bind_mounts, devices, network) = config::read_instance_config(instance)
+        .map(|c| (c.bind_mounts, c.devices, c.network))
+        .unwrap_or_default();
+    for mount in bind_mounts.iter() {
+        extra_options.push(mount.nspawn_option()?);
     }
+    extra_options.extend(device_options(&devices)?);
+    extra_options.extend(network.nspawn_options()?);
+    extra_options.extend(super::port::port_options(instance, network.is_private())?);
     if !inst.mounted {
         mount_fs(instance)?;
     }
+    if !inst.started && super::has_checkpoint(instance) {
+        warn!(
+            "{}: instance has a checkpoint, starting it afresh will make the checkpoint unusable.",
+            instance
+        );
+        info!(
+            "Use `ciel restore -i {}` to resume the instance instead.",
+            instance
+        );
+    }
     if !inst.started {
         spawn_container(&ns_name, instance, &extra_options, &mounts)?;
     }
@@ -304,12 +692,155 @@ pub fn start_container(instance: &str) -> Result<String> {
 
 /// Execute the specified command in the container
 pub fn run_in_container<S: AsRef<OsStr>>(instance: &str, args: &[S]) -> Result<i32> {
+    run_in_container_with_output(instance, args, None)
+}
+
+/// Run the command (an interactive shell if empty) in the container, in the working directory
+/// (the home directory of the user if not specified) as the user (created if missing,
+/// with the same UID as the user on the host if any), returns the exit code of the command
+pub fn shell_in_container(
+    instance: &str,
+    workdir: Option<&str>,
+    user: Option<&str>,
+    command: &[String],
+) -> Result<i32> {
+    // the same UID keeps the files in the shared directories (e.g. TREE) accessible on the host
+    let uid = user
+        .and_then(|x| nix::unistd::User::from_name(x).ok().flatten())
+        .map(|x| x.uid.to_string());
+    let mut args = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        SHELL_WRAPPER.to_string(),
+        "sh".to_string(),
+        user.unwrap_or_default().to_string(),
+        uid.unwrap_or_default(),
+        workdir.unwrap_or_default().to_string(),
+        "/bin/bash".to_string(),
+    ];
+    if !command.is_empty() {
+        args.push("-ec".to_string());
+        args.push(command.join(" "));
+    }
+
+    run_in_container(instance, &args)
+}
+
+/// Execute the specified command in the container, redirecting its output to the specified file (if any)
+pub fn run_in_container_with_output<S: AsRef<OsStr>>(
+    instance: &str,
+    args: &[S],
+    output: Option<&fs::File>,
+) -> Result<i32> {
     let ns_name = start_container(instance)?;
-    let status = machine::execute_container_command(&ns_name, args)?;
+    let status = machine::execute_container_command_with_output(&ns_name, args, output)?;
 
     Ok(status)
 }
 
+/// Color of the `[instance]` prefix, stable for the same instance
+fn instance_color(instance: &str) -> Color {
+    const COLORS: &[Color] = &[
+        Color::Cyan,
+        Color::Green,
+        Color::Yellow,
+        Color::Magenta,
+        Color::Blue,
+        Color::Red,
+    ];
+    let hash = instance.bytes().fold(0usize, |acc, x| {
+        acc.wrapping_mul(31).wrapping_add(x as usize)
+    });
+
+    COLORS[hash % COLORS.len()]
+}
+
+/// Execute the specified command in the container, copying its output to both the terminal and the log file.
+/// With `prefix`, each line on the terminal is prefixed with the colored `[instance]`
+/// (e.g. when several instances are building at the same time), the log file is left untouched.
+/// With `watchdog`, the command is aborted if it hangs (returning [WATCHDOG_STATUS]).
+pub fn run_in_container_with_log<S: AsRef<OsStr>>(
+    instance: &str,
+    args: &[S],
+    log: &fs::File,
+    prefix: bool,
+    watchdog: Option<&WatchdogConfig>,
+) -> Result<i32> {
+    // the pipe must not be inherited by the container itself, or the reader will never see EOF
+    let (reader, writer) = pipe2(OFlag::O_CLOEXEC)?;
+    let mut reader = fs::File::from(reader);
+    let mut log = log.try_clone()?;
+    let watchdog = watchdog.and_then(Watchdog::new).map(Arc::new);
+    let monitor = match &watchdog {
+        Some(watchdog) => {
+            let watchdog = watchdog.clone();
+            let instance = instance.to_string();
+            let ns_name = get_instance_ns_name(&instance)?;
+            Some(std::thread::spawn(move || {
+                watchdog.watch(&instance, &ns_name)
+            }))
+        }
+        None => None,
+    };
+    let output_watchdog = watchdog.clone();
+    let prefix = prefix.then(|| {
+        format!(
+            "{} ",
+            style(format!("[{}]", instance)).fg(instance_color(instance))
+        )
+    });
+    let tee = std::thread::spawn(move || -> std::io::Result<()> {
+        let mut buffer = [0u8; 8192];
+        let mut line = Vec::new();
+        let stderr = std::io::stderr();
+        loop {
+            let size = reader.read(&mut buffer)?;
+            if let Some(watchdog) = &output_watchdog {
+                watchdog.feed();
+            }
+            if size == 0 && !line.is_empty() {
+                line.push(b'\n');
+            }
+            log.write_all(&buffer[..size])?;
+            let prefix = match &prefix {
+                Some(prefix) => prefix,
+                None if size == 0 => return Ok(()),
+                None => {
+                    stderr.lock().write_all(&buffer[..size])?;
+                    continue;
+                }
+            };
+            // whole lines only, so that the output of the instances is not mixed up
+            line.extend_from_slice(&buffer[..size]);
+            while let Some(end) = line.iter().position(|x| *x == b'\n') {
+                let mut stderr = stderr.lock();
+                stderr.write_all(prefix.as_bytes())?;
+                stderr.write_all(&line[..=end])?;
+                line.drain(..=end);
+            }
+            if size == 0 {
+                return Ok(());
+            }
+        }
+    });
+    let writer = fs::File::from(writer);
+    let status = run_in_container_with_output(instance, args, Some(&writer));
+    drop(writer);
+    let mut aborted = false;
+    if let (Some(watchdog), Some(monitor)) = (watchdog, monitor) {
+        watchdog.stop();
+        monitor.join().ok();
+        aborted = watchdog.fired();
+    }
+    tee.join()
+        .map_err(|_| anyhow!("Unable to write the build log"))??;
+    if aborted {
+        return Ok(WATCHDOG_STATUS);
+    }
+
+    status
+}
+
 /// Stop the container/instance (without un-mounting the filesystem)
 pub fn stop_container(instance: &str) -> Result<()> {
     let ns_name = get_instance_ns_name(instance)?;
@@ -322,6 +853,11 @@ pub fn stop_container(instance: &str) -> Result<()> {
     machine::terminate_container_by_name(&ns_name)?;
     machine::clean_child_process();
     info!("{}: instance stopped.", instance);
+    if is_tmpfs_instance(instance) {
+        // the changes are not kept for the instances on tmpfs
+        unmount_fs(instance)?;
+        info!("{}: changes discarded.", instance);
+    }
 
     Ok(())
 }
@@ -338,19 +874,97 @@ pub fn container_down(instance: &str) -> Result<()> {
 /// Commit the container/instance upper layer changes to the base layer of the filesystem
 pub fn commit_container(instance: &str) -> Result<()> {
     container_down(instance)?;
-    commit(instance)?;
+    commit(instance, None)?;
     info!("{}: instance has been committed.", instance);
 
     Ok(())
 }
 
+/// List the changes made in the instance (relative to the base OS)
+pub fn instance_changes(instance: &str) -> Result<Vec<overlayfs::Change>> {
+    get_instance_ns_name(instance)?;
+    let man = &mut *overlayfs::get_layer_manager(instance)?;
+
+    man.list_changes()
+}
+
+/// Ask which of the changes should be committed, similar to `git add -p`
+fn select_changes(changes: Vec<overlayfs::Change>) -> Result<Vec<PathBuf>> {
+    let theme = ColorfulTheme::default();
+    let total = changes.len();
+    let mut selected = Vec::new();
+    let mut changes = changes.into_iter().enumerate();
+    while let Some((i, change)) = changes.next() {
+        let choice = Select::with_theme(&theme)
+            .with_prompt(format!(
+                "({}/{}) {} /{}: commit this change?",
+                i + 1,
+                total,
+                change.kind.symbol(),
+                change.path.display()
+            ))
+            .items(&[
+                "yes",
+                "no",
+                "all the remaining changes",
+                "none of the remaining changes",
+            ])
+            .default(0)
+            .interact()?;
+        match choice {
+            0 => selected.push(change.path),
+            1 => (),
+            2 => {
+                selected.push(change.path);
+                selected.extend(changes.by_ref().map(|(_, x)| x.path));
+            }
+            _ => break,
+        }
+    }
+
+    Ok(selected)
+}
+
+/// Commit only some of the changes to the base layer, the rest stays in the instance.
+/// `paths` limits the changes to the given paths (in the instance), `interactive` asks for each change
+pub fn commit_container_selected(
+    instance: &str,
+    paths: &[PathBuf],
+    interactive: bool,
+) -> Result<()> {
+    if interactive {
+        ensure_interactive("use --path to select the changes instead")?;
+    }
+    container_down(instance)?;
+    let changes = filter_changes(instance_changes(instance)?, paths);
+    let selected = if interactive {
+        select_changes(changes)?
+    } else {
+        changes.into_iter().map(|x| x.path).collect()
+    };
+    if selected.is_empty() {
+        info!("{}: nothing to commit.", instance);
+        return Ok(());
+    }
+    commit(instance, Some(&selected))?;
+    info!(
+        "{}: {} change(s) committed, the others are kept in the instance.",
+        instance,
+        selected.len()
+    );
+
+    Ok(())
+}
+
 /// Clear the upper layer of the container/instance filesystem
 pub fn rollback_container(instance: &str) -> Result<()> {
     container_down(instance)?;
     rollback(instance)?;
+    // the checkpoint refers to the files in the upper layer
+    super::discard_checkpoint(instance)?;
     info!("{}: instance has been rolled back.", instance);
 
-    Ok(())
+    run_hook(Hook::PostRollback, Some(instance), &[])
 }
 
 /// Create a new instance
@@ -359,6 +973,82 @@ pub fn add_instance(instance: &str) -> Result<()> {
     overlayfs::create_new_instance_fs(CIEL_INST_DIR, instance)?;
     info!("{}: instance created.", instance);
 
+    run_hook(Hook::PostAdd, Some(instance), &[])
+}
+
+/// Create a new instance whose upper layer is on tmpfs, `size` limits the size of the tmpfs
+pub fn add_tmpfs_instance(instance: &str, size: Option<&str>) -> Result<()> {
+    if let Some(size) = size {
+        config::validate_tmpfs_size(size)?;
+    }
+    overlayfs::create_new_instance_fs(CIEL_INST_DIR, instance)?;
+    let mut overrides = config::InstanceConfig::default();
+    overrides.tmpfs = Some(config::TmpfsConfig {
+        size: size.map(|x| x.to_string()),
+    });
+    config::write_instance_overrides(instance, &overrides)?;
+    info!("{}: instance created (upper layer on tmpfs).", instance);
+
+    run_hook(Hook::PostAdd, Some(instance), &[])
+}
+
+/// Create a new instance running in a virtual machine, `kernel` is the kernel image to boot
+pub fn add_vm_instance(instance: &str, kernel: Option<&Path>) -> Result<()> {
+    let kernel = kernel.map(fs::canonicalize).transpose()?;
+    overlayfs::create_new_instance_fs(CIEL_INST_DIR, instance)?;
+    let mut overrides = config::InstanceConfig::default();
+    overrides.backend = Some(BackendKind::Vm);
+    overrides.vm = Some(config::VmConfig {
+        kernel,
+        ..Default::default()
+    });
+    config::write_instance_overrides(instance, &overrides)?;
+    info!(
+        "{}: instance created (running in a virtual machine).",
+        instance
+    );
+
+    run_hook(Hook::PostAdd, Some(instance), &[])
+}
+
+/// Returns true if the upper layer of the instance is on tmpfs
+#[inline]
+pub fn is_tmpfs_instance(instance: &str) -> bool {
+    config::read_instance_overrides(instance).is_ok_and(|x| x.is_some_and(|x| x.tmpfs.is_some()))
+}
+
+/// Duplicate the instance (filesystem changes and configuration) into a new instance
+pub fn clone_instance(source: &str, target: &str) -> Result<()> {
+    if !is_instance_exists(source) {
+        bail!("Instance {} does not exist.", source);
+    }
+    if is_instance_exists(target) {
+        bail!("Instance {} already exists.", target);
+    }
+    // make sure the filesystem is in a consistent state
+    container_down(source)?;
+    info!("{}: cloning into {}...", source, target);
+    let spinner = create_spinner("Cloning the instance...", 200);
+    let man = &mut *overlayfs::get_layer_manager(source)?;
+    let result = man.clone_to(target).and_then(|_| {
+        let instance_config = Path::new(CIEL_INST_DIR).join(source).join("config.toml");
+        if instance_config.is_file() {
+            fs::copy(
+                instance_config,
+                Path::new(CIEL_INST_DIR).join(target).join("config.toml"),
+            )?;
+        }
+
+        Ok(())
+    });
+    spinner.finish_and_clear();
+    if let Err(e) = result {
+        // clean up the incomplete instance
+        fs::remove_dir_all(Path::new(CIEL_INST_DIR).join(target)).ok();
+        return Err(e);
+    }
+    info!("{}: instance created.", target);
+
     Ok(())
 }
 
@@ -367,7 +1057,8 @@ pub fn remove_instance(instance: &str) -> Result<()> {
     container_down(instance)?;
     info!("{}: removing instance...", instance);
     let spinner = create_spinner("Removing the instance...", 200);
-    let man = &mut *overlayfs::get_overlayfs_manager(instance)?;
+    super::snapshot::remove_instance_snapshots(instance)?;
+    let man = &mut *overlayfs::get_layer_manager(instance)?;
     man.destroy()?;
     spinner.finish_and_clear();
     info!("{}: instance removed.", instance);
@@ -377,6 +1068,8 @@ pub fn remove_instance(instance: &str) -> Result<()> {
 
 /// Update AOSC OS in the container/instance
 pub fn update_os(force_use_apt: bool) -> Result<()> {
+    overlayfs::ensure_dist_writable("updating the base OS")?;
+    check_update_space()?;
     info!("Updating base OS...");
     let instance = format!("update-{:x}", random::<u32>());
     add_instance(&instance)?;
@@ -385,7 +1078,7 @@ pub fn update_os(force_use_apt: bool) -> Result<()> {
         return apt_update_os(&instance);
     }
 
-    let status = run_in_container(&instance, &["/bin/bash", "-ec", OMA_UPDATE_SCRIPT])?;
+    let (status, _) = run_apt_script_with_progress(&instance, OMA_UPDATE_SCRIPT)?;
     if status != 0 {
         return apt_update_os(&instance);
     }
@@ -396,15 +1089,62 @@ pub fn update_os(force_use_apt: bool) -> Result<()> {
     Ok(())
 }
 
+/// Run the script in the base OS directly using a non-booted container, with the shared package cache
+fn run_in_dist(script: &str) -> Result<i32> {
+    let _lock = lock_apt_cache()?;
+    let cache = fs::canonicalize(APT_CACHE_HOST_DIR)?;
+    let mut command = std::process::Command::new("systemd-nspawn");
+    command
+        .args(["--quiet", "--register=no", "--resolv-conf=bind-host", "-D"])
+        .arg(CIEL_DIST_DIR)
+        .arg(format!(
+            "--bind={}:/var/cache/apt/archives",
+            cache.display()
+        ));
+    for (name, value) in network::proxy_environment() {
+        command.arg(format!("--setenv={}={}", name, value));
+    }
+    let status = command
+        .args(["/bin/bash", "-ec", script])
+        .status()
+        .map_err(|e| anyhow!("Unable to execute systemd-nspawn: {}", e))?;
+
+    Ok(status.code().unwrap_or(127))
+}
+
+/// Update the base OS in place without creating and booting an instance (quicker than `update_os`)
+pub fn update_dist(force_use_apt: bool) -> Result<()> {
+    overlayfs::ensure_dist_writable("updating the base OS")?;
+    check_update_space()?;
+    info!("Shutting down instance(s) before updating the base OS...");
+    // the base OS must not change under the mounted instances
+    for_each_instance(&container_down)?;
+    info!("Updating base OS...");
+    let mut status = -1;
+    if !force_use_apt {
+        status = run_in_dist(OMA_UPDATE_SCRIPT)?;
+    }
+    if status != 0 {
+        status = run_in_dist(APT_UPDATE_SCRIPT)?;
+    }
+    if status != 0 {
+        bail!("Failed to update OS: {}", status);
+    }
+    sync();
+    info!("Base OS updated.");
+
+    Ok(())
+}
+
 fn apt_update_os(instance: &str) -> Result<()> {
-    let status = run_in_container(instance, &["/bin/bash", "-ec", APT_UPDATE_SCRIPT])?;
+    let (status, _) = run_apt_script_with_progress(instance, APT_UPDATE_SCRIPT)?;
 
     if status != 0 {
         return Err(anyhow!("Failed to update OS: {}", status));
     }
 
-    commit_container(&instance)?;
-    remove_instance(&instance)?;
+    commit_container(instance)?;
+    remove_instance(instance)?;
 
     Ok(())
 }
@@ -0,0 +1,463 @@
+//! Dependency ordering of the packages using the build recipes in TREE
+
+use anyhow::{anyhow, Result};
+use console::style;
+use git2::Repository;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    path::Path,
+};
+
+use crate::{info, warn};
+
+use super::packaging::BuildSettings;
+
+const TREE_DIR: &str = "TREE";
+/// Variables in the `defines` files listing the dependencies
+const DEPENDENCY_VARIABLES: &[&str] = &["PKGDEP", "BUILDDEP"];
+
+/// Dependencies of the packages in the tree, keyed by the package (directory) name
+struct DependencyGraph {
+    /// Package directory name -> names of the packages it depends on
+    dependencies: HashMap<String, Vec<String>>,
+    /// Binary package name (PKGNAME) -> package directory name
+    providers: HashMap<String, String>,
+}
+
+/// Read the value of the shell variable from a `defines` file.
+/// Quoted values spanning multiple lines and line continuations are supported.
+pub(super) fn read_variable(defines: &str, name: &str) -> Option<String> {
+    let prefix = format!("{}=", name);
+    let start = defines
+        .lines()
+        .scan(0, |pos, line| {
+            let start = *pos;
+            *pos += line.len() + 1;
+            Some((start, line))
+        })
+        .find(|(_, line)| line.trim_start().starts_with(&prefix))
+        .map(|(start, line)| start + line.len() - line.trim_start().len() + prefix.len())?;
+    let rest = &defines[start..];
+    let value = match rest.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let rest = &rest[1..];
+            &rest[..rest.find(quote).unwrap_or(rest.len())]
+        }
+        _ => rest.lines().next().unwrap_or_default(),
+    };
+
+    Some(value.replace("\\\n", " "))
+}
+
+/// Extract the package names from a dependency list (e.g. `foo>=1.0 bar`)
+fn parse_dependencies(value: &str) -> Vec<String> {
+    value
+        .split_whitespace()
+        .map(|x| x.split(['<', '>', '=']).next().unwrap_or(x))
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_string())
+        .collect()
+}
+
+impl DependencyGraph {
+    /// Scan all the `defines` files in the tree
+    fn scan(tree: &Path) -> Result<Self> {
+        let mut dependencies: HashMap<String, Vec<String>> = HashMap::new();
+        let mut providers = HashMap::new();
+        for section in fs::read_dir(tree)?.flatten() {
+            let section_name = section.file_name().to_string_lossy().to_string();
+            if !section.path().is_dir() || section_name.starts_with('.') || section_name == "groups"
+            {
+                continue;
+            }
+            for package in fs::read_dir(section.path())?.flatten() {
+                if !package.path().join("spec").is_file() {
+                    continue;
+                }
+                let name = package.file_name().to_string_lossy().to_string();
+                // split packages have a directory for each of the sub-packages
+                for recipe in fs::read_dir(package.path())?.flatten() {
+                    let defines = match fs::read_to_string(recipe.path().join("defines")) {
+                        Ok(defines) => defines,
+                        Err(_) => continue,
+                    };
+                    if let Some(pkgname) = read_variable(&defines, "PKGNAME") {
+                        providers.insert(pkgname.trim().to_string(), name.clone());
+                    }
+                    let entry = dependencies.entry(name.clone()).or_default();
+                    for variable in DEPENDENCY_VARIABLES {
+                        if let Some(value) = read_variable(&defines, variable) {
+                            entry.extend(parse_dependencies(&value));
+                        }
+                    }
+                }
+                providers.entry(name.clone()).or_insert(name);
+            }
+        }
+
+        Ok(Self {
+            dependencies,
+            providers,
+        })
+    }
+
+    /// Directory name of the package, which may be specified using the binary package name
+    /// or with a section prefix
+    fn resolve(&self, package: &str) -> String {
+        let name = package.rsplit('/').next().unwrap_or(package);
+        self.providers
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// In-tree packages the package depends on (by directory name)
+    fn tree_dependencies(&self, package: &str) -> Vec<&str> {
+        let mut result = Vec::new();
+        for dep in self.dependencies.get(package).into_iter().flatten() {
+            if let Some(provider) = self.providers.get(dep) {
+                if provider != package && !result.contains(&provider.as_str()) {
+                    result.push(provider.as_str());
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Packages in the tree depending on the package (by directory name)
+    fn reverse_dependencies(&self, package: &str) -> Vec<String> {
+        let mut result = self
+            .dependencies
+            .keys()
+            .filter(|x| self.tree_dependencies(x).contains(&package))
+            .cloned()
+            .collect::<Vec<_>>();
+        result.sort_unstable();
+
+        result
+    }
+}
+
+/// Sort the packages so that every package comes after its dependencies,
+/// keeping the original order otherwise. Packages in a dependency cycle are appended as-is.
+fn topological_sort<'a, F: Fn(&str) -> Vec<&'a str>>(
+    packages: &[String],
+    dependencies: F,
+) -> Vec<String> {
+    let requested = packages.iter().map(|x| x.as_str()).collect::<HashSet<_>>();
+    let mut remaining = packages
+        .iter()
+        .map(|x| {
+            let deps = dependencies(x)
+                .into_iter()
+                .filter(|d| requested.contains(d) && *d != x)
+                .collect::<HashSet<_>>();
+            (x.as_str(), deps)
+        })
+        .collect::<Vec<_>>();
+    let mut sorted = Vec::new();
+    while !remaining.is_empty() {
+        let ready = match remaining.iter().position(|(_, deps)| deps.is_empty()) {
+            Some(ready) => ready,
+            None => {
+                warn!(
+                    "Circular dependencies detected among: {}",
+                    remaining.iter().map(|x| x.0).collect::<Vec<_>>().join(", ")
+                );
+                sorted.extend(remaining.iter().map(|x| x.0.to_string()));
+                break;
+            }
+        };
+        let (package, _) = remaining.remove(ready);
+        for (_, deps) in remaining.iter_mut() {
+            deps.remove(package);
+        }
+        sorted.push(package.to_string());
+    }
+
+    sorted
+}
+
+/// Reorder the packages according to their dependencies in TREE,
+/// optionally adding the in-tree dependencies that are not in the list
+/// and the packages depending on the listed ones (reverse dependencies)
+pub(super) fn order_packages(
+    packages: Vec<String>,
+    settings: &BuildSettings,
+) -> Result<Vec<String>> {
+    Ok(order_packages_with_dependencies(packages, settings)?.0)
+}
+
+/// Same as `order_packages`, also returning the packages (by index in the ordered list)
+/// each package depends on. Only the packages ordered before it are included,
+/// so that a dependency cycle can not block the build.
+pub(super) fn order_packages_with_dependencies(
+    packages: Vec<String>,
+    settings: &BuildSettings,
+) -> Result<(Vec<String>, Vec<Vec<usize>>)> {
+    let tree = Path::new(TREE_DIR);
+    if !tree.is_dir() {
+        let dependencies = vec![Vec::new(); packages.len()];
+        return Ok((packages, dependencies));
+    }
+    let graph = DependencyGraph::scan(tree)?;
+    let resolve = |x: &str| graph.resolve(x);
+    let mut packages = packages;
+    if settings.revdeps {
+        let mut added = Vec::new();
+        for package in packages.iter() {
+            for revdep in graph.reverse_dependencies(&resolve(package)) {
+                if !packages
+                    .iter()
+                    .chain(added.iter())
+                    .any(|x| resolve(x) == revdep)
+                {
+                    added.push(revdep);
+                }
+            }
+        }
+        if !added.is_empty() {
+            info!(
+                "Added {} reverse dependencies: {}",
+                added.len(),
+                added.join(" ")
+            );
+        }
+        packages.extend(added);
+    }
+    if settings.with_deps {
+        let mut queue = packages.iter().map(|x| resolve(x)).collect::<VecDeque<_>>();
+        let mut added = Vec::new();
+        while let Some(package) = queue.pop_front() {
+            for dep in graph.tree_dependencies(&package) {
+                if !packages.iter().any(|x| resolve(x) == dep) {
+                    packages.push(dep.to_string());
+                    added.push(dep.to_string());
+                    queue.push_back(dep.to_string());
+                }
+            }
+        }
+        if !added.is_empty() {
+            info!(
+                "Added {} in-tree dependencies: {}",
+                added.len(),
+                added.join(" ")
+            );
+        }
+    }
+
+    let keys = packages.iter().map(|x| resolve(x)).collect::<Vec<_>>();
+    let sorted = topological_sort(&packages, |x| {
+        let key = packages
+            .iter()
+            .position(|p| p == x)
+            .map_or(x, |i| keys[i].as_str());
+        graph
+            .tree_dependencies(key)
+            .into_iter()
+            .filter_map(|dep| keys.iter().position(|k| k == dep))
+            .map(|i| packages[i].as_str())
+            .collect()
+    });
+    let keys = sorted.iter().map(|x| resolve(x)).collect::<Vec<_>>();
+    let dependencies = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+            graph
+                .tree_dependencies(key)
+                .into_iter()
+                .filter_map(|dep| keys[..i].iter().position(|k| k == dep))
+                .collect()
+        })
+        .collect();
+
+    Ok((sorted, dependencies))
+}
+
+/// For each package of the ordered list, the indices of the packages it depends on in TREE
+/// among the ones before it (the packages in a dependency cycle do not wait for each other)
+pub(super) fn listed_dependencies(packages: &[String]) -> Result<Vec<Vec<usize>>> {
+    let tree = Path::new(TREE_DIR);
+    if !tree.is_dir() {
+        return Ok(vec![Vec::new(); packages.len()]);
+    }
+    let graph = DependencyGraph::scan(tree)?;
+    let keys = packages
+        .iter()
+        .map(|x| graph.resolve(x))
+        .collect::<Vec<_>>();
+
+    Ok(keys
+        .iter()
+        .enumerate()
+        .map(|(index, key)| {
+            graph
+                .tree_dependencies(key)
+                .into_iter()
+                .filter_map(|dep| keys[..index].iter().position(|k| k == dep))
+                .collect()
+        })
+        .collect())
+}
+
+/// Dependencies (PKGDEP and BUILDDEP) of the package as listed in its recipes in TREE
+pub(super) fn package_dependencies(package: &str) -> Result<Vec<String>> {
+    let name = package.rsplit('/').next().unwrap_or(package);
+    let mut dependencies = Vec::new();
+    for section in fs::read_dir(TREE_DIR)?.flatten() {
+        let directory = section.path().join(name);
+        if !directory.join("spec").is_file() {
+            continue;
+        }
+        for recipe in fs::read_dir(directory)?.flatten() {
+            let defines = match fs::read_to_string(recipe.path().join("defines")) {
+                Ok(defines) => defines,
+                Err(_) => continue,
+            };
+            for variable in DEPENDENCY_VARIABLES {
+                for dep in read_variable(&defines, variable)
+                    .map(|x| parse_dependencies(&x))
+                    .unwrap_or_default()
+                {
+                    if !dependencies.contains(&dep) {
+                        dependencies.push(dep);
+                    }
+                }
+            }
+        }
+        break;
+    }
+
+    Ok(dependencies)
+}
+
+/// Path of the package's spec file relative to TREE (e.g. `app-utils/foo/spec`)
+pub fn package_recipe_path(package: &str) -> Option<String> {
+    let name = package.rsplit('/').next().unwrap_or(package);
+    fs::read_dir(TREE_DIR)
+        .ok()?
+        .flatten()
+        .find(|x| x.path().join(name).join("spec").is_file())
+        .map(|x| format!("{}/{}/spec", x.file_name().to_string_lossy(), name))
+}
+
+/// Determine the section and the package (directory) name if the file is a build recipe
+/// (`<section>/<package>/spec` or `<section>/<package>/<recipe>/defines`)
+fn recipe_package(path: &Path) -> Option<(String, String)> {
+    let components = path
+        .components()
+        .map(|x| x.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    let (section, package) = match components.as_slice() {
+        [section, package, file] if file == "spec" => (section, package),
+        [section, package, _, file] if file == "defines" => (section, package),
+        _ => return None,
+    };
+    if section == "groups" || section.starts_with('.') {
+        return None;
+    }
+
+    Some((section.clone(), package.clone()))
+}
+
+/// Find the packages whose build recipes were changed since the Git revision
+/// (including the uncommitted changes), deleted packages are skipped
+pub fn changed_packages(since: &str) -> Result<Vec<String>> {
+    let tree = Path::new(TREE_DIR);
+    let repo = Repository::open(tree)?;
+    let base = repo
+        .revparse_single(since)
+        .map_err(|e| anyhow!("Unable to resolve {}: {}", since, e.message()))?
+        .peel_to_tree()?;
+    let diff = repo.diff_tree_to_workdir_with_index(Some(&base), None)?;
+    let mut packages = Vec::new();
+    for delta in diff.deltas() {
+        for file in [delta.old_file(), delta.new_file()].iter() {
+            let path = match file.path() {
+                Some(path) => path,
+                None => continue,
+            };
+            let (section, package) = match recipe_package(path) {
+                Some(package) => package,
+                None => continue,
+            };
+            let exists = tree.join(section).join(&package).join("spec").is_file();
+            if exists && !packages.contains(&package) {
+                packages.push(package);
+            }
+        }
+    }
+    info!(
+        "{} package(s) changed since {}: {}",
+        packages.len(),
+        since,
+        packages.join(" ")
+    );
+
+    Ok(packages)
+}
+
+#[test]
+fn test_read_variable() {
+    let defines = "PKGNAME=foo\nPKGDEP=\"bar>=1.0 \\\n    baz\"\nBUILDDEP='qux'\n";
+    assert_eq!(read_variable(defines, "PKGNAME").unwrap(), "foo");
+    assert_eq!(
+        parse_dependencies(&read_variable(defines, "PKGDEP").unwrap()),
+        vec!["bar", "baz"]
+    );
+    assert_eq!(read_variable(defines, "BUILDDEP").unwrap(), "qux");
+    assert!(read_variable(defines, "PKGDES").is_none());
+}
+
+#[test]
+fn test_topological_sort() {
+    let packages = ["app", "lib", "base", "tool"].map(|x| x.to_string());
+    let sorted = topological_sort(&packages, |x| match x {
+        "app" => vec!["lib", "tool"],
+        "lib" => vec!["base"],
+        _ => vec![],
+    });
+    assert_eq!(sorted, vec!["base", "lib", "tool", "app"]);
+}
+
+#[test]
+fn test_reverse_dependencies() {
+    let graph = DependencyGraph {
+        dependencies: [
+            ("openssl", vec![]),
+            ("curl", vec!["openssl"]),
+            ("git", vec!["curl", "libssl"]),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.iter().map(|x| x.to_string()).collect()))
+        .collect(),
+        providers: [
+            ("openssl", "openssl"),
+            ("libssl", "openssl"),
+            ("curl", "curl"),
+            ("git", "git"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect(),
+    };
+    assert_eq!(graph.reverse_dependencies("openssl"), vec!["curl", "git"]);
+    assert!(graph.reverse_dependencies("git").is_empty());
+}
+
+#[test]
+fn test_recipe_package() {
+    assert_eq!(
+        recipe_package(Path::new("core-devel/gcc/spec")),
+        Some(("core-devel".to_string(), "gcc".to_string()))
+    );
+    assert_eq!(
+        recipe_package(Path::new("core-devel/gcc/autobuild/defines")),
+        Some(("core-devel".to_string(), "gcc".to_string()))
+    );
+    assert!(recipe_package(Path::new("core-devel/gcc/autobuild/build")).is_none());
+    assert!(recipe_package(Path::new("groups/bootstrap")).is_none());
+}
@@ -0,0 +1,128 @@
+//! Inspecting the changes made in the instances (`ciel diff`)
+
+use anyhow::{anyhow, Result};
+use console::style;
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    logging::{json_output, print_json},
+    overlayfs::{self, Change, ChangeKind},
+};
+
+use super::container::instance_changes;
+
+/// Same heuristic as git: files with a NUL byte in the first 8000 bytes are binary
+const BINARY_CHECK_SIZE: u64 = 8000;
+
+/// Keep the changes under the given paths (in the instance), all of them if `paths` is empty
+pub(super) fn filter_changes(changes: Vec<Change>, paths: &[PathBuf]) -> Vec<Change> {
+    let paths = paths
+        .iter()
+        .map(|x| x.strip_prefix("/").unwrap_or(x).to_path_buf())
+        .collect::<Vec<_>>();
+
+    changes
+        .into_iter()
+        .filter(|x| paths.is_empty() || paths.iter().any(|p| x.path.starts_with(p)))
+        .collect()
+}
+
+fn is_text_file(path: &Path) -> bool {
+    let mut buffer = Vec::new();
+    match File::open(path) {
+        Ok(file) => {
+            file.take(BINARY_CHECK_SIZE)
+                .read_to_end(&mut buffer)
+                .is_ok()
+                && !buffer.contains(&0)
+        }
+        Err(_) => false,
+    }
+}
+
+/// Print the unified diff of a changed file, `old` or `new` is `None` for added or deleted files
+fn print_content_diff(change: &Change, old: Option<&Path>, new: Option<&Path>) -> Result<()> {
+    let regular = |x: Option<&Path>| {
+        x.is_none_or(|x| fs::symlink_metadata(x).is_ok_and(|m| m.file_type().is_file()))
+    };
+    // directories, symlinks and devices have no content to compare
+    if !regular(old) || !regular(new) {
+        return Ok(());
+    }
+    if !old.is_none_or(is_text_file) || !new.is_none_or(is_text_file) {
+        println!("Binary file /{} differs", change.path.display());
+        return Ok(());
+    }
+    let null = Path::new("/dev/null");
+    Command::new("diff")
+        .arg("-u")
+        .arg("--label")
+        .arg(Path::new("a").join(&change.path))
+        .arg("--label")
+        .arg(Path::new("b").join(&change.path))
+        .arg(old.unwrap_or(null))
+        .arg(new.unwrap_or(null))
+        .status()
+        .map_err(|e| anyhow!("Unable to execute diff: {}", e))?;
+
+    Ok(())
+}
+
+/// Print the files added, modified and deleted in the instance (relative to the base OS),
+/// `paths` limits the output to the given paths, `content` also prints the diffs of the text files
+pub fn print_instance_diff(instance: &str, paths: &[PathBuf], content: bool) -> Result<()> {
+    let changes = filter_changes(instance_changes(instance)?, paths);
+    if json_output() {
+        return print_json(&changes);
+    }
+    let mut layers = None;
+    if content {
+        let man = &mut *overlayfs::get_layer_manager(instance)?;
+        layers = Some((
+            man.get_config_layer()?,
+            man.get_base_layer()?,
+            man.get_upper_layer()?,
+        ));
+    }
+    for change in changes.iter() {
+        let symbol = match change.kind {
+            ChangeKind::Added => style(change.kind.symbol()).green(),
+            ChangeKind::Modified => style(change.kind.symbol()).yellow(),
+            ChangeKind::Deleted => style(change.kind.symbol()).red(),
+        };
+        println!("{} /{}", symbol, change.path.display());
+        if let Some((config, base, upper)) = &layers {
+            // the configuration layer is above the base layer
+            let old = [config.join(&change.path), base.join(&change.path)]
+                .iter()
+                .find(|x| fs::symlink_metadata(x).is_ok())
+                .cloned();
+            let new = upper.join(&change.path);
+            let (old, new) = match change.kind {
+                ChangeKind::Added => (None, Some(new.as_path())),
+                ChangeKind::Modified => (old.as_deref(), Some(new.as_path())),
+                ChangeKind::Deleted => (old.as_deref(), None),
+            };
+            print_content_diff(change, old, new)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_filter_changes() {
+    let change = |path: &str| Change {
+        kind: ChangeKind::Added,
+        path: PathBuf::from(path),
+    };
+    let changes = vec![change("etc/apt/sources.list"), change("usr/bin/gcc")];
+    let filtered = filter_changes(changes, &[PathBuf::from("/etc")]);
+    assert_eq!(filtered, vec![change("etc/apt/sources.list")]);
+    assert_eq!(filter_changes(filtered, &[]).len(), 1);
+}
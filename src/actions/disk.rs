@@ -0,0 +1,134 @@
+//! Disk usage of the workspace (`ciel du`)
+
+use anyhow::Result;
+use console::style;
+use indicatif::HumanBytes;
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use tabwriter::TabWriter;
+
+use crate::{
+    common::{directory_size, CIEL_DATA_DIR, CIEL_DIST_DIR, CIEL_INST_DIR, CIEL_LAYERS_DIR},
+    logging::{json_output, print_json},
+    machine::list_instances_simple,
+    network::tarball_cache_dir,
+    overlayfs::dist_image,
+};
+
+use super::{APT_CACHE_HOST_DIR, CCACHE_HOST_DIR, SOURCES_HOST_DIR};
+
+/// Shared cache directories in the workspace
+const CACHE_DIRS: &[&str] = &[APT_CACHE_HOST_DIR, SOURCES_HOST_DIR, CCACHE_HOST_DIR];
+
+#[derive(Debug, Serialize)]
+pub struct DiskUsage {
+    /// `dist`, `layer`, `instance`, `output`, `cache` or `data`
+    pub kind: &'static str,
+    pub name: String,
+    pub path: PathBuf,
+    /// Allocated size in bytes (hard links are counted once)
+    pub size: u64,
+}
+
+/// Compute the disk usage of the base OS, the instances, the output and the cache directories
+pub fn disk_usage() -> Result<Vec<DiskUsage>> {
+    let mut seen = HashSet::new();
+    let mut usage = Vec::new();
+    let mut add = |kind: &'static str, name: String, path: PathBuf| {
+        if path.exists() {
+            let size = directory_size(&path, &mut seen);
+            usage.push(DiskUsage {
+                kind,
+                name,
+                path,
+                size,
+            });
+        }
+    };
+    match dist_image() {
+        // the mounted image would be counted twice
+        Some((format, image)) => add("dist", format!("base OS ({} image)", format.name()), image),
+        None => add("dist", "base OS".to_string(), PathBuf::from(CIEL_DIST_DIR)),
+    }
+    let mut layers = fs::read_dir(CIEL_LAYERS_DIR)
+        .map(|x| {
+            x.filter_map(|x| x.ok())
+                .filter_map(|x| x.file_name().into_string().ok())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    layers.sort();
+    for layer in layers {
+        let path = Path::new(CIEL_LAYERS_DIR).join(&layer);
+        add("layer", layer, path);
+    }
+    let mut instances = list_instances_simple()?;
+    instances.sort();
+    for instance in instances {
+        let path = Path::new(CIEL_INST_DIR).join(&instance);
+        add("instance", instance, path);
+    }
+    let mut outputs = fs::read_dir(".")?
+        .filter_map(|x| x.ok())
+        .filter(|x| x.path().is_dir())
+        .filter_map(|x| x.file_name().into_string().ok())
+        .filter(|x| x == "OUTPUT" || x.starts_with("OUTPUT-"))
+        .collect::<Vec<_>>();
+    outputs.sort();
+    for output in outputs {
+        add("output", output.clone(), PathBuf::from(output));
+    }
+    for cache in CACHE_DIRS {
+        add("cache", cache.to_string(), PathBuf::from(cache));
+    }
+    if let Some(tarballs) = tarball_cache_dir() {
+        add("cache", "OS tarballs (shared)".to_string(), tarballs);
+    }
+    add(
+        "data",
+        "logs and state".to_string(),
+        PathBuf::from(CIEL_DATA_DIR),
+    );
+
+    Ok(usage)
+}
+
+/// Print the disk usage of the workspace
+pub fn print_disk_usage() -> Result<()> {
+    let usage = disk_usage()?;
+    if json_output() {
+        return print_json(&usage);
+    }
+    let mut formatter = TabWriter::new(io::stderr());
+    writeln!(&mut formatter, "KIND\tNAME\tSIZE\tPATH")?;
+    for entry in usage.iter() {
+        writeln!(
+            &mut formatter,
+            "{}\t{}\t{}\t{}",
+            entry.kind,
+            entry.name,
+            HumanBytes(entry.size),
+            entry.path.display()
+        )?;
+    }
+    // the shared tarball cache is outside of the workspace
+    let total: u64 = usage
+        .iter()
+        .filter(|x| x.path.is_relative())
+        .map(|x| x.size)
+        .sum();
+    writeln!(
+        &mut formatter,
+        "{}\t\t{}\t",
+        style("TOTAL").bold(),
+        style(HumanBytes(total)).bold()
+    )?;
+    formatter.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+//! Minimal ELF parsing, to inspect the binaries shipped in the built packages
+
+use std::{collections::HashSet, convert::TryFrom};
+
+const SHT_DYNSYM: u64 = 11;
+const SHN_UNDEF: u64 = 0;
+const STB_GLOBAL: u64 = 1;
+const STB_WEAK: u64 = 2;
+const STV_DEFAULT: u64 = 0;
+
+/// Section header, only the fields used here
+struct Section {
+    /// Offset of the name in the section name table
+    name: u64,
+    kind: u64,
+    offset: u64,
+    size: u64,
+    /// Index of the associated section (e.g. the string table of a symbol table)
+    link: u64,
+}
+
+pub(super) struct Elf<'a> {
+    data: &'a [u8],
+    is_64: bool,
+    little_endian: bool,
+}
+
+impl<'a> Elf<'a> {
+    /// `None` if the data is not an ELF file
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.get(..4)? != b"\x7fELF" {
+            return None;
+        }
+
+        Some(Self {
+            data,
+            is_64: *data.get(4)? == 2,
+            little_endian: *data.get(5)? == 1,
+        })
+    }
+
+    fn read(&self, offset: u64, size: usize) -> Option<u64> {
+        let offset = usize::try_from(offset).ok()?;
+        let bytes = self.data.get(offset..offset.checked_add(size)?)?;
+        let mut value = 0u64;
+        for i in 0..size {
+            let byte = if self.little_endian {
+                bytes[size - 1 - i]
+            } else {
+                bytes[i]
+            };
+            value = (value << 8) | byte as u64;
+        }
+
+        Some(value)
+    }
+
+    /// Address-sized field
+    fn read_word(&self, offset: u64) -> Option<u64> {
+        self.read(offset, if self.is_64 { 8 } else { 4 })
+    }
+
+    fn sections(&self) -> Option<Vec<Section>> {
+        let (shoff, shentsize, shnum) = if self.is_64 {
+            (
+                self.read(0x28, 8)?,
+                self.read(0x3a, 2)?,
+                self.read(0x3c, 2)?,
+            )
+        } else {
+            (
+                self.read(0x20, 4)?,
+                self.read(0x2e, 2)?,
+                self.read(0x30, 2)?,
+            )
+        };
+        // offsets of sh_offset, sh_size and sh_link in the section header
+        let (offset, size, link) = if self.is_64 {
+            (0x18, 0x20, 0x28)
+        } else {
+            (0x10, 0x14, 0x18)
+        };
+
+        (0..shnum)
+            .map(|index| {
+                let header = shoff.checked_add(index.checked_mul(shentsize)?)?;
+                Some(Section {
+                    name: self.read(header, 4)?,
+                    kind: self.read(header.saturating_add(4), 4)?,
+                    offset: self.read_word(header.saturating_add(offset))?,
+                    size: self.read_word(header.saturating_add(size))?,
+                    link: self.read(header.saturating_add(link), 4)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Null-terminated string at `offset` in the string table
+    fn string(&self, table: &Section, offset: u64) -> Option<&'a str> {
+        let start = usize::try_from(table.offset.checked_add(offset)?).ok()?;
+        let bytes = self.data.get(start..)?;
+        let end = bytes.iter().position(|x| *x == 0)?;
+
+        std::str::from_utf8(&bytes[..end]).ok()
+    }
+
+    fn section_names(&self) -> Option<Vec<&'a str>> {
+        let shstrndx = self.read(if self.is_64 { 0x3e } else { 0x32 }, 2)?;
+        let sections = self.sections()?;
+        let names = sections.get(usize::try_from(shstrndx).ok()?)?;
+
+        Some(
+            sections
+                .iter()
+                .filter_map(|x| self.string(names, x.name))
+                .collect(),
+        )
+    }
+
+    /// Whether the symbol table or the debug information is still present
+    pub fn has_symbols(&self) -> bool {
+        self.section_names()
+            .unwrap_or_default()
+            .iter()
+            .any(|x| *x == ".symtab" || *x == ".debug_info")
+    }
+
+    /// Symbols exported by the shared library (the defined global symbols with default visibility),
+    /// `None` if the dynamic symbol table can not be read
+    pub fn exported_symbols(&self) -> Option<HashSet<String>> {
+        let sections = self.sections()?;
+        // entry size, offsets of st_info and st_shndx in the entry
+        let (entry_size, info, shndx) = if self.is_64 { (24, 4, 6) } else { (16, 12, 14) };
+        let mut symbols = HashSet::new();
+        for table in sections.iter().filter(|x| x.kind == SHT_DYNSYM) {
+            let strings = sections.get(usize::try_from(table.link).ok()?)?;
+            // the first entry is reserved
+            for index in 1..table.size / entry_size {
+                let entry = table.offset.checked_add(index * entry_size)?;
+                let binding = self.read(entry.saturating_add(info), 1)? >> 4;
+                let visibility = self.read(entry.saturating_add(info + 1), 1)? & 0x3;
+                if self.read(entry.saturating_add(shndx), 2)? == SHN_UNDEF
+                    || !(binding == STB_GLOBAL || binding == STB_WEAK)
+                    || visibility != STV_DEFAULT
+                {
+                    continue;
+                }
+                if let Some(name) = self.string(strings, self.read(entry, 4)?) {
+                    symbols.insert(name.to_string());
+                }
+            }
+        }
+
+        Some(symbols)
+    }
+}
+
+#[test]
+fn test_elf_parse() {
+    assert!(Elf::parse(b"#!/bin/sh\n").is_none());
+    let elf = Elf::parse(b"\x7fELF\x02\x01\x01").unwrap();
+    assert!(!elf.has_symbols());
+    assert!(elf.exported_symbols().is_none());
+}
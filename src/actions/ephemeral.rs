@@ -0,0 +1,26 @@
+//! One-shot commands in temporary instances created from the base OS
+
+use anyhow::Result;
+use console::style;
+use rand::random;
+use std::{fs::File, path::Path};
+
+use crate::{info, warn};
+
+use super::container::{add_instance, remove_instance, run_in_container_with_output};
+
+/// Run the command in a temporary instance, which is removed afterwards whatever the outcome,
+/// the output is written to the file if any. Returns the exit code of the command.
+pub fn run_ephemeral(command: &[String], output: Option<&Path>) -> Result<i32> {
+    let output = output.map(File::create).transpose()?;
+    let instance = format!("ephemeral-{:x}", random::<u32>());
+    add_instance(&instance)?;
+    let result = run_in_container_with_output(&instance, command, output.as_ref());
+    if let Err(e) = remove_instance(&instance) {
+        warn!("Unable to remove instance {}: {}", instance, e);
+    }
+    let status = result?;
+    info!("Command exited with status {}.", status);
+
+    Ok(status)
+}
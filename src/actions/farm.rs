@@ -0,0 +1,353 @@
+//! Build farm: the packages of the dependency-ordered queue are dispatched to the remote builders
+//! (see `ciel remote`) for each of their architectures, the output is aggregated into the local
+//! repository and a combined report is written
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::Path,
+    sync::{Condvar, Mutex},
+    thread,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+use tabwriter::TabWriter;
+
+use crate::{config, error, info, repo, warn};
+
+use super::{
+    container::get_output_directory,
+    deps::{listed_dependencies, order_packages},
+    packaging::{expand_package_list, format_duration, BuildSettings},
+    remote::{check_remote, find_remote, pull_results, push_output, run_remote_build, sync_tree},
+};
+
+const FARM_REPORT_FILE: &str = "farm-report.json";
+/// Architecture of the builders without one configured
+const DEFAULT_ARCH: &str = "default";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum JobStatus {
+    Pending,
+    Building,
+    Success,
+    Failed,
+    /// A dependency failed to build
+    Skipped,
+}
+
+/// A package to build for an architecture
+#[derive(Debug, Serialize)]
+struct FarmJob {
+    package: String,
+    arch: String,
+    status: JobStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    builder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration: Option<u64>,
+    /// Jobs (of the same architecture) to wait for
+    #[serde(skip)]
+    dependencies: Vec<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct FarmReport {
+    status: i32,
+    duration: u64,
+    timestamp: u64,
+    jobs: Vec<FarmJob>,
+}
+
+/// Jobs shared between the builders
+struct FarmQueue {
+    jobs: Mutex<Vec<FarmJob>>,
+    changed: Condvar,
+}
+
+impl FarmQueue {
+    /// Take the next job of the architecture whose dependencies are built, waiting for the builds
+    /// in progress if needed. Returns `None` when no job is left for the architecture.
+    fn next(&self, arch: &str) -> Option<usize> {
+        let mut jobs = self.jobs.lock().ok()?;
+        loop {
+            let mut pending = false;
+            for index in 0..jobs.len() {
+                if jobs[index].arch != arch || jobs[index].status != JobStatus::Pending {
+                    continue;
+                }
+                let statuses = jobs[index]
+                    .dependencies
+                    .iter()
+                    .map(|x| jobs[*x].status)
+                    .collect::<Vec<_>>();
+                if statuses
+                    .iter()
+                    .any(|x| *x == JobStatus::Failed || *x == JobStatus::Skipped)
+                {
+                    jobs[index].status = JobStatus::Skipped;
+                    continue;
+                }
+                if statuses.iter().all(|x| *x == JobStatus::Success) {
+                    jobs[index].status = JobStatus::Building;
+                    return Some(index);
+                }
+                pending = true;
+            }
+            if !pending {
+                return None;
+            }
+            jobs = self.changed.wait(jobs).ok()?;
+        }
+    }
+
+    fn finish(&self, index: usize, builder: &str, status: i32, duration: u64) {
+        if let Ok(mut jobs) = self.jobs.lock() {
+            let job = &mut jobs[index];
+            job.status = if status == 0 {
+                JobStatus::Success
+            } else {
+                JobStatus::Failed
+            };
+            job.builder = Some(builder.to_string());
+            job.exit_code = Some(status);
+            job.duration = Some(duration);
+        }
+        self.changed.notify_all();
+    }
+}
+
+/// The jobs of the packages for each architecture, in the order of the packages
+fn farm_jobs(packages: &[String], dependencies: &[Vec<usize>], archs: &[String]) -> Vec<FarmJob> {
+    let mut jobs = Vec::new();
+    for arch in archs {
+        let offset = jobs.len();
+        for (package, dependencies) in packages.iter().zip(dependencies) {
+            jobs.push(FarmJob {
+                package: package.clone(),
+                arch: arch.clone(),
+                status: JobStatus::Pending,
+                builder: None,
+                exit_code: None,
+                duration: None,
+                dependencies: dependencies.iter().map(|x| x + offset).collect(),
+            });
+        }
+    }
+
+    jobs
+}
+
+/// Build the jobs of the builder's architecture on the builder until none is left
+fn farm_worker(
+    name: &str,
+    arch: &str,
+    instance: &str,
+    queue: &FarmQueue,
+    transfer: &Mutex<()>,
+    output: &Path,
+) -> Result<()> {
+    let conf = config::read_config()?;
+    let remote = find_remote(&conf, name)?;
+    while let Some(index) = queue.next(arch) {
+        let package = match queue.jobs.lock() {
+            Ok(jobs) => jobs[index].package.clone(),
+            Err(_) => bail!("Farm queue lock poisoned"),
+        };
+        info!("{}: building {} ({})...", name, package, arch);
+        let started = Instant::now();
+        let status = (|| -> Result<i32> {
+            {
+                // the output of the other builders is needed by the dependent packages
+                let _transfer = transfer
+                    .lock()
+                    .map_err(|_| anyhow!("Transfer lock poisoned"))?;
+                push_output(remote)?;
+            }
+            let status = run_remote_build(
+                remote,
+                conf.maintainer(),
+                instance,
+                std::slice::from_ref(&package),
+                false,
+            )?;
+            let _transfer = transfer
+                .lock()
+                .map_err(|_| anyhow!("Transfer lock poisoned"))?;
+            pull_results(name, remote)?;
+            if output.is_dir() {
+                repo::refresh_repo(output)?;
+            }
+
+            Ok(status)
+        })()
+        .unwrap_or_else(|e| {
+            error!("{}: {:?}", name, e);
+            -1
+        });
+        if status == 0 {
+            info!("{}: {} ({}) built successfully.", name, package, arch);
+        } else {
+            error!(
+                "{}: failed to build {} ({}, status: {}), see the logs in .ciel/data/remote-logs/{}.",
+                name, package, arch, status, name
+            );
+        }
+        queue.finish(index, name, status, started.elapsed().as_secs());
+    }
+
+    Ok(())
+}
+
+/// Build the packages on the remote builders (all the registered ones by default),
+/// for each architecture of the builders
+pub fn farm_build<S: AsRef<str>, K: IntoIterator<Item = S>>(
+    packages: K,
+    builders: Option<Vec<String>>,
+    settings: BuildSettings,
+) -> Result<i32> {
+    let conf = config::read_config()?;
+    let builders = builders.unwrap_or_else(|| conf.remotes.keys().cloned().collect());
+    if builders.is_empty() {
+        bail!("No builder is registered, add them with `ciel remote add`.");
+    }
+    let mut workers = Vec::new();
+    for name in builders.iter() {
+        let remote = find_remote(&conf, name)?;
+        let instance = remote.instance.clone().ok_or_else(|| {
+            anyhow!(
+                "Please set the instance of {} (`ciel remote add -i`).",
+                name
+            )
+        })?;
+        let arch = remote
+            .arch
+            .clone()
+            .unwrap_or_else(|| DEFAULT_ARCH.to_string());
+        check_remote(remote)?;
+        info!("Synchronizing TREE to {}...", name);
+        sync_tree(remote)?;
+        workers.push((name.clone(), arch, instance));
+    }
+    let mut archs = workers.iter().map(|x| x.1.clone()).collect::<Vec<_>>();
+    archs.sort();
+    archs.dedup();
+
+    let packages = order_packages(expand_package_list(packages), &settings)?;
+    let dependencies = listed_dependencies(&packages)?;
+    let queue = FarmQueue {
+        jobs: Mutex::new(farm_jobs(&packages, &dependencies, &archs)),
+        changed: Condvar::new(),
+    };
+    let output = std::env::current_dir()?.join(get_output_directory(conf.sep_mount));
+    let transfer = Mutex::new(());
+    info!(
+        "Building {} packages for {} architecture(s) using {} builders...",
+        packages.len(),
+        archs.len(),
+        workers.len()
+    );
+    let start = Instant::now();
+    thread::scope(|s| {
+        for (name, arch, instance) in workers.iter() {
+            let (queue, transfer, output) = (&queue, &transfer, output.as_path());
+            s.spawn(move || {
+                if let Err(e) = farm_worker(name, arch, instance, queue, transfer, output) {
+                    error!("{}: {:?}", name, e);
+                }
+            });
+        }
+    });
+
+    let jobs = queue
+        .jobs
+        .into_inner()
+        .map_err(|_| anyhow!("Farm queue lock poisoned"))?;
+    // jobs left pending when all the builders of their architecture gave up fail the farm too
+    let status = match jobs.iter().find(|x| x.status != JobStatus::Success) {
+        Some(job) => job.exit_code.filter(|x| *x != 0).unwrap_or(1),
+        None => 0,
+    };
+    let mut formatter = TabWriter::new(io::stderr());
+    writeln!(&mut formatter, "PACKAGE\tARCH\tBUILDER\tSTATUS\tDURATION")?;
+    for job in jobs.iter() {
+        writeln!(
+            &mut formatter,
+            "{}\t{}\t{}\t{:?}\t{}",
+            job.package,
+            job.arch,
+            job.builder.as_deref().unwrap_or("-"),
+            job.status,
+            job.duration
+                .map_or_else(|| "-".to_string(), format_duration)
+        )?;
+    }
+    formatter.flush()?;
+    let mut counts = BTreeMap::new();
+    for job in jobs.iter() {
+        *counts.entry(format!("{:?}", job.status)).or_insert(0) += 1;
+    }
+    let report = FarmReport {
+        status,
+        duration: start.elapsed().as_secs(),
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        jobs,
+    };
+    fs::create_dir_all(&output)?;
+    fs::write(
+        output.join(FARM_REPORT_FILE),
+        serde_json::to_vec_pretty(&report)?,
+    )?;
+    info!(
+        "Farm report written to {}",
+        output.join(FARM_REPORT_FILE).display()
+    );
+    if status != 0 {
+        warn!(
+            "{}",
+            counts
+                .iter()
+                .map(|(status, count)| format!("{}: {}", status, count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        return Ok(status);
+    }
+    eprintln!(
+        "{} - {} packages in {}",
+        style("BUILD SUCCESSFUL").bold().green(),
+        report.jobs.len(),
+        format_duration(report.duration)
+    );
+
+    Ok(0)
+}
+
+#[test]
+fn test_farm_queue() {
+    let packages = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    // b depends on a
+    let dependencies = vec![vec![], vec![0], vec![]];
+    let queue = FarmQueue {
+        jobs: Mutex::new(farm_jobs(
+            &packages,
+            &dependencies,
+            &["amd64".to_string(), "arm64".to_string()],
+        )),
+        changed: Condvar::new(),
+    };
+    assert_eq!(queue.next("arm64"), Some(3));
+    queue.finish(3, "builder", 1, 0);
+    // b is skipped for arm64 because a failed
+    assert_eq!(queue.next("arm64"), Some(5));
+    queue.finish(5, "builder", 0, 0);
+    assert_eq!(queue.next("arm64"), None);
+    assert_eq!(queue.jobs.lock().unwrap()[4].status, JobStatus::Skipped);
+    assert_eq!(queue.next("amd64"), Some(0));
+}
@@ -0,0 +1,218 @@
+//! Garbage collection of the data left behind in the workspace (`ciel gc`)
+
+use anyhow::Result;
+use console::style;
+use indicatif::HumanBytes;
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tabwriter::TabWriter;
+
+use crate::{
+    common::{directory_size, CIEL_DATA_DIR, CIEL_INST_DIR},
+    diagnose::{find_orphaned_machines, fix_orphaned_machines},
+    info,
+    machine::list_instances_simple,
+    network::tarball_cache_dir,
+};
+
+use super::{
+    archive::IMPORT_TEMP_PREFIX,
+    cache::{is_apt_cache_lock_unused, remove_unused_apt_cache_lock},
+    checkpoint::{discard_checkpoint, CHECKPOINT_DIR},
+    logs::{remove_logs, stale_logs},
+    snapshot::{remove_instance_snapshots, SNAPSHOT_DIR},
+};
+
+/// Partial downloads not touched for this long are considered abandoned
+const STALE_DOWNLOAD_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+enum Cleanup {
+    Remove(PathBuf),
+    Snapshots(String),
+    Checkpoint(String),
+    Logs(Vec<PathBuf>),
+    Machines,
+    AptCacheLock,
+}
+
+/// Something left behind in the workspace
+struct Garbage {
+    kind: &'static str,
+    name: String,
+    size: u64,
+    cleanup: Cleanup,
+}
+
+fn size_of(path: &Path) -> u64 {
+    directory_size(path, &mut HashSet::new())
+}
+
+/// Directories of `.ciel/data/<directory>` named after instances that no longer exist
+fn orphaned_instance_data(directory: &str, instances: &[String]) -> Vec<(String, PathBuf)> {
+    fs::read_dir(Path::new(CIEL_DATA_DIR).join(directory))
+        .map(|entries| {
+            entries
+                .filter_map(|x| x.ok())
+                .filter(|x| x.path().is_dir())
+                .map(|x| (x.file_name().to_string_lossy().to_string(), x.path()))
+                .filter(|(name, _)| !instances.contains(name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Partial downloads (`.part` files and their state) older than `STALE_DOWNLOAD_AGE`
+fn stale_downloads(directory: &Path) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(directory)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|x| x.ok())
+        .filter(|x| {
+            let name = x.file_name().to_string_lossy();
+            x.file_type().is_file() && (name.ends_with(".part") || name.ends_with(".part.toml"))
+        })
+        .filter(|x| {
+            x.metadata()
+                .ok()
+                .and_then(|x| x.modified().ok())
+                .and_then(|x| x.elapsed().ok())
+                .is_some_and(|x| x > STALE_DOWNLOAD_AGE)
+        })
+        .map(|x| x.into_path())
+        .collect()
+}
+
+fn find_garbage(log_max_age: Duration) -> Result<Vec<Garbage>> {
+    let instances = list_instances_simple()?;
+    let mut garbage = Vec::new();
+    // anything in the instance directory that is not an instance (see `list_instances_simple`)
+    for entry in fs::read_dir(CIEL_INST_DIR)?.filter_map(|x| x.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if instances.contains(&name) {
+            continue;
+        }
+        garbage.push(Garbage {
+            kind: if name.starts_with(IMPORT_TEMP_PREFIX) {
+                "incomplete import"
+            } else {
+                "not an instance"
+            },
+            size: size_of(&entry.path()),
+            name,
+            cleanup: Cleanup::Remove(entry.path()),
+        });
+    }
+    for (name, path) in orphaned_instance_data(SNAPSHOT_DIR, &instances) {
+        garbage.push(Garbage {
+            kind: "orphaned snapshots",
+            size: size_of(&path),
+            cleanup: Cleanup::Snapshots(name.clone()),
+            name,
+        });
+    }
+    for (name, path) in orphaned_instance_data(CHECKPOINT_DIR, &instances) {
+        garbage.push(Garbage {
+            kind: "orphaned checkpoint",
+            size: size_of(&path),
+            cleanup: Cleanup::Checkpoint(name.clone()),
+            name,
+        });
+    }
+    let machines = find_orphaned_machines().unwrap_or_default();
+    if !machines.is_empty() {
+        garbage.push(Garbage {
+            kind: "dangling machines",
+            name: machines.join(", "),
+            size: 0,
+            cleanup: Cleanup::Machines,
+        });
+    }
+    let mut downloads = stale_downloads(Path::new(CIEL_DATA_DIR));
+    if let Some(cache) = tarball_cache_dir() {
+        downloads.extend(stale_downloads(&cache));
+    }
+    for path in downloads {
+        garbage.push(Garbage {
+            kind: "stale download",
+            name: path.display().to_string(),
+            size: size_of(&path),
+            cleanup: Cleanup::Remove(path),
+        });
+    }
+    let logs = stale_logs(log_max_age, &instances)?;
+    if !logs.is_empty() {
+        garbage.push(Garbage {
+            kind: "old logs",
+            name: format!("{} file(s)", logs.len()),
+            size: logs.iter().map(|x| size_of(x)).sum(),
+            cleanup: Cleanup::Logs(logs),
+        });
+    }
+    if is_apt_cache_lock_unused() {
+        garbage.push(Garbage {
+            kind: "unused lock",
+            name: "package cache".to_string(),
+            size: 0,
+            cleanup: Cleanup::AptCacheLock,
+        });
+    }
+
+    Ok(garbage)
+}
+
+fn clean(garbage: &Garbage) -> Result<()> {
+    match &garbage.cleanup {
+        Cleanup::Remove(path) if path.is_dir() => fs::remove_dir_all(path)?,
+        Cleanup::Remove(path) => fs::remove_file(path)?,
+        Cleanup::Snapshots(instance) => remove_instance_snapshots(instance)?,
+        Cleanup::Checkpoint(instance) => discard_checkpoint(instance)?,
+        Cleanup::Logs(paths) => remove_logs(paths)?,
+        Cleanup::Machines => {
+            fix_orphaned_machines()?;
+        }
+        Cleanup::AptCacheLock => {
+            remove_unused_apt_cache_lock()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the data left behind in the workspace (incomplete imports and other directories that are
+/// not instances, snapshots and checkpoints of the removed instances, dangling machine registrations, partial downloads, logs older than
+/// `log_max_age_days` and the unused lock files) and remove it unless `dry_run`
+pub fn gc(log_max_age_days: u64, dry_run: bool) -> Result<()> {
+    let garbage = find_garbage(Duration::from_secs(log_max_age_days * 24 * 60 * 60))?;
+    if garbage.is_empty() {
+        info!("Nothing to clean up.");
+        return Ok(());
+    }
+    let mut formatter = TabWriter::new(io::stderr());
+    writeln!(&mut formatter, "KIND\tNAME\tSIZE")?;
+    for item in garbage.iter() {
+        writeln!(
+            &mut formatter,
+            "{}\t{}\t{}",
+            item.kind,
+            item.name,
+            HumanBytes(item.size)
+        )?;
+    }
+    formatter.flush()?;
+    let total = garbage.iter().map(|x| x.size).sum::<u64>();
+    if dry_run {
+        info!("{} can be reclaimed.", HumanBytes(total));
+        return Ok(());
+    }
+    for item in garbage.iter() {
+        clean(item)?;
+    }
+    info!("Reclaimed {}.", HumanBytes(total));
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! User hook scripts in `.ciel/hooks/`, executed on the host to customize the environments
+
+use anyhow::{bail, Result};
+use console::style;
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    common::{CIEL_DIST_DIR, CIEL_INST_DIR},
+    config, info, warn,
+};
+
+use super::container::get_output_directory;
+
+const HOOK_DIR: &str = ".ciel/hooks";
+
+/// Points where the hooks are executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    /// After the base OS is loaded
+    PostLoadOs,
+    /// After a new instance is created
+    PostAdd,
+    /// Before each package is built
+    PreBuild,
+    /// After each package is built (successful or not)
+    PostBuild,
+    /// After the instance is rolled back
+    PostRollback,
+}
+
+impl Hook {
+    /// Name of the hook script
+    pub fn name(self) -> &'static str {
+        match self {
+            Hook::PostLoadOs => "post-load-os",
+            Hook::PostAdd => "post-add",
+            Hook::PreBuild => "pre-build",
+            Hook::PostBuild => "post-build",
+            Hook::PostRollback => "post-rollback",
+        }
+    }
+}
+
+/// Absolute path of the file in the workspace
+fn workspace_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    std::env::current_dir().unwrap_or_default().join(path)
+}
+
+/// Environment variables passed to the hook
+fn hook_environment(hook: Hook, instance: Option<&str>) -> Vec<(&'static str, String)> {
+    let sep_mount = config::read_config().is_ok_and(|c| c.sep_mount);
+    let path = |x: PathBuf| x.to_string_lossy().to_string();
+    let mut env = vec![
+        ("CIEL_HOOK", hook.name().to_string()),
+        ("CIEL_WORKSPACE", path(workspace_path("."))),
+        ("CIEL_DIST_DIR", path(workspace_path(CIEL_DIST_DIR))),
+        ("CIEL_TREE_DIR", path(workspace_path("TREE"))),
+        (
+            "CIEL_OUTPUT_DIR",
+            path(workspace_path(get_output_directory(sep_mount))),
+        ),
+    ];
+    if let Some(instance) = instance {
+        env.push(("CIEL_INSTANCE", instance.to_string()));
+        env.push((
+            "CIEL_INSTANCE_DIR",
+            path(workspace_path(Path::new(CIEL_INST_DIR).join(instance))),
+        ));
+        // the merged filesystem of the instance
+        env.push(("CIEL_ROOTFS", path(workspace_path(instance))));
+    }
+
+    env
+}
+
+/// Run the hook script if it exists, `extra` is passed as additional environment variables
+pub fn run_hook(hook: Hook, instance: Option<&str>, extra: &[(&str, String)]) -> Result<()> {
+    let script = workspace_path(Path::new(HOOK_DIR).join(hook.name()));
+    let metadata = match fs::metadata(&script) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        _ => return Ok(()),
+    };
+    if metadata.permissions().mode() & 0o111 == 0 {
+        warn!(
+            "Hook {} is not executable, skipped (run `chmod +x {}/{}`).",
+            hook.name(),
+            HOOK_DIR,
+            hook.name()
+        );
+        return Ok(());
+    }
+    info!("Running {} hook...", hook.name());
+    let status = Command::new(&script)
+        .envs(hook_environment(hook, instance))
+        .envs(extra.iter().map(|(k, v)| (*k, v)))
+        .status()?;
+    if !status.success() {
+        bail!("Hook {} failed: {}", hook.name(), status);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_hook_environment() {
+    let env = hook_environment(Hook::PostAdd, Some("alice"));
+    let get = |key: &str| env.iter().find(|x| x.0 == key).map(|x| x.1.clone());
+    assert_eq!(get("CIEL_HOOK").as_deref(), Some("post-add"));
+    assert_eq!(get("CIEL_INSTANCE").as_deref(), Some("alice"));
+    assert!(get("CIEL_INSTANCE_DIR")
+        .unwrap()
+        .ends_with(".ciel/container/instances/alice"));
+    assert!(hook_environment(Hook::PostLoadOs, None)
+        .iter()
+        .all(|x| x.0 != "CIEL_INSTANCE"));
+}
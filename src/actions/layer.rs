@@ -0,0 +1,169 @@
+//! Shared read-only layers stacked between the base OS and the instances
+
+use anyhow::{bail, Result};
+use console::style;
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+use tabwriter::TabWriter;
+
+use crate::{
+    common::{is_instance_exists, CIEL_LAYERS_DIR},
+    config, info,
+    machine::list_instances_simple,
+    overlayfs::{self, copy_tree},
+};
+
+use super::container::{container_down, is_tmpfs_instance, rollback_container};
+
+/// Layers of the instance, from the bottom to the top
+fn instance_layers(instance: &str) -> Result<Vec<String>> {
+    Ok(config::read_instance_overrides(instance)?
+        .and_then(|x| x.layers)
+        .unwrap_or_default())
+}
+
+fn set_instance_layers(instance: &str, layers: Vec<String>) -> Result<()> {
+    let mut overrides = config::read_instance_overrides(instance)?.unwrap_or_default();
+    overrides.layers = Some(layers).filter(|x| !x.is_empty());
+
+    config::write_instance_overrides(instance, &overrides)
+}
+
+/// Instances using the layer
+fn layer_users(name: &str) -> Result<Vec<String>> {
+    let mut users = Vec::new();
+    for instance in list_instances_simple()? {
+        if instance_layers(&instance)?.iter().any(|x| x == name) {
+            users.push(instance);
+        }
+    }
+    users.sort();
+
+    Ok(users)
+}
+
+/// Create a new shared layer. With `instance`, the changes of the instance are moved into the layer:
+/// the layer is attached to the instance and the instance is rolled back
+pub fn create_layer(name: &str, instance: Option<&str>) -> Result<()> {
+    if name.is_empty() || name.contains('/') || name.starts_with('.') {
+        bail!("Invalid layer name: {}", name);
+    }
+    let path = Path::new(CIEL_LAYERS_DIR).join(name);
+    if path.exists() {
+        bail!("Shared layer {} already exists.", name);
+    }
+    let instance = match instance {
+        Some(instance) => instance,
+        None => {
+            fs::create_dir_all(&path)?;
+            info!("Shared layer {} created at {}", name, path.display());
+            return Ok(());
+        }
+    };
+    if !is_instance_exists(instance) {
+        bail!("Instance {} does not exist.", instance);
+    }
+    if is_tmpfs_instance(instance) {
+        bail!(
+            "{}: the changes on tmpfs are discarded when the instance is stopped.",
+            instance
+        );
+    }
+    container_down(instance)?;
+    info!("{}: copying the changes into layer {}...", instance, name);
+    let upper = overlayfs::get_layer_manager(instance)?.get_upper_layer()?;
+    fs::create_dir_all(&path)?;
+    // whiteouts and opaque directories keep working in the lower layers
+    if let Err(e) = copy_tree(&upper.join("."), &path) {
+        fs::remove_dir_all(&path).ok();
+        return Err(e);
+    }
+    let mut layers = instance_layers(instance)?;
+    layers.push(name.to_string());
+    set_instance_layers(instance, layers)?;
+    rollback_container(instance)?;
+    info!(
+        "Shared layer {} created from {}, attach it to other instances with `ciel layer attach`.",
+        name, instance
+    );
+
+    Ok(())
+}
+
+/// Stack the layer on top of the other layers of the instance
+pub fn attach_layer(instance: &str, name: &str) -> Result<()> {
+    overlayfs::shared_layer_path(name)?;
+    if !is_instance_exists(instance) {
+        bail!("Instance {} does not exist.", instance);
+    }
+    let mut layers = instance_layers(instance)?;
+    if layers.iter().any(|x| x == name) {
+        bail!("{}: layer {} is already attached.", instance, name);
+    }
+    container_down(instance)?;
+    layers.push(name.to_string());
+    set_instance_layers(instance, layers)?;
+    info!("{}: layer {} attached.", instance, name);
+
+    Ok(())
+}
+
+/// Remove the layer from the instance
+pub fn detach_layer(instance: &str, name: &str) -> Result<()> {
+    let mut layers = instance_layers(instance)?;
+    if !layers.iter().any(|x| x == name) {
+        bail!("{}: layer {} is not attached.", instance, name);
+    }
+    container_down(instance)?;
+    layers.retain(|x| x != name);
+    set_instance_layers(instance, layers)?;
+    info!("{}: layer {} detached.", instance, name);
+
+    Ok(())
+}
+
+/// Remove the shared layer, it must not be used by any instance
+pub fn remove_layer(name: &str) -> Result<()> {
+    let path = overlayfs::shared_layer_path(name)?;
+    let users = layer_users(name)?;
+    if !users.is_empty() {
+        bail!(
+            "Shared layer {} is used by: {}, please detach it first.",
+            name,
+            users.join(", ")
+        );
+    }
+    fs::remove_dir_all(path)?;
+    info!("Shared layer {} removed.", name);
+
+    Ok(())
+}
+
+/// List the shared layers and the instances using them
+pub fn list_layers() -> Result<()> {
+    let mut names = fs::read_dir(CIEL_LAYERS_DIR)
+        .map(|x| {
+            x.filter_map(|x| x.ok())
+                .filter(|x| x.path().is_dir())
+                .filter_map(|x| x.file_name().into_string().ok())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    names.sort();
+    let mut formatter = TabWriter::new(io::stderr());
+    writeln!(&mut formatter, "LAYER\tUSED BY")?;
+    for name in names {
+        writeln!(
+            &mut formatter,
+            "{}\t{}",
+            name,
+            layer_users(&name)?.join(", ")
+        )?;
+    }
+    formatter.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,362 @@
+//! Per-package build logs
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use fs3::FileExt;
+use indicatif::HumanBytes;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    process::Command,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tabwriter::TabWriter;
+use time::{format_description::FormatItem, macros::format_description, OffsetDateTime};
+
+use crate::{
+    common::{can_prompt, CIEL_DATA_DIR},
+    logging::{json_output, print_json},
+};
+
+use super::{packaging::format_duration, report::BuildUsage};
+
+const LOG_DIR: &str = "logs";
+const LOG_INDEX_FILE: &str = "index.json";
+/// `<instance>.active` contains the name of the log of the build in progress in the instance
+const ACTIVE_LOG_SUFFIX: &str = ".active";
+/// Polling interval when following a log
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+const TIMESTAMP_FORMAT: &[FormatItem] =
+    format_description!("[year]-[month]-[day] [hour]:[minute] UTC");
+
+/// An entry in the build log index
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PackageLog {
+    pub package: String,
+    pub instance: String,
+    /// Name of the log file, relative to the log directory
+    pub file: String,
+    pub timestamp: u64,
+    pub status: i32,
+    /// Wall time in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+    /// CPU time in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_time: Option<u64>,
+    /// Peak memory usage of the instance in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_memory: Option<u64>,
+}
+
+/// A log file being written for a package build, marked as the active log of the instance until finished
+pub struct PackageLogFile {
+    pub file: File,
+    entry: PackageLog,
+}
+
+#[inline]
+fn log_dir() -> PathBuf {
+    Path::new(CIEL_DATA_DIR).join(LOG_DIR)
+}
+
+#[inline]
+fn active_log_marker(instance: &str) -> PathBuf {
+    log_dir().join(format!("{}{}", instance, ACTIVE_LOG_SUFFIX))
+}
+
+/// Name of the log of the build in progress in the instance
+fn active_log(instance: &str) -> Option<String> {
+    fs::read_to_string(active_log_marker(instance))
+        .ok()
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty())
+}
+
+fn read_index() -> Result<Vec<PackageLog>> {
+    match File::open(log_dir().join(LOG_INDEX_FILE)) {
+        Ok(f) => Ok(serde_json::from_reader(f)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Open and lock the log index, this serializes the updates to the index between the
+/// threads and the processes (e.g. when building in parallel)
+fn lock_index() -> Result<File> {
+    let path = log_dir().join(LOG_INDEX_FILE);
+    loop {
+        let f = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        f.lock_exclusive()?;
+        // the index may have been replaced while waiting for the lock
+        if fs::metadata(&path)?.ino() == f.metadata()?.ino() {
+            return Ok(f);
+        }
+    }
+}
+
+/// Read the log index opened by `lock_index` (empty if it has just been created)
+fn read_locked_index(file: &File) -> Result<Vec<PackageLog>> {
+    if file.metadata()?.len() == 0 {
+        return Ok(Vec::new());
+    }
+
+    Ok(serde_json::from_reader(file)?)
+}
+
+/// Write the log index to a temporary file first, so that the readers never see a partial index
+fn write_index(index: &[PackageLog]) -> Result<()> {
+    let path = log_dir().join(LOG_INDEX_FILE);
+    let temp_path = path.with_extension("json.tmp");
+    let mut f = File::create(&temp_path)?;
+    f.write_all(&serde_json::to_vec_pretty(index)?)?;
+    f.sync_all()?;
+    fs::rename(&temp_path, &path)?;
+
+    Ok(())
+}
+
+impl PackageLogFile {
+    /// Create a new log file for the package, named `<package>-<timestamp>.log`
+    pub fn create(package: &str, instance: &str) -> Result<Self> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let name = format!("{}-{}.log", package.replace('/', "_"), timestamp);
+        fs::create_dir_all(log_dir())?;
+        let file = File::create(log_dir().join(&name))?;
+        fs::write(active_log_marker(instance), &name)?;
+
+        Ok(Self {
+            file,
+            entry: PackageLog {
+                package: package.to_string(),
+                instance: instance.to_string(),
+                file: name,
+                timestamp,
+                status: -1,
+                duration: None,
+                cpu_time: None,
+                peak_memory: None,
+            },
+        })
+    }
+
+    /// Path of the log file
+    pub fn path(&self) -> PathBuf {
+        log_dir().join(&self.entry.file)
+    }
+
+    /// Record the exit status and the resource usage of the build in the log index
+    pub(super) fn finish(self, status: i32, usage: &BuildUsage) -> Result<()> {
+        // the lock is released when the file is closed
+        let lock = lock_index()?;
+        let mut index = read_locked_index(&lock)?;
+        index.push(PackageLog {
+            status,
+            duration: Some(usage.duration),
+            cpu_time: usage.cpu_time,
+            peak_memory: usage.peak_memory,
+            ..self.entry.clone()
+        });
+        write_index(&index)?;
+
+        Ok(())
+    }
+}
+
+impl Drop for PackageLogFile {
+    fn drop(&mut self) {
+        // another build may have started in the instance in the meantime
+        if active_log(&self.entry.instance).as_deref() == Some(self.entry.file.as_str()) {
+            fs::remove_file(active_log_marker(&self.entry.instance)).ok();
+        }
+    }
+}
+
+/// Find the logs of the builds still in progress (not recorded in the index yet)
+/// that were updated within `window`, sorted from the most recently updated
+pub fn active_package_logs(window: Duration) -> Result<Vec<PathBuf>> {
+    let index = read_index()?;
+    let mut logs = Vec::new();
+    let entries = match fs::read_dir(log_dir()) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.ends_with(".log") || index.iter().any(|x| x.file == name) {
+            continue;
+        }
+        let modified = entry.metadata()?.modified()?;
+        if modified.elapsed().map_or(true, |x| x <= window) {
+            logs.push((modified, entry.path()));
+        }
+    }
+    logs.sort_by_key(|x| std::cmp::Reverse(x.0));
+
+    Ok(logs.into_iter().map(|x| x.1).collect())
+}
+
+/// Logs older than `max_age` (the ones of the interrupted builds included)
+/// and the active log markers of the removed instances
+pub(super) fn stale_logs(max_age: Duration, instances: &[String]) -> Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(log_dir()) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut stale = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if let Some(instance) = name.strip_suffix(ACTIVE_LOG_SUFFIX) {
+            if !instances.iter().any(|x| x == instance) {
+                stale.push(entry.path());
+            }
+        } else if name.ends_with(".log")
+            && entry
+                .metadata()?
+                .modified()?
+                .elapsed()
+                .is_ok_and(|x| x > max_age)
+        {
+            stale.push(entry.path());
+        }
+    }
+    stale.sort();
+
+    Ok(stale)
+}
+
+/// Remove the logs and their entries in the index
+pub(super) fn remove_logs(paths: &[PathBuf]) -> Result<()> {
+    let lock = lock_index()?;
+    for path in paths {
+        fs::remove_file(path)?;
+    }
+    let mut index = read_locked_index(&lock)?;
+    let count = index.len();
+    index.retain(|x| !paths.iter().any(|p| p.file_name() == Some(x.file.as_ref())));
+    if index.len() != count {
+        write_index(&index)?;
+    }
+
+    Ok(())
+}
+
+/// Find the most recent log of the package (the package can be specified with or without the section)
+fn find_package_log(package: &str) -> Result<PackageLog> {
+    read_index()?
+        .into_iter()
+        .rev()
+        .find(|x| x.package == package || x.package.rsplit('/').next() == Some(package))
+        .ok_or_else(|| anyhow!("No build log found for {}", package))
+}
+
+/// Show the most recent build log of the package
+pub fn show_package_log(package: &str) -> Result<()> {
+    let entry = find_package_log(package)?;
+    if json_output() {
+        return print_json(&entry);
+    }
+    let path = log_dir().join(&entry.file);
+    if can_prompt() {
+        let pager = std::env::var("PAGER").unwrap_or_else(|_| "less".to_string());
+        Command::new(pager).arg(&path).status()?;
+    } else {
+        io::copy(&mut File::open(&path)?, &mut io::stdout())?;
+    }
+
+    Ok(())
+}
+
+/// List the recorded builds (optionally of the package only) with their resource usage,
+/// the most recent builds are listed last
+pub fn list_builds(package: Option<&str>, limit: usize) -> Result<()> {
+    let mut builds = read_index()?
+        .into_iter()
+        .filter(|x| {
+            package.is_none_or(|p| x.package == p || x.package.rsplit('/').next() == Some(p))
+        })
+        .collect::<Vec<_>>();
+    builds.drain(..builds.len().saturating_sub(limit));
+    if json_output() {
+        return print_json(&builds);
+    }
+    let mut formatter = TabWriter::new(io::stderr());
+    writeln!(
+        &mut formatter,
+        "PACKAGE\tINSTANCE\tSTATUS\tSTARTED\tDURATION\tCPU TIME\tPEAK MEMORY"
+    )?;
+    let started = |x: &PackageLog| {
+        OffsetDateTime::from_unix_timestamp(x.timestamp as i64)
+            .ok()
+            .and_then(|t| t.format(TIMESTAMP_FORMAT).ok())
+            .unwrap_or_default()
+    };
+    for build in builds.iter() {
+        let status = if build.status == 0 {
+            style("OK".to_string()).green()
+        } else {
+            style(format!("failed ({})", build.status)).red()
+        };
+        writeln!(
+            &mut formatter,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            build.package,
+            build.instance,
+            status,
+            started(build),
+            build.duration.map(format_duration).unwrap_or_default(),
+            build.cpu_time.map(format_duration).unwrap_or_default(),
+            build
+                .peak_memory
+                .map(|x| HumanBytes(x).to_string())
+                .unwrap_or_default()
+        )?;
+    }
+    formatter.flush()?;
+
+    Ok(())
+}
+
+/// Show the log of the build in progress in the instance (or its most recent build log).
+/// With `follow`, the new output is printed as it is written until the build finishes.
+pub fn show_instance_log(instance: &str, follow: bool) -> Result<()> {
+    let name = match active_log(instance) {
+        Some(name) => name,
+        None if follow => bail!("{}: no build in progress.", instance),
+        None => {
+            read_index()?
+                .into_iter()
+                .rev()
+                .find(|x| x.instance == instance)
+                .ok_or_else(|| anyhow!("No build log found for {}", instance))?
+                .file
+        }
+    };
+    let mut file = File::open(log_dir().join(&name))?;
+    let mut stdout = io::stdout();
+    io::copy(&mut file, &mut stdout)?;
+    if !follow {
+        return Ok(());
+    }
+    loop {
+        stdout.flush()?;
+        thread::sleep(FOLLOW_INTERVAL);
+        let finished = active_log(instance).as_deref() != Some(name.as_str());
+        // the output written just before the build finished
+        io::copy(&mut file, &mut stdout)?;
+        if finished {
+            return Ok(());
+        }
+    }
+}
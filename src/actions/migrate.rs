@@ -0,0 +1,171 @@
+//! Upgrading the workspaces created by older versions of Ciel to the current layout
+
+use anyhow::{bail, Result};
+use console::style;
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    common::{
+        workspace_version, write_workspace_version, CIEL_INST_DIR, CURRENT_CIEL_VERSION,
+        SKELETON_DIRS,
+    },
+    config, info,
+    machine::list_instances_simple,
+};
+
+use super::container::container_down;
+
+/// Version 1 workspaces kept the layers directly in the instance directories
+const LEGACY_LAYER_DIRS: &[&str] = &["local", "diff", "diff.tmp"];
+
+/// A single change to the workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationStep {
+    /// Shut down the instance while its container can still be found by the legacy name
+    ShutDownInstance(String),
+    /// Move a file or a directory
+    Move {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    CreateDirectory(PathBuf),
+    /// Rewrite the configuration file in the current format and location
+    RewriteConfig,
+    /// Record the new version of the workspace
+    SetVersion(usize),
+}
+
+impl fmt::Display for MigrationStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationStep::ShutDownInstance(instance) => {
+                write!(f, "shut down instance {}", instance)
+            }
+            MigrationStep::Move { from, to } => {
+                write!(f, "move {} to {}", from.display(), to.display())
+            }
+            MigrationStep::CreateDirectory(path) => {
+                write!(f, "create directory {}", path.display())
+            }
+            MigrationStep::RewriteConfig => write!(f, "rewrite the workspace configuration"),
+            MigrationStep::SetVersion(version) => {
+                write!(f, "set the workspace version to {}", version)
+            }
+        }
+    }
+}
+
+impl MigrationStep {
+    fn apply(&self) -> Result<()> {
+        match self {
+            MigrationStep::ShutDownInstance(instance) => container_down(instance),
+            MigrationStep::Move { from, to } => {
+                if to.exists() {
+                    bail!("Unable to move {}: {} exists", from.display(), to.display());
+                }
+                if let Some(parent) = to.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::rename(from, to)?;
+
+                Ok(())
+            }
+            MigrationStep::CreateDirectory(path) => Ok(fs::create_dir_all(path)?),
+            MigrationStep::RewriteConfig => config::upgrade_config(),
+            MigrationStep::SetVersion(version) => write_workspace_version(*version),
+        }
+    }
+}
+
+/// Version 1 to 2: the layers are moved into the `layers` directory of the instances
+fn plan_instance_layers(instances: &[String], steps: &mut Vec<MigrationStep>) {
+    for instance in instances {
+        let inst = Path::new(CIEL_INST_DIR).join(instance);
+        for name in LEGACY_LAYER_DIRS {
+            let from = inst.join(name);
+            if from.exists() {
+                steps.push(MigrationStep::Move {
+                    from,
+                    to: inst.join("layers").join(name),
+                });
+            }
+        }
+    }
+}
+
+/// Compute the steps to upgrade the workspace from the given version
+pub fn plan_migration(version: usize) -> Result<Vec<MigrationStep>> {
+    if version > CURRENT_CIEL_VERSION {
+        bail!(
+            "This workspace was created by a newer version of Ciel (version {}), please upgrade Ciel.",
+            version
+        );
+    }
+    let mut steps = Vec::new();
+    if version == CURRENT_CIEL_VERSION {
+        return Ok(steps);
+    }
+    // the container names are derived differently before version 3,
+    // the running containers can not be found once the version is changed
+    let instances = list_instances_simple()?;
+    for instance in instances.iter() {
+        steps.push(MigrationStep::ShutDownInstance(instance.clone()));
+    }
+    if version < 2 {
+        plan_instance_layers(&instances, &mut steps);
+    }
+    for dir in SKELETON_DIRS {
+        if !Path::new(dir).is_dir() {
+            steps.push(MigrationStep::CreateDirectory(PathBuf::from(dir)));
+        }
+    }
+    if config::config_needs_upgrade() {
+        steps.push(MigrationStep::RewriteConfig);
+    }
+    steps.push(MigrationStep::SetVersion(CURRENT_CIEL_VERSION));
+
+    Ok(steps)
+}
+
+/// Upgrade the current workspace to the current version, `dry_run` only prints the planned changes
+pub fn upgrade_workspace(dry_run: bool) -> Result<()> {
+    let version = workspace_version()?;
+    let steps = plan_migration(version)?;
+    if steps.is_empty() {
+        info!(
+            "This workspace is already up to date (version {}).",
+            version
+        );
+        return Ok(());
+    }
+    info!(
+        "Upgrading workspace from version {} to {} ({} steps)",
+        version,
+        CURRENT_CIEL_VERSION,
+        steps.len()
+    );
+    for (i, step) in steps.iter().enumerate() {
+        if dry_run {
+            eprintln!("[{}/{}] {}", i + 1, steps.len(), step);
+            continue;
+        }
+        info!("[{}/{}] {}", i + 1, steps.len(), step);
+        step.apply()?;
+    }
+    if dry_run {
+        info!("Dry run, nothing has been changed.");
+    } else {
+        info!("Workspace upgraded to version {}.", CURRENT_CIEL_VERSION);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_plan_migration_up_to_date() {
+    assert!(plan_migration(CURRENT_CIEL_VERSION).unwrap().is_empty());
+    assert!(plan_migration(CURRENT_CIEL_VERSION + 1).is_err());
+}
@@ -3,23 +3,131 @@ use console::style;
 
 use crate::machine;
 
+mod abi;
+mod apt_progress;
+mod archive;
+mod cache;
+mod checkpoint;
 mod container;
+mod deps;
+mod diff;
+mod disk;
+mod elf;
+mod ephemeral;
+mod farm;
+mod gc;
+mod hooks;
+mod layer;
+mod logs;
+mod migrate;
+mod multiarch;
+mod notify;
+mod oci;
 mod onboarding;
 mod packaging;
+mod port;
+mod provenance;
+mod provision;
+mod qa;
+mod recover;
+mod remote;
+mod report;
+mod reproducible;
+mod retry;
+mod sbom;
+mod scheduler;
+mod search;
+mod snapshot;
+mod sources;
+mod status;
+mod template;
+mod topic;
+mod tree;
+mod verify;
+mod watchdog;
 
 // re-export all the functions from the sub
+pub use self::archive::{export_instance, import_instance};
+pub use self::cache::{apt_cache_prune, cache_clear, cache_stats};
+pub use self::checkpoint::{
+    checkpoint_container, discard_checkpoint, has_checkpoint, restore_container,
+};
 pub use self::container::*;
+pub use self::deps::{changed_packages, package_recipe_path};
+pub use self::diff::print_instance_diff;
+pub use self::disk::{disk_usage, print_disk_usage, DiskUsage};
+pub use self::ephemeral::run_ephemeral;
+pub use self::farm::farm_build;
+pub use self::gc::gc;
+pub use self::hooks::{run_hook, Hook};
+pub use self::layer::{attach_layer, create_layer, detach_layer, list_layers, remove_layer};
+pub use self::logs::{active_package_logs, list_builds, show_instance_log, show_package_log};
+pub use self::migrate::{plan_migration, upgrade_workspace, MigrationStep};
+pub use self::multiarch::build_archs;
+pub use self::oci::export_oci_image;
 pub use self::onboarding::onboarding;
 pub use self::packaging::*;
+pub use self::port::{add_port, list_ports, remove_port};
+pub use self::provision::provision_instance;
+pub use self::qa::QA_STATUS;
+pub use self::recover::{needs_recovery, recover};
+pub use self::remote::{add_remote, list_remotes, remote_build, remove_remote};
+pub use self::report::{failed_package_builds, FailedBuild};
+pub use self::reproducible::verify_reproducible;
+pub use self::scheduler::package_build_parallel;
+pub use self::search::{print_search_results, search_packages, SearchResult, SearchStatus};
+pub use self::snapshot::{
+    create_snapshot, list_snapshots, remove_snapshot, restore_snapshot, SnapshotInfo,
+};
+pub use self::sources::fetch_sources;
+pub use self::status::{package_versions, print_package_status, PackageStatus, PackageVersions};
+pub use self::template::{
+    add_instance_from_template, list_templates, remove_template, save_template, InstanceTemplate,
+};
+pub use self::topic::{print_topic, switch_topic};
+pub use self::tree::{clone_tree, switch_tree_branch, update_tree, DEFAULT_TREE_REMOTE};
+pub use self::verify::{check_dist, verify_dist, DistProblem, DistProblemKind};
+pub use self::watchdog::WATCHDOG_STATUS;
 
 const DEFAULT_MOUNTS: &[(&str, &str)] = &[
     ("OUTPUT/debs/", "/debs/"),
     ("TREE", "/tree"),
-    ("SRCS", "/var/cache/acbs/tarballs"),
-    ("CACHE", "/var/cache/apt/archives"),
+    (SOURCES_HOST_DIR, "/var/cache/acbs/tarballs"),
+    (APT_CACHE_HOST_DIR, "/var/cache/apt/archives"),
 ];
-const APT_UPDATE_SCRIPT: &str = r#"export DEBIAN_FRONTEND=noninteractive;apt-get update -y --allow-releaseinfo-change && apt-get -y -o Dpkg::Options::="--force-confnew" full-upgrade --autoremove --purge && apt autoclean"#;
-const OMA_UPDATE_SCRIPT: &str = r#"oma upgrade -y --force-confnew --no-progress --force-unsafe-io && oma autoremove -y --remove-config && oma clean"#;
+// the package cache is shared between the instances, use `ciel cache prune` to clean it
+const APT_UPDATE_SCRIPT: &str = r#"export DEBIAN_FRONTEND=noninteractive;apt-get update -y --allow-releaseinfo-change && apt-get -y -o Dpkg::Options::="--force-confnew" full-upgrade --autoremove --purge"#;
+const OMA_UPDATE_SCRIPT: &str = r#"oma upgrade -y --force-confnew --no-progress --force-unsafe-io && oma autoremove -y --remove-config"#;
+
+const CCACHE_HOST_DIR: &str = "CCACHE";
+const APT_CACHE_HOST_DIR: &str = "CACHE";
+/// Source tarballs shared by the instances, named after their checksums (see `ciel fetch`)
+const SOURCES_HOST_DIR: &str = "SRCS";
+
+/// Script installing the packages into the instance with oma or apt,
+/// refreshing the package lists first with `refresh`
+fn install_script<S: AsRef<str>>(packages: &[S], oma: bool, refresh: bool) -> String {
+    let packages = packages
+        .iter()
+        .map(|x| x.as_ref())
+        .collect::<Vec<_>>()
+        .join(" ");
+    match (oma, refresh) {
+        (true, true) => format!("oma install -y --force-confnew --no-progress {}", packages),
+        (true, false) => format!(
+            "oma install -y --force-confnew --no-progress --no-refresh {}",
+            packages
+        ),
+        (false, true) => format!(
+            "export DEBIAN_FRONTEND=noninteractive\napt-get update -y\napt-get install -y {}",
+            packages
+        ),
+        (false, false) => format!(
+            "export DEBIAN_FRONTEND=noninteractive\napt-get install -y {}",
+            packages
+        ),
+    }
+}
 
 type MountOptions = (Vec<String>, Vec<(String, &'static str)>);
 /// Ensure that the directories exist and mounted
@@ -37,7 +145,14 @@ pub fn ensure_host_sanity() -> Result<MountOptions, std::io::Error> {
             // remove SRCS
             mounts.swap_remove(2);
         }
-        if c.sep_mount {
+        if !c.dns_servers.is_empty() {
+            // the resolv.conf written by apply_config is kept
+            extra_options.push("--resolv-conf=off".to_string());
+        }
+        if c.ccache {
+            mounts.push((CCACHE_HOST_DIR.to_string(), crate::config::CCACHE_DIR));
+        }
+        if c.sep_mount || get_output_branch().is_some() {
             mounts.push((format!("{}/debs", get_output_directory(true)), "/debs/"));
             mounts.swap_remove(0);
         }
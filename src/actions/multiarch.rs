@@ -0,0 +1,151 @@
+//! Multi-architecture builds: the packages are built for each architecture in a workspace of its
+//! own (`.ciel/data/arches/<arch>`, sharing TREE with this one), natively or with QEMU user-mode
+//! emulation. The workspace of the architecture of this workspace is this one.
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use std::{
+    fs,
+    io::{self, Write},
+    os::unix::fs::symlink,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use tabwriter::TabWriter;
+
+use crate::{
+    common::{
+        ciel_init_at, find_arch_name, get_host_arch_name, CIEL_DATA_DIR, CIEL_DIST_DIR,
+        CIEL_INST_DIR,
+    },
+    config::{self, CielConfig},
+    error, info,
+};
+
+use super::{container::get_output_directory, remote::forwarded_build_args};
+
+const ARCH_WORKSPACES_DIR: &str = "arches";
+
+/// Architecture of the base OS of this workspace
+pub(super) fn workspace_arch(conf: &CielConfig) -> Option<&'static str> {
+    conf.arch
+        .as_deref()
+        .and_then(find_arch_name)
+        .or_else(get_host_arch_name)
+}
+
+/// Run ciel in the workspace, returns its exit status
+fn run_ciel(workspace: &Path, args: &[String]) -> Result<i32> {
+    let status = Command::new(std::env::current_exe()?)
+        .args(args)
+        .current_dir(workspace)
+        .status()
+        .map_err(|e| anyhow!("Unable to execute ciel: {}", e))?;
+
+    Ok(status.code().unwrap_or(-1))
+}
+
+/// Set up the workspace of the architecture (if needed) with the base OS and the instance,
+/// its output directory is `OUTPUT-<arch>` in this workspace
+fn prepare_arch_workspace(conf: &CielConfig, arch: &str, instance: &str) -> Result<PathBuf> {
+    let cwd = std::env::current_dir()?;
+    let root = cwd.join(CIEL_DATA_DIR).join(ARCH_WORKSPACES_DIR).join(arch);
+    if !root.join(".ciel").is_dir() {
+        info!("Creating the workspace for {}...", arch);
+        ciel_init_at(&root)?;
+        let mut arch_conf = CielConfig::load_config(&conf.save_config()?)?;
+        arch_conf.arch = Some(arch.to_string());
+        arch_conf.sep_mount = false;
+        config::write_workspace_config(&root, &arch_conf)?;
+    }
+    if !root.join("TREE").exists() {
+        symlink(cwd.join("TREE"), root.join("TREE"))?;
+    }
+    let output = cwd.join(format!("OUTPUT-{}", arch));
+    fs::create_dir_all(&output)?;
+    if !root.join("OUTPUT").exists() {
+        symlink(&output, root.join("OUTPUT"))?;
+    }
+    if fs::read_dir(root.join(CIEL_DIST_DIR)).map_or(true, |mut x| x.next().is_none()) {
+        info!("Loading the base OS for {}...", arch);
+        let status = run_ciel(
+            &root,
+            &["load-os".to_string(), "-a".to_string(), arch.to_string()],
+        )?;
+        if status != 0 {
+            bail!("Unable to load the base OS for {}.", arch);
+        }
+    }
+    if !root.join(CIEL_INST_DIR).join(instance).is_dir() {
+        let status = run_ciel(&root, &["add".to_string(), instance.to_string()])?;
+        if status != 0 {
+            bail!("Unable to create instance {} for {}.", instance, arch);
+        }
+    }
+
+    Ok(root)
+}
+
+/// Build the packages for each architecture with the arguments of `ciel build`,
+/// returns the exit status of the first failed build
+pub fn build_archs(archs: &[String], instance: &str, args: &[String]) -> Result<i32> {
+    let archs = archs
+        .iter()
+        .map(|x| find_arch_name(x).ok_or_else(|| anyhow!("Unknown architecture: {}", x)))
+        .collect::<Result<Vec<_>>>()?;
+    if !Path::new("TREE").is_dir() {
+        bail!("TREE does not exist, please clone it first.");
+    }
+    let conf = config::read_config()?;
+    let native = workspace_arch(&conf);
+    let mut command = vec!["build".to_string(), "-i".to_string(), instance.to_string()];
+    command.extend(forwarded_build_args(args));
+
+    let mut results = Vec::new();
+    for arch in archs {
+        let (workspace, output) = if native == Some(arch) {
+            (
+                std::env::current_dir()?,
+                get_output_directory(conf.sep_mount),
+            )
+        } else {
+            (
+                prepare_arch_workspace(&conf, arch, instance)?,
+                format!("OUTPUT-{}", arch),
+            )
+        };
+        info!("Building for {}...", arch);
+        let status = run_ciel(&workspace, &command)?;
+        if status != 0 {
+            error!("The build for {} failed with status {}.", arch, status);
+        }
+        results.push((arch, output, status));
+    }
+
+    let mut formatter = TabWriter::new(io::stderr());
+    writeln!(&mut formatter, "ARCH\tOUTPUT\tSTATUS")?;
+    for (arch, output, status) in results.iter() {
+        writeln!(
+            &mut formatter,
+            "{}\t{}\t{}",
+            arch,
+            output,
+            if *status == 0 {
+                style("success").green().to_string()
+            } else {
+                style(format!("failed ({})", status)).red().to_string()
+            }
+        )?;
+    }
+    formatter.flush()?;
+
+    Ok(results.iter().map(|x| x.2).find(|x| *x != 0).unwrap_or(0))
+}
+
+#[test]
+fn test_workspace_arch() {
+    let mut conf = CielConfig::default();
+    assert_eq!(workspace_arch(&conf), get_host_arch_name());
+    conf.arch = Some("loongarch64".to_string());
+    assert_eq!(workspace_arch(&conf), Some("loongarch64"));
+}
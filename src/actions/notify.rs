@@ -0,0 +1,128 @@
+//! Notification hooks fired when a build finishes or fails
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use serde::Serialize;
+use std::{process::Command, time::Duration};
+
+use crate::{
+    config::{self, NotifyConfig},
+    network, warn,
+};
+
+use super::packaging::{format_duration, BuildState};
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Build result sent to the hooks
+#[derive(Debug, Serialize)]
+struct BuildNotification<'a> {
+    instance: &'a str,
+    packages: &'a [String],
+    /// The package that failed to build (if any)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed: Option<&'a str>,
+    status: i32,
+    duration: u64,
+}
+
+impl BuildNotification<'_> {
+    fn summary(&self) -> String {
+        match self.failed {
+            Some(package) => format!(
+                "{} failed to build (status: {}) after {}",
+                package,
+                self.status,
+                format_duration(self.duration)
+            ),
+            None => format!(
+                "{} package(s) built in {}",
+                self.packages.len(),
+                format_duration(self.duration)
+            ),
+        }
+    }
+}
+
+fn run_command(command: &str, notification: &BuildNotification) -> Result<()> {
+    let status = Command::new("/bin/sh")
+        .args(["-c", command])
+        .env("CIEL_BUILD_INSTANCE", notification.instance)
+        .env("CIEL_BUILD_PACKAGES", notification.packages.join(" "))
+        .env("CIEL_BUILD_FAILED", notification.failed.unwrap_or_default())
+        .env("CIEL_BUILD_STATUS", notification.status.to_string())
+        .env("CIEL_BUILD_DURATION", notification.duration.to_string())
+        .env("CIEL_BUILD_SUMMARY", notification.summary())
+        .status()
+        .map_err(|e| anyhow!("Unable to execute the command: {}", e))?;
+    if !status.success() {
+        bail!("Command exited with error: {}", status);
+    }
+
+    Ok(())
+}
+
+fn post_webhook(url: &str, notification: &BuildNotification) -> Result<()> {
+    network::ensure_online("Sending the webhook")?;
+    network::http_client_builder()?
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()?
+        .post(url)
+        .json(notification)
+        .send()?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+fn show_desktop_notification(notification: &BuildNotification) -> Result<()> {
+    let title = if notification.status == 0 {
+        "Ciel: build successful"
+    } else {
+        "Ciel: build failed"
+    };
+    let status = Command::new("notify-send")
+        .args(["--app-name=ciel", title, &notification.summary()])
+        .status()
+        .map_err(|e| anyhow!("Unable to execute notify-send: {}", e))?;
+    if !status.success() {
+        bail!("notify-send exited with error: {}", status);
+    }
+
+    Ok(())
+}
+
+fn fire_hooks(config: &NotifyConfig, notification: &BuildNotification) {
+    if let Some(command) = &config.command {
+        if let Err(e) = run_command(command, notification) {
+            warn!("Notification command failed: {}", e);
+        }
+    }
+    if let Some(url) = &config.webhook {
+        if let Err(e) = post_webhook(url, notification) {
+            warn!("Notification webhook failed: {}", e);
+        }
+    }
+    if config.desktop {
+        if let Err(e) = show_desktop_notification(notification) {
+            warn!("Desktop notification failed: {}", e);
+        }
+    }
+}
+
+/// Fire the configured notification hooks (failures of the hooks are not fatal)
+pub(super) fn notify_build_result(state: &BuildState, status: i32, duration: u64) {
+    let config = match config::read_config() {
+        Ok(c) => c.notify,
+        Err(_) => return,
+    };
+    let notification = BuildNotification {
+        instance: &state.instance,
+        packages: &state.packages,
+        failed: state.failed.last().map(|x| x.package.as_str()),
+        status,
+        duration,
+    };
+
+    fire_hooks(&config, &notification);
+}
@@ -0,0 +1,343 @@
+//! OCI image export: the base OS, the shared layers and the changes of the instance become the
+//! layers of an image (OCI image layout), which can be pushed to a registry with skopeo
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use flate2::{write::GzEncoder, Compression};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    os::unix::fs::{FileTypeExt, MetadataExt},
+    path::{Path, PathBuf},
+    process::Command,
+};
+use tar::{Builder, EntryType, Header, HeaderMode};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{
+    common::{create_spinner, is_instance_exists, CIEL_DIST_DIR, CIEL_INST_DIR},
+    config, info,
+    overlayfs::{self, OPAQUE_MARKER, WHITEOUT_PREFIX},
+};
+
+use super::{container::container_down, multiarch::workspace_arch};
+
+const OCI_LAYOUT_VERSION: &str = "1.0.0";
+const MEDIA_TYPE_INDEX: &str = "application/vnd.oci.image.index.v1+json";
+const MEDIA_TYPE_MANIFEST: &str = "application/vnd.oci.image.manifest.v1+json";
+const MEDIA_TYPE_CONFIG: &str = "application/vnd.oci.image.config.v1+json";
+const MEDIA_TYPE_LAYER: &str = "application/vnd.oci.image.layer.v1.tar+gzip";
+const DEFAULT_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+const OPAQUE_XATTRS: &[&str] = &["trusted.overlay.opaque", "user.fuseoverlayfs.opaque"];
+
+/// Write the data through, keeping its SHA-256 checksum and size
+struct DigestWriter<W: Write> {
+    inner: W,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl<W: Write> DigestWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            size: 0,
+        }
+    }
+
+    /// The writer, the digest (`sha256:<hex>`) and the size of the data
+    fn finish(self) -> (W, String, u64) {
+        (
+            self.inner,
+            format!("sha256:{:x}", self.hasher.finalize()),
+            self.size,
+        )
+    }
+}
+
+impl<W: Write> Write for DigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let size = self.inner.write(buf)?;
+        self.hasher.update(&buf[..size]);
+        self.size += size as u64;
+
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A layer written to the blobs: its descriptor in the manifest and its uncompressed digest
+struct Layer {
+    descriptor: Value,
+    diff_id: String,
+}
+
+/// The OCI architecture and variant of the AOSC OS architecture
+fn oci_platform(arch: &str) -> (&'static str, Option<&'static str>) {
+    match arch {
+        "ppc64el" => ("ppc64le", None),
+        "loongarch64" | "loongarch64_nosimd" => ("loong64", None),
+        "loongson3" => ("mips64le", None),
+        "mips64r6el" => ("mips64le", Some("r6")),
+        "i486" => ("386", None),
+        "armv4" => ("arm", Some("v4")),
+        "armv6hf" => ("arm", Some("v6")),
+        "armv7hf" => ("arm", Some("v7")),
+        "powerpc" => ("ppc", None),
+        "m68k" => ("m68k", None),
+        "riscv64" => ("riscv64", None),
+        "arm64" => ("arm64", None),
+        _ => ("amd64", None),
+    }
+}
+
+/// Path of the OCI whiteout for the deleted file
+fn whiteout_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(WHITEOUT_PREFIX);
+    name.push(path.file_name().unwrap_or_default());
+
+    path.with_file_name(name)
+}
+
+/// Move the blob (written to a temporary file) to its place in the layout
+fn store_blob(layout: &Path, temp: &Path, digest: &str) -> Result<PathBuf> {
+    let path = layout.join("blobs").join(digest.replace(':', "/"));
+    fs::rename(temp, &path)?;
+
+    Ok(path)
+}
+
+fn write_json_blob(layout: &Path, value: &Value) -> Result<(String, u64)> {
+    let data = serde_json::to_vec(value)?;
+    let digest = format!("sha256:{:x}", Sha256::digest(&data));
+    fs::write(layout.join("blobs").join(digest.replace(':', "/")), &data)?;
+
+    Ok((digest, data.len() as u64))
+}
+
+fn append_empty<W: Write>(builder: &mut Builder<W>, path: &Path) -> Result<()> {
+    let mut header = Header::new_gnu();
+    header.set_entry_type(EntryType::Regular);
+    header.set_mode(0o644);
+    header.set_size(0);
+    builder.append_data(&mut header, path, io::empty())?;
+
+    Ok(())
+}
+
+/// Archive the overlay layer as an OCI layer: the overlayfs whiteouts (character devices)
+/// and opaque directories (extended attributes) are converted to the OCI whiteout files
+fn write_layer(layout: &Path, dir: &Path) -> Result<Layer> {
+    let temp = layout.join("blobs").join("layer.tmp");
+    let compressed = DigestWriter::new(File::create(&temp)?);
+    let uncompressed = DigestWriter::new(GzEncoder::new(compressed, Compression::default()));
+    let mut builder = Builder::new(uncompressed);
+    for entry in walkdir::WalkDir::new(dir).min_depth(1).sort_by_file_name() {
+        let entry = entry?;
+        let path = entry.path();
+        let relative = path.strip_prefix(dir)?;
+        let metadata = fs::symlink_metadata(path)?;
+        let file_type = metadata.file_type();
+        if file_type.is_char_device() && metadata.rdev() == 0 {
+            append_empty(&mut builder, &whiteout_path(relative))?;
+            continue;
+        }
+        if file_type.is_dir() && xattr::get(path, "trusted.overlay.redirect")?.is_some() {
+            bail!(
+                "{} is a renamed directory (overlayfs redirect_dir), which can not be exported.",
+                path.display()
+            );
+        }
+        let mut header = Header::new_gnu();
+        header.set_metadata_in_mode(&metadata, HeaderMode::Complete);
+        if file_type.is_file() {
+            builder.append_data(&mut header, relative, File::open(path)?)?;
+            continue;
+        }
+        header.set_size(0);
+        if file_type.is_symlink() {
+            builder.append_link(&mut header, relative, fs::read_link(path)?)?;
+            continue;
+        }
+        builder.append_data(&mut header, relative, io::empty())?;
+        if file_type.is_dir() && !path.join(OPAQUE_MARKER).exists() {
+            for name in OPAQUE_XATTRS {
+                if xattr::get(path, name)?.as_deref() == Some(b"y") {
+                    append_empty(&mut builder, &relative.join(OPAQUE_MARKER))?;
+                    break;
+                }
+            }
+        }
+    }
+    let (encoder, diff_id, _) = builder.into_inner()?.finish();
+    let (mut file, digest, size) = encoder.finish()?.finish();
+    file.flush()?;
+    store_blob(layout, &temp, &digest)?;
+
+    Ok(Layer {
+        descriptor: json!({
+            "mediaType": MEDIA_TYPE_LAYER,
+            "digest": digest,
+            "size": size,
+        }),
+        diff_id,
+    })
+}
+
+/// The layers of the instance from the bottom to the top, with their descriptions
+fn instance_layers(instance: &str) -> Result<Vec<(PathBuf, String)>> {
+    let inst_dir = Path::new(CIEL_INST_DIR).join(instance);
+    let mut layers = vec![(PathBuf::from(CIEL_DIST_DIR), "base OS".to_string())];
+    let overrides = config::read_instance_overrides(instance)?.unwrap_or_default();
+    for name in overrides.layers.unwrap_or_default() {
+        layers.push((
+            overlayfs::shared_layer_path(&name)?,
+            format!("shared layer {}", name),
+        ));
+    }
+    for (name, description) in [("local", "configuration"), ("diff", "changes")] {
+        let path = inst_dir.join("layers").join(name);
+        if path.is_dir() {
+            layers.push((path, format!("{} of {}", description, instance)));
+        }
+    }
+
+    Ok(layers)
+}
+
+/// Export the instance as an OCI image in the directory (OCI image layout, tagged with `tag`),
+/// and push it to the destination (e.g. `docker://registry.example.org/aosc/buildenv:latest`) if any
+pub fn export_oci_image(
+    instance: &str,
+    output: &Path,
+    tag: &str,
+    push: Option<&str>,
+) -> Result<()> {
+    if !is_instance_exists(instance) {
+        bail!("Instance {} does not exist.", instance);
+    }
+    let inst_dir = Path::new(CIEL_INST_DIR).join(instance);
+    if inst_dir.join("layers/snapshot").exists() || inst_dir.join("layers/clone").exists() {
+        bail!("Only instances using the overlay filesystem backend can be exported.");
+    }
+    if fs::read_dir(output).is_ok_and(|mut x| x.next().is_some()) {
+        bail!("{} already exists and is not empty.", output.display());
+    }
+    // make sure the filesystem is in a consistent state
+    container_down(instance)?;
+    overlayfs::mount_dist_image()?;
+    let conf = config::read_config()?;
+    let (architecture, variant) = oci_platform(workspace_arch(&conf).unwrap_or_default());
+
+    fs::create_dir_all(output.join("blobs/sha256"))?;
+    fs::write(
+        output.join("oci-layout"),
+        serde_json::to_vec(&json!({ "imageLayoutVersion": OCI_LAYOUT_VERSION }))?,
+    )?;
+    let created = OffsetDateTime::now_utc().format(&Rfc3339)?;
+    let mut layers = Vec::new();
+    let mut history = Vec::new();
+    for (path, description) in instance_layers(instance)? {
+        let spinner = create_spinner("Archiving the layer...", 200);
+        spinner.set_message(format!("Archiving the {}...", description));
+        let layer = write_layer(output, &path);
+        spinner.finish_and_clear();
+        layers.push(layer?);
+        history.push(json!({ "created": created, "created_by": format!("ciel: {}", description) }));
+    }
+
+    let mut image_config = json!({
+        "created": created,
+        "architecture": architecture,
+        "os": "linux",
+        "config": {
+            "Env": [format!("PATH={}", DEFAULT_PATH)],
+            "Cmd": ["/bin/bash"],
+            "WorkingDir": "/",
+            "Labels": {
+                "org.opencontainers.image.title": instance,
+                "io.aosc.ciel.version": env!("CARGO_PKG_VERSION"),
+            },
+        },
+        "rootfs": {
+            "type": "layers",
+            "diff_ids": layers.iter().map(|x| x.diff_id.clone()).collect::<Vec<_>>(),
+        },
+        "history": history,
+    });
+    if let Some(variant) = variant {
+        image_config["variant"] = json!(variant);
+    }
+    let (config_digest, config_size) = write_json_blob(output, &image_config)?;
+    let manifest = json!({
+        "schemaVersion": 2,
+        "mediaType": MEDIA_TYPE_MANIFEST,
+        "config": {
+            "mediaType": MEDIA_TYPE_CONFIG,
+            "digest": config_digest,
+            "size": config_size,
+        },
+        "layers": layers.iter().map(|x| x.descriptor.clone()).collect::<Vec<_>>(),
+    });
+    let (manifest_digest, manifest_size) = write_json_blob(output, &manifest)?;
+    let mut platform = json!({ "architecture": architecture, "os": "linux" });
+    if let Some(variant) = variant {
+        platform["variant"] = json!(variant);
+    }
+    fs::write(
+        output.join("index.json"),
+        serde_json::to_vec_pretty(&json!({
+            "schemaVersion": 2,
+            "mediaType": MEDIA_TYPE_INDEX,
+            "manifests": [{
+                "mediaType": MEDIA_TYPE_MANIFEST,
+                "digest": manifest_digest,
+                "size": manifest_size,
+                "platform": platform,
+                "annotations": { "org.opencontainers.image.ref.name": tag },
+            }],
+        }))?,
+    )?;
+    info!(
+        "{}: exported to {} ({}), load it with `skopeo copy oci:{}:{} ...` or `podman load`.",
+        instance,
+        output.display(),
+        style(tag).cyan(),
+        output.display(),
+        tag
+    );
+
+    if let Some(destination) = push {
+        info!("Pushing the image to {}...", destination);
+        let status = Command::new("skopeo")
+            .arg("copy")
+            .arg(format!("oci:{}:{}", output.display(), tag))
+            .arg(destination)
+            .status()
+            .map_err(|e| anyhow!("Unable to execute skopeo: {}", e))?;
+        if !status.success() {
+            bail!("Unable to push the image to {}: {}", destination, status);
+        }
+        info!("Image pushed to {}.", destination);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_oci_platform() {
+    assert_eq!(oci_platform("ppc64el"), ("ppc64le", None));
+    assert_eq!(oci_platform("armv7hf"), ("arm", Some("v7")));
+    assert_eq!(oci_platform("loongarch64_nosimd"), ("loong64", None));
+    assert_eq!(
+        whiteout_path(Path::new("usr/bin/foo")),
+        PathBuf::from("usr/bin/.wh.foo")
+    );
+}
@@ -1,14 +1,13 @@
-use anyhow::{anyhow, Result};
-use console::{style, user_attended, Term};
+use anyhow::{anyhow, bail, Result};
+use console::{style, Term};
 use dialoguer::{theme::ColorfulTheme, Confirm, Input};
 use std::{fs, path::Path, process::exit};
 
 use crate::{
     actions::get_branch_name,
-    cli::GIT_TREE_URL,
     common::*,
     config, error, info,
-    network::{download_git, pick_latest_rootfs},
+    network::{download_git, is_offline, pick_latest_rootfs, strip_multi_source_suffix},
     overlayfs::create_new_instance_fs,
     repo::{init_repo, refresh_repo},
     warn,
@@ -17,7 +16,13 @@ use crate::{
 use super::{load_os, mount_fs};
 
 /// Show interactive onboarding guide, triggered by issuing `ciel new`
-pub fn onboarding(custom_tarball: Option<&String>, arch: Option<&str>) -> Result<()> {
+pub fn onboarding(
+    custom_tarball: Option<&String>,
+    arch: Option<&str>,
+    rootless: bool,
+    instance: Option<&str>,
+    tree_url: &str,
+) -> Result<()> {
     ctrlc::set_handler(move || {
         let _ = Term::stderr().show_cursor();
         exit(1);
@@ -31,6 +36,13 @@ pub fn onboarding(custom_tarball: Option<&String>, arch: Option<&str>) -> Result
         info!("Please run `ciel farewell` to nuke it before running this command.");
         return Err(anyhow!("Unable to create a ciel workspace."));
     }
+    // fail before asking any questions
+    if is_offline() && custom_tarball.is_none() {
+        bail!("Please specify a local tarball (--from-tarball) to create a workspace in offline mode.");
+    }
+    if is_offline() && !Path::new("TREE").is_dir() {
+        bail!("Creating a workspace without an existing TREE requires network access, which is not allowed in offline mode.");
+    }
     info!("Before continuing, I need to ask you a few questions:");
     let real_arch = if let Some(arch) = arch {
         arch
@@ -39,9 +51,16 @@ pub fn onboarding(custom_tarball: Option<&String>, arch: Option<&str>) -> Result
     } else {
         ask_for_target_arch()?
     };
-    let config = config::ask_for_config(None)?;
-    let mut init_instance: Option<String> = None;
-    if user_attended()
+    let mut defaults = config::CielConfig::default();
+    defaults.rootless = rootless;
+    let config = config::ask_for_config(Some(defaults))?;
+    let mut init_instance: Option<String> = instance.map(|x| x.to_string());
+    if let Some(name) = &init_instance {
+        info!(
+            "`{}` will be created after initialization is finished.",
+            name
+        );
+    } else if can_prompt()
         && Confirm::with_theme(&theme)
             .with_prompt("Do you want to add a new instance now?")
             .interact()?
@@ -63,7 +82,9 @@ pub fn onboarding(custom_tarball: Option<&String>, arch: Option<&str>) -> Result
     info!("Initializing container OS...");
     let (rootfs_url, rootfs_sha256, use_tarball) = match custom_tarball {
         Some(rootfs) => {
-            let use_tarball = !rootfs.ends_with(".squashfs");
+            let use_tarball = !strip_multi_source_suffix(rootfs)
+                .unwrap_or(rootfs)
+                .ends_with(".squashfs");
             info!(
                 "Using custom {} from {}",
                 if use_tarball { "tarball" } else { "squashfs" },
@@ -76,21 +97,18 @@ pub fn onboarding(custom_tarball: Option<&String>, arch: Option<&str>) -> Result
             auto_pick_rootfs(&theme, real_arch)?
         }
     };
-    load_os(&rootfs_url, rootfs_sha256, use_tarball)?;
+    load_os(&rootfs_url, rootfs_sha256, use_tarball, false)?;
     info!("Initializing ABBS tree...");
     if Path::new("TREE").is_dir() {
         warn!("TREE already exists, skipping this step...");
     } else {
         // if TREE is a file, then remove it
         fs::remove_file("TREE").ok();
-        download_git(GIT_TREE_URL, Path::new("TREE"))?;
+        download_git(tree_url, Path::new("TREE"))?;
     }
     config::apply_config(CIEL_DIST_DIR, &config)?;
     info!("Applying configurations...");
-    fs::write(
-        Path::new(CIEL_DATA_DIR).join("config.toml"),
-        config.save_config()?,
-    )?;
+    config::write_config(&config)?;
     info!("Configurations applied.");
     let cwd = std::env::current_dir()?;
     let mut output_dir_name = "OUTPUT".to_string();
@@ -132,7 +150,7 @@ fn auto_pick_rootfs(
             rootfs.arch, rootfs.date
         );
         Ok((
-            format!("https://releases.aosc.io/{}", rootfs.path),
+            format!("{}{}", config::releases_url(), rootfs.path),
             Some(rootfs.sha256sum),
             false,
         ))
@@ -140,6 +158,7 @@ fn auto_pick_rootfs(
         warn!(
             "Ciel was unable to find a suitable buildkit release. Please specify the URL manually."
         );
+        ensure_interactive("please specify the OS tarball with --from-tarball")?;
         let rootfs_url = Input::<String>::with_theme(theme)
             .with_prompt("Rootfs URL")
             .interact_text()?;
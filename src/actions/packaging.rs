@@ -1,4 +1,4 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use console::style;
 use dialoguer::{theme::ColorfulTheme, Select};
 use nix::unistd::gethostname;
@@ -6,19 +6,53 @@ use serde::{Deserialize, Serialize};
 use std::{
     fs::{self, File},
     io::{BufRead, BufReader, Write},
-    path::Path,
+    path::{Path, PathBuf},
     thread::{self, sleep},
     time::{Duration, Instant},
 };
 use walkdir::WalkDir;
 
-use crate::{actions::OMA_UPDATE_SCRIPT, common::create_spinner, config, error, info, repo, warn};
+use crate::{
+    actions::OMA_UPDATE_SCRIPT,
+    backend::get_container_backend,
+    common::{create_spinner, ensure_free_space, ensure_interactive, CIEL_DATA_DIR},
+    config::{self, WatchdogConfig},
+    error, info,
+    logging::{self, json_output, print_json},
+    repo, warn,
+};
 
 use super::{
-    container::{get_output_directory, mount_fs, rollback_container, run_in_container},
+    apt_progress::run_apt_script_with_progress,
+    cache::run_apt_script,
+    container::{
+        apply_instance_config, get_instance_ns_name, get_output_directory, mount_fs,
+        rollback_container, run_in_container, run_in_container_with_log,
+    },
+    deps::{order_packages, package_dependencies},
+    hooks::{run_hook, Hook},
+    install_script,
+    logs::PackageLogFile,
+    notify::notify_build_result,
+    report::{record_package_build, write_build_report, BuildMeter, BuildUsage},
+    retry::{build_retries, classify_failure, reduced_jobs, FailureKind, RETRY_DELAY},
     APT_UPDATE_SCRIPT,
 };
 
+const BUILD_STATE_FILE: &str = "state.json";
+/// Cross toolchain packages (the target architecture is appended to the names)
+const CROSS_TOOLCHAIN_PACKAGES: &[&str] = &["gcc+cross-", "binutils+cross-", "glibc+cross-"];
+/// Runs the command in a new network namespace with only the loopback interface
+const ISOLATE_NETWORK_WRAPPER: &[&str] = &[
+    "unshare",
+    "--net",
+    "--",
+    "/bin/sh",
+    "-c",
+    "ip link set lo up 2>/dev/null; exec \"$@\"",
+    "sh",
+];
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BuildCheckPoint {
     packages: Vec<String>,
@@ -27,10 +61,124 @@ pub struct BuildCheckPoint {
     attempts: usize,
 }
 
+/// A package that failed to build
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BuildFailure {
+    pub package: String,
+    pub status: i32,
+}
+
+/// Persistent state of the build queue, saved in the workspace data directory
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BuildState {
+    pub instance: String,
+    pub packages: Vec<String>,
+    pub completed: Vec<String>,
+    pub failed: Vec<BuildFailure>,
+    pub attempts: usize,
+}
+
+impl BuildState {
+    pub fn new(instance: &str, packages: &[String], attempts: usize) -> Self {
+        Self {
+            instance: instance.to_string(),
+            packages: packages.to_vec(),
+            attempts,
+            ..Default::default()
+        }
+    }
+
+    fn path() -> PathBuf {
+        Path::new(CIEL_DATA_DIR).join(BUILD_STATE_FILE)
+    }
+
+    /// Load the build state of the last build
+    pub fn load() -> Result<Self> {
+        let f = File::open(Self::path())?;
+
+        Ok(serde_json::from_reader(f)?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(CIEL_DATA_DIR)?;
+        fs::write(Self::path(), serde_json::to_vec_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    /// Mark the package as successfully built and save the state
+    pub fn complete(&mut self, package: &str) -> Result<()> {
+        self.completed.push(package.to_string());
+
+        self.save()
+    }
+
+    /// Record the failed package and save the state
+    pub fn fail(&mut self, package: &str, status: i32) -> Result<()> {
+        self.failed.push(BuildFailure {
+            package: package.to_string(),
+            status,
+        });
+
+        self.save()
+    }
+
+    /// Packages that have not been built yet (in the original order)
+    pub fn remaining(&self) -> Vec<String> {
+        self.packages
+            .iter()
+            .filter(|x| !self.completed.contains(x))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Result of a build, printed to stdout in JSON output mode
+#[derive(Debug, Serialize)]
+struct BuildResult<'a> {
+    status: i32,
+    duration: u64,
+    remaining: Vec<String>,
+    #[serde(flatten)]
+    state: &'a BuildState,
+}
+
+/// Write the build report, fire the notification hooks, and print the result of the build if JSON output is requested
+pub(super) fn report_build_result(state: &BuildState, status: i32, duration: u64) -> Result<()> {
+    if let Err(e) = write_build_report(state, status, duration) {
+        warn!("Unable to write the build report: {}", e);
+    }
+    notify_build_result(state, status, duration);
+    if !json_output() {
+        return Ok(());
+    }
+
+    print_json(&BuildResult {
+        status,
+        duration,
+        remaining: state.remaining(),
+        state,
+    })
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct BuildSettings {
     pub offline: bool,
     pub stage2: bool,
+    /// Target architecture when cross-compiling
+    pub cross: Option<&'static str>,
+    /// Generate an HTML build report in addition to the JSON one
+    pub html_report: bool,
+    /// Also build the in-tree dependencies missing from the list
+    pub with_deps: bool,
+    /// Also rebuild the packages depending on the listed ones
+    pub revdeps: bool,
+    /// Build without network access, to verify that nothing is downloaded during the build
+    pub isolate_network: bool,
+    /// Number of retries when a build fails because of the network (e.g. when fetching the sources)
+    pub retries: usize,
+    /// Fail the builds when the QA checks find problems in the produced packages
+    pub strict: bool,
 }
 
 pub fn load_build_checkpoint<P: AsRef<Path>>(path: P) -> Result<BuildCheckPoint> {
@@ -39,6 +187,34 @@ pub fn load_build_checkpoint<P: AsRef<Path>>(path: P) -> Result<BuildCheckPoint>
     Ok(bincode::deserialize_from(f)?)
 }
 
+/// Load the persistent build state and continue from the last failure
+pub fn load_build_state() -> Result<BuildCheckPoint> {
+    let state = BuildState::load()
+        .map_err(|e| anyhow!("Unable to load the state of the last build: {}", e))?;
+    let packages = state.remaining();
+    if packages.is_empty() {
+        return Err(anyhow!(
+            "All the packages in the last build were built successfully."
+        ));
+    }
+    if let Some(failure) = state.failed.last() {
+        info!(
+            "Last build failed at {} (status: {}), {} of {} packages were completed.",
+            failure.package,
+            failure.status,
+            state.completed.len(),
+            state.packages.len()
+        );
+    }
+
+    Ok(BuildCheckPoint {
+        packages,
+        progress: 0,
+        time_elapsed: 0,
+        attempts: state.attempts,
+    })
+}
+
 fn dump_build_checkpoint(checkpoint: &BuildCheckPoint) -> Result<()> {
     let save_state = bincode::serialize(checkpoint)?;
     let last_package = checkpoint
@@ -59,7 +235,7 @@ fn dump_build_checkpoint(checkpoint: &BuildCheckPoint) -> Result<()> {
 }
 
 #[inline]
-fn format_duration(seconds: u64) -> String {
+pub(super) fn format_duration(seconds: u64) -> String {
     format!(
         "{:02}:{:02}:{:02}",
         seconds / 3600,
@@ -103,7 +279,9 @@ fn read_package_list<P: AsRef<Path>>(filename: P, depth: usize) -> Result<Vec<St
 }
 
 /// Expand the packages list to an array of packages
-fn expand_package_list<S: AsRef<str>, I: IntoIterator<Item = S>>(packages: I) -> Vec<String> {
+pub(super) fn expand_package_list<S: AsRef<str>, I: IntoIterator<Item = S>>(
+    packages: I,
+) -> Vec<String> {
     let mut expanded = Vec::new();
     for package in packages {
         let package = package.as_ref();
@@ -146,11 +324,248 @@ impl<T> Drop for RepoMonitorGuard<T> {
     }
 }
 
+/// Update the OS in the instance before building (with oma unless apt is configured),
+/// retrying with apt if oma fails. Dependency conflicts are not retried.
+pub(super) fn update_instance_os(instance: &str, output: Option<&File>) -> i32 {
+    let mut status = -1;
+    let mut oma = config::read_instance_config(instance).map_or(true, |x| x.use_oma());
+    for i in 1..=5 {
+        let script = if oma {
+            OMA_UPDATE_SCRIPT
+        } else {
+            APT_UPDATE_SCRIPT
+        };
+        let failure;
+        (status, failure) = match output {
+            Some(output) => run_apt_script(instance, script, Some(output)).map(|x| (x, None)),
+            None => run_apt_script_with_progress(instance, script),
+        }
+        .unwrap_or((-1, None));
+        if status == 0 || (!oma && failure == Some(FailureKind::DependencyConflict)) {
+            break;
+        } else {
+            let interval = 3u64.pow(i);
+            warn!(
+                "Failed to update the OS, will retry in {} seconds ...",
+                interval
+            );
+            oma = false;
+            sleep(Duration::from_secs(interval));
+        }
+    }
+
+    status
+}
+
+/// Install the cross toolchain into the instance when cross-compiling
+pub(super) fn install_cross_toolchain(instance: &str, output: Option<&File>) -> Result<i32> {
+    let arch = match std::env::var("CIEL_CROSS") {
+        Ok(arch) => arch,
+        Err(_) => return Ok(0),
+    };
+    let packages = CROSS_TOOLCHAIN_PACKAGES
+        .iter()
+        .map(|x| format!("{}{}", x, arch))
+        .collect::<Vec<_>>();
+    let oma = config::read_instance_config(instance).is_ok_and(|x| x.use_oma());
+    let script = install_script(&packages, oma, false);
+
+    run_apt_script(instance, &script, output)
+}
+
+/// Check the free space required for building the package (`free-space.build` or the package hint)
+pub(super) fn check_build_space(package: &str) -> Result<()> {
+    let config = config::read_config()
+        .map(|c| c.free_space)
+        .unwrap_or_default();
+
+    ensure_free_space(
+        config.build_requirement(package)?,
+        &format!("building {}", package),
+    )
+}
+
+/// Environment variables passed to the build hooks, with the exit status and the log after the build
+pub(super) fn build_hook_env(
+    package: &str,
+    result: Option<(i32, &Path)>,
+) -> Vec<(&'static str, String)> {
+    let mut env = vec![("CIEL_PACKAGE", package.to_string())];
+    if let Some((status, log)) = result {
+        env.push(("CIEL_BUILD_STATUS", status.to_string()));
+        env.push(("CIEL_BUILD_LOG", log.to_string_lossy().to_string()));
+    }
+
+    env
+}
+
+/// Command building the package in the container, with the number of parallel jobs if specified
+/// (picked up by the autobuild configuration), without network access if the network isolation is enabled
+pub(super) fn build_command(package: &str, jobs: Option<usize>) -> Vec<String> {
+    let mut command = Vec::new();
+    if let Some(jobs) = jobs {
+        command.push("env".to_string());
+        command.push(format!("CIEL_JOBS={}", jobs));
+    }
+    if std::env::var("CIEL_ISOLATE_NETWORK").is_ok() {
+        command.extend(ISOLATE_NETWORK_WRAPPER.iter().map(|x| x.to_string()));
+    }
+    command.extend(
+        ["/bin/acbs-build", "--", package]
+            .iter()
+            .map(|x| x.to_string()),
+    );
+
+    command
+}
+
+/// Build the package in the instance and record its log, the builds failing because of
+/// the network (e.g. when fetching the sources) are retried up to `--retries` times,
+/// the ones killed by the OOM killer are retried with half of the parallel jobs
+pub(super) fn run_package_build(
+    instance: &str,
+    package: &str,
+    prefix: bool,
+    watchdog: &WatchdogConfig,
+) -> Result<(i32, BuildUsage, PathBuf)> {
+    let retries = build_retries();
+    let mut attempt = 0;
+    let mut jobs = config::read_instance_config(instance)
+        .ok()
+        .and_then(|x| x.jobs.package_override(package));
+    loop {
+        let meter = BuildMeter::start(instance);
+        let log = PackageLogFile::create(package, instance)?;
+        let log_path = log.path();
+        let status = run_in_container_with_log(
+            instance,
+            &build_command(package, jobs),
+            &log.file,
+            prefix,
+            Some(watchdog),
+        )?;
+        let usage = meter.finish();
+        log.finish(status, &usage)?;
+        if status == 0 {
+            return Ok((status, usage, log_path));
+        }
+        let kind = classify_failure(status, &log_path, usage.oom_killed());
+        if kind == FailureKind::OutOfMemory {
+            if let Some(reduced) = reduced_jobs(jobs) {
+                warn!(
+                    "{}: {} ran out of memory, retrying with {} parallel job(s)...",
+                    instance, package, reduced
+                );
+                jobs = Some(reduced);
+                continue;
+            }
+        }
+        if attempt >= retries || kind != FailureKind::Network {
+            return Ok((status, usage, log_path));
+        }
+        attempt += 1;
+        warn!(
+            "{}: {} failed because of a network error, retrying ({}/{})...",
+            instance, package, attempt, retries
+        );
+        sleep(RETRY_DELAY * attempt as u32);
+    }
+}
+
+/// Download the build dependencies of the package into the package cache beforehand
+/// when the network isolation is enabled, so they can be installed without network access
+pub(super) fn prefetch_build_dependencies(
+    instance: &str,
+    package: &str,
+    output: Option<&File>,
+) -> Result<()> {
+    if std::env::var("CIEL_ISOLATE_NETWORK").is_err() {
+        return Ok(());
+    }
+    let dependencies = package_dependencies(package)?;
+    if dependencies.is_empty() {
+        return Ok(());
+    }
+    let script = format!(
+        "export DEBIAN_FRONTEND=noninteractive; apt-get install --download-only -y {}",
+        dependencies.join(" ")
+    );
+    let status = run_apt_script(instance, &script, output)?;
+    if status != 0 {
+        warn!(
+            "{}: unable to download all the dependencies of {}, the build may fail.",
+            instance, package
+        );
+    }
+
+    Ok(())
+}
+
+/// Apply the offline, network isolation, stage 2 and cross-compiling build settings
+pub(super) fn apply_build_settings(
+    instance: &str,
+    packages: &[String],
+    settings: BuildSettings,
+) -> Result<()> {
+    if settings.offline || std::env::var("CIEL_OFFLINE").is_ok() {
+        info!("Preparing offline mode. Fetching source packages first ...");
+        package_fetch(instance, packages)?;
+        std::env::set_var("CIEL_OFFLINE", "ON");
+        // FIXME: does not work with current version of systemd
+        info!("Running in offline mode. Network access disabled.");
+    } else if settings.isolate_network {
+        if get_container_backend(&get_instance_ns_name(instance)?).name() != "nspawn" {
+            bail!("Network isolation is only supported by the systemd-nspawn backend.");
+        }
+        info!("Preparing network isolation. Fetching source packages first ...");
+        package_fetch(instance, packages)?;
+    }
+
+    if settings.isolate_network {
+        std::env::set_var("CIEL_ISOLATE_NETWORK", "ON");
+        info!("Network isolation enabled. Builds may only use the local repository and fetched sources.");
+    }
+
+    if settings.stage2 {
+        std::env::set_var("CIEL_STAGE2", "ON");
+        info!("Running in stage 2 mode. ACBS and autobuild3 may behave differently.");
+    }
+
+    if settings.html_report {
+        std::env::set_var("CIEL_HTML_REPORT", "ON");
+    }
+
+    if settings.strict {
+        std::env::set_var("CIEL_QA_STRICT", "ON");
+    }
+
+    // CIEL_MAINTAINER set by the user (e.g. in .env) overrides the configuration
+    if std::env::var("CIEL_MAINTAINER").is_err() {
+        let conf = config::read_instance_config(instance)?;
+        std::env::set_var("CIEL_MAINTAINER", conf.maintainer());
+    }
+
+    if settings.retries > 0 {
+        std::env::set_var("CIEL_RETRIES", settings.retries.to_string());
+    }
+
+    if let Some(arch) = settings.cross {
+        std::env::set_var("CIEL_CROSS", arch);
+        info!(
+            "Cross-compiling for {} using the AOSC OS cross toolchain.",
+            arch
+        );
+    }
+
+    Ok(())
+}
+
 #[inline]
 fn package_build_inner<P: AsRef<Path>>(
     packages: &[String],
     instance: &str,
     root: P,
+    state: &mut BuildState,
 ) -> Result<(i32, usize)> {
     let total = packages.len();
     let hostname = gethostname().map_or_else(
@@ -161,6 +576,7 @@ fn package_build_inner<P: AsRef<Path>>(
     let root_path = root.as_ref().to_path_buf();
     let refresh_monitor = thread::spawn(move || repo::start_monitor(&root_path, rx));
     let guard = RepoMonitorGuard::new(refresh_monitor, tx);
+    let watchdog = config::read_config()?.watchdog;
     for (index, package) in packages.iter().enumerate() {
         // set terminal title, \r is for hiding the message if the terminal does not support the sequence
         eprint!(
@@ -171,42 +587,47 @@ fn package_build_inner<P: AsRef<Path>>(
             instance,
             hostname
         );
+        logging::begin_group(&format!("[{}/{}] {}", index + 1, total, package));
         // hopefully the sequence gets flushed together with the `info!` below
         info!("[{}/{}] Building {}...", index + 1, total, package);
         mount_fs(instance)?;
         info!("Refreshing local repository...");
         repo::init_repo(root.as_ref(), Path::new(instance))?;
-        let mut status = -1;
-        let mut oma = true;
-        for i in 1..=5 {
-            status = if oma {
-                run_in_container(instance, &["/bin/bash", "-ec", OMA_UPDATE_SCRIPT]).unwrap_or(-1)
-            } else {
-                run_in_container(instance, &["/bin/bash", "-ec", APT_UPDATE_SCRIPT]).unwrap_or(-1)
-            };
-            if status == 0 {
-                break;
-            } else {
-                let interval = 3u64.pow(i);
-                warn!(
-                    "Failed to update the OS, will retry in {} seconds ...",
-                    interval
-                );
-                oma = false;
-                sleep(Duration::from_secs(interval));
-            }
-        }
+        let status = update_instance_os(instance, None);
         if status != 0 {
             error!("Failed to update the OS before building packages");
+            state.fail(package, status)?;
             return Ok((status, index));
         }
-        let status = run_in_container(instance, &["/bin/acbs-build", "--", package])?;
+        let status = install_cross_toolchain(instance, None)?;
+        if status != 0 {
+            error!("Failed to install the cross toolchain");
+            state.fail(package, status)?;
+            return Ok((status, index));
+        }
+        prefetch_build_dependencies(instance, package, None)?;
+        check_build_space(package)?;
+        run_hook(
+            Hook::PreBuild,
+            Some(instance),
+            &build_hook_env(package, None),
+        )?;
+        let (status, usage, log_path) = run_package_build(instance, package, false, &watchdog)?;
+        let status = record_package_build(package, instance, status, &usage, Some(&log_path))?;
+        run_hook(
+            Hook::PostBuild,
+            Some(instance),
+            &build_hook_env(package, Some((status, log_path.as_path()))),
+        )?;
         if status != 0 {
             error!("Build failed with status: {}", status);
+            state.fail(package, status)?;
             return Ok((status, index));
         }
         rollback_container(instance)?;
+        state.complete(package)?;
     }
+    logging::end_group();
     drop(guard);
 
     Ok((0, 0))
@@ -218,7 +639,7 @@ pub fn packages_stage_select<S: AsRef<str>, K: Clone + ExactSizeIterator<Item =
     settings: BuildSettings,
     start_package: Option<&String>,
 ) -> Result<i32> {
-    let packages = expand_package_list(packages);
+    let packages = order_packages(expand_package_list(packages), &settings)?;
 
     let selection = if let Some(start_package) = start_package {
         packages
@@ -228,6 +649,7 @@ pub fn packages_stage_select<S: AsRef<str>, K: Clone + ExactSizeIterator<Item =
             })
             .ok_or_else(|| anyhow!("Can not find the specified package in the list!"))?
     } else {
+        ensure_interactive("please specify the package to start from (--stage-select <PACKAGE>)")?;
         eprintln!("-*-* S T A G E\t\tS E L E C T *-*-");
 
         Select::with_theme(&ColorfulTheme::default())
@@ -296,37 +718,41 @@ pub fn package_build<S: AsRef<str>, K: Clone + ExactSizeIterator<Item = S>>(
         );
         p.packages[p.progress..].to_owned()
     } else {
-        expand_package_list(packages)
+        order_packages(expand_package_list(packages), &settings)?
     };
 
-    if settings.offline || std::env::var("CIEL_OFFLINE").is_ok() {
-        info!("Preparing offline mode. Fetching source packages first ...");
-        package_fetch(instance, &packages)?;
-        std::env::set_var("CIEL_OFFLINE", "ON");
-        // FIXME: does not work with current version of systemd
-        info!("Running in offline mode. Network access disabled.");
-    }
-
-    if settings.stage2 {
-        std::env::set_var("CIEL_STAGE2", "ON");
-        info!("Running in stage 2 mode. ACBS and autobuild3 may behave differently.");
-    }
+    apply_instance_config(instance)?;
+    apply_build_settings(instance, &packages, settings)?;
 
     mount_fs(instance)?;
     rollback_container(instance)?;
 
+    let start = Instant::now();
     if !conf.local_repo {
+        let status = install_cross_toolchain(instance, None)?;
+        if status != 0 {
+            error!("Failed to install the cross toolchain");
+            return Ok(status);
+        }
         let mut cmd = vec!["/bin/acbs-build".to_string(), "--".to_string()];
-        cmd.extend(packages);
+        cmd.extend(packages.iter().cloned());
         let status = run_in_container(instance, &cmd)?;
+        // packages are built in one go, the individual results are unknown
+        let mut build_state = BuildState::new(instance, &packages, attempts);
+        if status == 0 {
+            build_state.completed = packages;
+        }
+        report_build_result(&build_state, status, start.elapsed().as_secs())?;
         return Ok(status);
     }
 
     let output_dir = get_output_directory(conf.sep_mount);
     let root = std::env::current_dir()?.join(output_dir);
     let total = packages.len();
-    let start = Instant::now();
-    let (exit_status, progress) = package_build_inner(&packages, instance, root)?;
+    let mut build_state = BuildState::new(instance, &packages, attempts);
+    build_state.save()?;
+    let (exit_status, progress) = package_build_inner(&packages, instance, root, &mut build_state)?;
+    report_build_result(&build_state, exit_status, start.elapsed().as_secs())?;
     if exit_status != 0 {
         let checkpoint = BuildCheckPoint {
             packages,
@@ -366,6 +792,9 @@ pub fn cleanup_outputs() -> Result<()> {
     if Path::new("./STATES").is_dir() {
         fs::remove_dir_all("./STATES")?;
     }
+    if Path::new("./LOGS").is_dir() {
+        fs::remove_dir_all("./LOGS")?;
+    }
     spinner.finish_with_message("Done.");
 
     Ok(())
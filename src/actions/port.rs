@@ -0,0 +1,170 @@
+//! Ports of the host forwarded to the instances, so the services running in them are reachable
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use std::{
+    fmt,
+    io::{self, Write},
+    str::FromStr,
+};
+use tabwriter::TabWriter;
+
+use crate::{common::is_instance_exists, config, info, machine::list_instances_simple};
+
+use super::container::container_down;
+
+/// `[tcp|udp:]<host port>[:<container port>]`, the same port in the container by default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct PortForward {
+    protocol: String,
+    host: u16,
+    container: u16,
+}
+
+impl FromStr for PortForward {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut fields = s.split(':').collect::<Vec<_>>();
+        let protocol = match fields.first() {
+            Some(&"tcp") | Some(&"udp") => fields.remove(0).to_string(),
+            _ => "tcp".to_string(),
+        };
+        let port = |x: &str| {
+            x.parse::<u16>()
+                .ok()
+                .filter(|x| *x > 0)
+                .ok_or_else(|| anyhow!("Invalid port forwarding: {}", s))
+        };
+        let (host, container) = match fields.as_slice() {
+            [host] => (port(host)?, port(host)?),
+            [host, container] => (port(host)?, port(container)?),
+            _ => bail!("Invalid port forwarding: {}", s),
+        };
+
+        Ok(Self {
+            protocol,
+            host,
+            container,
+        })
+    }
+}
+
+impl fmt::Display for PortForward {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.protocol, self.host, self.container)
+    }
+}
+
+fn instance_ports(instance: &str) -> Result<Vec<PortForward>> {
+    config::read_instance_overrides(instance)?
+        .and_then(|x| x.ports)
+        .unwrap_or_default()
+        .iter()
+        .map(|x| x.parse())
+        .collect()
+}
+
+fn set_instance_ports(instance: &str, ports: &[PortForward]) -> Result<()> {
+    let mut overrides = config::read_instance_overrides(instance)?.unwrap_or_default();
+    let ports = ports.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+    overrides.ports = Some(ports).filter(|x| !x.is_empty());
+
+    config::write_instance_overrides(instance, &overrides)
+}
+
+/// Options of the container forwarding the ports of the instance, the container gets its own
+/// network (a veth link to the host) when there are any, unless it already has one (`private`)
+pub(super) fn port_options(instance: &str, private: bool) -> Result<Vec<String>> {
+    let ports = instance_ports(instance)?;
+    if ports.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut options = Vec::new();
+    if !private {
+        options.push("--network-veth".to_string());
+    }
+    options.extend(ports.iter().map(|x| format!("--port={}", x)));
+
+    Ok(options)
+}
+
+/// Forward the port of the host to the instance (applied when the instance is started again)
+pub fn add_port(instance: &str, spec: &str) -> Result<()> {
+    if !is_instance_exists(instance) {
+        bail!("Instance {} does not exist.", instance);
+    }
+    let port = spec.parse::<PortForward>()?;
+    let mut ports = instance_ports(instance)?;
+    if ports
+        .iter()
+        .any(|x| x.protocol == port.protocol && x.host == port.host)
+    {
+        bail!("{}: port {} is already forwarded.", instance, port.host);
+    }
+    for other in list_instances_simple()?.iter().filter(|x| *x != instance) {
+        if instance_ports(other)?
+            .iter()
+            .any(|x| x.protocol == port.protocol && x.host == port.host)
+        {
+            bail!("Port {} is already forwarded to {}.", port.host, other);
+        }
+    }
+    container_down(instance)?;
+    ports.push(port.clone());
+    set_instance_ports(instance, &ports)?;
+    info!(
+        "{}: port {} of the host forwarded to port {} ({}).",
+        instance, port.host, port.container, port.protocol
+    );
+
+    Ok(())
+}
+
+/// Stop forwarding the port of the host to the instance
+pub fn remove_port(instance: &str, spec: &str) -> Result<()> {
+    let port = spec.parse::<PortForward>()?;
+    let mut ports = instance_ports(instance)?;
+    let count = ports.len();
+    ports.retain(|x| !(x.protocol == port.protocol && x.host == port.host));
+    if ports.len() == count {
+        bail!("{}: port {} is not forwarded.", instance, port.host);
+    }
+    container_down(instance)?;
+    set_instance_ports(instance, &ports)?;
+    info!("{}: port {} no longer forwarded.", instance, port.host);
+
+    Ok(())
+}
+
+/// List the forwarded ports of all the instances
+pub fn list_ports() -> Result<()> {
+    let mut formatter = TabWriter::new(io::stderr());
+    writeln!(&mut formatter, "INSTANCE\tPROTOCOL\tHOST\tCONTAINER")?;
+    for instance in list_instances_simple()? {
+        for port in instance_ports(&instance)? {
+            writeln!(
+                &mut formatter,
+                "{}\t{}\t{}\t{}",
+                instance, port.protocol, port.host, port.container
+            )?;
+        }
+    }
+    formatter.flush()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_port_forward() {
+    assert_eq!(
+        "8080:80".parse::<PortForward>().unwrap().to_string(),
+        "tcp:8080:80"
+    );
+    assert_eq!(
+        "udp:5353".parse::<PortForward>().unwrap().to_string(),
+        "udp:5353:5353"
+    );
+    assert!("http:80".parse::<PortForward>().is_err());
+    assert!("0:80".parse::<PortForward>().is_err());
+}
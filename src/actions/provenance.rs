@@ -0,0 +1,202 @@
+//! Build provenance: where the packages were built from (TREE, base OS, configuration),
+//! written as in-toto statements next to the artifacts so that the builds can be reproduced
+
+use anyhow::Result;
+use git2::{Repository, StatusOptions};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    fs::{self, File},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{
+    common::{sha256sum, CIEL_DATA_DIR, CIEL_DIST_DIR},
+    config,
+};
+
+/// Source of the base OS, recorded when it is loaded
+const DIST_SOURCE_FILE: &str = "dist-source.json";
+/// The dpkg database of the base OS, changes when the base OS is updated
+const DPKG_STATUS_FILE: &str = "var/lib/dpkg/status";
+const PROVENANCE_DIR: &str = "provenance";
+const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+const PREDICATE_TYPE: &str = "https://slsa.dev/provenance/v1";
+const BUILD_TYPE: &str = "https://github.com/AOSC-Dev/ciel-rs/build/v1";
+
+/// Where the base OS was loaded from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct DistSource {
+    /// URL or path of the tarball, or the rsync source
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// When the base OS was loaded (UNIX timestamp)
+    pub loaded: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct DistIdentity {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<DistSource>,
+    /// SHA-256 of the dpkg database, identifies the updates applied since the base OS was loaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packages_sha256: Option<String>,
+}
+
+/// State of the workspace a package was built with
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct BuildProvenance {
+    pub ciel_version: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tree_commit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tree_branch: Option<String>,
+    /// TREE had uncommitted changes
+    pub tree_dirty: bool,
+    pub dist: DistIdentity,
+    /// SHA-256 of the effective configuration of the instance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_sha256: Option<String>,
+}
+
+/// Record where the base OS was loaded from, called after loading (or synchronizing) it
+pub(super) fn record_dist_source(url: &str, sha256: Option<String>) -> Result<()> {
+    let source = DistSource {
+        url: url.to_string(),
+        sha256,
+        loaded: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    };
+    fs::create_dir_all(CIEL_DATA_DIR)?;
+    fs::write(
+        Path::new(CIEL_DATA_DIR).join(DIST_SOURCE_FILE),
+        serde_json::to_vec_pretty(&source)?,
+    )?;
+
+    Ok(())
+}
+
+fn dist_identity() -> DistIdentity {
+    let source = fs::read(Path::new(CIEL_DATA_DIR).join(DIST_SOURCE_FILE))
+        .ok()
+        .and_then(|x| serde_json::from_slice(&x).ok());
+    let packages_sha256 = File::open(Path::new(CIEL_DIST_DIR).join(DPKG_STATUS_FILE))
+        .ok()
+        .and_then(|x| sha256sum(x).ok());
+
+    DistIdentity {
+        source,
+        packages_sha256,
+    }
+}
+
+/// Commit, branch and whether TREE has uncommitted changes (to the tracked files)
+fn tree_state() -> (Option<String>, Option<String>, bool) {
+    let repo = match Repository::open("TREE") {
+        Ok(repo) => repo,
+        Err(_) => return (None, None, false),
+    };
+    let head = repo.head().ok();
+    let commit = head
+        .as_ref()
+        .and_then(|x| x.peel_to_commit().ok())
+        .map(|x| x.id().to_string());
+    let branch = head
+        .as_ref()
+        .filter(|x| x.is_branch())
+        .and_then(|x| x.shorthand())
+        .map(|x| x.to_string());
+    let dirty = repo
+        .statuses(Some(StatusOptions::new().include_untracked(false)))
+        .is_ok_and(|x| !x.is_empty());
+
+    (commit, branch, dirty)
+}
+
+/// Collect the provenance of a build in the instance
+pub(super) fn build_provenance(instance: &str) -> BuildProvenance {
+    let (tree_commit, tree_branch, tree_dirty) = tree_state();
+    let config_sha256 = config::read_instance_config(instance)
+        .ok()
+        .and_then(|x| toml::to_string(&x).ok())
+        .and_then(|x| sha256sum(x.as_bytes()).ok());
+
+    BuildProvenance {
+        ciel_version: env!("CARGO_PKG_VERSION"),
+        tree_commit,
+        tree_branch,
+        tree_dirty,
+        dist: dist_identity(),
+        config_sha256,
+    }
+}
+
+/// Write the in-toto statement of the package build to `<output>/provenance/<package>.intoto.json`,
+/// the subjects are the .deb files (path relative to the output directory and SHA-256)
+pub(super) fn write_provenance(
+    root: &Path,
+    package: &str,
+    instance: &str,
+    debs: &[(String, String)],
+    provenance: &BuildProvenance,
+    started: SystemTime,
+) -> Result<()> {
+    let timestamp = |x: SystemTime| OffsetDateTime::from(x).format(&Rfc3339).ok();
+    let mut dependencies = Vec::new();
+    if let Some(source) = &provenance.dist.source {
+        dependencies.push(json!({
+            "uri": source.url,
+            "digest": source.sha256.as_ref().map(|x| json!({ "sha256": x })).unwrap_or_else(|| json!({})),
+        }));
+    }
+    if let Some(commit) = &provenance.tree_commit {
+        dependencies.push(json!({
+            "uri": "TREE",
+            "digest": { "gitCommit": commit },
+        }));
+    }
+    let statement = json!({
+        "_type": STATEMENT_TYPE,
+        "subject": debs
+            .iter()
+            .map(|(path, sha256)| json!({ "name": path, "digest": { "sha256": sha256 } }))
+            .collect::<Vec<_>>(),
+        "predicateType": PREDICATE_TYPE,
+        "predicate": {
+            "buildDefinition": {
+                "buildType": BUILD_TYPE,
+                "externalParameters": {
+                    "package": package,
+                    "treeBranch": provenance.tree_branch,
+                    "treeDirty": provenance.tree_dirty,
+                },
+                "internalParameters": {
+                    "instance": instance,
+                    "configSha256": provenance.config_sha256,
+                    "distPackagesSha256": provenance.dist.packages_sha256,
+                },
+                "resolvedDependencies": dependencies,
+            },
+            "runDetails": {
+                "builder": {
+                    "id": "ciel",
+                    "version": { "ciel": provenance.ciel_version },
+                },
+                "metadata": {
+                    "startedOn": timestamp(started),
+                    "finishedOn": timestamp(SystemTime::now()),
+                },
+            },
+        },
+    });
+    let directory = root.join(PROVENANCE_DIR);
+    fs::create_dir_all(&directory)?;
+    fs::write(
+        directory.join(format!("{}.intoto.json", package.replace('/', "_"))),
+        serde_json::to_vec_pretty(&statement)?,
+    )?;
+
+    Ok(())
+}
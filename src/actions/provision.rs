@@ -0,0 +1,70 @@
+//! Provisioning of the new instances: the build essentials from the configuration (`provision`)
+//! are installed and the maintainer identity is applied to git
+
+use anyhow::{bail, Result};
+use console::style;
+use std::fs;
+
+use crate::{common::is_instance_exists, config, info};
+
+use super::{
+    apt_progress::run_apt_script_with_progress,
+    container::{apply_instance_config, mount_fs},
+    install_script,
+    template::validate_word,
+};
+
+const GIT_CONFIG_LOCATION: &str = "root/.gitconfig";
+const EDITOR_PROFILE_LOCATION: &str = "etc/profile.d/ciel-editor.sh";
+
+/// Install the build essentials into the instance and apply the maintainer identity,
+/// the changes are kept in the instance (discarded by `ciel rollback`)
+pub fn provision_instance(instance: &str) -> Result<()> {
+    if !is_instance_exists(instance) {
+        bail!("Instance {} does not exist.", instance);
+    }
+    let conf = config::read_instance_config(instance)?;
+    let mut packages = conf.provision.packages.clone();
+    if let Some(editor) = &conf.provision.editor {
+        packages.push(editor.clone());
+    }
+    for package in packages.iter() {
+        validate_word("package", package)?;
+    }
+    apply_instance_config(instance)?;
+    mount_fs(instance)?;
+    if !packages.is_empty() {
+        info!("{}: installing {}...", instance, packages.join(", "));
+        let script = install_script(&packages, conf.use_oma(), true);
+        let (status, _) = run_apt_script_with_progress(instance, &script)?;
+        if status != 0 {
+            bail!("{}: provisioning failed with status {}", instance, status);
+        }
+    }
+    let rootfs = std::env::current_dir()?.join(instance);
+    match config::split_maintainer(conf.maintainer()) {
+        Some((name, email)) => {
+            fs::write(
+                rootfs.join(GIT_CONFIG_LOCATION),
+                format!("[user]\n\tname = {}\n\temail = {}\n", name, email),
+            )?;
+        }
+        None => bail!("Invalid maintainer identity: {}", conf.maintainer()),
+    }
+    if let Some(editor) = &conf.provision.editor {
+        let path = rootfs.join(EDITOR_PROFILE_LOCATION);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(
+            path,
+            format!(
+                "export EDITOR={editor}\nexport VISUAL={editor}\n",
+                editor = editor
+            ),
+        )?;
+    }
+    info!("{}: instance provisioned.", instance);
+
+    Ok(())
+}
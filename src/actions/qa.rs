@@ -0,0 +1,439 @@
+//! Quality assurance checks on the .deb files produced by the builds
+
+use anyhow::{anyhow, Result};
+use ar::Archive as ArArchive;
+use console::style;
+use flate2::read::GzDecoder;
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::Read,
+    path::{Component, Path, PathBuf},
+};
+use tar::{Archive as TarArchive, EntryType};
+use walkdir::WalkDir;
+use xz2::read::XzDecoder;
+
+use crate::{
+    config::{self, QaCheck},
+    network,
+    repo::compare_versions,
+    warn,
+};
+
+use super::{
+    abi::{library_name, removed_symbols, LibrarySymbols},
+    elf::Elf,
+    status::remote_package_url,
+};
+
+/// Exit status of the builds failed by the QA checks in strict mode
+pub const QA_STATUS: i32 = 125;
+const MAINTAINER_SCRIPTS: &[&str] = &["preinst", "postinst", "prerm", "postrm", "config"];
+/// Removed symbols listed in the problem, the full list is long when the ABI is overhauled
+const MAX_LISTED_SYMBOLS: usize = 10;
+
+/// A problem found in a .deb file
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct QaProblem {
+    pub check: QaCheck,
+    /// Path relative to the output directory
+    pub deb: String,
+    pub message: String,
+}
+
+/// What the checks need to know about a .deb file
+#[derive(Debug, Default)]
+struct DebContents {
+    /// Whether the package depends on other packages (e.g. metapackages)
+    depends: bool,
+    /// Maintainer scripts and their permissions
+    scripts: Vec<(String, u32)>,
+    /// All the paths in the data archive, relative to the root
+    paths: HashSet<PathBuf>,
+    /// Number of regular files and symbolic links
+    files: usize,
+    /// Symbolic links and their targets
+    symlinks: Vec<(PathBuf, PathBuf)>,
+    /// ELF files still having their symbol table or debug information
+    unstripped: Vec<PathBuf>,
+    /// Exported symbols of the shared libraries
+    libraries: LibrarySymbols,
+}
+
+/// Whether the QA problems fail the builds (`ciel build --strict`)
+pub(super) fn qa_strict() -> bool {
+    std::env::var("CIEL_QA_STRICT").is_ok()
+}
+
+/// Decompress the member of the .deb according to its name (e.g. `data.tar.xz`)
+pub(super) fn decompress<'a, R: Read + 'a>(name: &[u8], reader: R) -> Result<Box<dyn Read + 'a>> {
+    if name.ends_with(b".xz") {
+        Ok(Box::new(XzDecoder::new(reader)))
+    } else if name.ends_with(b".gz") {
+        Ok(Box::new(GzDecoder::new(reader)))
+    } else if name.ends_with(b".zst") {
+        Ok(Box::new(zstd::stream::read::Decoder::new(reader)?))
+    } else if name.ends_with(b".tar") {
+        Ok(Box::new(reader))
+    } else {
+        Err(anyhow!(
+            "Unsupported archive format: {}",
+            String::from_utf8_lossy(name)
+        ))
+    }
+}
+
+/// Path in the archive relative to the root (`./usr/bin/foo` -> `usr/bin/foo`)
+fn relative_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|x| matches!(x, Component::Normal(_)))
+        .collect()
+}
+
+fn read_control_archive<R: Read>(reader: R, contents: &mut DebContents) -> Result<()> {
+    let mut tar = TarArchive::new(reader);
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let name = relative_path(&entry.path()?).to_string_lossy().to_string();
+        if name == "control" {
+            let mut control = String::new();
+            entry.read_to_string(&mut control)?;
+            contents.depends = control
+                .lines()
+                .any(|x| x.starts_with("Depends:") || x.starts_with("Pre-Depends:"));
+        } else if MAINTAINER_SCRIPTS.contains(&name.as_str()) {
+            contents.scripts.push((name, entry.header().mode()?));
+        }
+    }
+
+    Ok(())
+}
+
+fn read_data_archive<R: Read>(
+    reader: R,
+    check_elf: bool,
+    check_abi: bool,
+    contents: &mut DebContents,
+) -> Result<()> {
+    let mut tar = TarArchive::new(reader);
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let path = relative_path(&entry.path()?);
+        if path.as_os_str().is_empty() {
+            continue;
+        }
+        match entry.header().entry_type() {
+            EntryType::Symlink => {
+                contents.files += 1;
+                if let Some(target) = entry.link_name()? {
+                    contents.symlinks.push((path.clone(), target.into_owned()));
+                }
+            }
+            EntryType::Regular | EntryType::Link => {
+                contents.files += 1;
+                let library = if check_abi { library_name(&path) } else { None };
+                let mut data = Vec::new();
+                if check_elf || library.is_some() {
+                    (&mut entry).take(4).read_to_end(&mut data)?;
+                }
+                if data == b"\x7fELF" {
+                    entry.read_to_end(&mut data)?;
+                }
+                if let Some(elf) = Elf::parse(&data) {
+                    if check_elf && elf.has_symbols() {
+                        contents.unstripped.push(path.clone());
+                    }
+                    if let Some(library) = library {
+                        if let Some(symbols) = elf.exported_symbols() {
+                            contents.libraries.insert(library, symbols);
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+        contents.paths.insert(path);
+    }
+
+    Ok(())
+}
+
+/// Read the control and data archives of the .deb file, the binaries are only inspected
+/// with `check_elf` (symbols) and `check_abi` (exported symbols of the shared libraries)
+fn read_deb<R: Read>(reader: R, check_elf: bool, check_abi: bool) -> Result<DebContents> {
+    let mut contents = DebContents::default();
+    let mut deb = ArArchive::new(reader);
+    while let Some(entry) = deb.next_entry() {
+        let entry = entry?;
+        let identifier = entry.header().identifier().to_vec();
+        if identifier.starts_with(b"control.tar") {
+            read_control_archive(decompress(&identifier, entry)?, &mut contents)?;
+        } else if identifier.starts_with(b"data.tar") {
+            read_data_archive(
+                decompress(&identifier, entry)?,
+                check_elf,
+                check_abi,
+                &mut contents,
+            )?;
+        }
+    }
+
+    Ok(contents)
+}
+
+/// Package name from the file name of the .deb (`<name>_<version>_<arch>.deb`)
+fn deb_package_name(path: &str) -> &str {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+
+    file_name.split('_').next().unwrap_or(file_name)
+}
+
+/// Target of the symbolic link, relative to the root
+fn resolve_symlink(link: &Path, target: &Path) -> PathBuf {
+    let mut resolved = if target.is_absolute() {
+        PathBuf::new()
+    } else {
+        link.parent().map(|x| x.to_path_buf()).unwrap_or_default()
+    };
+    for component in target.components() {
+        match component {
+            Component::Normal(x) => resolved.push(x),
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            _ => (),
+        }
+    }
+
+    resolved
+}
+
+/// Sonames (`lib*.so.N`) of the shared libraries shipped in the package
+fn sonames(contents: &DebContents) -> HashSet<String> {
+    contents
+        .paths
+        .iter()
+        .filter_map(|x| x.file_name()?.to_str())
+        .filter(|x| {
+            x.starts_with("lib")
+                && x.rsplit_once(".so.")
+                    .is_some_and(|(_, version)| version.parse::<u32>().is_ok())
+        })
+        .map(|x| x.to_string())
+        .collect()
+}
+
+/// The previous build of the package in the output repository,
+/// i.e. the latest version not produced by the current build
+fn previous_deb(root: &Path, name: &str, current: &[String]) -> Option<PathBuf> {
+    WalkDir::new(root.join("debs"))
+        .into_iter()
+        .filter_map(|x| x.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_str()?;
+            let mut fields = file_name.strip_suffix(".deb")?.split('_');
+            if fields.next()? != name {
+                return None;
+            }
+            let version = fields.next()?.to_string();
+            let relative = entry.path().strip_prefix(root).ok()?.to_string_lossy();
+            if current.iter().any(|x| x == &relative) {
+                return None;
+            }
+            Some((version, entry.path().to_path_buf()))
+        })
+        .max_by(|a, b| compare_versions(&a.0, &b.0))
+        .map(|x| x.1)
+}
+
+/// Contents of the previous build of the package, from the output repository
+/// or the remote repository (unless in offline mode)
+fn previous_contents(
+    root: &Path,
+    name: &str,
+    current: &[String],
+    check_abi: bool,
+) -> Result<Option<DebContents>> {
+    if let Some(path) = previous_deb(root, name, current) {
+        return read_deb(File::open(path)?, false, check_abi).map(Some);
+    }
+    if network::is_offline() || std::env::var("CIEL_OFFLINE").is_ok() {
+        return Ok(None);
+    }
+    match remote_package_url(name)? {
+        Some(url) => read_deb(network::open_download_stream(&url)?.0, false, check_abi).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Run the checks (except the skipped ones) on the .deb files produced by a build in the instance,
+/// the paths are relative to the output directory
+fn check_debs(
+    root: &Path,
+    debs: &[String],
+    instance: &str,
+    skip: &[QaCheck],
+) -> Result<Vec<QaProblem>> {
+    let enabled = |check| !skip.contains(&check);
+    let names = debs.iter().map(|x| deb_package_name(x)).collect::<Vec<_>>();
+    let mut contents = Vec::new();
+    for (deb, name) in debs.iter().zip(names.iter()) {
+        // autobuild3 splits the debug symbols into `<name>-dbg`
+        let check_elf = enabled(QaCheck::UnstrippedBinary)
+            && names.iter().any(|x| *x == format!("{}-dbg", name));
+        contents.push(read_deb(
+            File::open(root.join(deb))?,
+            check_elf,
+            enabled(QaCheck::AbiBreak),
+        )?);
+    }
+    let shipped = contents
+        .iter()
+        .flat_map(|x| x.paths.iter())
+        .collect::<HashSet<_>>();
+    let instance_root = std::env::current_dir()?.join(instance);
+    let mut problems = Vec::new();
+    for ((deb, name), contents) in debs.iter().zip(names.iter()).zip(contents.iter()) {
+        let mut report = |check, message: String| {
+            if enabled(check) {
+                problems.push(QaProblem {
+                    check,
+                    deb: deb.clone(),
+                    message,
+                });
+            }
+        };
+        for (script, mode) in contents.scripts.iter() {
+            if mode & 0o111 == 0 {
+                report(
+                    QaCheck::ScriptPermissions,
+                    format!("{} is not executable (mode {:o})", script, mode),
+                );
+            }
+        }
+        if contents.files == 0 && !contents.depends {
+            report(
+                QaCheck::EmptyPackage,
+                "the package has neither files nor dependencies".to_string(),
+            );
+        }
+        for (link, target) in contents.symlinks.iter() {
+            let resolved = resolve_symlink(link, target);
+            if !shipped.contains(&resolved)
+                && fs::symlink_metadata(instance_root.join(&resolved)).is_err()
+            {
+                report(
+                    QaCheck::DanglingSymlink,
+                    format!(
+                        "/{} -> {} points to nothing",
+                        link.display(),
+                        target.display()
+                    ),
+                );
+            }
+        }
+        for binary in contents.unstripped.iter() {
+            report(
+                QaCheck::UnstrippedBinary,
+                format!(
+                    "/{} is not stripped although {}-dbg is produced",
+                    binary.display(),
+                    name
+                ),
+            );
+        }
+        let check_abi = enabled(QaCheck::AbiBreak) && !contents.libraries.is_empty();
+        if !enabled(QaCheck::SonameBump) && !check_abi {
+            continue;
+        }
+        let previous = match previous_contents(root, name, debs, check_abi) {
+            Ok(Some(previous)) => previous,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!("{}: unable to read the previous build: {}", name, e);
+                continue;
+            }
+        };
+        let current = sonames(contents);
+        let mut removed = sonames(&previous)
+            .into_iter()
+            .filter(|x| !current.contains(x))
+            .collect::<Vec<_>>();
+        removed.sort();
+        for soname in removed {
+            report(
+                QaCheck::SonameBump,
+                format!(
+                    "{} is no longer provided, the packages linked to it need to be rebuilt",
+                    soname
+                ),
+            );
+        }
+        for abi_break in removed_symbols(&previous.libraries, &contents.libraries) {
+            let mut listed =
+                abi_break.removed[..abi_break.removed.len().min(MAX_LISTED_SYMBOLS)].join(", ");
+            if abi_break.removed.len() > MAX_LISTED_SYMBOLS {
+                listed.push_str(", ...");
+            }
+            report(
+                QaCheck::AbiBreak,
+                format!(
+                    "{} no longer exports {} symbol(s): {}",
+                    abi_break.library,
+                    abi_break.removed.len(),
+                    listed
+                ),
+            );
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Check the .deb files produced by the build of the package and print the problems found,
+/// the paths are relative to the output directory `root`
+pub(super) fn check_build(
+    package: &str,
+    instance: &str,
+    root: &Path,
+    debs: &[String],
+) -> Vec<QaProblem> {
+    if debs.is_empty() {
+        return Vec::new();
+    }
+    let skip = config::read_config().map(|x| x.qa.skip).unwrap_or_default();
+    let problems = match check_debs(root, debs, instance, &skip) {
+        Ok(problems) => problems,
+        Err(e) => {
+            warn!("{}: unable to run the QA checks: {}", package, e);
+            return Vec::new();
+        }
+    };
+    for problem in problems.iter() {
+        warn!("{}: {}: {}", package, problem.deb, problem.message);
+    }
+
+    problems
+}
+
+#[test]
+fn test_resolve_symlink() {
+    assert_eq!(
+        resolve_symlink(Path::new("usr/lib/libfoo.so"), Path::new("libfoo.so.1")),
+        Path::new("usr/lib/libfoo.so.1")
+    );
+    assert_eq!(
+        resolve_symlink(Path::new("usr/bin/foo"), Path::new("../lib/foo/foo")),
+        Path::new("usr/lib/foo/foo")
+    );
+    assert_eq!(
+        resolve_symlink(Path::new("usr/bin/sh"), Path::new("/bin/bash")),
+        Path::new("bin/bash")
+    );
+    assert_eq!(
+        deb_package_name("debs/f/foo-dbg_1.0-1_amd64.deb"),
+        "foo-dbg"
+    );
+}
@@ -0,0 +1,54 @@
+//! Recovery from unclean shutdowns (e.g. host crashes)
+
+use anyhow::Result;
+use console::style;
+use std::{fs, path::Path};
+
+use crate::{
+    common::{CIEL_DIST_DIR, CIEL_INST_DIR},
+    diagnose::{find_stale_mounts, fix_leftover_scopes, fix_orphaned_machines, fix_stale_mounts},
+    info,
+};
+
+use super::archive::IMPORT_TEMP_PREFIX;
+
+/// The base layer is renamed during the commit (btrfs backend)
+#[inline]
+fn old_dist_dir() -> std::path::PathBuf {
+    Path::new(CIEL_DIST_DIR).with_extension("old")
+}
+
+#[inline]
+fn has_interrupted_commit() -> bool {
+    !Path::new(CIEL_DIST_DIR).exists() && old_dist_dir().is_dir()
+}
+
+/// Returns true if the workspace seems to be left in an inconsistent state
+pub fn needs_recovery() -> bool {
+    has_interrupted_commit() || find_stale_mounts().is_ok_and(|x| !x.is_empty())
+}
+
+/// Tear down the stale mounts, unregister the dead containers
+/// and restore the workspace to a consistent state
+pub fn recover() -> Result<()> {
+    info!("{}", fix_stale_mounts()?);
+    info!("{}", fix_orphaned_machines()?);
+    info!("{}", fix_leftover_scopes()?);
+    if has_interrupted_commit() {
+        fs::rename(old_dist_dir(), CIEL_DIST_DIR)?;
+        info!("Restored the base layer from an interrupted commit");
+    }
+    for entry in fs::read_dir(CIEL_INST_DIR)?.filter_map(|x| x.ok()) {
+        if entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(IMPORT_TEMP_PREFIX)
+        {
+            fs::remove_dir_all(entry.path())?;
+            info!("Removed an incomplete instance import");
+        }
+    }
+    info!("Workspace recovered.");
+
+    Ok(())
+}
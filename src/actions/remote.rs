@@ -0,0 +1,337 @@
+//! Remote builders: the packages are built by ciel on another machine over SSH,
+//! TREE is synchronized beforehand and the output and the logs are pulled back afterwards
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+use tabwriter::TabWriter;
+
+use crate::{
+    common::{find_arch_name, shell_quote, CIEL_DATA_DIR},
+    config::{self, CielConfig, RemoteConfig},
+    info,
+};
+
+use super::template::validate_word;
+
+/// The logs of the remote builds are pulled into `.ciel/data/remote-logs/<remote>`
+const REMOTE_LOGS_DIR: &str = "remote-logs";
+/// Location of the logs in the remote workspace
+const LOGS_LOCATION: &str = ".ciel/data/logs";
+
+/// Register the remote machine, the workspace on it must be set up already
+pub fn add_remote(
+    name: &str,
+    host: &str,
+    port: Option<u16>,
+    path: Option<&str>,
+    instance: Option<&str>,
+    arch: Option<&str>,
+) -> Result<()> {
+    validate_word("remote", name)?;
+    if host.is_empty() || host.starts_with('-') {
+        bail!("Invalid SSH destination: {}", host);
+    }
+    let arch = match arch {
+        Some(arch) => {
+            Some(find_arch_name(arch).ok_or_else(|| anyhow!("Unknown architecture: {}", arch))?)
+        }
+        None => None,
+    };
+    let mut conf = config::read_config()?;
+    if conf.remotes.contains_key(name) {
+        bail!("Remote {} already exists.", name);
+    }
+    conf.remotes.insert(
+        name.to_string(),
+        RemoteConfig {
+            host: host.to_string(),
+            port,
+            path: path.map_or_else(RemoteConfig::default_path, |x| x.to_string()),
+            instance: instance.map(|x| x.to_string()),
+            arch: arch.map(|x| x.to_string()),
+        },
+    );
+    config::write_config(&conf)?;
+    info!("Remote {} added.", name);
+
+    Ok(())
+}
+
+pub fn remove_remote(name: &str) -> Result<()> {
+    let mut conf = config::read_config()?;
+    if conf.remotes.remove(name).is_none() {
+        bail!("Remote {} does not exist.", name);
+    }
+    config::write_config(&conf)?;
+    info!("Remote {} removed.", name);
+
+    Ok(())
+}
+
+pub fn list_remotes() -> Result<()> {
+    let conf = config::read_config()?;
+    let mut formatter = TabWriter::new(io::stderr());
+    writeln!(&mut formatter, "NAME\tHOST\tPATH\tINSTANCE\tARCH")?;
+    for (name, remote) in conf.remotes.iter() {
+        writeln!(
+            &mut formatter,
+            "{}\t{}\t{}\t{}\t{}",
+            name,
+            match remote.port {
+                Some(port) => format!("{}:{}", remote.host, port),
+                None => remote.host.clone(),
+            },
+            remote.path,
+            remote.instance.as_deref().unwrap_or("-"),
+            remote.arch.as_deref().unwrap_or("-")
+        )?;
+    }
+    formatter.flush()?;
+
+    Ok(())
+}
+
+/// SSH command line, as the remote shell of rsync
+fn ssh_command(remote: &RemoteConfig) -> Vec<String> {
+    let mut command = vec!["ssh".to_string()];
+    if let Some(port) = remote.port {
+        command.push("-p".to_string());
+        command.push(port.to_string());
+    }
+
+    command
+}
+
+/// Run the script on the remote machine, with a terminal if `tty`
+fn run_remote(remote: &RemoteConfig, script: &str, tty: bool) -> Result<i32> {
+    let ssh = ssh_command(remote);
+    let mut command = Command::new(&ssh[0]);
+    command.args(&ssh[1..]);
+    if tty && console::user_attended() {
+        command.arg("-t");
+    }
+    let status = command
+        .arg("--")
+        .arg(&remote.host)
+        .arg(script)
+        .status()
+        .map_err(|e| anyhow!("Unable to execute ssh: {}", e))?;
+
+    Ok(status.code().unwrap_or(-1))
+}
+
+fn rsync(remote: &RemoteConfig, source: &str, destination: &str) -> Result<()> {
+    let status = Command::new("rsync")
+        .args(["-az", "--delete", "-e"])
+        .arg(ssh_command(remote).join(" "))
+        .arg(source)
+        .arg(destination)
+        .status()
+        .map_err(|e| anyhow!("Unable to execute rsync: {}", e))?;
+    if !status.success() {
+        bail!(
+            "Unable to synchronize {} to {}: {}",
+            source,
+            destination,
+            status
+        );
+    }
+
+    Ok(())
+}
+
+/// Arguments of `ciel build` to forward to the remote machine (or the other workspaces):
+/// the ones after `build`, without `--on`, `--archs` and the instance (which is passed separately)
+pub(super) fn forwarded_build_args(args: &[String]) -> Vec<String> {
+    let mut forwarded = Vec::new();
+    let mut args = args.iter().skip_while(|x| *x != "build").skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--on" || arg == "--archs" || arg == "-i" {
+            args.next();
+        } else if !arg.starts_with("--on=")
+            && !arg.starts_with("--archs=")
+            && (!arg.starts_with("-i") || arg.starts_with("--"))
+        {
+            forwarded.push(arg.clone());
+        }
+    }
+
+    forwarded
+}
+
+/// The registered remote machine
+pub(super) fn find_remote<'a>(conf: &'a CielConfig, name: &str) -> Result<&'a RemoteConfig> {
+    conf.remotes.get(name).ok_or_else(|| {
+        anyhow!(
+            "Remote {} does not exist, add it with `ciel remote add`.",
+            name
+        )
+    })
+}
+
+/// Check that the workspace exists on the remote machine and ciel is installed
+pub(super) fn check_remote(remote: &RemoteConfig) -> Result<()> {
+    let script = format!(
+        "test -d {}/.ciel && command -v ciel > /dev/null",
+        shell_quote(&remote.path)
+    );
+    if run_remote(remote, &script, false)? != 0 {
+        bail!(
+            "{} is not a ciel workspace on {} (or ciel is not installed).",
+            remote.path,
+            remote.host
+        );
+    }
+
+    Ok(())
+}
+
+pub(super) fn sync_tree(remote: &RemoteConfig) -> Result<()> {
+    if !Path::new("TREE").is_dir() {
+        bail!("TREE does not exist, please clone it first.");
+    }
+
+    rsync(
+        remote,
+        "TREE/",
+        &format!("{}:{}/TREE/", remote.host, remote.path),
+    )
+}
+
+/// Copy the local output directories to the remote machine,
+/// so that the packages built elsewhere are available to its builds
+pub(super) fn push_output(remote: &RemoteConfig) -> Result<()> {
+    let mut command = Command::new("rsync");
+    command
+        .args(["-az", "-e"])
+        .arg(ssh_command(remote).join(" "));
+    let mut found = false;
+    for entry in fs::read_dir(".")? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if name.starts_with("OUTPUT") {
+            command.arg(name);
+            found = true;
+        }
+    }
+    if !found {
+        return Ok(());
+    }
+    let status = command
+        .arg(format!("{}:{}/", remote.host, remote.path))
+        .status()
+        .map_err(|e| anyhow!("Unable to execute rsync: {}", e))?;
+    if !status.success() {
+        bail!("Unable to push the output to {}: {}", remote.host, status);
+    }
+
+    Ok(())
+}
+
+/// Pull the output directories and the logs from the remote machine,
+/// returns where the logs are
+pub(super) fn pull_results(name: &str, remote: &RemoteConfig) -> Result<PathBuf> {
+    // the output directories of all the branches, only the changes are transferred
+    let status = Command::new("rsync")
+        .args(["-az", "-e"])
+        .arg(ssh_command(remote).join(" "))
+        .arg(format!("{}:{}/OUTPUT*", remote.host, remote.path))
+        .arg(".")
+        .status()
+        .map_err(|e| anyhow!("Unable to execute rsync: {}", e))?;
+    if !status.success() {
+        bail!("Unable to pull the output from {}: {}", name, status);
+    }
+    let logs = Path::new(CIEL_DATA_DIR).join(REMOTE_LOGS_DIR).join(name);
+    fs::create_dir_all(&logs)?;
+    rsync(
+        remote,
+        &format!("{}:{}/{}/", remote.host, remote.path, LOGS_LOCATION),
+        &format!("{}/", logs.display()),
+    )?;
+
+    Ok(logs)
+}
+
+/// Run `ciel build` in the instance on the remote machine with the maintainer identity
+/// (with a terminal if `tty`), returns its exit status
+pub(super) fn run_remote_build(
+    remote: &RemoteConfig,
+    maintainer: &str,
+    instance: &str,
+    args: &[String],
+    tty: bool,
+) -> Result<i32> {
+    let mut command = vec![
+        "cd".to_string(),
+        shell_quote(&remote.path),
+        "&&".to_string(),
+        "env".to_string(),
+        shell_quote(format!("CIEL_MAINTAINER={}", maintainer)),
+        "ciel".to_string(),
+        "build".to_string(),
+        "-i".to_string(),
+        shell_quote(instance),
+    ];
+    command.extend(args.iter().map(shell_quote));
+
+    run_remote(remote, &command.join(" "), tty)
+}
+
+/// Build the packages on the remote machine with the arguments of the local `ciel build`,
+/// returns the exit status of the remote build
+pub fn remote_build(name: &str, instance: Option<&str>, args: &[String]) -> Result<i32> {
+    let conf = config::read_config()?;
+    let remote = find_remote(&conf, name)?;
+    let instance = instance
+        .or(remote.instance.as_deref())
+        .ok_or_else(|| anyhow!("Please specify the instance to build in on {} (-i).", name))?;
+    check_remote(remote)?;
+
+    info!("Synchronizing TREE to {}...", name);
+    sync_tree(remote)?;
+    info!("Building on {} ({})...", name, remote.host);
+    let status = run_remote_build(
+        remote,
+        conf.maintainer(),
+        instance,
+        &forwarded_build_args(args),
+        true,
+    )?;
+    info!("Pulling the output and the logs from {}...", name);
+    let logs = pull_results(name, remote)?;
+    info!("The logs of the build are in {}.", logs.display());
+
+    Ok(status)
+}
+
+#[test]
+fn test_forwarded_build_args() {
+    let args = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        forwarded_build_args(&args(&[
+            "build",
+            "--on",
+            "beefy",
+            "-i",
+            "main",
+            "--retries",
+            "2",
+            "webkit2gtk"
+        ])),
+        args(&["--retries", "2", "webkit2gtk"])
+    );
+    assert_eq!(
+        forwarded_build_args(&args(&["build", "--on=beefy", "-imain", "-g", "firefox"])),
+        args(&["-g", "firefox"])
+    );
+    assert_eq!(
+        forwarded_build_args(&args(&["build", "--archs", "amd64,arm64", "-x", "curl"])),
+        args(&["-x", "curl"])
+    );
+}
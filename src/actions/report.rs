@@ -0,0 +1,458 @@
+//! Machine-readable build reports, written to the output directory after each build
+
+use anyhow::{anyhow, Result};
+use console::style;
+use indicatif::HumanBytes;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use walkdir::WalkDir;
+
+use crate::{common::sha256sum, config, error, info, machine, warn};
+
+use super::{
+    container::{get_instance_ns_name, get_output_directory},
+    packaging::BuildState,
+    provenance::{build_provenance, write_provenance, BuildProvenance},
+    qa::{check_build, qa_strict, QaProblem, QA_STATUS},
+    retry::{classify_failure, report_failure, FailureKind},
+    sbom::write_sboms,
+};
+
+const REPORT_FILE: &str = "report.json";
+const HTML_REPORT_FILE: &str = "report.html";
+
+lazy_static! {
+    /// Details of the packages built in this session (packages may be built in parallel)
+    static ref RECORDS: Mutex<Vec<PackageRecord>> = Mutex::new(Vec::new());
+}
+
+/// A .deb file produced by the build
+#[derive(Debug, Clone, Serialize)]
+struct DebFile {
+    /// Path relative to the output directory
+    path: String,
+    size: u64,
+    sha256: String,
+}
+
+#[derive(Debug, Clone)]
+struct PackageRecord {
+    package: String,
+    instance: String,
+    status: i32,
+    usage: BuildUsage,
+    debs: Vec<DebFile>,
+    qa: Vec<QaProblem>,
+    provenance: Option<BuildProvenance>,
+    log: Option<String>,
+    failure: Option<FailureKind>,
+}
+
+/// Wall time and resources used by a package build
+#[derive(Debug, Clone, Copy)]
+pub(super) struct BuildUsage {
+    pub started: SystemTime,
+    /// Wall time in seconds
+    pub duration: u64,
+    /// CPU time in seconds
+    pub cpu_time: Option<u64>,
+    /// Peak memory usage of the instance in bytes
+    pub peak_memory: Option<u64>,
+    /// Number of the processes killed by the OOM killer during the build
+    pub oom_kills: Option<u64>,
+}
+
+impl BuildUsage {
+    pub fn oom_killed(&self) -> bool {
+        self.oom_kills.is_some_and(|x| x > 0)
+    }
+}
+
+/// Measures the resources used by a package build using the cgroup accounting of the instance
+pub(super) struct BuildMeter {
+    started: SystemTime,
+    ns_name: Option<String>,
+    cpu_time: Option<u64>,
+    oom_kills: Option<u64>,
+}
+
+impl BuildMeter {
+    /// Start measuring, the instance should be running already
+    pub(super) fn start(instance: &str) -> Self {
+        let ns_name = get_instance_ns_name(instance).ok();
+        let usage = ns_name
+            .as_deref()
+            .and_then(|x| machine::container_resource_usage(x).ok());
+
+        Self {
+            started: SystemTime::now(),
+            ns_name,
+            cpu_time: usage.map(|x| x.cpu_time),
+            oom_kills: usage.and_then(|x| x.oom_kills),
+        }
+    }
+
+    /// Stop measuring, must be called before the instance is stopped
+    pub(super) fn finish(&self) -> BuildUsage {
+        let usage = self
+            .ns_name
+            .as_deref()
+            .and_then(|x| machine::container_resource_usage(x).ok());
+
+        BuildUsage {
+            started: self.started,
+            duration: self
+                .started
+                .elapsed()
+                .map(|x| x.as_secs())
+                .unwrap_or_default(),
+            cpu_time: usage
+                .zip(self.cpu_time)
+                .map(|(after, before)| after.cpu_time.saturating_sub(before) / 1_000_000),
+            peak_memory: usage.and_then(|x| x.peak_memory),
+            oom_kills: usage
+                .and_then(|x| x.oom_kills)
+                .map(|x| x.saturating_sub(self.oom_kills.unwrap_or_default())),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum PackageStatus {
+    Success,
+    Failed,
+    Pending,
+}
+
+#[derive(Debug, Serialize)]
+struct PackageReport {
+    package: String,
+    status: PackageStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration: Option<u64>,
+    /// CPU time in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu_time: Option<u64>,
+    /// Peak memory usage in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    peak_memory: Option<u64>,
+    debs: Vec<DebFile>,
+    /// Problems found by the QA checks in the .deb files
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    qa: Vec<QaProblem>,
+    /// TREE commit, base OS and configuration the package was built with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provenance: Option<BuildProvenance>,
+    /// URI of the build log
+    #[serde(skip_serializing_if = "Option::is_none")]
+    log: Option<String>,
+    /// Cause of the failure (see `FailureKind`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failure: Option<FailureKind>,
+    /// Machine-readable error code of the failure, e.g. `E_MISSING_DEPENDENCY`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_code: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+struct BuildReport {
+    status: i32,
+    duration: u64,
+    timestamp: u64,
+    attempts: usize,
+    packages: Vec<PackageReport>,
+}
+
+#[inline]
+fn output_dir() -> PathBuf {
+    let sep_mount = config::read_config().is_ok_and(|c| c.sep_mount);
+
+    std::env::current_dir()
+        .unwrap_or_default()
+        .join(get_output_directory(sep_mount))
+}
+
+/// Find the .deb files modified since `since` that are not claimed by other packages yet
+fn collect_debs(since: SystemTime, claimed: &[PackageRecord]) -> Result<Vec<DebFile>> {
+    let root = output_dir();
+    let mut debs = Vec::new();
+    for entry in WalkDir::new(root.join("debs"))
+        .into_iter()
+        .filter_map(|x| x.ok())
+    {
+        let path = entry.path();
+        if !entry.file_type().is_file() || path.extension().is_none_or(|x| x != "deb") {
+            continue;
+        }
+        if entry.metadata()?.modified()? < since {
+            continue;
+        }
+        let relative = path.strip_prefix(&root)?.to_string_lossy().to_string();
+        if claimed
+            .iter()
+            .any(|x| x.debs.iter().any(|d| d.path == relative))
+        {
+            continue;
+        }
+        debs.push(DebFile {
+            path: relative,
+            size: entry.metadata()?.len(),
+            sha256: sha256sum(File::open(path)?)?,
+        });
+    }
+
+    Ok(debs)
+}
+
+/// Record the result of a package build, the .deb files produced since the build started
+/// are attributed to the package and checked. Returns the status of the build,
+/// which fails in strict mode if the QA checks found problems.
+pub(super) fn record_package_build(
+    package: &str,
+    instance: &str,
+    status: i32,
+    usage: &BuildUsage,
+    log: Option<&Path>,
+) -> Result<i32> {
+    let mut records = RECORDS
+        .lock()
+        .map_err(|_| anyhow!("Build report lock poisoned"))?;
+    let debs = if status == 0 {
+        collect_debs(usage.started, &records)?
+    } else {
+        Vec::new()
+    };
+    let root = output_dir();
+    let paths = debs.iter().map(|x| x.path.clone()).collect::<Vec<_>>();
+    let qa = check_build(package, instance, &root, &paths);
+    let provenance = if debs.is_empty() {
+        None
+    } else {
+        let provenance = build_provenance(instance);
+        let subjects = debs
+            .iter()
+            .map(|x| (x.path.clone(), x.sha256.clone()))
+            .collect::<Vec<_>>();
+        if let Err(e) = write_provenance(
+            &root,
+            package,
+            instance,
+            &subjects,
+            &provenance,
+            usage.started,
+        ) {
+            warn!("{}: unable to write the provenance: {}", package, e);
+        }
+        if let Err(e) = write_sboms(&root, package, instance, &subjects) {
+            warn!("{}: unable to write the SBOM: {}", package, e);
+        }
+        Some(provenance)
+    };
+    let status = if !qa.is_empty() && qa_strict() {
+        error!(
+            "{}: {} QA problem(s) found, failing the build.",
+            package,
+            qa.len()
+        );
+        QA_STATUS
+    } else {
+        status
+    };
+    let failure = match log {
+        Some(log) if status != 0 && status != QA_STATUS => {
+            let kind = classify_failure(status, log, usage.oom_killed());
+            report_failure(package, kind);
+            Some(kind)
+        }
+        _ => None,
+    };
+    records.push(PackageRecord {
+        package: package.to_string(),
+        instance: instance.to_string(),
+        status,
+        usage: *usage,
+        debs,
+        qa,
+        provenance,
+        log: log
+            .and_then(|x| fs::canonicalize(x).ok())
+            .map(|x| format!("file://{}", x.display())),
+        failure,
+    });
+
+    Ok(status)
+}
+
+/// The .deb files produced by the last successful build of the package in this session
+pub(super) fn recorded_debs(package: &str) -> Vec<PathBuf> {
+    let records = match RECORDS.lock() {
+        Ok(records) => records,
+        Err(_) => return Vec::new(),
+    };
+    let root = output_dir();
+
+    records
+        .iter()
+        .rev()
+        .find(|x| x.package == package && x.status == 0)
+        .map(|x| x.debs.iter().map(|deb| root.join(&deb.path)).collect())
+        .unwrap_or_default()
+}
+
+/// A package build failed in this session
+#[derive(Debug, Clone)]
+pub struct FailedBuild {
+    pub package: String,
+    pub instance: String,
+    pub status: i32,
+    pub log: Option<PathBuf>,
+}
+
+/// Packages whose builds (the build itself, not the preparation) failed in this session
+pub fn failed_package_builds() -> Vec<FailedBuild> {
+    let records = match RECORDS.lock() {
+        Ok(records) => records,
+        Err(_) => return Vec::new(),
+    };
+
+    records
+        .iter()
+        .filter(|x| x.status != 0)
+        .map(|x| FailedBuild {
+            package: x.package.clone(),
+            instance: x.instance.clone(),
+            status: x.status,
+            log: x
+                .log
+                .as_deref()
+                .and_then(|x| x.strip_prefix("file://"))
+                .map(PathBuf::from),
+        })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(report: &BuildReport) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Build Report</title></head><body>\n<h1>Build {} ({} s)</h1>\n<table border=\"1\">\n<tr><th>Package</th><th>Status</th><th>Instance</th><th>Duration</th><th>CPU Time</th><th>Peak Memory</th><th>Packages</th><th>QA</th><th>Log</th></tr>\n",
+        if report.status == 0 { "Succeeded" } else { "Failed" },
+        report.duration
+    );
+    for package in report.packages.iter() {
+        let debs = package
+            .debs
+            .iter()
+            .map(|x| format!("{} ({} bytes)", escape_html(&x.path), x.size))
+            .collect::<Vec<_>>()
+            .join("<br>");
+        let qa = package
+            .qa
+            .iter()
+            .map(|x| format!("{}: {}", escape_html(&x.deb), escape_html(&x.message)))
+            .collect::<Vec<_>>()
+            .join("<br>");
+        let log = package
+            .log
+            .as_deref()
+            .map(|x| format!("<a href=\"{}\">log</a>", escape_html(x)))
+            .unwrap_or_default();
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{:?}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&package.package),
+            package.status,
+            escape_html(package.instance.as_deref().unwrap_or_default()),
+            package
+                .duration
+                .map(|x| format!("{} s", x))
+                .unwrap_or_default(),
+            package
+                .cpu_time
+                .map(|x| format!("{} s", x))
+                .unwrap_or_default(),
+            package
+                .peak_memory
+                .map(|x| HumanBytes(x).to_string())
+                .unwrap_or_default(),
+            debs,
+            qa,
+            log
+        ));
+    }
+    html.push_str("</table>\n</body></html>\n");
+
+    html
+}
+
+/// Write the report of the build to `<output>/report.json` (and `report.html` if requested)
+pub(super) fn write_build_report(state: &BuildState, status: i32, duration: u64) -> Result<()> {
+    let records = RECORDS
+        .lock()
+        .map_err(|_| anyhow!("Build report lock poisoned"))?;
+    let packages = state
+        .packages
+        .iter()
+        .map(|package| {
+            // use the most recent record if the package was built more than once
+            let record = records.iter().rev().find(|x| &x.package == package);
+            let failure = state.failed.iter().find(|x| &x.package == package);
+            let (status, exit_code) = if state.completed.contains(package) {
+                (PackageStatus::Success, Some(0))
+            } else if let Some(failure) = failure {
+                (PackageStatus::Failed, Some(failure.status))
+            } else {
+                (PackageStatus::Pending, None)
+            };
+            PackageReport {
+                package: package.clone(),
+                status,
+                exit_code,
+                instance: record.map(|x| x.instance.clone()),
+                duration: record.map(|x| x.usage.duration),
+                cpu_time: record.and_then(|x| x.usage.cpu_time),
+                peak_memory: record.and_then(|x| x.usage.peak_memory),
+                debs: record.map(|x| x.debs.clone()).unwrap_or_default(),
+                qa: record.map(|x| x.qa.clone()).unwrap_or_default(),
+                provenance: record.and_then(|x| x.provenance.clone()),
+                log: record.and_then(|x| x.log.clone()),
+                failure: record.and_then(|x| x.failure),
+                error_code: record.and_then(|x| x.failure).map(|x| x.code()),
+            }
+        })
+        .collect();
+    let report = BuildReport {
+        status,
+        duration,
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        attempts: state.attempts,
+        packages,
+    };
+    let root = output_dir();
+    fs::create_dir_all(&root)?;
+    fs::write(root.join(REPORT_FILE), serde_json::to_vec_pretty(&report)?)?;
+    if std::env::var("CIEL_HTML_REPORT").is_ok() {
+        fs::write(root.join(HTML_REPORT_FILE), render_html(&report))?;
+    }
+    info!(
+        "Build report written to {}",
+        root.join(REPORT_FILE).display()
+    );
+
+    Ok(())
+}
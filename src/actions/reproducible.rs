@@ -0,0 +1,302 @@
+//! Reproducibility verification: the package is built twice, the second time in a fresh instance
+//! with a different time zone, and the produced .deb files are compared
+
+use anyhow::{anyhow, bail, Result};
+use ar::Archive as ArArchive;
+use console::style;
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+};
+use tar::Archive as TarArchive;
+
+use crate::{
+    common::{is_instance_exists, sha256sum, CIEL_DATA_DIR},
+    config, error, info, warn,
+};
+
+use super::{
+    container::{add_instance, remove_instance},
+    packaging::{package_build, BuildSettings},
+    qa::decompress,
+    report::recorded_debs,
+};
+
+const REPRODUCIBLE_DIR: &str = "reproducible";
+/// Time zone of the second build, far from the usual ones
+const VARIED_TZ: &str = "Etc/GMT-14";
+const MAX_LISTED_DIFFERENCES: usize = 20;
+
+/// Metadata and checksum of a file in a .deb
+#[derive(Debug, PartialEq, Eq)]
+struct DebEntry {
+    mode: u32,
+    owner: (u64, u64),
+    mtime: u64,
+    link: Option<PathBuf>,
+    sha256: String,
+}
+
+/// Files of the .deb by `<member>/<path>`, e.g. `data.tar/usr/bin/foo`
+fn read_deb_entries(path: &Path) -> Result<BTreeMap<String, DebEntry>> {
+    let mut entries = BTreeMap::new();
+    let mut deb = ArArchive::new(File::open(path)?);
+    while let Some(member) = deb.next_entry() {
+        let mut member = member?;
+        let identifier = String::from_utf8_lossy(member.header().identifier()).to_string();
+        // the compression is compared through the contents
+        let prefix = match identifier.find(".tar") {
+            Some(end) => identifier[..end + 4].to_string(),
+            None => {
+                entries.insert(
+                    identifier,
+                    DebEntry {
+                        mode: 0,
+                        owner: (0, 0),
+                        mtime: 0,
+                        link: None,
+                        sha256: sha256sum(&mut member)?,
+                    },
+                );
+                continue;
+            }
+        };
+        let mut tar = TarArchive::new(decompress(identifier.as_bytes(), member)?);
+        for entry in tar.entries()? {
+            let mut entry = entry?;
+            let name = format!(
+                "{}/{}",
+                prefix,
+                entry.path()?.to_string_lossy().trim_start_matches("./")
+            );
+            let header = entry.header();
+            let metadata = (
+                header.mode()?,
+                (header.uid()?, header.gid()?),
+                header.mtime()?,
+                entry.link_name()?.map(|x| x.into_owned()),
+            );
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            entries.insert(
+                name,
+                DebEntry {
+                    mode: metadata.0,
+                    owner: metadata.1,
+                    mtime: metadata.2,
+                    link: metadata.3,
+                    sha256: sha256sum(data.as_slice())?,
+                },
+            );
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Differences between the files of two builds of a .deb, one line per file
+fn compare_entries(
+    first: &BTreeMap<String, DebEntry>,
+    second: &BTreeMap<String, DebEntry>,
+) -> Vec<String> {
+    let mut differences = Vec::new();
+    for (name, a) in first.iter() {
+        let b = match second.get(name) {
+            Some(b) => b,
+            None => {
+                differences.push(format!("{}: only in the first build", name));
+                continue;
+            }
+        };
+        let mut fields = Vec::new();
+        if a.sha256 != b.sha256 {
+            fields.push("content");
+        }
+        if a.mode != b.mode {
+            fields.push("permissions");
+        }
+        if a.owner != b.owner {
+            fields.push("owner");
+        }
+        if a.mtime != b.mtime {
+            fields.push("modification time");
+        }
+        if a.link != b.link {
+            fields.push("link target");
+        }
+        if !fields.is_empty() {
+            differences.push(format!("{}: {} differ", name, fields.join(", ")));
+        }
+    }
+    for name in second.keys().filter(|x| !first.contains_key(*x)) {
+        differences.push(format!("{}: only in the second build", name));
+    }
+
+    differences
+}
+
+/// Copy the .deb files produced by the last build of the package into the directory
+fn save_debs(package: &str, directory: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(directory)?;
+    let mut saved = Vec::new();
+    for deb in recorded_debs(package) {
+        let target = directory.join(
+            deb.file_name()
+                .ok_or_else(|| anyhow!("Invalid package path: {}", deb.display()))?,
+        );
+        fs::copy(&deb, &target)?;
+        saved.push(target);
+    }
+    saved.sort();
+
+    Ok(saved)
+}
+
+/// Build the package in the fresh instance, which is removed afterwards
+fn build_in_fresh_instance(
+    instance: &str,
+    fresh: &str,
+    package: &str,
+    settings: BuildSettings,
+) -> Result<i32> {
+    add_instance(fresh)?;
+    let result = (|| -> Result<i32> {
+        if let Some(overrides) = config::read_instance_overrides(instance)? {
+            config::write_instance_overrides(fresh, &overrides)?;
+        }
+        std::env::set_var("CIEL_BUILD_TZ", VARIED_TZ);
+        let status = package_build(fresh, [package].iter(), None, settings);
+        std::env::remove_var("CIEL_BUILD_TZ");
+        status
+    })();
+    if let Err(e) = remove_instance(fresh) {
+        warn!("Unable to remove instance {}: {}", fresh, e);
+    }
+
+    result
+}
+
+/// Build the package twice and compare the produced .deb files,
+/// returns 0 if the package is reproducible
+pub fn verify_reproducible(instance: &str, package: &str, settings: BuildSettings) -> Result<i32> {
+    if package.starts_with("groups/") {
+        bail!("Only a single package can be verified, not a group.");
+    }
+    let fresh = format!("{}-repro", instance);
+    if is_instance_exists(&fresh) {
+        bail!("Instance {} already exists, please remove it first.", fresh);
+    }
+    let settings = BuildSettings {
+        with_deps: false,
+        revdeps: false,
+        ..settings
+    };
+    let directory = Path::new(CIEL_DATA_DIR)
+        .join(REPRODUCIBLE_DIR)
+        .join(package.replace('/', "_"));
+    if directory.exists() {
+        fs::remove_dir_all(&directory)?;
+    }
+
+    info!("Building {} for the first time in {}...", package, instance);
+    let status = package_build(instance, [package].iter(), None, settings)?;
+    if status != 0 {
+        error!("The first build of {} failed.", package);
+        return Ok(status);
+    }
+    let first = save_debs(package, &directory.join("first"))?;
+    if first.is_empty() {
+        bail!("No package was produced by the build of {}.", package);
+    }
+    info!(
+        "Building {} for the second time in a fresh instance ({}, TZ={})...",
+        package, fresh, VARIED_TZ
+    );
+    let status = build_in_fresh_instance(instance, &fresh, package, settings)?;
+    if status != 0 {
+        error!("The second build of {} failed.", package);
+        return Ok(status);
+    }
+    let second = save_debs(package, &directory.join("second"))?;
+
+    let mut reproducible = true;
+    let mut names = first
+        .iter()
+        .chain(second.iter())
+        .filter_map(|x| x.file_name())
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    for name in names {
+        let (a, b) = (
+            directory.join("first").join(name),
+            directory.join("second").join(name),
+        );
+        let name = name.to_string_lossy();
+        if !a.is_file() || !b.is_file() {
+            reproducible = false;
+            error!(
+                "{}: only produced by the {} build",
+                name,
+                if a.is_file() { "first" } else { "second" }
+            );
+            continue;
+        }
+        if sha256sum(File::open(&a)?)? == sha256sum(File::open(&b)?)? {
+            info!("{}: identical", name);
+            continue;
+        }
+        reproducible = false;
+        let differences = compare_entries(&read_deb_entries(&a)?, &read_deb_entries(&b)?);
+        error!("{}: not reproducible", name);
+        for difference in differences.iter().take(MAX_LISTED_DIFFERENCES) {
+            eprintln!("    {}", difference);
+        }
+        if differences.len() > MAX_LISTED_DIFFERENCES {
+            eprintln!(
+                "    ... and {} more",
+                differences.len() - MAX_LISTED_DIFFERENCES
+            );
+        }
+    }
+    if !reproducible {
+        info!(
+            "The packages of both builds are kept in {}, use diffoscope for the details.",
+            directory.display()
+        );
+        return Ok(1);
+    }
+    info!("{} is reproducible.", package);
+    fs::remove_dir_all(&directory).ok();
+
+    Ok(0)
+}
+
+#[test]
+fn test_compare_entries() {
+    let entry = |sha256: &str, mtime| DebEntry {
+        mode: 0o644,
+        owner: (0, 0),
+        mtime,
+        link: None,
+        sha256: sha256.to_string(),
+    };
+    let first = BTreeMap::from([
+        ("data.tar/usr/bin/foo".to_string(), entry("a", 1)),
+        ("data.tar/usr/share/foo".to_string(), entry("b", 1)),
+    ]);
+    let second = BTreeMap::from([
+        ("data.tar/usr/bin/foo".to_string(), entry("c", 2)),
+        ("data.tar/usr/share/foo".to_string(), entry("b", 1)),
+        ("data.tar/usr/share/bar".to_string(), entry("d", 1)),
+    ]);
+    assert_eq!(
+        compare_entries(&first, &second),
+        vec![
+            "data.tar/usr/bin/foo: content, modification time differ",
+            "data.tar/usr/share/bar: only in the second build",
+        ]
+    );
+}
@@ -0,0 +1,239 @@
+//! Classification of the build and update failures: the transient ones are retried,
+//! the others are reported with a suggestion and a machine-readable error code
+
+use console::style;
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+    thread,
+    time::Duration,
+};
+
+use crate::{config, error};
+
+use super::watchdog::WATCHDOG_STATUS;
+
+/// Only the end of the log is inspected, where the failure is reported
+const LOG_TAIL_SIZE: u64 = 64 * 1024;
+/// Delay before the first retry, multiplied by the number of the attempt
+pub(super) const RETRY_DELAY: Duration = Duration::from_secs(10);
+/// Messages of the failed downloads (curl, wget, git and the source fetchers of ACBS)
+const NETWORK_ERRORS: &[&str] = &[
+    "could not resolve host",
+    "temporary failure in name resolution",
+    "connection timed out",
+    "connection refused",
+    "connection reset by peer",
+    "network is unreachable",
+    "failed to fetch",
+    "unable to download",
+    "fatal: unable to access",
+    "early eof",
+    "the requested url returned error: 5",
+    "gnutls_handshake() failed",
+    "ssl_connect",
+];
+/// Files missing on the mirror or the source server
+const NOT_FOUND_ERRORS: &[&str] = &[
+    "404  not found",
+    "404 not found",
+    "the requested url returned error: 404",
+    "error 404",
+];
+const DISK_FULL_ERRORS: &[&str] = &["no space left on device", "disk quota exceeded"];
+/// The compiler (or the linker) killed by the OOM killer
+const OUT_OF_MEMORY_ERRORS: &[&str] = &[
+    "signal terminated program",
+    "internal compiler error: killed",
+    "out of memory",
+    "cannot allocate memory",
+    "memory exhausted",
+];
+const DEPENDENCY_CONFLICT_ERRORS: &[&str] = &[
+    "unmet dependencies",
+    "broken packages",
+    "dependency problems",
+    "but it is not going to be installed",
+];
+/// Tools, headers or libraries not found by the build
+const MISSING_DEPENDENCY_ERRORS: &[&str] = &[
+    "command not found",
+    // missing headers
+    ".h: no such file or directory",
+    // CMake
+    "could not find",
+    "no package '",
+    "modulenotfounderror",
+    "cannot find -l",
+    "unable to locate package",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(super) enum FailureKind {
+    /// Transient failure when downloading, worth retrying
+    Network,
+    /// A file is missing from the mirror or the source server (404)
+    NotFound,
+    DiskFull,
+    /// The compiler was killed by the OOM killer
+    OutOfMemory,
+    /// The dependencies can not be satisfied by the repository
+    DependencyConflict,
+    /// A tool, header or library is missing from the build dependencies
+    MissingDependency,
+    /// Aborted by the watchdog
+    Hung,
+    /// Genuine build failure (compile errors, ...)
+    Build,
+}
+
+impl FailureKind {
+    /// Machine-readable error code, printed with the suggestion and recorded in the build report
+    pub fn code(&self) -> &'static str {
+        match self {
+            FailureKind::Network => "E_NETWORK",
+            FailureKind::NotFound => "E_NOT_FOUND",
+            FailureKind::DiskFull => "E_DISK_FULL",
+            FailureKind::OutOfMemory => "E_OUT_OF_MEMORY",
+            FailureKind::DependencyConflict => "E_DEPENDENCY_CONFLICT",
+            FailureKind::MissingDependency => "E_MISSING_DEPENDENCY",
+            FailureKind::Hung => "E_HUNG",
+            FailureKind::Build => "E_BUILD",
+        }
+    }
+
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            FailureKind::Network => Some("A download failed, check the network or the proxy settings (`ciel build --retries` retries the network failures)."),
+            FailureKind::NotFound => Some("A file is missing from the server (404): the package lists may be outdated (try `ciel update-os` or another mirror with `ciel mirror`), or the source URL of the package has changed."),
+            FailureKind::DiskFull => Some("The disk is full, free some space with `ciel gc`, `ciel cache prune` or `ciel clean`."),
+            FailureKind::OutOfMemory => Some("The compiler was killed, most likely out of memory, even with a single job: add swap or raise the memory limit of the instance."),
+            FailureKind::DependencyConflict => Some("The dependencies can not be satisfied, retrying will not help: check the conflicting or held back packages, a dependency may need to be rebuilt first."),
+            FailureKind::MissingDependency => Some("A tool, header or library was not found, it is likely missing from BUILDDEP (or PKGDEP) of the package."),
+            FailureKind::Hung => Some("The build hung and was aborted by the watchdog, see `watchdog` in the configuration."),
+            FailureKind::Build => None,
+        }
+    }
+}
+
+/// Number of retries for the network failures, set by `ciel build --retries`
+pub(super) fn build_retries() -> usize {
+    std::env::var("CIEL_RETRIES")
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Classify the failure from the end of the output, the most specific causes first
+pub(super) fn classify_output(output: &str) -> FailureKind {
+    let output = output.to_lowercase();
+    let matches = |patterns: &[&str]| patterns.iter().any(|x| output.contains(x));
+    if matches(DISK_FULL_ERRORS) {
+        FailureKind::DiskFull
+    } else if matches(OUT_OF_MEMORY_ERRORS) {
+        FailureKind::OutOfMemory
+    } else if matches(DEPENDENCY_CONFLICT_ERRORS) {
+        FailureKind::DependencyConflict
+    } else if matches(NOT_FOUND_ERRORS) {
+        FailureKind::NotFound
+    } else if matches(NETWORK_ERRORS) {
+        FailureKind::Network
+    } else if matches(MISSING_DEPENDENCY_ERRORS) {
+        FailureKind::MissingDependency
+    } else {
+        FailureKind::Build
+    }
+}
+
+/// Print the suggestion and the error code of the failure
+pub(super) fn report_failure(subject: &str, kind: FailureKind) {
+    match kind.suggestion() {
+        Some(suggestion) => {
+            error!("{}: {} [{}]", subject, suggestion, kind.code());
+        }
+        None => {
+            error!("{}: failed [{}]", subject, kind.code());
+        }
+    }
+}
+
+/// Parallel jobs of the next attempt of a build killed by the OOM killer (half of the current ones),
+/// `None` if they can not be reduced any further
+pub(super) fn reduced_jobs(current: Option<usize>) -> Option<usize> {
+    let current = current.unwrap_or_else(|| {
+        config::read_config()
+            .ok()
+            .and_then(|x| {
+                x.remote_compile
+                    .jobs
+                    .filter(|_| x.remote_compile.enabled())
+                    .or(x.jobs.default)
+            })
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, |x| x.get()))
+    });
+
+    (current > 1).then_some(current / 2)
+}
+
+/// Classify the failed build from its exit status, the end of its log
+/// and whether the OOM killer was triggered in the instance
+pub(super) fn classify_failure(status: i32, log: &Path, oom_killed: bool) -> FailureKind {
+    if status == WATCHDOG_STATUS {
+        return FailureKind::Hung;
+    }
+    if oom_killed {
+        return FailureKind::OutOfMemory;
+    }
+    let mut tail = Vec::new();
+    let read = File::open(log).and_then(|mut f| {
+        let size = f.metadata()?.len();
+        f.seek(SeekFrom::Start(size.saturating_sub(LOG_TAIL_SIZE)))?;
+        f.read_to_end(&mut tail)
+    });
+    if read.is_err() {
+        return FailureKind::Build;
+    }
+
+    classify_output(&String::from_utf8_lossy(&tail))
+}
+
+#[test]
+fn test_classify_output() {
+    assert_eq!(
+        classify_output("curl: (6) Could not resolve host: example.org\n"),
+        FailureKind::Network
+    );
+    assert_eq!(
+        classify_output(
+            "fatal: unable to access 'https://example.org/foo.git/': Failed to connect"
+        ),
+        FailureKind::Network
+    );
+    assert_eq!(
+        classify_output("main.c:1:10: fatal error: foo.h: No such file or directory\nmake: *** [Makefile:2: all] Error 1\n"),
+        FailureKind::MissingDependency
+    );
+    assert_eq!(
+        classify_output("E: Failed to fetch https://repo.aosc.io/debs/pool/foo.deb  404  Not Found [IP: 1.2.3.4 443]\n"),
+        FailureKind::NotFound
+    );
+    assert_eq!(
+        classify_output(
+            "x86_64-aosc-linux-gnu-g++: fatal error: Killed signal terminated program cc1plus\n"
+        ),
+        FailureKind::OutOfMemory
+    );
+    assert_eq!(
+        classify_output("tar: foo: Cannot write: No space left on device\n"),
+        FailureKind::DiskFull
+    );
+    assert_eq!(
+        classify_output("make: *** [Makefile:2: all] Error 1\n"),
+        FailureKind::Build
+    );
+    assert_eq!(reduced_jobs(Some(16)), Some(8));
+    assert_eq!(reduced_jobs(Some(1)), None);
+}
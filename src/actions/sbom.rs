@@ -0,0 +1,182 @@
+//! SPDX software bill of materials of the built packages: the sources from the ACBS spec
+//! and the packages installed in the instance during the build
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::{fs, path::Path};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use super::sources::package_sources;
+
+const SPDX_VERSION: &str = "SPDX-2.3";
+const DOCUMENT_NAMESPACE: &str = "https://aosc.io/spdx";
+const DPKG_STATUS_FILE: &str = "var/lib/dpkg/status";
+const NOASSERTION: &str = "NOASSERTION";
+
+/// A package installed in the instance
+#[derive(Debug, PartialEq, Eq)]
+struct InstalledPackage {
+    name: String,
+    version: String,
+    arch: String,
+}
+
+/// Packages installed according to a dpkg status database
+fn parse_dpkg_status(status: &str) -> Vec<InstalledPackage> {
+    status
+        .split("\n\n")
+        .filter_map(|paragraph| {
+            let field = |name: &str| {
+                paragraph
+                    .lines()
+                    .find_map(|x| x.strip_prefix(name))
+                    .map(|x| x.trim().to_string())
+            };
+            if field("Status:")?.split_whitespace().last()? != "installed" {
+                return None;
+            }
+            Some(InstalledPackage {
+                name: field("Package:")?,
+                version: field("Version:")?,
+                arch: field("Architecture:").unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+fn purl(name: &str, version: &str, arch: &str) -> String {
+    format!("pkg:deb/aosc/{}@{}?arch={}", name, version, arch)
+}
+
+fn spdx_package(id: &str, name: &str, version: Option<&str>, location: &str) -> Value {
+    let mut package = json!({
+        "SPDXID": id,
+        "name": name,
+        "downloadLocation": location,
+        "filesAnalyzed": false,
+        "licenseConcluded": NOASSERTION,
+        "licenseDeclared": NOASSERTION,
+        "copyrightText": NOASSERTION,
+    });
+    if let Some(version) = version {
+        package["versionInfo"] = json!(version);
+    }
+
+    package
+}
+
+fn relationship(element: &str, kind: &str, related: &str) -> Value {
+    json!({
+        "spdxElementId": element,
+        "relationshipType": kind,
+        "relatedSpdxElement": related,
+    })
+}
+
+/// Write the SBOM of each .deb produced by the build of the package next to it
+/// (`<name>_<version>_<arch>.spdx.json`), `debs` are the paths relative to the output directory
+/// and the SHA-256 checksums
+pub(super) fn write_sboms(
+    root: &Path,
+    package: &str,
+    instance: &str,
+    debs: &[(String, String)],
+) -> Result<()> {
+    let sources = package_sources(package).unwrap_or_default();
+    let installed = fs::read_to_string(
+        std::env::current_dir()?
+            .join(instance)
+            .join(DPKG_STATUS_FILE),
+    )
+    .map(|x| parse_dpkg_status(&x))
+    .unwrap_or_default();
+    let created = OffsetDateTime::now_utc().format(&Rfc3339)?;
+    for (path, sha256) in debs {
+        let file_name = Path::new(path)
+            .file_name()
+            .and_then(|x| x.to_str())
+            .ok_or_else(|| anyhow!("Invalid package path: {}", path))?;
+        let mut fields = file_name.trim_end_matches(".deb").splitn(3, '_');
+        let (name, version, arch) = (
+            fields.next().unwrap_or_default(),
+            fields.next().unwrap_or_default(),
+            fields.next().unwrap_or_default(),
+        );
+        let mut deb = spdx_package("SPDXRef-Package", name, Some(version), NOASSERTION);
+        deb["checksums"] = json!([{ "algorithm": "SHA256", "checksumValue": sha256 }]);
+        deb["externalRefs"] = json!([{
+            "referenceCategory": "PACKAGE-MANAGER",
+            "referenceType": "purl",
+            "referenceLocator": purl(name, version, arch),
+        }]);
+        let mut packages = vec![deb];
+        let mut relationships = vec![relationship(
+            "SPDXRef-DOCUMENT",
+            "DESCRIBES",
+            "SPDXRef-Package",
+        )];
+        for (index, source) in sources.iter().enumerate() {
+            let id = format!("SPDXRef-Source-{}", index);
+            let name = source.url.rsplit('/').next().unwrap_or(&source.url);
+            let mut package = spdx_package(&id, name, None, &source.url);
+            if let Some((algorithm, value)) =
+                source.checksum.as_deref().and_then(|x| x.split_once("::"))
+            {
+                package["checksums"] = json!([{
+                    "algorithm": algorithm.to_uppercase(),
+                    "checksumValue": value,
+                }]);
+            }
+            packages.push(package);
+            relationships.push(relationship(&id, "GENERATES", "SPDXRef-Package"));
+        }
+        for (index, dependency) in installed.iter().enumerate() {
+            let id = format!("SPDXRef-BuildEnvironment-{}", index);
+            let mut package = spdx_package(
+                &id,
+                &dependency.name,
+                Some(&dependency.version),
+                NOASSERTION,
+            );
+            package["externalRefs"] = json!([{
+                "referenceCategory": "PACKAGE-MANAGER",
+                "referenceType": "purl",
+                "referenceLocator": purl(&dependency.name, &dependency.version, &dependency.arch),
+            }]);
+            packages.push(package);
+            relationships.push(relationship(&id, "BUILD_DEPENDENCY_OF", "SPDXRef-Package"));
+        }
+        let document = json!({
+            "spdxVersion": SPDX_VERSION,
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": file_name,
+            "documentNamespace": format!("{}/{}-{}", DOCUMENT_NAMESPACE, name, sha256),
+            "creationInfo": {
+                "created": created,
+                "creators": [format!("Tool: ciel-{}", env!("CARGO_PKG_VERSION"))],
+            },
+            "packages": packages,
+            "relationships": relationships,
+        });
+        fs::write(
+            root.join(path).with_extension("spdx.json"),
+            serde_json::to_vec_pretty(&document)?,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_dpkg_status() {
+    let status = "Package: bash\nStatus: install ok installed\nArchitecture: amd64\nVersion: 5.2.15\n\nPackage: foo\nStatus: deinstall ok config-files\nVersion: 1.0\n";
+    assert_eq!(
+        parse_dpkg_status(status),
+        vec![InstalledPackage {
+            name: "bash".to_string(),
+            version: "5.2.15".to_string(),
+            arch: "amd64".to_string(),
+        }]
+    );
+}
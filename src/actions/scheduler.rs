@@ -0,0 +1,339 @@
+//! Parallel build scheduler: splits the package list across multiple instances
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Condvar, Mutex,
+    },
+    thread,
+    time::Instant,
+};
+
+use crate::{
+    common::is_instance_exists,
+    config::{self, WatchdogConfig},
+    error, info, repo, warn,
+};
+
+use super::{
+    container::{
+        add_instance, apply_instance_config, get_output_directory, mount_fs, rollback_container,
+    },
+    deps::order_packages_with_dependencies,
+    hooks::{run_hook, Hook},
+    packaging::{
+        apply_build_settings, build_hook_env, check_build_space, expand_package_list,
+        format_duration, install_cross_toolchain, prefetch_build_dependencies, report_build_result,
+        run_package_build, update_instance_os, BuildSettings, BuildState,
+    },
+    report::record_package_build,
+    sources::prefetch_sources,
+};
+
+const LOG_DIR: &str = "LOGS";
+
+/// Shared state between the build workers
+struct BuildQueue {
+    pending: Mutex<VecDeque<(usize, String)>>,
+    /// Packages (by index) each package depends on, they must be built before it
+    dependencies: Vec<Vec<usize>>,
+    built: Vec<AtomicBool>,
+    changed: Condvar,
+    failed: Mutex<Vec<(String, i32)>>,
+    state: Mutex<BuildState>,
+    stopped: AtomicBool,
+    total: usize,
+}
+
+impl BuildQueue {
+    fn new(packages: Vec<String>, dependencies: Vec<Vec<usize>>, state: BuildState) -> Self {
+        Self {
+            total: packages.len(),
+            built: packages.iter().map(|_| AtomicBool::new(false)).collect(),
+            state: Mutex::new(state),
+            pending: Mutex::new(packages.into_iter().enumerate().collect()),
+            dependencies,
+            changed: Condvar::new(),
+            failed: Mutex::new(Vec::new()),
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    /// Take the next package whose dependencies are built, waiting for the builds in progress
+    /// if needed. Returns `None` if the queue is drained or stopped.
+    fn next(&self) -> Option<(usize, String)> {
+        let mut pending = self.pending.lock().ok()?;
+        loop {
+            if self.stopped.load(Ordering::SeqCst) || pending.is_empty() {
+                return None;
+            }
+            let ready = pending.iter().position(|(index, _)| {
+                self.dependencies
+                    .get(*index)
+                    .into_iter()
+                    .flatten()
+                    .all(|x| self.built[*x].load(Ordering::SeqCst))
+            });
+            if let Some(position) = ready {
+                return pending.remove(position);
+            }
+            pending = self.changed.wait(pending).ok()?;
+        }
+    }
+
+    /// Record a built package, the packages depending on it can be built now
+    fn finish(&self, index: usize) {
+        // holding the lock so that the waiting workers do not miss the notification
+        let _pending = self.pending.lock();
+        self.built[index].store(true, Ordering::SeqCst);
+        self.changed.notify_all();
+    }
+
+    /// Record a successfully built package
+    fn complete(&self, package: &str) -> Result<()> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("Build state lock poisoned"))?
+            .complete(package)
+    }
+
+    /// Record a failed package and stop scheduling new builds
+    fn fail(&self, package: String, status: i32) {
+        self.stopped.store(true, Ordering::SeqCst);
+        if let Ok(mut state) = self.state.lock() {
+            state.fail(&package, status).ok();
+        }
+        if let Ok(mut failed) = self.failed.lock() {
+            failed.push((package, status));
+        }
+        let _pending = self.pending.lock();
+        self.changed.notify_all();
+    }
+}
+
+/// Get the names of the worker instances, the first worker is the specified instance itself
+fn worker_instances(instance: &str, jobs: usize) -> Vec<String> {
+    (0..jobs)
+        .map(|i| {
+            if i == 0 {
+                instance.to_string()
+            } else {
+                format!("{}-{}", instance, i)
+            }
+        })
+        .collect()
+}
+
+fn build_one(
+    instance: &str,
+    package: &str,
+    root: Option<&Path>,
+    log: &File,
+    watchdog: &WatchdogConfig,
+) -> Result<i32> {
+    mount_fs(instance)?;
+    if let Some(root) = root {
+        // refreshing is serialized by the repo module
+        repo::init_repo(root, Path::new(instance))?;
+    }
+    let status = update_instance_os(instance, Some(log));
+    if status != 0 {
+        error!(
+            "{}: failed to update the OS before building packages",
+            instance
+        );
+        return Ok(status);
+    }
+    let status = install_cross_toolchain(instance, Some(log))?;
+    if status != 0 {
+        error!("{}: failed to install the cross toolchain", instance);
+        return Ok(status);
+    }
+    prefetch_build_dependencies(instance, package, Some(log))?;
+    check_build_space(package)?;
+    run_hook(
+        Hook::PreBuild,
+        Some(instance),
+        &build_hook_env(package, None),
+    )?;
+    // multiplexed with the output of the other instances, prefixed with the instance name
+    let (status, usage, log_path) = run_package_build(instance, package, true, watchdog)?;
+    let status = record_package_build(package, instance, status, &usage, Some(&log_path))?;
+    run_hook(
+        Hook::PostBuild,
+        Some(instance),
+        &build_hook_env(package, Some((status, log_path.as_path()))),
+    )?;
+    if status == 0 {
+        rollback_container(instance)?;
+    }
+
+    Ok(status)
+}
+
+fn build_worker(
+    instance: &str,
+    queue: &BuildQueue,
+    root: Option<&Path>,
+    watchdog: &WatchdogConfig,
+) -> Result<()> {
+    let log_path = Path::new(LOG_DIR).join(format!("{}.log", instance));
+    let log = File::options().create(true).append(true).open(&log_path)?;
+    while let Some((index, package)) = queue.next() {
+        info!(
+            "[{}/{}] {}: building {}...",
+            index + 1,
+            queue.total,
+            instance,
+            package
+        );
+        let status = build_one(instance, &package, root, &log, watchdog).unwrap_or_else(|e| {
+            error!("{}: {:?}", instance, e);
+            -1
+        });
+        if status != 0 {
+            error!(
+                "{}: failed to build {} (status: {}), see `ciel log {}` or {} for details.",
+                instance,
+                package,
+                status,
+                package,
+                log_path.display()
+            );
+            queue.fail(package, status);
+            break;
+        }
+        info!("{}: {} built successfully.", instance, package);
+        queue.finish(index);
+        queue.complete(&package)?;
+    }
+
+    Ok(())
+}
+
+/// Build packages concurrently using multiple instances
+pub fn package_build_parallel<S: AsRef<str>, K: IntoIterator<Item = S>>(
+    instance: &str,
+    packages: K,
+    settings: BuildSettings,
+    jobs: usize,
+) -> Result<i32> {
+    let conf =
+        config::read_config().map_err(|_| anyhow!("Please configure this workspace first!"))?;
+    if jobs == 0 {
+        bail!("Number of parallel jobs must be at least 1.");
+    }
+    let (packages, dependencies) =
+        order_packages_with_dependencies(expand_package_list(packages), &settings)?;
+    let workers = worker_instances(instance, jobs.min(packages.len()).max(1));
+    for worker in workers.iter() {
+        if !is_instance_exists(worker) {
+            add_instance(worker)?;
+        }
+        apply_instance_config(worker)?;
+    }
+    apply_build_settings(instance, &packages, settings)?;
+    if conf.local_sources && std::env::var("CIEL_OFFLINE").is_err() {
+        // so that the instances do not download the same tarballs
+        info!("Fetching the source tarballs...");
+        if let Err(e) = prefetch_sources(&packages) {
+            warn!("Unable to fetch the sources beforehand: {}", e);
+        }
+    }
+    for worker in workers.iter() {
+        mount_fs(worker)?;
+        rollback_container(worker)?;
+    }
+    let root = if conf.local_repo {
+        Some(std::env::current_dir()?.join(get_output_directory(conf.sep_mount)))
+    } else {
+        warn!("Local repository is disabled, packages built by other instances will not be available.");
+        None
+    };
+    fs::create_dir_all(LOG_DIR)?;
+
+    info!(
+        "Building {} packages using {} instances...",
+        packages.len(),
+        workers.len()
+    );
+    let state = BuildState::new(instance, &packages, 1);
+    state.save()?;
+    let queue = BuildQueue::new(packages, dependencies, state);
+    let start = Instant::now();
+    thread::scope(|s| {
+        for worker in workers.iter() {
+            let queue = &queue;
+            let root = root.as_deref();
+            let watchdog = &conf.watchdog;
+            s.spawn(move || {
+                if let Err(e) = build_worker(worker, queue, root, watchdog) {
+                    error!("{}: {:?}", worker, e);
+                    queue.fail(worker.to_string(), -1);
+                }
+            });
+        }
+    });
+
+    let total = queue.total;
+    let remaining = queue.pending.lock().map(|x| x.len()).unwrap_or(0);
+    let failed = queue
+        .failed
+        .into_inner()
+        .map_err(|_| anyhow!("Build queue lock poisoned"))?;
+    let state = queue
+        .state
+        .into_inner()
+        .map_err(|_| anyhow!("Build state lock poisoned"))?;
+    let status = failed.first().map_or(0, |(_, status)| *status);
+    report_build_result(&state, status, start.elapsed().as_secs())?;
+    if let Some((_, status)) = failed.first() {
+        error!("{} package(s) failed to build:", failed.len());
+        for (package, status) in failed.iter() {
+            eprintln!("\t{} (status: {})", package, status);
+        }
+        if remaining > 0 {
+            warn!("{} package(s) were not built.", remaining);
+        }
+        return Ok(*status);
+    }
+    eprintln!(
+        "{} - {} packages in {}",
+        style("BUILD SUCCESSFUL").bold().green(),
+        total,
+        format_duration(start.elapsed().as_secs())
+    );
+
+    Ok(0)
+}
+
+#[test]
+fn test_worker_instances() {
+    assert_eq!(
+        worker_instances("main", 3),
+        vec![
+            "main".to_string(),
+            "main-1".to_string(),
+            "main-2".to_string()
+        ]
+    );
+}
+
+#[test]
+fn test_build_queue() {
+    let packages = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    // b depends on a
+    let state = BuildState::new("main", &packages, 1);
+    let queue = BuildQueue::new(packages, vec![vec![], vec![0], vec![]], state);
+    assert_eq!(queue.next(), Some((0, "a".to_string())));
+    // b has to wait for a
+    assert_eq!(queue.next(), Some((2, "c".to_string())));
+    queue.finish(0);
+    assert_eq!(queue.next(), Some((1, "b".to_string())));
+    assert_eq!(queue.next(), None);
+}
@@ -0,0 +1,181 @@
+//! Package search in TREE, with the versions built in the output repository
+
+use anyhow::{anyhow, Result};
+use console::{style, StyledObject};
+use serde::Serialize;
+use std::{
+    cmp::Ordering,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+use tabwriter::TabWriter;
+
+use crate::{
+    config,
+    logging::{json_output, print_json},
+    repo::{compare_versions, latest_package_versions},
+};
+
+use super::{container::get_output_directory, deps::read_variable, sources::expand_variables};
+
+const TREE_DIR: &str = "TREE";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SearchStatus {
+    NotBuilt,
+    /// The version in the output repository is older than the one in TREE
+    Outdated,
+    UpToDate,
+}
+
+impl SearchStatus {
+    fn describe(self) -> StyledObject<&'static str> {
+        match self {
+            SearchStatus::NotBuilt => style("not built").red(),
+            SearchStatus::Outdated => style("outdated").yellow(),
+            SearchStatus::UpToDate => style("up to date").green(),
+        }
+    }
+}
+
+/// A package in TREE matching the search
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub name: String,
+    pub section: String,
+    pub description: String,
+    /// Version in TREE
+    pub version: String,
+    /// Latest version in the output repository
+    pub built: Option<String>,
+    pub status: SearchStatus,
+}
+
+/// Version of the package as built by autobuild (`[epoch:]VER[-REL]`)
+pub(super) fn recipe_version(spec: &str, defines: &str) -> String {
+    let variable = |source: &str, name| {
+        read_variable(source, name)
+            .map(|x| expand_variables(&x, spec, 0).trim().to_string())
+            .filter(|x| !x.is_empty())
+    };
+    let mut version = variable(spec, "VER").unwrap_or_default();
+    if let Some(release) = variable(spec, "REL").filter(|x| x != "0") {
+        version = format!("{}-{}", version, release);
+    }
+    match variable(defines, "PKGEPOCH").filter(|x| x != "0") {
+        Some(epoch) => format!("{}:{}", epoch, version),
+        None => version,
+    }
+}
+
+/// Packages defined in TREE, as (section, spec, defines) of each recipe
+pub(super) fn tree_recipes() -> Result<Vec<(String, String, String)>> {
+    let mut recipes = Vec::new();
+    for section in fs::read_dir(TREE_DIR)?.flatten() {
+        let section_name = section.file_name().to_string_lossy().to_string();
+        if section_name.starts_with('.') || !section.path().is_dir() {
+            continue;
+        }
+        for package in fs::read_dir(section.path())?.flatten() {
+            let spec = match fs::read_to_string(package.path().join("spec")) {
+                Ok(spec) => spec,
+                Err(_) => continue,
+            };
+            // `autobuild/defines`, or `<recipe>/defines` for the packages split into several recipes
+            for recipe in fs::read_dir(package.path())?.flatten() {
+                if let Ok(defines) = fs::read_to_string(recipe.path().join("defines")) {
+                    recipes.push((section_name.clone(), spec.clone(), defines));
+                }
+            }
+        }
+    }
+
+    Ok(recipes)
+}
+
+/// Search the package names and descriptions in TREE (case-insensitive),
+/// with the built versions in the current output repository
+pub fn search_packages(pattern: &str) -> Result<Vec<SearchResult>> {
+    let conf =
+        config::read_config().map_err(|_| anyhow!("Please configure this workspace first!"))?;
+    let built =
+        latest_package_versions(&Path::new(&get_output_directory(conf.sep_mount)).join("debs"))
+            .unwrap_or_default();
+    let pattern = pattern.to_lowercase();
+    let mut results = Vec::new();
+    for (section, spec, defines) in tree_recipes()? {
+        let name = match read_variable(&defines, "PKGNAME") {
+            Some(name) => name.trim().to_string(),
+            None => continue,
+        };
+        let description = read_variable(&defines, "PKGDES").unwrap_or_default();
+        if !name.to_lowercase().contains(&pattern) && !description.to_lowercase().contains(&pattern)
+        {
+            continue;
+        }
+        let version = recipe_version(&spec, &defines);
+        let built = built.get(&name).cloned();
+        let status = match &built {
+            None => SearchStatus::NotBuilt,
+            Some(built) if compare_versions(built, &version) == Ordering::Less => {
+                SearchStatus::Outdated
+            }
+            Some(_) => SearchStatus::UpToDate,
+        };
+        results.push(SearchResult {
+            name,
+            section,
+            description,
+            version,
+            built,
+            status,
+        });
+    }
+    results.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(results)
+}
+
+/// Print the packages matching the pattern, only the ones needing a build with `outdated`
+pub fn print_search_results(pattern: &str, outdated: bool) -> Result<()> {
+    let mut results = search_packages(pattern)?;
+    if outdated {
+        results.retain(|x| x.status != SearchStatus::UpToDate);
+    }
+    if json_output() {
+        return print_json(&results);
+    }
+    let mut formatter = TabWriter::new(io::stderr());
+    writeln!(
+        &mut formatter,
+        "PACKAGE\tSECTION\tTREE\tBUILT\tSTATUS\tDESCRIPTION"
+    )?;
+    for result in results.iter() {
+        writeln!(
+            &mut formatter,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            result.name,
+            result.section,
+            result.version,
+            result.built.as_deref().unwrap_or("-"),
+            result.status.describe(),
+            result.description
+        )?;
+    }
+    formatter.flush()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_recipe_version() {
+    let spec = "VER=1.2.3\nREL=2\n";
+    assert_eq!(recipe_version(spec, "PKGNAME=foo\n"), "1.2.3-2");
+    assert_eq!(recipe_version("VER=1.0\n", "PKGEPOCH=1\n"), "1:1.0");
+    assert_eq!(
+        recipe_version("UPSTREAM=2.0\nVER=${UPSTREAM}+git\nREL=0\n", ""),
+        "2.0+git"
+    );
+}
@@ -0,0 +1,213 @@
+//! Named snapshots of the instance state
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tabwriter::TabWriter;
+use time::{format_description::FormatItem, macros::format_description, OffsetDateTime};
+
+use crate::{
+    common::{is_instance_exists, CIEL_DATA_DIR},
+    info,
+    logging::{json_output, print_json},
+    overlayfs,
+};
+
+use super::container::{container_down, is_tmpfs_instance};
+
+pub(super) const SNAPSHOT_DIR: &str = "snapshots";
+const SNAPSHOT_METADATA: &str = "snapshot.json";
+const TIMESTAMP_FORMAT: &[FormatItem] =
+    format_description!("[year]-[month]-[day] [hour]:[minute] UTC");
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub name: String,
+    pub instance: String,
+    /// Creation time (UNIX timestamp)
+    pub created: u64,
+}
+
+fn validate_snapshot_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || name.starts_with('.')
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        bail!("Invalid snapshot name: {}", name);
+    }
+
+    Ok(())
+}
+
+/// Directory holding all the snapshots of the instance
+fn instance_snapshot_directory(instance: &str) -> PathBuf {
+    Path::new(CIEL_DATA_DIR).join(SNAPSHOT_DIR).join(instance)
+}
+
+fn snapshot_directory(instance: &str, name: &str) -> Result<PathBuf> {
+    validate_snapshot_name(name)?;
+
+    Ok(instance_snapshot_directory(instance).join(name))
+}
+
+/// Stop the instance so that the saved state is consistent
+fn prepare_instance(instance: &str) -> Result<()> {
+    if !is_instance_exists(instance) {
+        bail!("Instance {} does not exist.", instance);
+    }
+    if is_tmpfs_instance(instance) {
+        bail!(
+            "{}: the upper layer is on tmpfs, snapshots are not supported.",
+            instance
+        );
+    }
+
+    container_down(instance)
+}
+
+/// Save the current state of the instance as a named snapshot
+pub fn create_snapshot(instance: &str, name: &str) -> Result<()> {
+    let directory = snapshot_directory(instance, name)?;
+    if directory.exists() {
+        bail!("{}: snapshot {} already exists.", instance, name);
+    }
+    prepare_instance(instance)?;
+    info!("{}: saving snapshot {}...", instance, name);
+    let man = &mut *overlayfs::get_layer_manager(instance)?;
+    if let Err(e) = man.save_snapshot(&directory) {
+        man.delete_snapshot(&directory).ok();
+        return Err(e);
+    }
+    let info = SnapshotInfo {
+        name: name.to_string(),
+        instance: instance.to_string(),
+        created: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    };
+    fs::write(
+        directory.join(SNAPSHOT_METADATA),
+        serde_json::to_vec(&info)?,
+    )?;
+    info!("{}: snapshot {} saved.", instance, name);
+
+    Ok(())
+}
+
+/// Replace the state of the instance with the named snapshot
+pub fn restore_snapshot(instance: &str, name: &str) -> Result<()> {
+    let directory = snapshot_directory(instance, name)?;
+    if !directory.is_dir() {
+        bail!("{}: snapshot {} not found.", instance, name);
+    }
+    prepare_instance(instance)?;
+    info!("{}: restoring snapshot {}...", instance, name);
+    let man = &mut *overlayfs::get_layer_manager(instance)?;
+    man.restore_snapshot(&directory)?;
+    // the checkpoint refers to the files replaced by the snapshot
+    super::discard_checkpoint(instance)?;
+    info!("{}: snapshot {} restored.", instance, name);
+
+    Ok(())
+}
+
+/// Remove the named snapshot of the instance
+pub fn remove_snapshot(instance: &str, name: &str) -> Result<()> {
+    let directory = snapshot_directory(instance, name)?;
+    if !directory.is_dir() {
+        bail!("{}: snapshot {} not found.", instance, name);
+    }
+    let man = &mut *overlayfs::get_layer_manager(instance)?;
+    man.delete_snapshot(&directory)?;
+    info!("{}: snapshot {} removed.", instance, name);
+
+    Ok(())
+}
+
+/// Remove all the snapshots of the instance (when the instance itself is removed)
+pub(super) fn remove_instance_snapshots(instance: &str) -> Result<()> {
+    let directory = instance_snapshot_directory(instance);
+    if !directory.is_dir() {
+        return Ok(());
+    }
+    let man = &mut *overlayfs::get_layer_manager(instance)?;
+    for entry in fs::read_dir(&directory)? {
+        man.delete_snapshot(&entry?.path())?;
+    }
+    fs::remove_dir(&directory)?;
+
+    Ok(())
+}
+
+fn read_snapshots(instance: &str) -> Result<Vec<SnapshotInfo>> {
+    let entries = match fs::read_dir(instance_snapshot_directory(instance)) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let path = entry?.path().join(SNAPSHOT_METADATA);
+        // incomplete snapshots do not have the metadata
+        if let Ok(data) = fs::read(&path) {
+            snapshots.push(
+                serde_json::from_slice(&data)
+                    .map_err(|e| anyhow!("Invalid snapshot {}: {}", path.display(), e))?,
+            );
+        }
+    }
+
+    Ok(snapshots)
+}
+
+/// List the snapshots of the instance, or of all the instances
+pub fn list_snapshots(instance: Option<&str>) -> Result<()> {
+    let instances = match instance {
+        Some(instance) => vec![instance.to_string()],
+        None => fs::read_dir(Path::new(CIEL_DATA_DIR).join(SNAPSHOT_DIR))
+            .map(|x| {
+                x.filter_map(|x| x.ok())
+                    .filter_map(|x| x.file_name().into_string().ok())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    let mut snapshots = Vec::new();
+    for instance in instances {
+        snapshots.extend(read_snapshots(&instance)?);
+    }
+    snapshots.sort_by(|a, b| (&a.instance, a.created).cmp(&(&b.instance, b.created)));
+    if json_output() {
+        return print_json(&snapshots);
+    }
+    let mut formatter = TabWriter::new(io::stderr());
+    writeln!(&mut formatter, "INSTANCE\tSNAPSHOT\tCREATED")?;
+    for snapshot in snapshots.iter() {
+        let created = OffsetDateTime::from_unix_timestamp(snapshot.created as i64)
+            .ok()
+            .and_then(|t| t.format(TIMESTAMP_FORMAT).ok())
+            .unwrap_or_default();
+        writeln!(
+            &mut formatter,
+            "{}\t{}\t{}",
+            snapshot.instance, snapshot.name, created
+        )?;
+    }
+    formatter.flush()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_directory() {
+    assert!(snapshot_directory("alice", "before-upgrade")
+        .unwrap()
+        .ends_with("snapshots/alice/before-upgrade"));
+    assert!(snapshot_directory("alice", "../bob").is_err());
+    assert!(snapshot_directory("alice", "").is_err());
+}
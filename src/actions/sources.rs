@@ -0,0 +1,210 @@
+//! Source tarball cache shared by the instances, filled on the host from the ACBS specs
+
+use anyhow::{anyhow, Result};
+use console::style;
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{config, error, info, network, warn};
+
+use super::{
+    deps::{package_recipe_path, read_variable},
+    packaging::{expand_package_list, package_fetch},
+    SOURCES_HOST_DIR,
+};
+
+const TREE_DIR: &str = "TREE";
+/// Nesting limit of the variable references in the specs
+const MAX_EXPANSION_DEPTH: usize = 4;
+
+/// A source listed in the `SRCS` variable of a spec
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct SpecSource {
+    /// Fetcher type, e.g. `tbl` or `git`
+    pub kind: String,
+    pub url: String,
+    /// Checksum from `CHKSUMS`, e.g. `sha256::<hex>`
+    pub checksum: Option<String>,
+}
+
+impl SpecSource {
+    /// SHA-256 checksum of the tarball, the only sources downloaded on the host
+    fn tarball_sha256(&self) -> Option<&str> {
+        if self.kind != "tbl" {
+            return None;
+        }
+        self.checksum
+            .as_deref()
+            .and_then(|x| x.strip_prefix("sha256::"))
+    }
+}
+
+/// Expand the `$NAME` and `${NAME}` references using the variables defined in the spec
+pub(super) fn expand_variables(value: &str, spec: &str, depth: usize) -> String {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, next) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => ("", after),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        match read_variable(spec, name).filter(|_| !name.is_empty()) {
+            Some(value) if depth < MAX_EXPANSION_DEPTH => {
+                expanded.push_str(&expand_variables(&value, spec, depth + 1))
+            }
+            _ => expanded.push_str(&rest[start..rest.len() - next.len()]),
+        }
+        rest = next;
+    }
+    expanded.push_str(rest);
+
+    expanded
+}
+
+/// Parse the sources of a spec, paired with their checksums (in the same order)
+fn parse_spec_sources(spec: &str) -> Vec<SpecSource> {
+    let variable = |name| {
+        read_variable(spec, name)
+            .map(|x| expand_variables(&x, spec, 0))
+            .unwrap_or_default()
+    };
+    let checksums = variable("CHKSUMS");
+    let mut checksums = checksums.split_whitespace();
+    variable("SRCS")
+        .split_whitespace()
+        .map(|source| {
+            // `<type>::[<options>::]<url>`
+            let mut parts = source.split("::");
+            let kind = parts.next().unwrap_or_default().to_string();
+            let url = parts.last().unwrap_or(source).to_string();
+            let checksum = checksums
+                .next()
+                .filter(|x| *x != "SKIP")
+                .map(|x| x.to_string());
+
+            SpecSource {
+                kind,
+                url,
+                checksum,
+            }
+        })
+        .collect()
+}
+
+/// Sources of the package as listed in its spec in TREE
+pub(super) fn package_sources(package: &str) -> Result<Vec<SpecSource>> {
+    let path = package_recipe_path(package)
+        .ok_or_else(|| anyhow!("Package {} is not found in TREE.", package))?;
+    let spec = fs::read_to_string(Path::new(TREE_DIR).join(path))?;
+
+    Ok(parse_spec_sources(&spec))
+}
+
+/// Cached tarball of the source, named after its checksum like ACBS does
+fn cached_source_path(sha256: &str) -> Result<PathBuf> {
+    Ok(std::env::current_dir()?.join(SOURCES_HOST_DIR).join(sha256))
+}
+
+/// Download the tarball sources of the packages into the source cache (each tarball once),
+/// returns the packages having other sources (e.g. VCS) left to ACBS
+pub(super) fn prefetch_sources(packages: &[String]) -> Result<Vec<String>> {
+    fs::create_dir_all(SOURCES_HOST_DIR)?;
+    let mut seen = HashSet::new();
+    let mut remaining = Vec::new();
+    for package in packages {
+        let sources = package_sources(package)?;
+        if sources.iter().any(|x| x.tarball_sha256().is_none()) {
+            remaining.push(package.clone());
+        }
+        for (url, sha256) in sources
+            .iter()
+            .filter_map(|x| x.tarball_sha256().map(|sum| (&x.url, sum)))
+        {
+            if !seen.insert(sha256.to_string()) {
+                continue;
+            }
+            let path = cached_source_path(sha256)?;
+            if path.is_file() {
+                continue;
+            }
+            info!("{}: fetching {}...", package, url);
+            network::download_file_progress(url, &path.to_string_lossy(), Some(sha256))?;
+        }
+    }
+
+    Ok(remaining)
+}
+
+/// Fetch the sources of the packages into the shared source cache, so that they can be built offline.
+/// The tarballs are downloaded and verified on the host, the other sources are fetched by ACBS
+/// in the instance (if specified).
+pub fn fetch_sources<S: AsRef<str>, K: IntoIterator<Item = S>>(
+    packages: K,
+    instance: Option<&str>,
+) -> Result<i32> {
+    let conf =
+        config::read_config().map_err(|_| anyhow!("Please configure this workspace first!"))?;
+    if !conf.local_sources {
+        warn!("Local sources caching is disabled, the instances will not use the fetched sources.");
+    }
+    let packages = expand_package_list(packages);
+    let remaining = prefetch_sources(&packages)?;
+    if remaining.is_empty() {
+        info!("All the sources are fetched.");
+        return Ok(0);
+    }
+    let instance = match instance {
+        Some(instance) => instance,
+        None => {
+            error!(
+                "The sources of {} package(s) must be fetched using ACBS: {}",
+                remaining.len(),
+                remaining.join(", ")
+            );
+            info!("Specify an instance (`-i`) to fetch them.");
+            return Ok(1);
+        }
+    };
+    info!(
+        "Fetching the other sources of {} package(s) in {}...",
+        remaining.len(),
+        instance
+    );
+
+    package_fetch(instance, &remaining)
+}
+
+#[test]
+fn test_parse_spec_sources() {
+    let spec = "VER=1.2\nREL=1\nSRCS=\"tbl::https://example.org/foo-$VER.tar.xz \\\n      git::commit=tags/v${VER}::https://example.org/bar.git\"\nCHKSUMS=\"sha256::abcd \\\n         SKIP\"\nCHKUPDATE=\"anitya::id=1\"\n";
+    assert_eq!(
+        parse_spec_sources(spec),
+        vec![
+            SpecSource {
+                kind: "tbl".to_string(),
+                url: "https://example.org/foo-1.2.tar.xz".to_string(),
+                checksum: Some("sha256::abcd".to_string()),
+            },
+            SpecSource {
+                kind: "git".to_string(),
+                url: "https://example.org/bar.git".to_string(),
+                checksum: None,
+            },
+        ]
+    );
+    assert_eq!(parse_spec_sources(spec)[0].tarball_sha256(), Some("abcd"));
+    assert_eq!(expand_variables("$UNSET-$", spec, 0), "$UNSET-$");
+}
@@ -0,0 +1,277 @@
+//! Version gaps between TREE, the output repository and the remote repository
+
+use anyhow::{anyhow, Result};
+use console::{style, StyledObject};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    io::{self, Read, Write},
+    path::Path,
+    sync::Mutex,
+};
+use tabwriter::TabWriter;
+use xz2::read::XzDecoder;
+
+use crate::{
+    common::get_host_arch_name,
+    config::{self, CielConfig},
+    logging::{json_output, print_json},
+    network,
+    repo::{compare_versions, latest_package_versions},
+    warn,
+};
+
+use super::{
+    container::get_output_directory,
+    deps::read_variable,
+    search::{recipe_version, tree_recipes},
+};
+
+/// Suite of the remote repository the topics are based on
+const STABLE_SUITE: &str = "stable";
+/// Architecture of the architecture-independent packages in the AOSC OS repository
+const NOARCH: &str = "noarch";
+
+lazy_static! {
+    /// Index of the remote repository, fetched once for the QA checks of all the builds
+    static ref REMOTE_INDEX: Mutex<Option<HashMap<String, RemotePackage>>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PackageStatus {
+    /// The output repository has the version in TREE
+    UpToDate,
+    /// Only the remote repository has the version in TREE
+    Published,
+    /// Neither has the version in TREE, the package needs to be rebuilt
+    Outdated,
+}
+
+impl PackageStatus {
+    fn describe(self) -> StyledObject<&'static str> {
+        match self {
+            PackageStatus::UpToDate => style("up to date").green(),
+            PackageStatus::Published => style("published").cyan(),
+            PackageStatus::Outdated => style("outdated").red(),
+        }
+    }
+}
+
+/// Versions of a package in TREE, the output repository and the remote repository
+#[derive(Debug, Serialize)]
+pub struct PackageVersions {
+    pub name: String,
+    pub section: String,
+    pub tree: String,
+    pub output: Option<String>,
+    pub remote: Option<String>,
+    pub status: PackageStatus,
+}
+
+/// Latest version of a package in the remote repository
+#[derive(Debug, Clone)]
+pub(super) struct RemotePackage {
+    pub version: String,
+    /// Path of the .deb file relative to the repository
+    pub filename: Option<String>,
+}
+
+/// Keep the latest version of each package
+fn merge_package(
+    packages: &mut HashMap<String, RemotePackage>,
+    name: String,
+    package: RemotePackage,
+) {
+    match packages.get(&name) {
+        Some(current) if compare_versions(&current.version, &package.version) != Ordering::Less => {
+        }
+        _ => {
+            packages.insert(name, package);
+        }
+    }
+}
+
+/// Latest version of each package in a `Packages` index
+fn parse_packages_index(index: &str) -> HashMap<String, RemotePackage> {
+    let mut packages = HashMap::new();
+    for paragraph in index.split("\n\n") {
+        let field = |name: &str| {
+            paragraph
+                .lines()
+                .find_map(|x| x.strip_prefix(name))
+                .map(|x| x.trim().to_string())
+        };
+        let (name, version) = match (field("Package:"), field("Version:")) {
+            (Some(name), Some(version)) => (name, version),
+            _ => continue,
+        };
+        let filename = field("Filename:");
+        merge_package(&mut packages, name, RemotePackage { version, filename });
+    }
+
+    packages
+}
+
+/// Download the `Packages` index of the suite and architecture (compressed if available)
+fn fetch_packages_index(conf: &CielConfig, suite: &str, arch: &str) -> Result<String> {
+    let base = format!("{}dists/{}/main/binary-{}/", conf.repo_url(), suite, arch);
+    let client = network::http_client_builder()?.build()?;
+    let mut index = String::new();
+    match client
+        .get(format!("{}Packages.xz", base))
+        .send()?
+        .error_for_status()
+    {
+        Ok(resp) => {
+            XzDecoder::new(resp).read_to_string(&mut index)?;
+        }
+        Err(_) => {
+            index = client
+                .get(format!("{}Packages", base))
+                .send()?
+                .error_for_status()?
+                .text()?;
+        }
+    }
+
+    Ok(index)
+}
+
+/// Latest versions in the remote repository (the stable suite and the current topic)
+fn remote_packages(conf: &CielConfig) -> Result<HashMap<String, RemotePackage>> {
+    let arch = conf
+        .arch
+        .as_deref()
+        .or_else(|| get_host_arch_name())
+        .ok_or_else(|| anyhow!("Unable to determine the architecture of the workspace"))?;
+    let mut packages = HashMap::new();
+    let suites = std::iter::once(STABLE_SUITE).chain(conf.topic.as_deref());
+    for suite in suites {
+        for arch in [arch, NOARCH] {
+            let index = match fetch_packages_index(conf, suite, arch) {
+                Ok(index) => index,
+                // the topics do not necessarily have packages for every architecture
+                Err(_) if suite != STABLE_SUITE || arch == NOARCH => continue,
+                Err(e) => return Err(e),
+            };
+            for (name, package) in parse_packages_index(&index) {
+                merge_package(&mut packages, name, package);
+            }
+        }
+    }
+
+    Ok(packages)
+}
+
+/// URL of the latest .deb file of the package in the remote repository
+pub(super) fn remote_package_url(name: &str) -> Result<Option<String>> {
+    let conf = config::read_config()?;
+    let mut index = REMOTE_INDEX
+        .lock()
+        .map_err(|_| anyhow!("Remote index lock poisoned"))?;
+    if index.is_none() {
+        *index = Some(remote_packages(&conf)?);
+    }
+
+    Ok(index
+        .as_ref()
+        .and_then(|x| x.get(name))
+        .and_then(|x| x.filename.as_ref())
+        .map(|x| format!("{}{}", conf.repo_url(), x)))
+}
+
+/// Compare the versions in TREE against the output repository and the remote repository
+/// (unless in offline mode)
+pub fn package_versions() -> Result<Vec<PackageVersions>> {
+    let conf =
+        config::read_config().map_err(|_| anyhow!("Please configure this workspace first!"))?;
+    let output =
+        latest_package_versions(&Path::new(&get_output_directory(conf.sep_mount)).join("debs"))
+            .unwrap_or_default();
+    let remote = if network::is_offline() {
+        HashMap::new()
+    } else {
+        remote_packages(&conf)
+            .map(|x| x.into_iter().map(|(k, v)| (k, v.version)).collect())
+            .unwrap_or_else(|e| {
+                warn!("Unable to fetch the remote repository index: {}", e);
+                HashMap::new()
+            })
+    };
+    let mut packages = Vec::new();
+    for (section, spec, defines) in tree_recipes()? {
+        let name = match read_variable(&defines, "PKGNAME") {
+            Some(name) => name.trim().to_string(),
+            None => continue,
+        };
+        let tree = recipe_version(&spec, &defines);
+        let has_tree_version = |x: &Option<String>| {
+            x.as_ref()
+                .is_some_and(|x| compare_versions(x, &tree) != Ordering::Less)
+        };
+        let output = output.get(&name).cloned();
+        let remote = remote.get(&name).cloned();
+        let status = if has_tree_version(&output) {
+            PackageStatus::UpToDate
+        } else if has_tree_version(&remote) {
+            PackageStatus::Published
+        } else {
+            PackageStatus::Outdated
+        };
+        packages.push(PackageVersions {
+            name,
+            section,
+            tree,
+            output,
+            remote,
+            status,
+        });
+    }
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(packages)
+}
+
+/// Print the versions of the packages, only the ones needing a rebuild with `outdated`
+pub fn print_package_status(outdated: bool) -> Result<()> {
+    let mut packages = package_versions()?;
+    if outdated {
+        packages.retain(|x| x.status == PackageStatus::Outdated);
+    }
+    if json_output() {
+        return print_json(&packages);
+    }
+    let mut formatter = TabWriter::new(io::stderr());
+    writeln!(
+        &mut formatter,
+        "PACKAGE\tSECTION\tTREE\tOUTPUT\tREMOTE\tSTATUS"
+    )?;
+    for package in packages.iter() {
+        writeln!(
+            &mut formatter,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            package.name,
+            package.section,
+            package.tree,
+            package.output.as_deref().unwrap_or("-"),
+            package.remote.as_deref().unwrap_or("-"),
+            package.status.describe()
+        )?;
+    }
+    formatter.flush()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_packages_index() {
+    let index = "Package: foo\nVersion: 1.0-1\nArchitecture: amd64\n\nPackage: foo\nVersion: 1:0.9\nFilename: pool/f/foo.deb\n\nPackage: bar\nVersion: 2.0\n";
+    let packages = parse_packages_index(index);
+    assert_eq!(packages["foo"].version, "1:0.9");
+    assert_eq!(packages["foo"].filename.as_deref(), Some("pool/f/foo.deb"));
+    assert_eq!(packages["bar"].version, "2.0");
+    assert_eq!(packages.len(), 2);
+}
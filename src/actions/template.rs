@@ -0,0 +1,202 @@
+//! Instance templates: configuration overrides and provisioning steps shared by the new instances
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    common::is_instance_exists,
+    config::{self, InstanceConfig},
+    info,
+};
+
+use super::{cache::run_apt_script, container::add_instance};
+
+const TEMPLATE_DIR: &str = ".ciel/templates";
+
+/// Stored in `.ciel/templates/<name>.toml`, so they can be shared between workspaces
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct InstanceTemplate {
+    /// Extra packages installed into the new instances
+    #[serde(default)]
+    pub packages: Vec<String>,
+    /// Users created in the new instances
+    #[serde(default)]
+    pub users: Vec<String>,
+    /// Shell script executed in the new instances after the packages and the users are set up
+    #[serde(default)]
+    pub script: Option<String>,
+    /// Configuration overrides of the new instances
+    #[serde(default)]
+    pub config: Option<InstanceConfig>,
+}
+
+/// Package and user names are interpolated into the script, so only the safe characters are allowed
+pub(super) fn validate_word(kind: &str, word: &str) -> Result<()> {
+    let valid = !word.is_empty()
+        && !word.starts_with('-')
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._+-:=~".contains(c));
+    if !valid {
+        bail!("Invalid {} name: {}", kind, word);
+    }
+
+    Ok(())
+}
+
+impl InstanceTemplate {
+    /// Script provisioning the new instances, `None` if there is nothing to do
+    fn provisioning_script(&self) -> Result<Option<String>> {
+        let mut script = Vec::new();
+        if !self.packages.is_empty() {
+            for package in self.packages.iter() {
+                validate_word("package", package)?;
+            }
+            script.push("export DEBIAN_FRONTEND=noninteractive".to_string());
+            script.push("apt-get update -y".to_string());
+            script.push(format!("apt-get install -y {}", self.packages.join(" ")));
+        }
+        for user in self.users.iter() {
+            validate_word("user", user)?;
+            script.push(format!(
+                "id -u {user} > /dev/null 2>&1 || useradd -m {user}",
+                user = user
+            ));
+        }
+        if let Some(extra) = &self.script {
+            script.push(extra.trim_end().to_string());
+        }
+        if script.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(script.join("\n")))
+    }
+}
+
+fn template_path(name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains('/') || name.starts_with('.') {
+        bail!("Invalid template name: {}", name);
+    }
+
+    Ok(Path::new(TEMPLATE_DIR).join(format!("{}.toml", name)))
+}
+
+fn read_template(name: &str) -> Result<InstanceTemplate> {
+    let path = template_path(name)?;
+    let data = fs::read_to_string(&path).map_err(|_| anyhow!("Template {} not found.", name))?;
+
+    toml::from_str(&data).map_err(|e| anyhow!("Invalid template {}: {}", name, e))
+}
+
+/// Save the configuration of the instance and the provisioning steps as a template
+pub fn save_template(
+    instance: &str,
+    name: &str,
+    packages: Vec<String>,
+    users: Vec<String>,
+    script: Option<&Path>,
+) -> Result<()> {
+    if !is_instance_exists(instance) {
+        bail!("Instance {} does not exist.", instance);
+    }
+    let template = InstanceTemplate {
+        packages,
+        users,
+        script: script.map(fs::read_to_string).transpose()?,
+        config: config::read_instance_overrides(instance)?,
+    };
+    // fail early instead of when the template is used
+    template.provisioning_script()?;
+    let path = template_path(name)?;
+    fs::create_dir_all(TEMPLATE_DIR)?;
+    fs::write(&path, toml::to_string(&template)?)?;
+    info!("Template {} saved to {}", name, path.display());
+
+    Ok(())
+}
+
+/// Create a new instance using the template
+pub fn add_instance_from_template(instance: &str, name: &str) -> Result<()> {
+    let template = read_template(name)?;
+    let script = template.provisioning_script()?;
+    if is_instance_exists(instance) {
+        bail!("Instance {} already exists.", instance);
+    }
+    add_instance(instance)?;
+    if let Some(overrides) = &template.config {
+        config::write_instance_overrides(instance, overrides)?;
+    }
+    if let Some(script) = script {
+        info!("{}: provisioning using template {}...", instance, name);
+        let status = run_apt_script(instance, &script, None)?;
+        if status != 0 {
+            bail!("Provisioning failed with status {}", status);
+        }
+    }
+    info!("{}: instance created from template {}.", instance, name);
+
+    Ok(())
+}
+
+/// List the templates in the workspace
+pub fn list_templates() -> Result<()> {
+    let entries = match fs::read_dir(TEMPLATE_DIR) {
+        Ok(entries) => entries,
+        Err(_) => {
+            info!("No templates found.");
+            return Ok(());
+        }
+    };
+    let mut names = entries
+        .filter_map(|x| x.ok())
+        .filter_map(|x| {
+            x.file_name()
+                .to_str()
+                .and_then(|x| x.strip_suffix(".toml"))
+                .map(|x| x.to_string())
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    for name in names {
+        println!("{}", name);
+    }
+
+    Ok(())
+}
+
+/// Remove the template from the workspace
+pub fn remove_template(name: &str) -> Result<()> {
+    fs::remove_file(template_path(name)?).map_err(|_| anyhow!("Template {} not found.", name))?;
+    info!("Template {} removed.", name);
+
+    Ok(())
+}
+
+#[test]
+fn test_provisioning_script() {
+    let template = InstanceTemplate {
+        packages: vec!["vim".to_string(), "git".to_string()],
+        users: vec!["dev".to_string()],
+        script: Some("echo done\n".to_string()),
+        config: None,
+    };
+    assert_eq!(
+        template.provisioning_script().unwrap().unwrap(),
+        "export DEBIAN_FRONTEND=noninteractive\napt-get update -y\napt-get install -y vim git\nid -u dev > /dev/null 2>&1 || useradd -m dev\necho done"
+    );
+    assert!(InstanceTemplate::default()
+        .provisioning_script()
+        .unwrap()
+        .is_none());
+    let template = InstanceTemplate {
+        users: vec!["dev; rm -rf /".to_string()],
+        ..Default::default()
+    };
+    assert!(template.provisioning_script().is_err());
+}
@@ -0,0 +1,93 @@
+//! Topic-based development: TREE, APT sources and output repository following the same topic
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+
+use crate::{common::CIEL_DIST_DIR, config, info, overlayfs, warn};
+
+use super::{
+    container::{container_down, get_branch_name},
+    for_each_instance,
+    tree::switch_tree_branch,
+};
+
+/// Branch of TREE when no topic is being worked on
+const DEFAULT_TREE_BRANCH: &str = "stable";
+
+/// Topic names are also used as branch, APT suite and directory names
+fn validate_topic(name: &str) -> Result<()> {
+    if name.is_empty()
+        || name.starts_with('.')
+        || name.starts_with('-')
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' || c == '+')
+    {
+        bail!("Invalid topic name: {}", name);
+    }
+
+    Ok(())
+}
+
+/// Start working on the topic (`None` to stop): TREE is switched to the topic branch, the instances
+/// install the packages from the topic repository and the packages are built into `OUTPUT-<topic>`
+pub fn switch_topic(topic: Option<&str>) -> Result<()> {
+    if let Some(topic) = topic {
+        validate_topic(topic)?;
+    }
+    let mut c =
+        config::read_config().map_err(|_| anyhow!("Please configure this workspace first!"))?;
+    if c.topic.as_deref() == topic {
+        info!("Already working on {}.", topic.unwrap_or("no topic"));
+        return Ok(());
+    }
+    overlayfs::ensure_dist_writable("switching topics")?;
+    let branch = topic
+        .map(|x| x.to_string())
+        .or_else(|| c.branch.clone())
+        .unwrap_or_else(|| DEFAULT_TREE_BRANCH.to_string());
+    if get_branch_name().ok().as_deref() != Some(branch.as_str()) {
+        switch_tree_branch(&branch, None)?;
+    }
+    c.topic = topic.map(|x| x.to_string());
+    info!("Shutting down instance(s) before applying config...");
+    for_each_instance(&container_down)?;
+    config::apply_config(CIEL_DIST_DIR, &c)?;
+    config::write_config(&c)?;
+    match topic {
+        Some(topic) => {
+            info!(
+                "Now working on {}: packages are installed from the topic repository and built into OUTPUT-{}.",
+                topic, topic
+            );
+        }
+        None => {
+            info!("Stopped working on topics, TREE is on {}.", branch);
+        }
+    }
+    warn!("Please rollback all your instances for the new config to take effect!");
+
+    Ok(())
+}
+
+/// Print the topic being worked on
+pub fn print_topic() -> Result<()> {
+    let c = config::read_config().map_err(|_| anyhow!("Please configure this workspace first!"))?;
+    match c.topic {
+        Some(topic) => println!("{}", topic),
+        None => {
+            info!("Not working on any topic.");
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_topic() {
+    assert!(validate_topic("gcc-14.2.0").is_ok());
+    assert!(validate_topic("survey-20240101").is_ok());
+    assert!(validate_topic("../stable").is_err());
+    assert!(validate_topic("-x").is_err());
+    assert!(validate_topic("").is_err());
+}
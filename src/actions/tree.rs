@@ -0,0 +1,102 @@
+//! Management of the ABBS tree checkout (TREE) in the workspace
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use git2::{build::CheckoutBuilder, BranchType, Repository, RepositoryState};
+use std::path::Path;
+
+use crate::{config, info, network};
+
+const TREE_DIR: &str = "TREE";
+/// The AOSC OS ABBS tree
+pub const DEFAULT_TREE_REMOTE: &str = "https://github.com/AOSC-Dev/aosc-os-abbs.git";
+
+/// Remote of the tree, from the workspace configuration (`tree-remote`) if set
+fn tree_remote() -> String {
+    config::read_config()
+        .ok()
+        .and_then(|x| x.tree_remote)
+        .unwrap_or_else(|| DEFAULT_TREE_REMOTE.to_string())
+}
+
+/// Clone the tree into the workspace (from the configured remote by default),
+/// then switch to `branch` or the default branch in the workspace configuration
+pub fn clone_tree(url: Option<&str>, branch: Option<&str>) -> Result<()> {
+    if Path::new(TREE_DIR).exists() {
+        bail!("TREE already exists, use `ciel tree update` to update it.");
+    }
+    let url = url.map_or_else(tree_remote, |x| x.to_string());
+    info!("Cloning {}...", url);
+    network::download_git(&url, Path::new(TREE_DIR))?;
+    let branch = branch
+        .map(|x| x.to_string())
+        .or_else(|| config::read_config().ok().and_then(|x| x.branch));
+    if let Some(branch) = branch {
+        let mut repo = Repository::open(TREE_DIR)?;
+        network::git_switch_branch(&mut repo, &branch, None)?;
+        info!("Switched to {}.", branch);
+    }
+    info!("TREE is ready.");
+
+    Ok(())
+}
+
+/// Fetch the remote and fast-forward the current branch of the tree to its remote counterpart.
+/// The local changes are kept, unless they conflict with the update.
+pub fn update_tree() -> Result<()> {
+    let repo = network::fetch_repo(TREE_DIR)?;
+    let head = repo.head()?;
+    if !head.is_branch() {
+        bail!("TREE is not on a branch, use `ciel tree switch-branch` first.");
+    }
+    let name = head
+        .shorthand()
+        .ok_or_else(|| anyhow!("Unable to resolve Git ref"))?
+        .to_string();
+    let refname = head
+        .name()
+        .ok_or_else(|| anyhow!("Unable to resolve Git ref"))?
+        .to_string();
+    drop(head);
+    let upstream = repo
+        .find_branch(&format!("origin/{}", name), BranchType::Remote)
+        .map_err(|_| anyhow!("Branch {} does not exist on the remote.", name))?;
+    let target = upstream.get().peel_to_commit()?;
+    let (analysis, _) = repo.merge_analysis(&[&repo.find_annotated_commit(target.id())?])?;
+    if analysis.is_up_to_date() {
+        info!("TREE is already up to date ({}).", name);
+        return Ok(());
+    }
+    if !analysis.is_fast_forward() {
+        bail!(
+            "{} has diverged from origin/{}, use `ciel tree switch-branch {} --rebase origin/{}` to rebase it.",
+            name, name, name, name
+        );
+    }
+    // fails instead of overwriting the conflicting local changes
+    repo.checkout_tree(target.as_object(), Some(CheckoutBuilder::new().safe()))?;
+    repo.find_reference(&refname)?
+        .set_target(target.id(), "ciel: fast-forward")?;
+    info!(
+        "TREE updated to {} ({}).",
+        &target.id().to_string()[..8],
+        name
+    );
+
+    Ok(())
+}
+
+/// Fetch the remote and switch the tree to the (topic) branch, the local changes are carried over.
+/// With `rebase_from`, the branch is rebased onto the specified upstream.
+pub fn switch_tree_branch(branch: &str, rebase_from: Option<&str>) -> Result<()> {
+    let mut repo = network::fetch_repo(TREE_DIR)?;
+    if repo.state() != RepositoryState::Clean {
+        bail!("Cannot switch branches, because your tree seems to have an operation in progress.");
+    }
+    if let Err(e) = network::git_switch_branch(&mut repo, branch, rebase_from) {
+        bail!("Failed to switch branches: {}\nNote that you can still use `git stash pop` to retrieve your previous changes.`", e);
+    }
+    info!("Successfully updated the tree and switched to {}.", branch);
+
+    Ok(())
+}
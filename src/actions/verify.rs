@@ -0,0 +1,211 @@
+//! Verification of the base OS against the file lists and checksums of the installed packages
+
+use anyhow::{bail, Result};
+use console::style;
+use md5::{Digest, Md5};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    ffi::OsString,
+    fs::{self, File},
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    common::{create_spinner, CIEL_DIST_DIR},
+    error, info,
+    logging::{json_output, print_json},
+    overlayfs,
+};
+
+/// The manifests (`<package>.md5sums`) shipped by the packages, relative to the root
+const DPKG_INFO_DIR: &str = "var/lib/dpkg/info";
+/// Same limit as the kernel (ELOOP)
+const MAX_SYMLINK_HOPS: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DistProblemKind {
+    Missing,
+    Modified,
+}
+
+/// A file in the base OS not matching the manifest of its package
+#[derive(Debug, Serialize)]
+pub struct DistProblem {
+    pub kind: DistProblemKind,
+    pub package: String,
+    pub path: PathBuf,
+}
+
+/// Parse the `<md5>  <path>` lines of a md5sums file, the paths are relative to the root
+fn parse_md5sums(data: &str) -> Vec<(&str, &str)> {
+    data.lines()
+        .filter_map(|x| x.split_once("  "))
+        .map(|(sum, path)| (sum.trim(), path.trim_start_matches('/')))
+        .filter(|x| !x.0.is_empty() && !x.1.is_empty())
+        .collect()
+}
+
+/// Resolve the path inside the root, absolute symlinks are relative to the root
+/// (instead of the host) and `..` never leaves the root
+fn resolve_in_root(root: &Path, path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    let mut pending = path
+        .components()
+        .rev()
+        .map(|x| x.as_os_str().to_os_string())
+        .collect::<Vec<OsString>>();
+    let mut hops = 0;
+    while let Some(name) = pending.pop() {
+        match Path::new(&name).components().next() {
+            Some(Component::Normal(_)) => (),
+            Some(Component::ParentDir) => {
+                resolved.pop();
+                continue;
+            }
+            _ => continue,
+        }
+        let candidate = resolved.join(&name);
+        let target = match fs::read_link(root.join(&candidate)) {
+            Ok(target) if hops < MAX_SYMLINK_HOPS => target,
+            _ => {
+                resolved = candidate;
+                continue;
+            }
+        };
+        hops += 1;
+        if target.is_absolute() {
+            resolved = PathBuf::new();
+        }
+        pending.extend(
+            target
+                .components()
+                .rev()
+                .map(|x| x.as_os_str().to_os_string()),
+        );
+    }
+
+    root.join(resolved)
+}
+
+fn md5sum(path: &Path) -> Result<String> {
+    let mut hasher = Md5::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// The configuration files are expected to be modified (e.g. by `ciel config`)
+fn read_conffiles(info: &Path) -> Result<HashSet<String>> {
+    let mut conffiles = HashSet::new();
+    for entry in fs::read_dir(info)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|x| x == "conffiles") {
+            for line in fs::read_to_string(&path)?.lines() {
+                conffiles.insert(line.trim().trim_start_matches('/').to_string());
+            }
+        }
+    }
+
+    Ok(conffiles)
+}
+
+/// Check the files of all the installed packages in the root against their md5sums
+pub fn check_dist(root: &Path) -> Result<Vec<DistProblem>> {
+    let info = root.join(DPKG_INFO_DIR);
+    if !info.is_dir() {
+        bail!("No package manifests found in {}.", info.display());
+    }
+    let conffiles = read_conffiles(&info)?;
+    let mut manifests = Vec::new();
+    for entry in fs::read_dir(&info)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|x| x == "md5sums") {
+            manifests.push(path);
+        }
+    }
+    let problems = manifests
+        .par_iter()
+        .map(|manifest| -> Result<Vec<DistProblem>> {
+            // `<name>:<arch>.md5sums` for the multi-arch packages
+            let package = manifest
+                .file_stem()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let data = fs::read_to_string(manifest)?;
+            let mut problems = Vec::new();
+            for (sum, path) in parse_md5sums(&data) {
+                if conffiles.contains(path) {
+                    continue;
+                }
+                let kind = match md5sum(&resolve_in_root(root, Path::new(path))) {
+                    Ok(actual) if actual == sum => continue,
+                    Ok(_) => DistProblemKind::Modified,
+                    Err(_) => DistProblemKind::Missing,
+                };
+                problems.push(DistProblem {
+                    kind,
+                    package: package.clone(),
+                    path: Path::new("/").join(path),
+                });
+            }
+
+            Ok(problems)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut problems = problems.into_iter().flatten().collect::<Vec<_>>();
+    problems.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(problems)
+}
+
+/// Verify the base OS against the manifests of the installed packages,
+/// to detect partial extractions or files modified on the host
+pub fn verify_dist() -> Result<()> {
+    // the packed base OS is verified through its mount point
+    overlayfs::mount_dist_image()?;
+    let spinner = create_spinner("Verifying the base OS...", 200);
+    let problems = check_dist(Path::new(CIEL_DIST_DIR));
+    spinner.finish_and_clear();
+    let problems = problems?;
+    if json_output() {
+        print_json(&problems)?;
+    } else {
+        for problem in problems.iter() {
+            let kind = match problem.kind {
+                DistProblemKind::Missing => style("missing").red(),
+                DistProblemKind::Modified => style("modified").yellow(),
+            };
+            println!("{}\t{}\t{}", kind, problem.package, problem.path.display());
+        }
+    }
+    if !problems.is_empty() {
+        error!(
+            "{} file(s) in the base OS do not match the package manifests.",
+            problems.len()
+        );
+        bail!("The base OS is damaged, please reload it using `ciel load-os`.");
+    }
+    info!("Base OS verified, all the files match the package manifests.");
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_md5sums() {
+    let data = "d41d8cd98f00b204e9800998ecf8427e  usr/bin/true\n\nbad line\n0cc175b9c0f1b6a831c399e269772661  /etc/os-release\n";
+    assert_eq!(
+        parse_md5sums(data),
+        vec![
+            ("d41d8cd98f00b204e9800998ecf8427e", "usr/bin/true"),
+            ("0cc175b9c0f1b6a831c399e269772661", "etc/os-release"),
+        ]
+    );
+    let root = Path::new("/nonexistent-root");
+    assert_eq!(
+        resolve_in_root(root, Path::new("usr/../../etc/passwd")),
+        root.join("etc/passwd")
+    );
+}
@@ -0,0 +1,134 @@
+//! Watchdog aborting the builds that stopped producing output or exceeded their time limit
+
+use console::style;
+use nix::sys::signal::Signal;
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{backend::signal_commands, config::WatchdogConfig, error};
+
+/// Exit status of the builds aborted by the watchdog (same as `timeout(1)`)
+pub const WATCHDOG_STATUS: i32 = 124;
+/// Time given to the build to exit after SIGTERM, before it is killed
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(15);
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Watches the output of a command running in the container
+pub(super) struct Watchdog {
+    idle_timeout: Option<Duration>,
+    time_limit: Option<Duration>,
+    start: Instant,
+    /// Milliseconds since the start when the command last produced output
+    last_output: AtomicU64,
+    done: AtomicBool,
+    fired: AtomicBool,
+}
+
+impl Watchdog {
+    /// `None` if no limit is configured
+    pub fn new(config: &WatchdogConfig) -> Option<Self> {
+        let minutes = |x: u64| Duration::from_secs(x * 60);
+        if config.idle_timeout.is_none() && config.time_limit.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            idle_timeout: config.idle_timeout.map(minutes),
+            time_limit: config.time_limit.map(minutes),
+            start: Instant::now(),
+            last_output: AtomicU64::new(0),
+            done: AtomicBool::new(false),
+            fired: AtomicBool::new(false),
+        })
+    }
+
+    /// Record that the command produced some output
+    pub fn feed(&self) {
+        self.last_output
+            .store(self.start.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// The reason for aborting the command, if any limit is exceeded
+    fn check(&self, now: Duration) -> Option<String> {
+        let idle = now.saturating_sub(Duration::from_millis(
+            self.last_output.load(Ordering::Relaxed),
+        ));
+        match (self.idle_timeout, self.time_limit) {
+            (Some(timeout), _) if idle >= timeout => {
+                Some(format!("no output for {} minutes", timeout.as_secs() / 60))
+            }
+            (_, Some(limit)) if now >= limit => Some(format!(
+                "the time limit of {} minutes is exceeded",
+                limit.as_secs() / 60
+            )),
+            _ => None,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    /// Sleep for `duration`, returns early (`true`) if the command exited in the meantime
+    fn wait(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        while Instant::now() < deadline {
+            if self.is_done() {
+                return true;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        self.is_done()
+    }
+
+    /// Watch the command running in the container until `stop` is called,
+    /// the command is terminated (then killed) when a limit is exceeded
+    pub fn watch(&self, instance: &str, ns_name: &str) {
+        let reason = loop {
+            if self.wait(POLL_INTERVAL) {
+                return;
+            }
+            if let Some(reason) = self.check(self.start.elapsed()) {
+                break reason;
+            }
+        };
+        self.fired.store(true, Ordering::Relaxed);
+        error!("{}: {}, aborting the build...", instance, reason);
+        signal_commands(ns_name, Signal::SIGTERM);
+        if !self.wait(KILL_GRACE_PERIOD) {
+            signal_commands(ns_name, Signal::SIGKILL);
+        }
+    }
+
+    /// The command has exited, stop watching it
+    pub fn stop(&self) {
+        self.done.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the command was aborted by the watchdog
+    pub fn fired(&self) -> bool {
+        self.fired.load(Ordering::Relaxed)
+    }
+}
+
+#[test]
+fn test_watchdog_check() {
+    let watchdog = Watchdog::new(&WatchdogConfig {
+        idle_timeout: Some(10),
+        time_limit: Some(60),
+    })
+    .unwrap();
+    let minutes = |x: u64| Duration::from_secs(x * 60);
+    assert!(watchdog.check(minutes(5)).is_none());
+    assert!(watchdog.check(minutes(10)).is_some());
+    watchdog
+        .last_output
+        .store(minutes(55).as_millis() as u64, Ordering::Relaxed);
+    assert!(watchdog.check(minutes(59)).is_none());
+    assert!(watchdog.check(minutes(60)).is_some());
+    assert!(Watchdog::new(&WatchdogConfig::default()).is_none());
+}
@@ -0,0 +1,114 @@
+//! This module contains the container backends used for running the instances
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{ffi::OsStr, fs::File, path::Path};
+
+mod nspawn;
+mod podman;
+mod signals;
+mod vm;
+
+pub use self::nspawn::NspawnBackend;
+pub use self::podman::PodmanBackend;
+pub use self::signals::{exit_code, exit_with_status, forward_signals};
+pub(crate) use self::signals::{signal_commands, wait_command};
+pub use self::vm::VmBackend;
+
+/// Available container backends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    #[default]
+    Nspawn,
+    Podman,
+    /// Virtual machines (systemd-vmspawn), for the builds depending on the kernel
+    Vm,
+}
+
+/// Runtime state of a container as seen by the backend
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ContainerState {
+    pub started: bool,
+    pub running: bool,
+    pub booted: Option<bool>,
+    /// PID (on the host) of the init process in the container
+    pub leader: Option<u32>,
+}
+
+pub trait ContainerBackend {
+    /// Return the name of the backend, e.g. "nspawn"
+    fn name(&self) -> &'static str;
+    /// Spawn a new container using the (already mounted) root filesystem at `path`
+    /// mounts: list of (host path, container path) pairs to be bind-mounted into the container
+    fn spawn(
+        &self,
+        ns_name: &str,
+        path: &Path,
+        extra_options: &[String],
+        mounts: &[(String, &str)],
+    ) -> Result<()>;
+    /// Execute a command in the container and return its exit code
+    /// output: if specified, stdout and stderr of the command are redirected to this file
+    fn execute(&self, ns_name: &str, args: &[&OsStr], output: Option<&File>) -> Result<i32>;
+    /// Terminate the container (use graceful method if possible)
+    fn terminate(&self, ns_name: &str) -> Result<()>;
+    /// Query the state of the container
+    fn inspect(&self, ns_name: &str) -> Result<ContainerState>;
+    /// Save the state of the running container into the directory using CRIU,
+    /// the container is stopped afterwards
+    fn checkpoint(&self, ns_name: &str, directory: &Path) -> Result<()>;
+    /// Resume the container from the checkpoint saved in the directory
+    fn restore(&self, ns_name: &str, directory: &Path) -> Result<()>;
+}
+
+impl BackendKind {
+    pub const ALL: &'static [BackendKind] =
+        &[BackendKind::Nspawn, BackendKind::Podman, BackendKind::Vm];
+
+    /// Return the name of the backend as used in the configuration file
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackendKind::Nspawn => "nspawn",
+            BackendKind::Podman => "podman",
+            BackendKind::Vm => "vm",
+        }
+    }
+
+    /// Create a backend instance of this kind
+    pub fn create(&self) -> Box<dyn ContainerBackend> {
+        match self {
+            BackendKind::Nspawn => Box::new(NspawnBackend),
+            BackendKind::Podman => Box::new(PodmanBackend),
+            BackendKind::Vm => Box::new(VmBackend),
+        }
+    }
+}
+
+/// Get the container backend configured for the current workspace
+/// (falls back to systemd-nspawn if the workspace is not configured)
+pub fn get_backend() -> Box<dyn ContainerBackend> {
+    match crate::config::read_config() {
+        // rootless containers are spawned in unprivileged user namespaces by podman
+        Ok(c) if c.rootless => BackendKind::Podman.create(),
+        Ok(c) => c.backend.create(),
+        Err(_) => BackendKind::default().create(),
+    }
+}
+
+/// Instance name of the container (`<instance>-<hash>`)
+pub(crate) fn container_instance(ns_name: &str) -> &str {
+    ns_name.rsplit_once('-').map_or(ns_name, |x| x.0)
+}
+
+/// Get the container backend of the container, instances may override the backend of the workspace
+pub fn get_container_backend(ns_name: &str) -> Box<dyn ContainerBackend> {
+    let kind = crate::config::read_instance_overrides(container_instance(ns_name))
+        .ok()
+        .flatten()
+        .and_then(|x| x.backend);
+    match kind {
+        Some(kind) => kind.create(),
+        None => get_backend(),
+    }
+}
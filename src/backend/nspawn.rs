@@ -0,0 +1,387 @@
+//! systemd-nspawn (and systemd-machined) container backend
+
+use crate::dbus_machine1::ManagerProxyBlocking;
+use crate::dbus_machine1_machine::MachineProxyBlocking;
+use crate::{config, info, network, warn};
+use anyhow::{anyhow, Result};
+use console::style;
+use libsystemd_sys::bus::{sd_bus_flush_close_unref, sd_bus_open_system_machine};
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::{CString, OsStr},
+    fs::File,
+    mem::MaybeUninit,
+    process::Command,
+};
+use std::{fs, time::Duration};
+use std::{os::unix::ffi::OsStrExt, process::Child};
+use std::{path::Path, process::Stdio, thread::sleep};
+use zbus::blocking::Connection;
+
+use super::{wait_command, ContainerBackend, ContainerState};
+
+const DEFAULT_NSPAWN_OPTIONS: &[&str] = &[
+    "-qb",
+    "--capability=CAP_IPC_LOCK",
+    "--system-call-filter=swapcontext",
+];
+
+/// Options for dumping and restoring the whole container with CRIU
+const CRIU_OPTIONS: &[&str] = &[
+    "--manage-cgroups",
+    "--ext-unix-sk",
+    "--tcp-established",
+    "--file-locks",
+    "--link-remap",
+    "--ext-mount-map",
+    "auto",
+    "--enable-external-sharing",
+    "--enable-external-masters",
+];
+/// Information needed to register the restored container with systemd-machined
+const CHECKPOINT_METADATA: &str = "machine.json";
+
+pub struct NspawnBackend;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckpointMetadata {
+    /// PID 1 of the container (CRIU restores the processes with the same PIDs)
+    leader: u32,
+    root_directory: String,
+}
+
+fn try_open_container_bus(ns_name: &str) -> Result<()> {
+    // There are bunch of trickeries happening here
+    // First we initialize an empty pointer
+    let mut buf = MaybeUninit::uninit();
+    // Convert the ns_name to C-style `const char*` (NUL-terminated)
+    let ns_name = CString::new(ns_name)?;
+    // unsafe: these functions are from libsystemd, which involving FFI calls
+    unsafe {
+        // Try opening a connection to the container
+        if sd_bus_open_system_machine(buf.as_mut_ptr(), ns_name.as_ptr()) >= 0 {
+            // If successful, just close the connection and drop the pointer
+            sd_bus_flush_close_unref(buf.assume_init());
+            return Ok(());
+        }
+    }
+
+    Err(anyhow!("Could not open container bus"))
+}
+
+/// Wait for the container to become ready, `child` is the systemd-nspawn process (if spawned by us)
+fn wait_for_container(mut child: Option<&mut Child>, ns_name: &str, retry: usize) -> Result<()> {
+    for i in 0..retry {
+        if let Some(child) = child.as_mut() {
+            if let Some(status) = child.try_wait()? {
+                return Err(anyhow!("nspawn exited too early! (Status: {})", status));
+            }
+        }
+        // why this is used: because PTY spawning can happen before the systemd in the container
+        // is fully initialized. To spawn a new process in the container, we need the systemd
+        // in the container to be fully initialized and listening for connections.
+        // One way to resolve this issue is to test the connection to the container's systemd.
+        if try_open_container_bus(ns_name).is_ok() {
+            return Ok(());
+        }
+        // wait for a while, sleep time follows a natural-logarithm distribution
+        sleep(Duration::from_secs_f32(((i + 1) as f32).ln().ceil()));
+    }
+
+    Err(anyhow!("Timeout waiting for container {}", ns_name))
+}
+
+/// Setting up cross-namespace bind-mounts for the container using systemd
+fn setup_bind_mounts(ns_name: &str, mounts: &[(String, &str)]) -> Result<()> {
+    let conn = Connection::system()?;
+    let proxy = ManagerProxyBlocking::new(&conn)?;
+    for mount in mounts {
+        fs::create_dir_all(&mount.0)?;
+        let source_path = fs::canonicalize(&mount.0)?;
+        proxy.bind_mount_machine(
+            ns_name,
+            &source_path.to_string_lossy(),
+            mount.1,
+            false,
+            true,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn kill_container(proxy: &MachineProxyBlocking) -> Result<()> {
+    proxy.kill("all", libc::SIGKILL)?;
+    proxy.terminate()?;
+
+    Ok(())
+}
+
+fn execute_poweroff(ns_name: &str) -> Result<()> {
+    // TODO: maybe replace with systemd API cross-namespace call?
+    let exit_code = Command::new("systemd-run")
+        .args(["-M", ns_name, "-q", "--no-block", "--", "poweroff"])
+        .spawn()?
+        .wait()?
+        .code()
+        .unwrap_or(127);
+
+    if exit_code != 0 {
+        Err(anyhow!("Could not execute shutdown command: {}", exit_code))
+    } else {
+        Ok(())
+    }
+}
+
+fn wait_for_poweroff(proxy: &ManagerProxyBlocking, ns_name: &str) -> Result<()> {
+    for _ in 0..10 {
+        if proxy.get_machine(ns_name).is_err() {
+            // machine object no longer exists
+            return Ok(());
+        }
+        sleep(Duration::from_secs(1));
+    }
+
+    Err(anyhow!("shutdown failed"))
+}
+
+fn is_booted(proxy: &MachineProxyBlocking) -> Result<bool> {
+    let leader_pid = proxy.leader()?;
+    // let's inspect the cmdline of the PID 1 in the container
+    let f = std::fs::read(format!("/proc/{}/cmdline", leader_pid))?;
+    // take until the first null byte
+    let pos: usize = f
+        .iter()
+        .position(|c| *c == 0u8)
+        .ok_or_else(|| anyhow!("Unable to parse the process cmdline of PID 1 in the container"))?;
+    // ... well, of course it's a path
+    let path = Path::new(OsStr::from_bytes(&f[..pos]));
+    let exe_name = path.file_name();
+    // if PID 1 is systemd or init (System V init) then it should be a "booted" container
+    if let Some(exe_name) = exe_name {
+        return Ok(exe_name == "systemd" || exe_name == "init");
+    }
+
+    Ok(false)
+}
+
+/// Get the parent PID of the process
+fn parent_pid(pid: u32) -> Result<u32> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid))?;
+    // the process name may contain spaces and parentheses, skip to the last ')'
+    let ppid = stat
+        .rsplit_once(')')
+        .and_then(|(_, rest)| rest.split_whitespace().nth(1))
+        .and_then(|x| x.parse().ok())
+        .ok_or_else(|| anyhow!("Unable to parse the process status of PID {}", pid))?;
+
+    Ok(ppid)
+}
+
+fn run_criu(args: &[&str], directory: &Path) -> Result<()> {
+    let status = Command::new("criu")
+        .args(args)
+        .arg("--images-dir")
+        .arg(directory)
+        .args(CRIU_OPTIONS)
+        .arg("--log-file")
+        .arg("criu.log")
+        .status()?;
+    if !status.success() {
+        return Err(anyhow!(
+            "CRIU failed ({}), see {} for details",
+            status,
+            directory.join("criu.log").display()
+        ));
+    }
+
+    Ok(())
+}
+
+fn terminate_container(
+    proxy: &ManagerProxyBlocking,
+    machine_proxy: &MachineProxyBlocking,
+    ns_name: &str,
+) -> Result<()> {
+    let _ = machine_proxy.receive_state_changed();
+    if execute_poweroff(ns_name).is_ok() {
+        // Successfully passed poweroff command to the container, wait for it
+        if wait_for_poweroff(proxy, ns_name).is_ok() {
+            return Ok(());
+        }
+        // still did not poweroff?
+        warn!("Container did not respond to the poweroff command correctly...");
+        warn!("Killing the container by sending SIGKILL...");
+        // fall back to nuke
+    }
+
+    // violently kill everything inside the container
+    kill_container(machine_proxy)?;
+    machine_proxy.terminate().ok();
+    // status re-check, in the event of I/O problems, the container may still be running (stuck)
+    if wait_for_poweroff(proxy, ns_name).is_ok() {
+        return Ok(());
+    }
+
+    Err(anyhow!("Failed to kill the container! This may indicate a problem with your I/O, see dmesg or journalctl for more details."))
+}
+
+impl ContainerBackend for NspawnBackend {
+    fn name(&self) -> &'static str {
+        "nspawn"
+    }
+
+    fn spawn(
+        &self,
+        ns_name: &str,
+        path: &Path,
+        extra_options: &[String],
+        mounts: &[(String, &str)],
+    ) -> Result<()> {
+        let path = path
+            .to_str()
+            .ok_or_else(|| anyhow!("Path contains invalid Unicode characters."))?;
+        let mut child = Command::new("systemd-nspawn")
+            .args(DEFAULT_NSPAWN_OPTIONS)
+            .args(extra_options)
+            .args(["-D", path, "-M", ns_name, "--"])
+            .env("SYSTEMD_NSPAWN_TMPFS_TMP", "0")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        info!("{}: waiting for container to start...", ns_name);
+        wait_for_container(Some(&mut child), ns_name, 10)?;
+        info!("{}: setting up mounts...", ns_name);
+        if let Err(e) = setup_bind_mounts(ns_name, mounts) {
+            warn!("Failed to setup bind mounts: {:?}", e);
+        }
+
+        Ok(())
+    }
+
+    fn execute(&self, ns_name: &str, args: &[&OsStr], output: Option<&File>) -> Result<i32> {
+        let mut extra_options = vec!["--setenv=HOME=/root".to_string()];
+        if std::env::var("CIEL_STAGE2").is_ok() {
+            extra_options.push("--setenv=ABSTAGE2=1".to_string());
+        }
+        if let Ok(arch) = std::env::var("CIEL_CROSS") {
+            extra_options.push(format!("--setenv=ABHOST={}", arch));
+        }
+        if let Ok(tz) = std::env::var("CIEL_BUILD_TZ") {
+            extra_options.push(format!("--setenv=TZ={}", tz));
+        }
+        for (name, value) in network::proxy_environment() {
+            extra_options.push(format!("--setenv={}={}", name, value));
+        }
+        for (name, value) in config::maintainer_environment() {
+            extra_options.push(format!("--setenv={}={}", name, value));
+        }
+        // the signals received by ciel are forwarded to the unit
+        let unit = format!("ciel-exec-{:x}.service", rand::random::<u32>());
+        // TODO: maybe replace with systemd API cross-namespace call?
+        let mut command = Command::new("systemd-run");
+        command
+            .args(extra_options)
+            .args(["-M", ns_name, "-q"])
+            .arg(format!("--unit={}", unit));
+        if let Some(output) = output {
+            // no PTY when the output is captured
+            command
+                .arg("--pipe")
+                .stdin(Stdio::null())
+                .stdout(output.try_clone()?)
+                .stderr(output.try_clone()?);
+        } else {
+            command.arg("-t");
+        }
+        let child = command.arg("--").args(args).spawn()?;
+
+        wait_command(child, ns_name, Some(unit))
+    }
+
+    fn terminate(&self, ns_name: &str) -> Result<()> {
+        let conn = Connection::system()?;
+        let proxy = ManagerProxyBlocking::new(&conn)?;
+        let path = proxy.get_machine(ns_name)?;
+        let machine_proxy = MachineProxyBlocking::builder(&conn).path(&path)?.build()?;
+
+        terminate_container(&proxy, &machine_proxy, ns_name)
+    }
+
+    fn inspect(&self, ns_name: &str) -> Result<ContainerState> {
+        let conn = Connection::system()?;
+        let proxy = ManagerProxyBlocking::new(&conn)?;
+        let path = proxy.get_machine(ns_name);
+        if let Err(e) = path {
+            if let zbus::Error::MethodError(ref err_name, _, _) = e {
+                if err_name.as_ref() == "org.freedesktop.machine1.NoSuchMachine" {
+                    return Ok(ContainerState::default());
+                }
+            }
+            // For all other errors, just return the original error object
+            return Err(anyhow!("{}", e));
+        }
+        let path = path?;
+        let proxy = MachineProxyBlocking::builder(&conn).path(&path)?.build()?;
+        let state = proxy.state()?;
+        // Sometimes the system in the container is misconfigured, so we also accept "degraded" status as "running"
+        let running = state == "running" || state == "degraded";
+        let booted = is_booted(&proxy)?;
+
+        Ok(ContainerState {
+            started: true,
+            running,
+            booted: Some(booted),
+            leader: Some(proxy.leader()?),
+        })
+    }
+
+    fn checkpoint(&self, ns_name: &str, directory: &Path) -> Result<()> {
+        let conn = Connection::system()?;
+        let proxy = ManagerProxyBlocking::new(&conn)?;
+        let path = proxy.get_machine(ns_name)?;
+        let machine_proxy = MachineProxyBlocking::builder(&conn).path(&path)?.build()?;
+        let metadata = CheckpointMetadata {
+            leader: machine_proxy.leader()?,
+            root_directory: machine_proxy.root_directory()?,
+        };
+        // dump the systemd-nspawn process as well, so that the restored container is supervised
+        let nspawn_pid = parent_pid(metadata.leader)?;
+        fs::create_dir_all(directory)?;
+        fs::write(
+            directory.join(CHECKPOINT_METADATA),
+            serde_json::to_vec_pretty(&metadata)?,
+        )?;
+        run_criu(
+            &["dump", "--tree", &nspawn_pid.to_string(), "--shell-job"],
+            directory,
+        )?;
+        // machined notices the leader is gone, but give it some time
+        wait_for_poweroff(&proxy, ns_name).ok();
+
+        Ok(())
+    }
+
+    fn restore(&self, ns_name: &str, directory: &Path) -> Result<()> {
+        let metadata: CheckpointMetadata =
+            serde_json::from_slice(&fs::read(directory.join(CHECKPOINT_METADATA))?)?;
+        run_criu(&["restore", "--restore-detached", "--shell-job"], directory)?;
+        let conn = Connection::system()?;
+        let proxy = ManagerProxyBlocking::new(&conn)?;
+        if proxy.get_machine(ns_name).is_err() {
+            // the registration is lost together with the original scope unit
+            proxy.register_machine(
+                ns_name,
+                &[],
+                "ciel",
+                "container",
+                metadata.leader,
+                &metadata.root_directory,
+            )?;
+        }
+        wait_for_container(None, ns_name, 10)?;
+
+        Ok(())
+    }
+}
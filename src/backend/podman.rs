@@ -0,0 +1,297 @@
+//! Podman container backend, for hosts without systemd (e.g. CI containers)
+
+use crate::{config, info, network, warn};
+use anyhow::{anyhow, Result};
+use console::{style, user_attended};
+use std::ffi::OsStr;
+use std::fs::File;
+use std::process::{Command, Stdio};
+use std::{fs, path::Path, thread::sleep, time::Duration};
+
+use super::{wait_command, ContainerBackend, ContainerState};
+
+/// Name of the exported checkpoint in the checkpoint directory
+const CHECKPOINT_ARCHIVE: &str = "podman-checkpoint.tar.zst";
+const DEFAULT_PODMAN_OPTIONS: &[&str] = &[
+    "--rm",
+    "--detach",
+    "--systemd=always",
+    "--cap-add=IPC_LOCK",
+    "--security-opt=seccomp=unconfined",
+];
+
+pub struct PodmanBackend;
+
+/// Translate the systemd unit property (resource control) into the podman option
+fn property_option(property: &str) -> Option<String> {
+    let (key, value) = property.split_once('=')?;
+    match key {
+        "CPUQuota" => {
+            let percent: f32 = value.strip_suffix('%')?.parse().ok()?;
+            Some(format!("--cpus={}", percent / 100.0))
+        }
+        "MemoryMax" => Some(format!("--memory={}", value.to_lowercase())),
+        "AllowedCPUs" => Some(format!("--cpuset-cpus={}", value)),
+        "DeviceAllow" => Some(format!("--device={}", value.split_whitespace().next()?)),
+        // blkio weight has a smaller range (10-1000) than IOWeight (1-10000)
+        "IOWeight" => {
+            let weight: u32 = value.parse().ok()?;
+            Some(format!("--blkio-weight={}", (weight / 10).clamp(10, 1000)))
+        }
+        _ => None,
+    }
+}
+
+/// Query the status of the container, returns `None` if the container does not exist
+fn container_status(ns_name: &str) -> Result<Option<String>> {
+    let output = Command::new("podman")
+        .args([
+            "container",
+            "inspect",
+            "--format",
+            "{{.State.Status}}",
+            ns_name,
+        ])
+        .stderr(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
+/// Query the PID of the init process in the container
+fn container_pid(ns_name: &str) -> Option<u32> {
+    let output = Command::new("podman")
+        .args([
+            "container",
+            "inspect",
+            "--format",
+            "{{.State.Pid}}",
+            ns_name,
+        ])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()
+        .filter(|x| *x != 0)
+}
+
+fn wait_for_container(ns_name: &str, retry: usize) -> Result<()> {
+    for i in 0..retry {
+        match container_status(ns_name)?.as_deref() {
+            Some("running") => (),
+            Some(status) => {
+                return Err(anyhow!("podman exited too early! (Status: {})", status));
+            }
+            None => return Err(anyhow!("podman exited too early!")),
+        }
+        // wait for the systemd in the container to become ready, see the nspawn backend for why
+        let status = Command::new("podman")
+            .args(["exec", ns_name, "systemctl", "is-system-running", "--wait"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        // "degraded" (exit code 1) is also acceptable, the system is up nonetheless
+        if matches!(status.code(), Some(0) | Some(1)) {
+            return Ok(());
+        }
+        sleep(Duration::from_secs_f32(((i + 1) as f32).ln().ceil()));
+    }
+
+    Err(anyhow!("Timeout waiting for container {}", ns_name))
+}
+
+impl ContainerBackend for PodmanBackend {
+    fn name(&self) -> &'static str {
+        "podman"
+    }
+
+    fn spawn(
+        &self,
+        ns_name: &str,
+        path: &Path,
+        extra_options: &[String],
+        mounts: &[(String, &str)],
+    ) -> Result<()> {
+        let rootfs = fs::canonicalize(path)?;
+        let mut options = Vec::new();
+        for option in extra_options {
+            let property = option.strip_prefix("--property=").and_then(property_option);
+            let bind = option
+                .strip_prefix("--bind=")
+                .map(|x| (x, ""))
+                .or_else(|| option.strip_prefix("--bind-ro=").map(|x| (x, ":ro")));
+            match option.as_str() {
+                "--private-network" => options.push("--network=none".to_string()),
+                // podman containers have their own network already
+                "--network-veth" => (),
+                _ if option.starts_with("--port=") => {
+                    let mut fields = option["--port=".len()..].splitn(2, ':');
+                    let protocol = fields.next().unwrap_or("tcp");
+                    options.push(format!(
+                        "--publish={}/{}",
+                        fields.next().unwrap_or_default(),
+                        protocol
+                    ));
+                }
+                // the devices are passed with --device (from DeviceAllow)
+                _ if bind.is_some_and(|(x, _)| x.starts_with("/dev/")) => (),
+                _ if bind.is_some() => {
+                    let (paths, flags) = bind.unwrap_or_default();
+                    options.push(format!("--volume={}{}", paths, flags));
+                }
+                _ if property.is_some() => options.extend(property),
+                _ => {
+                    warn!("Ignoring nspawn option `{}` for podman backend.", option);
+                }
+            }
+        }
+        for mount in mounts {
+            fs::create_dir_all(&mount.0)?;
+            let source_path = fs::canonicalize(&mount.0)?;
+            options.push(format!("--volume={}:{}", source_path.display(), mount.1));
+        }
+        let status = Command::new("podman")
+            .arg("run")
+            .args(DEFAULT_PODMAN_OPTIONS)
+            .args(options)
+            .args(["--name", ns_name, "--hostname", ns_name, "--rootfs"])
+            .arg(rootfs)
+            .arg("/sbin/init")
+            .stdout(Stdio::null())
+            .status()?;
+        if !status.success() {
+            return Err(anyhow!("Failed to start podman container: {}", status));
+        }
+
+        info!("{}: waiting for container to start...", ns_name);
+        wait_for_container(ns_name, 10)?;
+
+        Ok(())
+    }
+
+    fn execute(&self, ns_name: &str, args: &[&OsStr], output: Option<&File>) -> Result<i32> {
+        let mut extra_options = vec!["--env=HOME=/root".to_string()];
+        if std::env::var("CIEL_STAGE2").is_ok() {
+            extra_options.push("--env=ABSTAGE2=1".to_string());
+        }
+        if let Ok(arch) = std::env::var("CIEL_CROSS") {
+            extra_options.push(format!("--env=ABHOST={}", arch));
+        }
+        if let Ok(tz) = std::env::var("CIEL_BUILD_TZ") {
+            extra_options.push(format!("--env=TZ={}", tz));
+        }
+        for (name, value) in network::proxy_environment() {
+            extra_options.push(format!("--env={}={}", name, value));
+        }
+        for (name, value) in config::maintainer_environment() {
+            extra_options.push(format!("--env={}={}", name, value));
+        }
+        let mut command = Command::new("podman");
+        command.arg("exec").args(extra_options);
+        if let Some(output) = output {
+            command
+                .stdin(Stdio::null())
+                .stdout(output.try_clone()?)
+                .stderr(output.try_clone()?);
+        } else {
+            command.arg("--interactive");
+            if user_attended() {
+                command.arg("--tty");
+            }
+        }
+        let child = command.args([ns_name]).args(args).spawn()?;
+
+        wait_command(child, ns_name, None)
+    }
+
+    fn terminate(&self, ns_name: &str) -> Result<()> {
+        let status = Command::new("podman")
+            .args(["stop", "--time=10", ns_name])
+            .stdout(Stdio::null())
+            .status()?;
+        if status.success() {
+            return Ok(());
+        }
+        warn!("Container did not respond to the stop command correctly...");
+        warn!("Killing the container by sending SIGKILL...");
+        let status = Command::new("podman")
+            .args(["rm", "--force", ns_name])
+            .stdout(Stdio::null())
+            .status()?;
+        if !status.success() {
+            return Err(anyhow!("Failed to kill the container: {}", status));
+        }
+
+        Ok(())
+    }
+
+    fn inspect(&self, ns_name: &str) -> Result<ContainerState> {
+        let status = container_status(ns_name)?;
+        if let Some(status) = status {
+            let running = status == "running";
+            // container is always started with an init system
+            return Ok(ContainerState {
+                started: true,
+                running,
+                booted: Some(running),
+                leader: container_pid(ns_name),
+            });
+        }
+
+        Ok(ContainerState::default())
+    }
+
+    fn checkpoint(&self, ns_name: &str, directory: &Path) -> Result<()> {
+        fs::create_dir_all(directory)?;
+        let status = Command::new("podman")
+            .args([
+                "container",
+                "checkpoint",
+                "--tcp-established",
+                "--compress=zstd",
+            ])
+            .arg(format!(
+                "--export={}",
+                directory.join(CHECKPOINT_ARCHIVE).display()
+            ))
+            .arg(ns_name)
+            .stdout(Stdio::null())
+            .status()?;
+        if !status.success() {
+            return Err(anyhow!("Failed to checkpoint the container: {}", status));
+        }
+
+        Ok(())
+    }
+
+    fn restore(&self, ns_name: &str, directory: &Path) -> Result<()> {
+        let status = Command::new("podman")
+            .args([
+                "container",
+                "restore",
+                "--tcp-established",
+                "--name",
+                ns_name,
+            ])
+            .arg(format!(
+                "--import={}",
+                directory.join(CHECKPOINT_ARCHIVE).display()
+            ))
+            .stdout(Stdio::null())
+            .status()?;
+        if !status.success() {
+            return Err(anyhow!("Failed to restore the container: {}", status));
+        }
+
+        Ok(())
+    }
+}
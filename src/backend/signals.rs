@@ -0,0 +1,153 @@
+//! Exit status and signal propagation between ciel and the commands running in the containers
+
+use anyhow::Result;
+use console::Term;
+use lazy_static::lazy_static;
+use nix::{
+    sys::signal::{kill, raise, signal, SigHandler, SigSet, Signal},
+    unistd::Pid,
+};
+use std::{
+    convert::TryFrom,
+    os::unix::process::ExitStatusExt,
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::Mutex,
+    thread,
+};
+
+/// Signals forwarded to the commands running in the containers
+const FORWARDED_SIGNALS: &[Signal] = &[Signal::SIGINT, Signal::SIGTERM];
+/// Signals re-raised by ciel when they terminated the command (the ones not dumping cores)
+const PROPAGATED_SIGNALS: &[Signal] = &[
+    Signal::SIGHUP,
+    Signal::SIGINT,
+    Signal::SIGKILL,
+    Signal::SIGPIPE,
+    Signal::SIGTERM,
+];
+
+/// A command being executed in a container
+#[derive(Debug, Clone)]
+struct RunningCommand {
+    /// PID of the local process (systemd-run, podman exec or ssh)
+    pid: u32,
+    ns_name: String,
+    /// Transient unit running the command in the container, if any
+    unit: Option<String>,
+}
+
+lazy_static! {
+    static ref RUNNING_COMMANDS: Mutex<Vec<RunningCommand>> = Mutex::new(Vec::new());
+}
+
+/// Exit code of the command, 128 + the signal number if it was terminated by a signal (like shells)
+pub fn exit_code(status: ExitStatus) -> i32 {
+    match (status.code(), status.signal()) {
+        (Some(code), _) => code,
+        (None, Some(signal)) => 128 + signal,
+        (None, None) => 127,
+    }
+}
+
+/// Wait for the command executed in the container, the signals received by ciel
+/// are forwarded to it in the meantime (to the whole unit if `unit` is specified)
+pub(crate) fn wait_command(mut child: Child, ns_name: &str, unit: Option<String>) -> Result<i32> {
+    let pid = child.id();
+    if let Ok(mut running) = RUNNING_COMMANDS.lock() {
+        running.push(RunningCommand {
+            pid,
+            ns_name: ns_name.to_string(),
+            unit,
+        });
+    }
+    let status = child.wait();
+    if let Ok(mut running) = RUNNING_COMMANDS.lock() {
+        running.retain(|x| x.pid != pid);
+    }
+
+    Ok(exit_code(status?))
+}
+
+fn forward_signal(command: &RunningCommand, signal: Signal) {
+    if let Some(unit) = &command.unit {
+        // all the processes started by the command, not only the process group
+        let status = Command::new("systemctl")
+            .args(["-M", &command.ns_name, "kill", "--signal", signal.as_str()])
+            .arg(unit)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        if status.is_ok_and(|x| x.success()) {
+            return;
+        }
+    }
+    kill(Pid::from_raw(command.pid as i32), signal).ok();
+}
+
+/// Send the signal to the commands running in the container (e.g. to abort a hung build)
+pub(crate) fn signal_commands(ns_name: &str, signal: Signal) {
+    let running = RUNNING_COMMANDS
+        .lock()
+        .map(|x| x.clone())
+        .unwrap_or_default();
+    for command in running.iter().filter(|x| x.ns_name == ns_name) {
+        forward_signal(command, signal);
+    }
+}
+
+/// Handle SIGINT and SIGTERM in a dedicated thread: they are forwarded to the commands running
+/// in the containers (which then stop by themselves), or terminate ciel if there is none.
+/// Must be called before spawning any other thread, so that all the threads block the signals.
+pub fn forward_signals() -> Result<()> {
+    let mut set = SigSet::empty();
+    for signal in FORWARDED_SIGNALS {
+        set.add(*signal);
+    }
+    // the spawned processes have their signal mask reset by the standard library
+    set.thread_block()?;
+    thread::spawn(move || loop {
+        let signal = match set.wait() {
+            Ok(signal) => signal,
+            Err(_) => continue,
+        };
+        let running = RUNNING_COMMANDS
+            .lock()
+            .map(|x| x.clone())
+            .unwrap_or_default();
+        if running.is_empty() {
+            // the progress bars hide the cursor
+            Term::stderr().show_cursor().ok();
+            exit_with_status(128 + signal as i32);
+        }
+        for command in running.iter() {
+            forward_signal(command, signal);
+        }
+    });
+
+    Ok(())
+}
+
+/// Exit with the exit code of the command run in the container. If the command was terminated by
+/// a signal (exit code 128 + signal number), ciel terminates itself using the same signal,
+/// so that the caller (e.g. a CI system) sees the same result.
+pub fn exit_with_status(status: i32) -> ! {
+    let propagated = Signal::try_from(status - 128)
+        .ok()
+        .filter(|x| PROPAGATED_SIGNALS.contains(x));
+    if let Some(sig) = propagated {
+        // the default action terminates the process
+        unsafe { signal(sig, SigHandler::SigDfl) }.ok();
+        let mut set = SigSet::empty();
+        set.add(sig);
+        set.thread_unblock().ok();
+        raise(sig).ok();
+    }
+
+    std::process::exit(status)
+}
+
+#[test]
+fn test_exit_code() {
+    assert_eq!(exit_code(ExitStatus::from_raw(3 << 8)), 3);
+    assert_eq!(exit_code(ExitStatus::from_raw(libc::SIGINT)), 130);
+}
@@ -0,0 +1,323 @@
+//! Virtual machine backend (systemd-vmspawn), for the builds that load kernel modules
+//! or need a different kernel. The mounted instance filesystem is shared as the root
+//! filesystem of the virtual machine using virtiofs, commands are executed over SSH (AF_VSOCK).
+
+use crate::dbus_machine1::ManagerProxyBlocking;
+use crate::dbus_machine1_machine::MachineProxyBlocking;
+use crate::{
+    common::{shell_quote, CIEL_DATA_DIR},
+    config::{self, VmConfig},
+    info, network, warn,
+};
+use adler32::adler32;
+use anyhow::{anyhow, bail, Result};
+use console::{style, user_attended};
+use std::{
+    ffi::OsStr,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread::sleep,
+    time::Duration,
+};
+use zbus::blocking::Connection;
+
+use super::{container_instance, wait_command, ContainerBackend, ContainerState};
+
+const VM_DATA_DIR: &str = "vm";
+const SSH_KEY_NAME: &str = "id_ed25519";
+const SSH_PROXY: &str = "/usr/lib/systemd/systemd-ssh-proxy";
+const SSH_OPTIONS: &[&str] = &[
+    "-o",
+    "StrictHostKeyChecking=no",
+    "-o",
+    "UserKnownHostsFile=/dev/null",
+    "-o",
+    "LogLevel=ERROR",
+    "-o",
+    "ProxyUseFdpass=yes",
+];
+
+pub struct VmBackend;
+
+/// Private key used to log into the virtual machines, generated on the first use
+fn ssh_key() -> Result<PathBuf> {
+    let directory = Path::new(CIEL_DATA_DIR).join(VM_DATA_DIR);
+    let key = directory.join(SSH_KEY_NAME);
+    if !key.is_file() {
+        fs::create_dir_all(&directory)?;
+        let status = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "ciel", "-f"])
+            .arg(&key)
+            .status()
+            .map_err(|e| anyhow!("Unable to execute ssh-keygen: {}", e))?;
+        if !status.success() {
+            bail!("Unable to generate the SSH key: {}", status);
+        }
+    }
+
+    Ok(fs::canonicalize(key)?)
+}
+
+/// AF_VSOCK address of the virtual machine, derived from the container name
+fn vsock_cid(ns_name: &str) -> Result<u32> {
+    // 0-2 are reserved, 0xffffffff is VMADDR_CID_ANY
+    Ok(3 + adler32(ns_name.as_bytes())? % (u32::MAX - 3))
+}
+
+/// ssh command logging into the virtual machine, the remote command is appended by the caller
+fn ssh_command(ns_name: &str, options: &[&str]) -> Result<Command> {
+    let mut command = Command::new("ssh");
+    command
+        .arg("-i")
+        .arg(ssh_key()?)
+        .args(SSH_OPTIONS)
+        .arg("-o")
+        .arg(format!("ProxyCommand={} %h %p", SSH_PROXY))
+        .args(options)
+        .arg(format!("root@vsock/{}", vsock_cid(ns_name)?));
+
+    Ok(command)
+}
+
+/// Kernel and initrd to boot, from the configuration or the `/boot` directory of the instance
+fn find_kernel(rootfs: &Path, vm: &VmConfig) -> Result<(PathBuf, Option<PathBuf>)> {
+    if let Some(kernel) = &vm.kernel {
+        return Ok((kernel.clone(), vm.initrd.clone()));
+    }
+    let boot = rootfs.join("boot");
+    let mut kernels = fs::read_dir(&boot)
+        .map(|x| {
+            x.filter_map(|x| x.ok())
+                .filter_map(|x| x.file_name().into_string().ok())
+                .filter_map(|x| x.strip_prefix("vmlinuz-").map(|x| x.to_string()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    kernels.sort();
+    let version = kernels.pop().ok_or_else(|| {
+        anyhow!("No kernel found in /boot of the instance, please install one or set `vm.kernel` in the instance configuration.")
+    })?;
+    let initrd = vm.initrd.clone().or_else(|| {
+        let initrd = boot.join(format!("initramfs-{}.img", version));
+        initrd.is_file().then_some(initrd)
+    });
+
+    Ok((boot.join(format!("vmlinuz-{}", version)), initrd))
+}
+
+/// Translate the nspawn options (resource control and networking) into the vmspawn options
+fn vmspawn_options(extra_options: &[String]) -> Vec<String> {
+    let mut options = Vec::new();
+    let mut network = true;
+    for option in extra_options {
+        let property = option
+            .strip_prefix("--property=")
+            .and_then(|x| x.split_once('='));
+        match (option.as_str(), property) {
+            ("--private-network", _) => network = false,
+            (x, _) if x.starts_with("--bind=") || x.starts_with("--bind-ro=") => {
+                options.push(option.clone())
+            }
+            (_, Some(("MemoryMax", value))) => options.push(format!("--ram={}", value)),
+            (_, Some(("CPUQuota", value))) => {
+                if let Some(Ok(percent)) = value.strip_suffix('%').map(|x| x.parse::<u32>()) {
+                    options.push(format!("--cpus={}", percent.div_ceil(100).max(1)));
+                }
+            }
+            _ => {
+                warn!("Ignoring nspawn option `{}` for VM backend.", option);
+            }
+        }
+    }
+    if network {
+        options.push("--network-user-mode".to_string());
+    }
+
+    options
+}
+
+fn wait_for_machine(child: &mut Child, ns_name: &str, retry: usize) -> Result<()> {
+    for i in 0..retry {
+        if let Some(status) = child.try_wait()? {
+            return Err(anyhow!("vmspawn exited too early! (Status: {})", status));
+        }
+        // the SSH server is started after the system has booted
+        let status = ssh_command(ns_name, &["-o", "ConnectTimeout=5"])?
+            .arg("true")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        if status.success() {
+            return Ok(());
+        }
+        sleep(Duration::from_secs_f32(((i + 1) as f32).ln().ceil()));
+    }
+
+    Err(anyhow!("Timeout waiting for virtual machine {}", ns_name))
+}
+
+impl ContainerBackend for VmBackend {
+    fn name(&self) -> &'static str {
+        "vm"
+    }
+
+    fn spawn(
+        &self,
+        ns_name: &str,
+        path: &Path,
+        extra_options: &[String],
+        mounts: &[(String, &str)],
+    ) -> Result<()> {
+        which::which("systemd-vmspawn")
+            .map_err(|_| anyhow!("systemd-vmspawn is required for the VM backend."))?;
+        let rootfs = fs::canonicalize(path)?;
+        let vm = config::read_instance_overrides(container_instance(ns_name))?
+            .and_then(|x| x.vm)
+            .unwrap_or_default();
+        let (kernel, initrd) = find_kernel(&rootfs, &vm)?;
+        let public_key = fs::read_to_string(ssh_key()?.with_extension("pub"))?;
+        let mut command = Command::new("systemd-vmspawn");
+        command
+            .args(["--quiet", "--register=yes"])
+            .arg(format!("--machine={}", ns_name))
+            .arg(format!("--vsock-cid={}", vsock_cid(ns_name)?))
+            .arg("--directory")
+            .arg(&rootfs)
+            .arg("--linux")
+            .arg(&kernel);
+        if let Some(initrd) = initrd {
+            command.arg("--initrd").arg(initrd);
+        }
+        // picked up by systemd-tmpfiles in the virtual machine
+        command.arg(format!(
+            "--set-credential=ssh.authorized_keys.root:{}",
+            public_key.trim()
+        ));
+        command.args(vmspawn_options(extra_options));
+        for mount in mounts {
+            fs::create_dir_all(&mount.0)?;
+            let source_path = fs::canonicalize(&mount.0)?;
+            command.arg(format!("--bind={}:{}", source_path.display(), mount.1));
+        }
+        if let Some(cmdline) = &vm.kernel_cmdline {
+            command.args(cmdline.split_whitespace());
+        }
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        info!("{}: waiting for virtual machine to boot...", ns_name);
+        wait_for_machine(&mut child, ns_name, 20)?;
+
+        Ok(())
+    }
+
+    fn execute(&self, ns_name: &str, args: &[&OsStr], output: Option<&File>) -> Result<i32> {
+        let mut environment = vec!["HOME=/root".to_string()];
+        if std::env::var("CIEL_STAGE2").is_ok() {
+            environment.push("ABSTAGE2=1".to_string());
+        }
+        if let Ok(arch) = std::env::var("CIEL_CROSS") {
+            environment.push(format!("ABHOST={}", arch));
+        }
+        if let Ok(tz) = std::env::var("CIEL_BUILD_TZ") {
+            environment.push(format!("TZ={}", tz));
+        }
+        for (name, value) in network::proxy_environment() {
+            environment.push(format!("{}={}", name, value));
+        }
+        for (name, value) in config::maintainer_environment() {
+            environment.push(format!("{}={}", name, value));
+        }
+        let mut command = if let Some(output) = output {
+            let mut command = ssh_command(ns_name, &["-T"])?;
+            command
+                .stdin(Stdio::null())
+                .stdout(output.try_clone()?)
+                .stderr(output.try_clone()?);
+            command
+        } else if user_attended() {
+            ssh_command(ns_name, &["-t"])?
+        } else {
+            ssh_command(ns_name, &[])?
+        };
+        command.arg("env");
+        command.args(environment.iter().map(|x| shell_quote(OsStr::new(x))));
+        command.args(args.iter().map(shell_quote));
+        let child = command.spawn()?;
+
+        wait_command(child, ns_name, None)
+    }
+
+    fn terminate(&self, ns_name: &str) -> Result<()> {
+        ssh_command(ns_name, &["-o", "ConnectTimeout=5"])?
+            .args(["systemctl", "poweroff"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .ok();
+        let conn = Connection::system()?;
+        let proxy = ManagerProxyBlocking::new(&conn)?;
+        for _ in 0..30 {
+            if proxy.get_machine(ns_name).is_err() {
+                return Ok(());
+            }
+            sleep(Duration::from_secs(1));
+        }
+        warn!("Virtual machine did not respond to the poweroff command correctly...");
+        warn!("Terminating the virtual machine...");
+        let path = proxy.get_machine(ns_name)?;
+        MachineProxyBlocking::builder(&conn)
+            .path(&path)?
+            .build()?
+            .terminate()?;
+
+        Ok(())
+    }
+
+    fn inspect(&self, ns_name: &str) -> Result<ContainerState> {
+        let conn = Connection::system()?;
+        let proxy = ManagerProxyBlocking::new(&conn)?;
+        let path = match proxy.get_machine(ns_name) {
+            Ok(path) => path,
+            Err(zbus::Error::MethodError(ref err_name, _, _))
+                if err_name.as_ref() == "org.freedesktop.machine1.NoSuchMachine" =>
+            {
+                return Ok(ContainerState::default());
+            }
+            Err(e) => return Err(anyhow!("{}", e)),
+        };
+        let proxy = MachineProxyBlocking::builder(&conn).path(&path)?.build()?;
+        let running = proxy.state()? == "running";
+
+        // the virtual machine always boots an init system
+        Ok(ContainerState {
+            started: true,
+            running,
+            booted: Some(running),
+            leader: Some(proxy.leader()?),
+        })
+    }
+
+    fn checkpoint(&self, _ns_name: &str, _directory: &Path) -> Result<()> {
+        bail!("Checkpointing is not supported by the VM backend.")
+    }
+
+    fn restore(&self, _ns_name: &str, _directory: &Path) -> Result<()> {
+        bail!("Checkpointing is not supported by the VM backend.")
+    }
+}
+
+#[test]
+fn test_vmspawn_options() {
+    let options = vmspawn_options(&[
+        "--property=MemoryMax=8G".to_string(),
+        "--property=CPUQuota=350%".to_string(),
+    ]);
+    assert_eq!(options, vec!["--ram=8G", "--cpus=4", "--network-user-mode"]);
+    assert!(vmspawn_options(&["--private-network".to_string()]).is_empty());
+}
@@ -0,0 +1,221 @@
+//! Non-interactive mode for CI runners (`ciel ci`): settings are read from the
+//! environment variables, logs are grouped and failures are reported as annotations
+
+use anyhow::Result;
+use ciel_core::{
+    actions::{self, BuildSettings, FailedBuild},
+    common::{self, find_arch_name, is_instance_exists, CIEL_DIST_DIR},
+    config, info,
+    logging::{self, CiFormat},
+    overlayfs,
+};
+use clap::ArgMatches;
+use console::style;
+use serde::Serialize;
+use std::{fs, path::Path};
+
+pub const EXIT_SUCCESS: i32 = 0;
+/// Unexpected errors (e.g. I/O errors)
+pub const EXIT_INTERNAL: i32 = 1;
+/// Invalid configuration or arguments
+pub const EXIT_CONFIG: i32 = 2;
+/// The workspace is not ready (e.g. no OS loaded)
+pub const EXIT_ENVIRONMENT: i32 = 3;
+/// At least one of the packages failed to build
+pub const EXIT_BUILD_FAILED: i32 = 4;
+/// The build environment could not be prepared (e.g. the OS update failed)
+pub const EXIT_PREPARE_FAILED: i32 = 5;
+
+const CODE_QUALITY_REPORT: &str = "gl-code-quality-report.json";
+const LOG_EXCERPT_LINES: usize = 20;
+
+/// Entry of the GitLab Code Quality report
+#[derive(Serialize)]
+struct CodeQualityIssue {
+    description: String,
+    check_name: &'static str,
+    fingerprint: String,
+    severity: &'static str,
+    location: CodeQualityLocation,
+}
+
+#[derive(Serialize)]
+struct CodeQualityLocation {
+    path: String,
+    lines: CodeQualityLines,
+}
+
+#[derive(Serialize)]
+struct CodeQualityLines {
+    begin: usize,
+}
+
+/// Report the failure and return the exit code
+fn fail(code: i32, title: &str, message: &str) -> Result<i32> {
+    logging::end_group();
+    logging::annotate_error(title, message, None);
+
+    Ok(code)
+}
+
+/// Last lines of the build log
+fn log_excerpt(path: &Path) -> String {
+    let content = fs::read(path).unwrap_or_default();
+    let content = String::from_utf8_lossy(&content);
+    let lines = content.lines().collect::<Vec<_>>();
+
+    lines[lines.len().saturating_sub(LOG_EXCERPT_LINES)..].join("\n")
+}
+
+/// Annotate the failed builds, also writing the GitLab Code Quality report if running in GitLab CI
+fn report_failures(failures: &[FailedBuild]) -> Result<()> {
+    let mut issues = Vec::new();
+    for failure in failures {
+        let recipe = actions::package_recipe_path(&failure.package);
+        let title = format!("Build failed: {}", failure.package);
+        let mut message = format!(
+            "{} failed to build in {} (exit status {})",
+            failure.package, failure.instance, failure.status
+        );
+        if let Some(log) = &failure.log {
+            message.push_str(&format!(
+                "\n{}\nFull log: {}",
+                log_excerpt(log),
+                log.display()
+            ));
+        }
+        logging::annotate_error(&title, &message, recipe.as_deref());
+        issues.push(CodeQualityIssue {
+            description: format!("{} (exit status {})", title, failure.status),
+            check_name: "ciel-build",
+            fingerprint: format!("ciel-build-{}", failure.package),
+            severity: "major",
+            location: CodeQualityLocation {
+                path: recipe.unwrap_or_else(|| failure.package.clone()),
+                lines: CodeQualityLines { begin: 1 },
+            },
+        });
+    }
+    if logging::ci_format() == CiFormat::GitLab {
+        fs::write(CODE_QUALITY_REPORT, serde_json::to_vec_pretty(&issues)?)?;
+        info!("Code Quality report written to {}", CODE_QUALITY_REPORT);
+    }
+
+    Ok(())
+}
+
+fn ci_build(args: &ArgMatches) -> Result<i32> {
+    logging::begin_group("Preparing the workspace");
+    if let Err(e) = config::read_config() {
+        return fail(
+            EXIT_CONFIG,
+            "Configuration error",
+            &format!("Unable to read the workspace configuration: {}", e),
+        );
+    }
+    let cross = match args.get_one::<String>("CROSS") {
+        Some(arch) => match find_arch_name(arch) {
+            Some(arch) => Some(arch),
+            None => {
+                return fail(
+                    EXIT_CONFIG,
+                    "Configuration error",
+                    &format!("Unsupported target architecture: {}", arch),
+                )
+            }
+        },
+        None => None,
+    };
+    let settings = BuildSettings {
+        offline: args.get_flag("OFFLINE"),
+        stage2: args.get_flag("STAGE2"),
+        html_report: args.get_flag("HTML_REPORT"),
+        with_deps: args.get_flag("WITH_DEPS"),
+        revdeps: args.get_flag("REVDEPS"),
+        isolate_network: args.get_flag("ISOLATE_NETWORK"),
+        retries: *args.get_one::<usize>("RETRIES").unwrap(),
+        strict: args.get_flag("STRICT"),
+        cross,
+    };
+    let instance = args.get_one::<String>("INSTANCE").unwrap();
+    if !is_instance_exists(instance) {
+        let os_loaded = fs::read_dir(CIEL_DIST_DIR).is_ok_and(|mut x| x.next().is_some())
+            || overlayfs::dist_image().is_some();
+        if !os_loaded {
+            return fail(
+                EXIT_ENVIRONMENT,
+                "Environment error",
+                "No OS is loaded in the workspace, please run `ciel load-os` first.",
+            );
+        }
+        if let Err(e) = actions::add_instance(instance) {
+            return fail(
+                EXIT_ENVIRONMENT,
+                "Environment error",
+                &format!("Unable to create the instance {}: {}", instance, e),
+            );
+        }
+    }
+    let changed_since = args.get_one::<String>("CHANGED_SINCE");
+    let packages = match changed_since {
+        Some(rev) => match actions::changed_packages(rev) {
+            Ok(packages) => packages,
+            Err(e) => return fail(EXIT_CONFIG, "Configuration error", &e.to_string()),
+        },
+        None => args
+            .get_many::<String>("PACKAGES")
+            .map(|x| x.filter(|x| !x.is_empty()).cloned().collect())
+            .unwrap_or_default(),
+    };
+    if packages.is_empty() {
+        if changed_since.is_some() {
+            logging::end_group();
+            info!("No packages changed, nothing to build.");
+            return Ok(EXIT_SUCCESS);
+        }
+        return fail(
+            EXIT_CONFIG,
+            "Configuration error",
+            "No packages specified, please specify them as arguments or in CIEL_PACKAGES.",
+        );
+    }
+    logging::end_group();
+
+    let status = actions::package_build(instance, packages.iter(), None, settings)?;
+    logging::end_group();
+    if status == 0 {
+        return Ok(EXIT_SUCCESS);
+    }
+    let failures = actions::failed_package_builds();
+    if failures.is_empty() {
+        return fail(
+            EXIT_PREPARE_FAILED,
+            "Preparation failed",
+            &format!(
+                "The build environment could not be prepared (exit status {})",
+                status
+            ),
+        );
+    }
+    report_failures(&failures)?;
+
+    Ok(EXIT_BUILD_FAILED)
+}
+
+/// Build the packages non-interactively, returns the exit code
+pub fn run_ci(args: &ArgMatches) -> i32 {
+    let format = match args.get_one::<String>("FORMAT").map(|x| x.as_str()) {
+        Some("github") => CiFormat::GitHub,
+        Some("gitlab") => CiFormat::GitLab,
+        Some("plain") => CiFormat::Plain,
+        _ => CiFormat::detect(),
+    };
+    logging::set_ci_format(format);
+    common::set_batch_mode(true);
+
+    ci_build(args).unwrap_or_else(|e| {
+        logging::end_group();
+        logging::annotate_error("Ciel error", &format!("{:?}", e), None);
+        EXIT_INTERNAL
+    })
+}
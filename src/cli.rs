@@ -1,9 +1,7 @@
 use anyhow::{anyhow, Result};
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
 use std::ffi::OsStr;
 
-pub const GIT_TREE_URL: &str = "https://github.com/AOSC-Dev/aosc-os-abbs.git";
-
 /// List all the available plugins/helper scripts
 fn list_helpers() -> Result<Vec<String>> {
     let exe_dir = std::env::current_exe().and_then(std::fs::canonicalize)?;
@@ -40,23 +38,45 @@ pub fn build_cli() -> Command {
         .about("CIEL! is a nspawn container manager")
         .allow_external_subcommands(true)
         .subcommand(Command::new("version").about("Display the version of CIEL!"))
+        .subcommand(
+            Command::new("archs")
+                .arg(Arg::new("REFRESH").long("refresh").action(clap::ArgAction::SetTrue).help("Fetch the list from the release manifest first"))
+                .about("List the supported architectures and their tarball variants")
+        )
         .subcommand(Command::new("init")
             .arg(Arg::new("upgrade").long("upgrade").action(clap::ArgAction::SetTrue).help("Upgrade Ciel workspace from an older version"))
+            .arg(Arg::new("dry_run").long("dry-run").short('n').action(clap::ArgAction::SetTrue).requires("upgrade").help("Only print the changes planned for the upgrade"))
             .about("Initialize the work directory"))
         .subcommand(
             Command::new("load-os")
-                .arg(Arg::new("url").help("URL or path to the tarball"))
+                .arg(Arg::new("url").help("URL or path to the tarball (rsync:// URLs or directories are synchronized incrementally)"))
                 .arg(Arg::new("arch").short('a').long("arch").help("Specify the target architecture for fetching OS tarball"))
+                .arg(Arg::new("source").short('s').long("source").value_parser(["http", "metalink", "torrent"]).default_value("http").help("Download the OS tarball using HTTP, metalink or BitTorrent (requires aria2c, falls back to HTTP)"))
+                .arg(Arg::new("STREAM").long("stream").action(clap::ArgAction::SetTrue).help("Extract the tarball while downloading it (no disk space needed for the tarball, not cached)"))
+                .arg(Arg::new("VERIFY").long("verify").action(clap::ArgAction::SetTrue).help("Verify the base OS against the package manifests after loading"))
                 .about("Unpack OS tarball or fetch the latest BuildKit from the repository"),
         )
+        .subcommand(
+            Command::new("verify-dist")
+                .about("Verify the base OS against the file lists and checksums of the installed packages")
+        )
         .subcommand(
             Command::new("update-os")
                 .arg(Arg::new("force_use_apt").long("force-use-apt").help("Use apt to update-os").action(clap::ArgAction::SetTrue))
+                .arg(Arg::new("INSTANCE").short('i').num_args(1).help("Update the specified instance only (using its configuration overrides)"))
+                .arg(Arg::new("DIST_ONLY").long("dist-only").action(clap::ArgAction::SetTrue).conflicts_with("INSTANCE").help("Update the base OS in place without creating and booting an instance (quicker)"))
                 .about("Update the OS in the container")
         )
+        .subcommand(
+            Command::new("pack-os")
+                .arg(Arg::new("FORMAT").long("format").short('f').value_parser(["erofs", "squashfs"]).default_value("erofs").help("Format of the image"))
+                .about("Pack the base OS into a compressed read-only image (run `ciel unpack-os` before updating it)")
+        )
+        .subcommand(Command::new("unpack-os").about("Unpack the base OS image back into a directory"))
         .subcommand(
             Command::new("load-tree")
-                .arg(Arg::new("url").default_value(GIT_TREE_URL).help("URL to the git repository"))
+                .arg(Arg::new("url").help("URL to the git repository (defaults to `tree-remote` in the workspace configuration or the AOSC OS ABBS tree)"))
+                .arg(Arg::new("branch").short('b').long("branch").num_args(1).help("Branch to switch to (defaults to the branch in the workspace configuration)"))
                 .about("Clone package tree from the link provided or AOSC OS ABBS main repository"),
         )
         .subcommand(
@@ -65,10 +85,24 @@ pub fn build_cli() -> Command {
                 .arg(Arg::new("branch").num_args(1).help("Branch to switch to"))
                 .about("Update the existing ABBS tree (fetch only) and optionally switch to a different branch")
         )
+        .subcommand(
+            Command::new("tree")
+                .arg_required_else_help(true)
+                .subcommands(vec![Command::new("clone").arg(Arg::new("URL").help("URL to the git repository (defaults to `tree-remote` in the workspace configuration or the AOSC OS ABBS tree)")).arg(Arg::new("BRANCH").short('b').long("branch").num_args(1).help("Branch to switch to (defaults to the branch in the workspace configuration)")).about("Clone the ABBS tree into the workspace"), Command::new("update").about("Fetch the remote and fast-forward the current branch of the tree"), Command::new("switch-branch").arg(Arg::new("BRANCH").required(true).help("Branch to switch to")).arg(Arg::new("REBASE").short('r').long("rebase").num_args(1).help("Rebase the branch onto the specified upstream (e.g. origin/stable)")).about("Fetch the remote and switch the tree to another (topic) branch, keeping the local changes")])
+                .about("Manage the ABBS tree checkout (TREE)")
+        )
+        .subcommand(
+            Command::new("topic")
+                .arg(Arg::new("NAME").conflicts_with("OFF").help("Topic to work on (also the name of its TREE branch and repository)"))
+                .arg(Arg::new("OFF").long("off").action(clap::ArgAction::SetTrue).help("Stop working on the topic, switching TREE back to the default branch"))
+                .about("Work on a topic: switch TREE to its branch, install the packages from its repository and build into OUTPUT-<topic>")
+        )
         .subcommand(
             Command::new("new")
             .arg(Arg::new("tarball").num_args(1).long("from-tarball").help("Create a new workspace from the specified tarball"))
             .arg(Arg::new("arch").num_args(1).short('a').long("arch").help("Create a new workspace for specified architecture"))
+            .arg(Arg::new("rootless").long("rootless").action(clap::ArgAction::SetTrue).help("Create a workspace that can be used without root privileges (requires fuse-overlayfs and podman)"))
+            .arg(Arg::new("instance").num_args(1).short('i').long("instance").help("Create the named instance after initialization (instead of asking)"))
             .about("Create a new CIEL workspace")
         )
         .subcommand(
@@ -79,8 +113,76 @@ pub fn build_cli() -> Command {
         .subcommand(
             Command::new("add")
                 .arg(Arg::new("INSTANCE").required(true))
+                .arg(Arg::new("TEMPLATE").long("template").short('t').num_args(1).help("Create the instance using the template (see `ciel template`)"))
+                .arg(Arg::new("TMPFS").long("tmpfs").action(clap::ArgAction::SetTrue).conflicts_with("TEMPLATE").help("Keep the changes on tmpfs, discarding them when the instance is stopped (overlay backend only)"))
+                .arg(Arg::new("TMPFS_SIZE").long("tmpfs-size").num_args(1).requires("TMPFS").help("Size limit of the tmpfs (e.g. 16G or 50%, defaults to half of the memory)"))
+                .arg(Arg::new("VM").long("vm").action(clap::ArgAction::SetTrue).conflicts_with_all(["TEMPLATE", "TMPFS"]).help("Run the instance in a virtual machine (systemd-vmspawn), for the builds depending on the kernel"))
+                .arg(Arg::new("KERNEL").long("kernel").num_args(1).requires("VM").help("Kernel image to boot (defaults to the newest kernel in /boot of the instance)"))
+                .arg(Arg::new("PROVISION").long("provision").action(clap::ArgAction::SetTrue).help("Install the build essentials (`provision.packages`) and apply the maintainer identity, the default with `provision.auto`"))
                 .about("Add a new instance"),
         )
+        .subcommand(
+            Command::new("template")
+                .arg_required_else_help(true)
+                .subcommands(vec![Command::new("save").arg(Arg::new("INSTANCE").required(true).help("Instance whose configuration is saved")).arg(Arg::new("NAME").required(true).help("Name of the template")).arg(Arg::new("PACKAGES").long("install").num_args(1..).help("Extra packages to install into the new instances")).arg(Arg::new("USERS").long("user").num_args(1..).help("Users to create in the new instances")).arg(Arg::new("SCRIPT").long("script").num_args(1).help("Shell script to run in the new instances after the packages are installed")).about("Save the instance configuration and the provisioning steps as a template"), Command::new("list").about("List the templates"), Command::new("remove").alias("rm").arg(Arg::new("NAME").required(true)).about("Remove the template")])
+                .about("Instance templates (stored in .ciel/templates)")
+        )
+        .subcommand(
+            Command::new("layer")
+                .arg_required_else_help(true)
+                .subcommands(vec![Command::new("create").arg(Arg::new("NAME").required(true).help("Name of the layer")).arg(Arg::new("INSTANCE").short('i').long("instance").num_args(1).help("Move the changes of this instance into the layer (the layer is attached to the instance)")).about("Create a shared layer (empty, or from the changes of an instance)"), Command::new("attach").arg(Arg::new("INSTANCE").required(true)).arg(Arg::new("NAME").required(true)).about("Stack the shared layer on the base OS of the instance"), Command::new("detach").arg(Arg::new("INSTANCE").required(true)).arg(Arg::new("NAME").required(true)).about("Remove the shared layer from the instance"), Command::new("list").alias("ls").about("List the shared layers"), Command::new("remove").alias("rm").arg(Arg::new("NAME").required(true)).about("Remove the shared layer (must not be attached to any instance)")])
+                .about("Shared read-only layers between the base OS and the instances (stored in .ciel/container/layers)")
+        )
+        .subcommand(
+            Command::new("port")
+                .arg_required_else_help(true)
+                .subcommands(vec![Command::new("add").arg(Arg::new("INSTANCE").required(true)).arg(Arg::new("PORT").required(true).help("[tcp|udp:]HOST[:CONTAINER], e.g. 8080:80")).about("Forward the port of the host to the instance"), Command::new("remove").alias("rm").arg(Arg::new("INSTANCE").required(true)).arg(Arg::new("PORT").required(true)).about("Stop forwarding the port of the host to the instance"), Command::new("list").alias("ls").about("List the forwarded ports")])
+                .about("Forward ports of the host to the services in the instances (the instances get their own network)")
+        )
+        .subcommand(
+            Command::new("remote")
+                .arg_required_else_help(true)
+                .subcommands(vec![Command::new("add").arg(Arg::new("NAME").required(true)).arg(Arg::new("HOST").required(true).help("SSH destination, e.g. user@host")).arg(Arg::new("PORT").short('p').long("port").num_args(1).value_parser(clap::value_parser!(u16)).help("SSH port")).arg(Arg::new("PATH").long("path").num_args(1).help("Workspace on the remote machine, relative to the home directory unless absolute (default: ciel)")).arg(Arg::new("INSTANCE").short('i').num_args(1).help("Instance to build in on the remote machine")).arg(Arg::new("ARCH").long("arch").num_args(1).help("Architecture of the base OS on the remote machine (for the build farm)")).about("Add a remote machine running ciel"), Command::new("remove").alias("rm").arg(Arg::new("NAME").required(true)).about("Remove the remote machine"), Command::new("list").alias("ls").about("List the remote machines")])
+                .about("Remote machines to build on over SSH (`ciel build --on`)")
+        )
+        .subcommand(
+            Command::new("farm")
+                .arg(Arg::new("BUILDERS").short('b').long("builders").num_args(1..).action(clap::ArgAction::Append).help("Remote machines to build on (default: all)"))
+                .arg(Arg::new("WITH_DEPS").long("with-deps").action(clap::ArgAction::SetTrue).help("Also build the in-tree dependencies of the packages that are not in the list"))
+                .arg(Arg::new("REVDEPS").long("revdeps").action(clap::ArgAction::SetTrue).help("Also rebuild the packages in the tree depending on the packages"))
+                .arg(Arg::new("PACKAGES").required(true).num_args(1..))
+                .about("Build the packages on the remote machines, for each of their architectures")
+        )
+        .subcommand(
+            Command::new("snapshot")
+                .arg_required_else_help(true)
+                .subcommands(vec![Command::new("create").arg(Arg::new("INSTANCE").required(true)).arg(Arg::new("NAME").required(true).help("Name of the snapshot")).about("Save the current state of the instance as a snapshot"), Command::new("restore").arg(Arg::new("INSTANCE").required(true)).arg(Arg::new("NAME").required(true).help("Name of the snapshot")).about("Restore the instance to the snapshot (the current state is discarded)"), Command::new("list").alias("ls").arg(Arg::new("INSTANCE").help("Only list the snapshots of this instance")).about("List the snapshots"), Command::new("remove").alias("rm").arg(Arg::new("INSTANCE").required(true)).arg(Arg::new("NAME").required(true)).about("Remove the snapshot")])
+                .about("Named snapshots of the instance state (stored in .ciel/data/snapshots)")
+        )
+        .subcommand(
+            Command::new("clone")
+                .arg(Arg::new("SOURCE").required(true).help("Instance to be cloned"))
+                .arg(Arg::new("TARGET").required(true).help("Name of the new instance"))
+                .about("Duplicate an instance into a new instance"),
+        )
+        .subcommand(
+            Command::new("export")
+                .arg(Arg::new("INSTANCE").required(true).help("Instance to be exported"))
+                .arg(Arg::new("FILE").required(true).help("Path to the bundle (.tar.zst)"))
+                .about("Export an instance as a portable bundle"),
+        )
+        .subcommand(
+            Command::new("oci")
+                .arg_required_else_help(true)
+                .subcommands(vec![Command::new("export").arg(Arg::new("INSTANCE").required(true).help("Instance to be exported")).arg(Arg::new("OUTPUT").short('o').long("output").num_args(1).help("Directory of the OCI image layout (default: oci-<instance>)")).arg(Arg::new("TAG").short('t').long("tag").num_args(1).default_value("latest").help("Tag of the image in the layout")).arg(Arg::new("PUSH").long("push").num_args(1).value_name("DESTINATION").help("Push the image with skopeo, e.g. docker://registry.example.org/aosc/buildenv:latest")).about("Export the instance as an OCI image (the base OS and the changes of the instance as the layers)")])
+                .about("OCI images of the instances, for Docker/Podman-based CI")
+        )
+        .subcommand(
+            Command::new("import")
+                .arg(Arg::new("FILE").required(true).help("Path to the bundle (.tar.zst)"))
+                .arg(Arg::new("NAME").short('n').long("name").num_args(1).help("Name of the imported instance (defaults to the original name)"))
+                .about("Import an instance from a bundle"),
+        )
         .subcommand(
             Command::new("del")
                 .alias("rm")
@@ -91,9 +193,17 @@ pub fn build_cli() -> Command {
             Command::new("shell")
                 .alias("sh")
                 .arg(instance_arg.clone().help("Instance to be used"))
+                .arg(Arg::new("WORKDIR").long("workdir").short('w').num_args(1).help("Working directory in the container (defaults to the home directory with --user)"))
+                .arg(Arg::new("USER").long("user").short('u').num_args(1).help("Run as the user instead of root (created if missing, using the UID of the same user on the host)"))
                 .arg(Arg::new("COMMANDS").required(false).num_args(1..))
                 .about("Start an interactive shell"),
         )
+        .subcommand(
+            Command::new("run-ephemeral")
+                .arg(Arg::new("OUTPUT").short('o').long("output").num_args(1).help("Write the output of the command to the file"))
+                .arg(Arg::new("COMMANDS").required(true).num_args(1..).last(true))
+                .about("Run the command in a temporary instance created from the base OS, removed afterwards"),
+        )
         .subcommand(
             Command::new("run")
                 .alias("exec")
@@ -105,28 +215,121 @@ pub fn build_cli() -> Command {
             Command::new("config")
                 .arg(instance_arg.clone().help("Instance to be configured"))
                 .arg(Arg::new("g").short('g').action(clap::ArgAction::SetTrue).conflicts_with("INSTANCE").help("Configure base system instead of an instance"))
+                .subcommand(Command::new("edit").about("Edit the workspace configuration file (.ciel/config.toml)"))
+                .args_conflicts_with_subcommands(true)
                 .about("Configure system and toolchain for building interactively"),
         )
         .subcommand(
             Command::new("commit")
                 .arg(instance_arg.clone().help("Instance to be committed"))
+                .arg(Arg::new("INTERACTIVE").long("interactive").short('p').action(ArgAction::SetTrue).help("Choose the changes to commit one by one (similar to `git add -p`)"))
+                .arg(Arg::new("PATHS").long("path").num_args(1..).help("Only commit the changes under these paths (in the instance)"))
+                .arg(Arg::new("LIST").long("list").short('l').action(ArgAction::SetTrue).conflicts_with_all(["INTERACTIVE", "PATHS"]).help("List the changes without committing them"))
                 .about("Commit changes onto the shared underlying OS"),
         )
+        .subcommand(
+            Command::new("diff")
+                .arg(instance_arg.clone())
+                .arg(Arg::new("CONTENT").long("content").short('c').action(ArgAction::SetTrue).help("Also show the content diffs of the text files"))
+                .arg(Arg::new("PATHS").num_args(1..).help("Only show the changes under these paths (in the instance)"))
+                .about("Show the files added, modified and deleted in the instance (relative to the base OS)"),
+        )
+        .subcommand(
+            Command::new("recover")
+                .about("Recover the workspace after a crash (stale mounts, dead containers, etc.)"),
+        )
         .subcommand(
             Command::new("doctor")
+                .arg(Arg::new("fix").long("fix").action(clap::ArgAction::SetTrue).help("Automatically fix the safe issues"))
                 .about("Diagnose problems (hopefully)"),
         )
         .subcommand(
             Command::new("build")
                 .arg(Arg::new("FETCH").short('g').action(clap::ArgAction::SetTrue).help("Fetch source packages only"))
                 .arg(Arg::new("OFFLINE").short('x').long("offline").action(clap::ArgAction::SetTrue).env("CIEL_OFFLINE").help("Disable network in the container during the build"))
+                .arg(Arg::new("ISOLATE_NETWORK").long("isolate-network").action(clap::ArgAction::SetTrue).env("CIEL_ISOLATE_NETWORK").help("Build without network access (except the local repository) to verify that nothing is downloaded during the build"))
                 .arg(instance_arg.clone().help("Instance to build in"))
+                .arg(Arg::new("CROSS").long("cross").num_args(1).env("CIEL_CROSS").help("Cross-compile the packages for the specified architecture using the AOSC OS cross toolchains"))
+                .arg(Arg::new("OUTPUT_BRANCH").long("branch").num_args(1).env("CIEL_OUTPUT_BRANCH").help("Put the packages into the named output repository (OUTPUT-<name>) and build against it"))
+                .arg(Arg::new("WITH_DEPS").long("with-deps").action(clap::ArgAction::SetTrue).help("Also build the in-tree dependencies of the packages that are not in the list"))
+                .arg(Arg::new("REVDEPS").long("revdeps").action(clap::ArgAction::SetTrue).help("Also rebuild the packages in the tree depending on the packages (e.g. after ABI-breaking updates)"))
+                .arg(Arg::new("CPUS").long("cpus").num_args(1).value_parser(clap::value_parser!(u32)).env("CIEL_CPUS").help("Limit the CPU time of the instance to the specified number of CPUs"))
+                .arg(Arg::new("MEMORY").long("memory").num_args(1).env("CIEL_MEMORY").help("Limit the memory usage of the instance (e.g. 16G)"))
+                .arg(Arg::new("HTML_REPORT").long("html-report").action(clap::ArgAction::SetTrue).env("CIEL_HTML_REPORT").help("Also generate an HTML build report in the output directory"))
                 .arg(Arg::new("STAGE2").long("stage2").short('2').action(clap::ArgAction::SetTrue).env("CIEL_STAGE2").help("Use stage 2 mode instead of the regular build mode"))
-                .arg(Arg::new("CONTINUE").conflicts_with("SELECT").short('c').long("resume").alias("continue").num_args(1).help("Continue from a Ciel checkpoint"))
+                .arg(Arg::new("RETRIES").long("retries").num_args(1).value_parser(clap::value_parser!(usize)).default_value("0").env("CIEL_RETRIES").help("Retry the builds failing because of network errors (e.g. when fetching the sources) up to the specified number of times"))
+                .arg(Arg::new("STRICT").long("strict").action(clap::ArgAction::SetTrue).env("CIEL_QA_STRICT").help("Fail the builds when the QA checks find problems in the produced packages"))
+                .arg(Arg::new("CONTINUE").conflicts_with("SELECT").short('c').long("resume").alias("continue").num_args(0..=1).help("Continue from a Ciel checkpoint, or from the last failure if no checkpoint is specified"))
                 .arg(Arg::new("SELECT").num_args(0..=1).long("stage-select").help("Select the starting point for a build"))
+                .arg(Arg::new("PARALLEL").short('j').long("parallel").num_args(1).value_parser(clap::value_parser!(usize)).conflicts_with_all(["CONTINUE", "SELECT", "FETCH"]).help("Build the packages concurrently using the specified number of instances"))
+                .arg(Arg::new("CHANGED_SINCE").long("changed-since").num_args(1).conflicts_with_all(["CONTINUE", "PACKAGES"]).help("Build the packages whose spec or defines changed in TREE since the Git revision"))
+                .arg(Arg::new("VERIFY_REPRODUCIBLE").long("verify-reproducible").num_args(1).value_name("PACKAGE").conflicts_with_all(["CONTINUE", "SELECT", "PARALLEL", "FETCH", "CHANGED_SINCE", "PACKAGES"]).help("Build the package twice (the second time in a fresh instance) and compare the produced packages"))
+                .arg(Arg::new("ARCHS").long("archs").num_args(1).value_delimiter(',').conflicts_with_all(["ON", "CROSS", "CONTINUE", "SELECT", "VERIFY_REPRODUCIBLE"]).help("Build the packages for each of the architectures (comma-separated), in a workspace of their own"))
+                .arg(Arg::new("ON").long("on").num_args(1).value_name("REMOTE").conflicts_with_all(["CONTINUE", "SELECT", "VERIFY_REPRODUCIBLE"]).help("Build on the remote machine (see `ciel remote`) and pull back the output and the logs"))
                 .arg(Arg::new("PACKAGES").conflicts_with("CONTINUE").num_args(1..))
                 .about("Build the packages using the specified instance"),
         )
+        .subcommand(
+            Command::new("fetch")
+                .arg(instance_arg.clone().help("Instance to fetch the sources other than tarballs (e.g. Git repositories) in"))
+                .arg(Arg::new("PACKAGES").required(true).num_args(1..))
+                .about("Download the sources of the packages into the shared source cache")
+        )
+        .subcommand(
+            Command::new("ci")
+                .arg(Arg::new("INSTANCE").short('i').num_args(1).env("CIEL_INST").default_value("ci").help("Instance to build in (created if missing)"))
+                .arg(Arg::new("FORMAT").long("format").num_args(1).value_parser(["auto", "github", "gitlab", "plain"]).default_value("auto").env("CIEL_CI_FORMAT").help("Log format (detected from the CI environment by default)"))
+                .arg(Arg::new("OFFLINE").long("offline").action(clap::ArgAction::SetTrue).env("CIEL_OFFLINE").help("Disable network in the container during the build"))
+                .arg(Arg::new("ISOLATE_NETWORK").long("isolate-network").action(clap::ArgAction::SetTrue).env("CIEL_ISOLATE_NETWORK").help("Build without network access (except the local repository)"))
+                .arg(Arg::new("CROSS").long("cross").num_args(1).env("CIEL_CROSS").help("Cross-compile the packages for the specified architecture"))
+                .arg(Arg::new("STAGE2").long("stage2").action(clap::ArgAction::SetTrue).env("CIEL_STAGE2").help("Use stage 2 mode instead of the regular build mode"))
+                .arg(Arg::new("RETRIES").long("retries").num_args(1).value_parser(clap::value_parser!(usize)).default_value("0").env("CIEL_RETRIES").help("Retry the builds failing because of network errors up to the specified number of times"))
+                .arg(Arg::new("STRICT").long("strict").action(clap::ArgAction::SetTrue).env("CIEL_QA_STRICT").help("Fail the builds when the QA checks find problems in the produced packages"))
+                .arg(Arg::new("HTML_REPORT").long("html-report").action(clap::ArgAction::SetTrue).env("CIEL_HTML_REPORT").help("Also generate an HTML build report in the output directory"))
+                .arg(Arg::new("WITH_DEPS").long("with-deps").action(clap::ArgAction::SetTrue).env("CIEL_WITH_DEPS").help("Also build the in-tree dependencies of the packages"))
+                .arg(Arg::new("REVDEPS").long("revdeps").action(clap::ArgAction::SetTrue).env("CIEL_REVDEPS").help("Also rebuild the packages depending on the packages"))
+                .arg(Arg::new("CHANGED_SINCE").long("changed-since").num_args(1).env("CIEL_CHANGED_SINCE").conflicts_with("PACKAGES").help("Build the packages changed in TREE since the Git revision"))
+                .arg(Arg::new("PACKAGES").num_args(1..).env("CIEL_PACKAGES").value_delimiter(' '))
+                .about("Build the packages non-interactively for CI runners (GitHub Actions, GitLab CI)")
+        )
+        .subcommand(
+            Command::new("du")
+                .about("Show the disk usage of the base OS, the instances, the output and the cache directories")
+        )
+        .subcommand(
+            Command::new("gc")
+                .arg(Arg::new("LOG_AGE").long("log-age").num_args(1).default_value("30").value_parser(clap::value_parser!(u64)).help("Remove the build logs older than this (in days)"))
+                .arg(Arg::new("DRY_RUN").long("dry-run").short('n').action(clap::ArgAction::SetTrue).help("Only show what would be removed"))
+                .about("Remove the data left behind in the workspace: incomplete imports, data of the removed instances, dangling machines, partial downloads, old logs and the unused package cache lock")
+        )
+        .subcommand(
+            Command::new("list-builds")
+                .arg(Arg::new("PACKAGE").help("Only list the builds of the package"))
+                .arg(Arg::new("LIMIT").short('n').long("limit").num_args(1).default_value("20").value_parser(clap::value_parser!(usize)).help("Number of builds to list"))
+                .about("List the recent builds with their duration, CPU time and peak memory usage")
+        )
+        .subcommand(
+            Command::new("status")
+                .arg(Arg::new("OUTDATED").long("outdated").action(clap::ArgAction::SetTrue).help("Only list the packages whose version in TREE is neither built nor in the remote repository"))
+                .about("Compare the package versions in TREE against the output repository and the remote repository")
+        )
+        .subcommand(
+            Command::new("search")
+                .arg(Arg::new("PATTERN").required(true).help("Text to search in the package names and descriptions (case-insensitive)"))
+                .arg(Arg::new("OUTDATED").long("outdated").action(clap::ArgAction::SetTrue).help("Only list the packages not built yet or older in the output repository"))
+                .about("Search the packages in TREE and show whether they are built in the output repository")
+        )
+        .subcommand(
+            Command::new("log")
+                .arg(Arg::new("PACKAGE").required(true).help("Package to show the build log of"))
+                .about("Show the most recent build log of the specified package")
+        )
+        .subcommand(
+            Command::new("logs")
+                .arg(Arg::new("INSTANCE").required(true).help("Instance to show the build output of"))
+                .arg(Arg::new("follow").short('f').long("follow").action(clap::ArgAction::SetTrue).help("Keep printing the output until the build finishes"))
+                .about("Show the output of the build in progress (or the last build) in the specified instance")
+        )
         .subcommand(
             Command::new("rollback")
                 .arg(instance_arg.clone().help("Instance to be rolled back"))
@@ -143,23 +346,71 @@ pub fn build_cli() -> Command {
                 .arg(instance_arg.clone().help("Instance to be stopped"))
                 .about("Shuts down an instance"),
         )
+        .subcommand(
+            Command::new("checkpoint")
+                .arg(instance_arg.clone().help("Instance to be checkpointed"))
+                .about("Save the state of a running instance and stop it (requires CRIU)"),
+        )
+        .subcommand(
+            Command::new("restore")
+                .arg(instance_arg.clone().help("Instance to be restored"))
+                .arg(Arg::new("DISCARD").long("discard").action(clap::ArgAction::SetTrue).help("Discard the checkpoint instead of restoring it"))
+                .about("Resume an instance from its checkpoint"),
+        )
         .subcommand(
             Command::new("mount")
-                .arg(instance_arg.help("Instance to be mounted"))
+                .arg(instance_arg.clone().help("Instance to be mounted"))
                 .about("Mount all or specified instance"),
         )
         .subcommand(
             Command::new("farewell")
                 .alias("harakiri")
+                .arg(Arg::new("yes").short('y').long("yes").action(clap::ArgAction::SetTrue).help("Delete the workspace without confirmation (required in batch mode)"))
                 .about("Remove everything related to CIEL!"),
         )
         .subcommand(
             Command::new("repo")
                 .arg_required_else_help(true)
-                .subcommands(vec![Command::new("refresh").about("Refresh the repository"), Command::new("init").arg(Arg::new("INSTANCE").required(true)).about("Initialize the repository"), Command::new("deinit").about("Uninitialize the repository")])
+                .arg(Arg::new("OUTPUT_BRANCH").long("branch").num_args(1).global(true).env("CIEL_OUTPUT_BRANCH").help("Use the named output repository (OUTPUT-<name>)"))
+                .subcommands(vec![Command::new("list").about("List the output repositories in the workspace"), Command::new("refresh").about("Refresh the repository"), Command::new("init").arg(Arg::new("INSTANCE").required(true)).about("Initialize the repository"), Command::new("deinit").about("Uninitialize the repository"), Command::new("sign").about("Sign the repository using the configured key"), Command::new("prune").arg(Arg::new("KEEP").long("keep").num_args(1).value_parser(clap::value_parser!(usize)).help("Number of versions to keep for each package")).arg(Arg::new("MAX_AGE").long("max-age").num_args(1).value_parser(clap::value_parser!(u64)).help("Remove the packages older than the specified number of days (the latest version is always kept)")).arg(Arg::new("DBG_LATEST_ONLY").long("dbg-latest-only").action(clap::ArgAction::SetTrue).help("Keep only the latest version of the debug symbol packages")).arg(Arg::new("DRY_RUN").long("dry-run").short('n').action(clap::ArgAction::SetTrue).help("Only list the packages to be removed")).about("Remove old packages according to the retention policy (defaults to the workspace configuration)"), Command::new("serve").arg(Arg::new("PORT").short('p').long("port").default_value("8000").value_parser(clap::value_parser!(u16)).help("Port to listen on")).arg(Arg::new("BIND").long("bind").default_value("0.0.0.0").help("Address to listen on")).about("Serve the repository over HTTP")])
                 .alias("localrepo")
                 .about("Local repository operations")
         )
+        .subcommand(
+            Command::new("cache")
+                .arg_required_else_help(true)
+                .subcommands(vec![Command::new("stats").arg(instance_arg.help("Instance to be used")).about("Show the statistics of the compiler cache"), Command::new("clear").about("Remove all the cached objects"), Command::new("prune").arg(Arg::new("KEEP").long("keep").num_args(1).default_value("1").value_parser(clap::value_parser!(usize)).help("Number of versions to keep for each package")).arg(Arg::new("MAX_AGE").long("max-age").num_args(1).value_parser(clap::value_parser!(u64)).help("Also remove the packages downloaded more than the specified number of days ago (the latest version is always kept)")).arg(Arg::new("ALL").long("all").short('a').action(clap::ArgAction::SetTrue).help("Remove all the cached packages")).arg(Arg::new("DRY_RUN").long("dry-run").short('n').action(clap::ArgAction::SetTrue).help("Only list the packages to be removed")).about("Remove old packages from the shared package cache (apt archives)")])
+                .about("Shared compiler cache (ccache) and package cache operations")
+        )
+        .subcommand(
+            Command::new("mirror")
+                .arg_required_else_help(true)
+                .subcommands(vec![Command::new("list").about("List the known mirrors"), Command::new("bench").about("Rank the mirrors by latency and throughput"), Command::new("set").arg(Arg::new("MIRROR").required(true).help("Name or URL of the mirror")).about("Use the specified mirror"), Command::new("auto").about("Use the fastest mirror")])
+                .about("AOSC OS mirror selection")
+        )
+        .subcommand(
+            Command::new("daemon")
+                .arg(Arg::new("LISTEN").short('l').long("listen").num_args(1).conflicts_with("SOCKET").help("Listen on the TCP address (e.g. 127.0.0.1:8080), requires a token"))
+                .arg(Arg::new("SOCKET").long("socket").num_args(1).default_value(".ciel/data/ciel.sock").help("Listen on the unix socket"))
+                .arg(Arg::new("TOKEN").long("token").num_args(1).env("CIEL_DAEMON_TOKEN").help("Token required in the Authorization header (Bearer)"))
+                .about("Serve the REST API for controlling Ciel remotely")
+        )
+        .subcommand(
+            Command::new("tui")
+                .about("Show the interactive dashboard of the instances and builds")
+        )
+        .subcommand(
+            Command::new("completion")
+                .arg(Arg::new("SHELL").required(true).value_parser(["bash", "zsh", "fish"]).help("Shell to generate the completion script for"))
+                .about("Print the shell completion script")
+        )
+        .subcommand(
+            Command::new("_complete")
+                .hide(true)
+                .arg(Arg::new("KIND").required(true).value_parser(["instances", "packages"]))
+                .arg(Arg::new("PREFIX").default_value(""))
+                .about("Print the completion candidates (used by the completion scripts)")
+        )
         .subcommand(
             Command::new("clean")
                 .about("Clean all the output directories and source cache directories")
@@ -185,11 +436,39 @@ pub fn build_cli() -> Command {
                     .default_value(".")
                     .num_args(1..)
                     .help("Set the CIEL! working directory"),
+                Arg::new("json")
+                    .long("json")
+                    .global(true)
+                    .action(clap::ArgAction::SetTrue)
+                    .help("Print machine-readable JSON to stdout (list, doctor, version, build, list-builds and du)"),
+                Arg::new("offline")
+                    .long("offline")
+                    .action(clap::ArgAction::SetTrue)
+                    .help("Forbid any network access (cached OS tarballs are still used)"),
+                Arg::new("insecure")
+                    .long("insecure")
+                    .action(clap::ArgAction::SetTrue)
+                    .help("Do not verify the signatures of the release manifest and the OS tarballs"),
+                Arg::new("limit_rate")
+                    .long("limit-rate")
+                    .value_name("RATE")
+                    .num_args(1)
+                    .help("Limit the download rate (bytes per second, e.g. 2M)"),
+                Arg::new("no_proxy")
+                    .long("no-proxy")
+                    .action(clap::ArgAction::SetTrue)
+                    .help("Ignore the proxy configuration and the proxy environment variables"),
+                Arg::new("no_space_check")
+                    .long("no-space-check")
+                    .env("CIEL_NO_SPACE_CHECK")
+                    .action(clap::ArgAction::SetTrue)
+                    .help("Do not check the free disk space before loading the OS, updating and building"),
                 Arg::new("batch")
                     .short('b')
                     .long("batch")
+                    .env("CIEL_BATCH")
                     .action(clap::ArgAction::SetTrue)
-                    .help("Batch mode, no input required"),
+                    .help("Batch mode, never prompt (fail if any input is required)"),
             ]
         )
 }
@@ -1,15 +1,22 @@
-use anyhow::{anyhow, Result};
-use console::user_attended;
+use crate::{info, warn};
+use anyhow::{anyhow, bail, Result};
+use console::{style, user_attended};
 use dialoguer::{theme::ColorfulTheme, FuzzySelect};
-use indicatif::ProgressBar;
+use indicatif::{HumanBytes, ProgressBar};
 use lazy_static::lazy_static;
 use sha2::{Digest, Sha256};
 use std::env::consts::ARCH;
 use std::fs::{self, File};
 use std::os::unix::prelude::MetadataExt;
 use std::{
-    io::{Read, Write},
+    collections::{BTreeMap, HashSet},
+    io::{BufRead, BufReader, IsTerminal, Read},
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{sync_channel, Receiver},
+        Mutex,
+    },
     time::Duration,
 };
 
@@ -20,15 +27,36 @@ pub const CIEL_MAINLINE_ARCHS: &[&str] = &[
     "mips64r6el",
     "riscv64",
     "loongarch64",
+    "loongarch64_nosimd",
     "loongson3",
 ];
+/// Other names of the architectures, e.g. the Debian ones
+const CIEL_ARCH_ALIASES: &[(&str, &str)] = &[("loong64", "loongarch64")];
+/// Path of the CPU information, for the features of the host CPU
+const CPUINFO_PATH: &str = "/proc/cpuinfo";
+/// Cache of the architectures published in the release manifest (relative to the user cache directory)
+const ARCH_REGISTRY_FILE: &str = "ciel/archs.json";
 pub const CIEL_RETRO_ARCHS: &[&str] = &["armv4", "armv6hf", "armv7hf", "i486", "m68k", "powerpc"];
 pub const CURRENT_CIEL_VERSION: usize = 3;
-const CURRENT_CIEL_VERSION_STR: &str = "3";
+const CIEL_VERSION_FILE: &str = ".ciel/version";
 pub const CIEL_DIST_DIR: &str = ".ciel/container/dist";
 pub const CIEL_INST_DIR: &str = ".ciel/container/instances";
+/// Shared read-only layers stacked on the base OS by the instances
+pub const CIEL_LAYERS_DIR: &str = ".ciel/container/layers";
 pub const CIEL_DATA_DIR: &str = ".ciel/data";
-const SKELETON_DIRS: &[&str] = &[CIEL_DIST_DIR, CIEL_INST_DIR, CIEL_DATA_DIR];
+pub(crate) const SKELETON_DIRS: &[&str] = &[CIEL_DIST_DIR, CIEL_INST_DIR, CIEL_DATA_DIR];
+
+/// The extracted OS takes about this many times the size of the compressed tarball
+const ROOTFS_EXPANSION_FACTOR: u64 = 4;
+/// Size of the decompressed chunks passed from the decompression thread to the unpacker
+const PIPELINE_CHUNK_SIZE: usize = 1024 * 1024;
+/// Number of decompressed chunks buffered between the decompression thread and the unpacker
+const PIPELINE_DEPTH: usize = 16;
+
+/// Never prompt the user (`--batch` or `CIEL_BATCH=1`)
+static BATCH_MODE: AtomicBool = AtomicBool::new(false);
+/// Skip the free space checks (`--no-space-check`)
+static NO_SPACE_CHECK: AtomicBool = AtomicBool::new(false);
 
 lazy_static! {
     static ref SPINNER_STYLE: indicatif::ProgressStyle =
@@ -36,6 +64,10 @@ lazy_static! {
             .tick_chars("⠋⠙⠸⠴⠦⠇ ")
             .template("{spinner:.green} {wide_msg}")
             .unwrap();
+    /// Architectures published in the release manifest with their tarball variants,
+    /// in addition to the built-in ones (the fallback when the manifest was never fetched)
+    static ref ARCH_REGISTRY: Mutex<BTreeMap<&'static str, Vec<String>>> =
+        Mutex::new(read_arch_registry());
 }
 
 #[macro_export]
@@ -58,9 +90,112 @@ pub fn create_spinner(msg: &'static str, tick_rate: u64) -> indicatif::ProgressB
     spinner
 }
 
+/// Quote the argument for a remote shell (in a virtual machine or over SSH)
+pub fn shell_quote<S: AsRef<std::ffi::OsStr>>(arg: S) -> String {
+    format!(
+        "'{}'",
+        arg.as_ref().to_string_lossy().replace('\'', "'\\''")
+    )
+}
+
 #[inline]
 pub fn check_arch_name(arch: &str) -> bool {
-    CIEL_MAINLINE_ARCHS.contains(&arch) || CIEL_RETRO_ARCHS.contains(&arch)
+    find_arch_name(arch).is_some()
+}
+
+/// Look up the architecture name in the list of supported architectures (built in or from the
+/// release manifest), the aliases (e.g. `loong64`) are resolved to the AOSC OS names
+#[inline]
+pub fn find_arch_name(arch: &str) -> Option<&'static str> {
+    let arch = CIEL_ARCH_ALIASES
+        .iter()
+        .find(|x| x.0 == arch)
+        .map_or(arch, |x| x.1);
+    CIEL_MAINLINE_ARCHS
+        .iter()
+        .chain(CIEL_RETRO_ARCHS.iter())
+        .find(|x| **x == arch)
+        .copied()
+        .or_else(|| {
+            ARCH_REGISTRY
+                .lock()
+                .ok()?
+                .keys()
+                .find(|x| **x == arch)
+                .copied()
+        })
+}
+
+fn arch_registry_path() -> Option<PathBuf> {
+    crate::network::user_cache_dir().map(|x| x.join(ARCH_REGISTRY_FILE))
+}
+
+/// The names live as long as the process, so that they can be used like the built-in ones
+fn intern_arch_name(name: String) -> &'static str {
+    Box::leak(name.into_boxed_str())
+}
+
+fn read_arch_registry() -> BTreeMap<&'static str, Vec<String>> {
+    arch_registry_path()
+        .and_then(|x| fs::read(x).ok())
+        .and_then(|x| serde_json::from_slice::<BTreeMap<String, Vec<String>>>(&x).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, variants)| (intern_arch_name(name), variants))
+        .collect()
+}
+
+/// Replace the architecture registry with the architectures from the release manifest
+pub fn register_archs(archs: BTreeMap<String, Vec<String>>) -> Result<()> {
+    if let Some(path) = arch_registry_path() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(&archs)?)?;
+    }
+    let mut registry = ARCH_REGISTRY
+        .lock()
+        .map_err(|_| anyhow!("Architecture registry lock poisoned"))?;
+    // only the new names are interned, the known ones are reused
+    let known = std::mem::take(&mut *registry);
+    for (name, variants) in archs {
+        match known.keys().find(|x| **x == name) {
+            Some(known) => registry.insert(*known, variants),
+            None => registry.insert(intern_arch_name(name), variants),
+        };
+    }
+
+    Ok(())
+}
+
+/// All the supported architectures with their tarball variants
+/// (empty for the built-in architectures missing from the release manifest)
+pub fn known_archs() -> BTreeMap<&'static str, Vec<String>> {
+    let mut archs = ARCH_REGISTRY.lock().map(|x| x.clone()).unwrap_or_default();
+    for arch in CIEL_MAINLINE_ARCHS.iter().chain(CIEL_RETRO_ARCHS.iter()) {
+        archs.entry(*arch).or_default();
+    }
+
+    archs
+}
+
+/// Whether the CPU features listed in /proc/cpuinfo include the feature
+fn has_cpu_feature(cpuinfo: &str, feature: &str) -> bool {
+    cpuinfo
+        .lines()
+        .filter(|x| x.starts_with("Features") || x.starts_with("flags"))
+        .filter_map(|x| x.split_once(':'))
+        .any(|(_, features)| features.split_whitespace().any(|x| x == feature))
+}
+
+/// The LoongArch port of the host: the CPUs without LSX (e.g. Loongson 2K1000LA)
+/// need the port built without SIMD
+fn loongarch_arch_name(cpuinfo: &str) -> &'static str {
+    if has_cpu_feature(cpuinfo, "lsx") {
+        "loongarch64"
+    } else {
+        "loongarch64_nosimd"
+    }
 }
 
 /// AOSC OS specific architecture mapping table
@@ -74,7 +209,9 @@ pub fn get_host_arch_name() -> Option<&'static str> {
         "aarch64" => Some("arm64"),
         "mips64" => Some("loongson3"),
         "riscv64" => Some("riscv64"),
-        "loongarch64" => Some("loongarch64"),
+        "loongarch64" => Some(loongarch_arch_name(
+            &fs::read_to_string(CPUINFO_PATH).unwrap_or_default(),
+        )),
         _ => None,
     }
 
@@ -101,17 +238,171 @@ pub fn sha256sum<R: Read>(mut reader: R) -> Result<String> {
     Ok(format!("{:x}", hasher.finalize()))
 }
 
-/// Extract the given .tar.xz stream and preserve all the file attributes
-pub fn extract_tar_xz<R: Read>(reader: R, path: &Path) -> Result<()> {
-    let decompress = xz2::read::XzDecoder::new(reader);
-    let mut tar_processor = tar::Archive::new(decompress);
+/// Compression formats of the system tarballs
+#[derive(Debug, PartialEq, Eq)]
+enum TarballCompression {
+    Xz,
+    Zstd,
+    Gzip,
+    None,
+}
+
+/// Determine the compression format using the magic bytes at the beginning of the stream
+fn detect_tarball_compression(magic: &[u8]) -> TarballCompression {
+    if magic.starts_with(b"\xfd7zXZ\x00") {
+        TarballCompression::Xz
+    } else if magic.starts_with(b"\x28\xb5\x2f\xfd") {
+        TarballCompression::Zstd
+    } else if magic.starts_with(b"\x1f\x8b") {
+        TarballCompression::Gzip
+    } else {
+        TarballCompression::None
+    }
+}
+
+/// Extract the given tar stream and preserve all the file attributes
+fn unpack_tarball<R: Read>(reader: R, path: &Path) -> Result<()> {
+    let mut tar_processor = tar::Archive::new(reader);
     tar_processor.set_unpack_xattrs(true);
     tar_processor.set_preserve_permissions(true);
-    tar_processor.unpack(path)?;
+    // unprivileged users (rootless mode) can't create device nodes
+    unpack_archive(&mut tar_processor, path, !nix::unistd::geteuid().is_root())
+}
+
+/// Unpack the tarball, skipping the device nodes if `skip_devices` is set. As in
+/// `Archive::unpack`, the directories are unpacked last so that their permissions
+/// don't get in the way.
+fn unpack_archive<R: Read>(
+    archive: &mut tar::Archive<R>,
+    path: &Path,
+    skip_devices: bool,
+) -> Result<()> {
+    if !skip_devices {
+        archive.unpack(path)?;
+        return Ok(());
+    }
+    fs::create_dir_all(path)?;
+    let mut directories = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        match entry.header().entry_type() {
+            tar::EntryType::Char | tar::EntryType::Block => continue,
+            tar::EntryType::Directory => directories.push(entry),
+            _ => {
+                entry.unpack_in(path)?;
+            }
+        }
+    }
+    for mut directory in directories {
+        directory.unpack_in(path)?;
+    }
 
     Ok(())
 }
 
+/// Reads the decompressed chunks sent by the decompression thread
+struct ChunkReader<'a> {
+    receiver: Receiver<std::io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    offset: usize,
+    unpacked: u64,
+    progress: Option<&'a ProgressBar>,
+}
+
+impl Read for ChunkReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.offset >= self.chunk.len() {
+            self.chunk = match self.receiver.recv() {
+                Ok(chunk) => chunk?,
+                // the decompression thread has reached the end of the stream
+                Err(_) => return Ok(0),
+            };
+            self.offset = 0;
+            self.unpacked += self.chunk.len() as u64;
+            if let Some(pb) = self.progress {
+                let elapsed = pb.elapsed().as_secs_f64().max(1.0);
+                pb.set_message(format!(
+                    "{}/s unpacked",
+                    HumanBytes((self.unpacked as f64 / elapsed) as u64)
+                ));
+            }
+        }
+        let size = buf.len().min(self.chunk.len() - self.offset);
+        buf[..size].copy_from_slice(&self.chunk[self.offset..self.offset + size]);
+        self.offset += size;
+
+        Ok(size)
+    }
+}
+
+/// Decompress in a separate thread, so that the decompression (CPU bound)
+/// and the unpacking (I/O bound) run at the same time
+fn unpack_tarball_pipelined<R: Read + Send>(
+    mut decoder: R,
+    path: &Path,
+    progress: Option<&ProgressBar>,
+) -> Result<()> {
+    let (sender, receiver) = sync_channel(PIPELINE_DEPTH);
+    std::thread::scope(|s| {
+        s.spawn(move || loop {
+            let mut chunk = vec![0u8; PIPELINE_CHUNK_SIZE];
+            let result = match decoder.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(size) => {
+                    chunk.truncate(size);
+                    Ok(chunk)
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(e),
+            };
+            let failed = result.is_err();
+            // the receiver is gone if the unpacking failed
+            if sender.send(result).is_err() || failed {
+                break;
+            }
+        });
+        unpack_tarball(
+            ChunkReader {
+                receiver,
+                chunk: Vec::new(),
+                offset: 0,
+                unpacked: 0,
+                progress,
+            },
+            path,
+        )
+    })
+}
+
+/// Extract the given tarball stream (.tar.xz, .tar.zst, .tar.gz or uncompressed .tar)
+/// and preserve all the file attributes. The compression format is detected automatically.
+pub fn extract_tarball<R: Read + Send>(reader: R, path: &Path) -> Result<()> {
+    extract_tarball_with_progress(reader, path, None)
+}
+
+/// Same as `extract_tarball`, showing the unpacking throughput in the progress bar message
+pub fn extract_tarball_with_progress<R: Read + Send>(
+    reader: R,
+    path: &Path,
+    progress: Option<&ProgressBar>,
+) -> Result<()> {
+    let mut reader = BufReader::new(reader);
+    match detect_tarball_compression(reader.fill_buf()?) {
+        TarballCompression::Xz => {
+            unpack_tarball_pipelined(xz2::read::XzDecoder::new(reader), path, progress)
+        }
+        TarballCompression::Zstd => unpack_tarball_pipelined(
+            zstd::stream::read::Decoder::with_buffer(reader)?,
+            path,
+            progress,
+        ),
+        TarballCompression::Gzip => {
+            unpack_tarball_pipelined(flate2::bufread::GzDecoder::new(reader), path, progress)
+        }
+        TarballCompression::None => unpack_tarball(reader, path),
+    }
+}
+
 /// Extract the given .squashfs
 pub fn extract_squashfs(path: &Path, dist_dir: &Path, pb: &ProgressBar, total: u64) -> Result<()> {
     use std::sync::atomic::AtomicBool;
@@ -129,19 +420,90 @@ pub fn extract_squashfs(path: &Path, dist_dir: &Path, pb: &ProgressBar, total: u
     Ok(())
 }
 
+/// Computes the SHA-256 checksum of the data passing through
+pub struct Sha256Reader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Sha256Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// Read the remaining data (e.g. the padding after the end of the tarball), then return the checksum
+    pub fn finish(mut self) -> Result<String> {
+        std::io::copy(&mut self, &mut std::io::sink())?;
+
+        Ok(format!("{:x}", self.hasher.finalize()))
+    }
+}
+
+impl<R: Read> Read for Sha256Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let size = self.inner.read(buf)?;
+        self.hasher.update(&buf[..size]);
+
+        Ok(size)
+    }
+}
+
 pub fn extract_system_rootfs(path: &Path, total: u64, use_tarball: bool) -> Result<()> {
     let f = File::open(path)?;
+    extract_rootfs_with(total, |dist_dir, progress_bar| {
+        if use_tarball {
+            extract_tarball_with_progress(progress_bar.wrap_read(f), dist_dir, Some(progress_bar))
+        } else {
+            extract_squashfs(path, dist_dir, progress_bar, total)
+        }
+    })
+}
+
+/// Extract the tarball while it is being downloaded, `total` is the size of the download.
+/// Returns the SHA-256 checksum of the whole stream.
+pub fn extract_system_rootfs_stream<R: Read + Send>(reader: R, total: u64) -> Result<String> {
+    let mut reader = Sha256Reader::new(reader);
+    extract_rootfs_with(total, |dist_dir, progress_bar| {
+        extract_tarball_with_progress(
+            progress_bar.wrap_read(&mut reader),
+            dist_dir,
+            Some(progress_bar),
+        )
+    })?;
+
+    reader.finish()
+}
+
+/// Replace the base OS with the one extracted by `extract`
+fn extract_rootfs_with<F: FnOnce(&Path, &ProgressBar) -> Result<()>>(
+    total: u64,
+    extract: F,
+) -> Result<()> {
     let progress_bar = indicatif::ProgressBar::new(total);
 
     progress_bar.set_style(
         indicatif::ProgressStyle::default_bar()
-            .template(make_progress_bar!("Extracting rootfs ..."))
+            .template(make_progress_bar!("Extracting rootfs ... {msg}"))
             .unwrap(),
     );
 
     progress_bar.set_draw_target(indicatif::ProgressDrawTarget::stderr_with_hz(5));
 
     let dist_dir = PathBuf::from(CIEL_DIST_DIR);
+    let required = total.saturating_mul(ROOTFS_EXPANSION_FACTOR);
+    if available_space().is_ok_and(|x| x < required) {
+        // the space used by the old base OS can be reused
+        let reclaimable = directory_size(&dist_dir, &mut HashSet::new());
+        ensure_free_space(
+            required.saturating_sub(reclaimable),
+            "extracting the base OS",
+        )?;
+    }
+    // the new base OS replaces the packed one
+    crate::overlayfs::remove_dist_image()?;
     if dist_dir.exists() {
         fs::remove_dir_all(&dist_dir).ok();
         fs::create_dir_all(&dist_dir)?;
@@ -157,11 +519,7 @@ pub fn extract_system_rootfs(path: &Path, total: u64, use_tarball: bool) -> Resu
         }
     }
 
-    let res = if use_tarball {
-        extract_tar_xz(progress_bar.wrap_read(f), &dist_dir)
-    } else {
-        extract_squashfs(path, &dist_dir, &progress_bar, total)
-    };
+    let res = extract(&dist_dir, &progress_bar);
 
     if !in_systemd_nspawn {
         res?
@@ -169,15 +527,39 @@ pub fn extract_system_rootfs(path: &Path, total: u64, use_tarball: bool) -> Resu
 
     progress_bar.finish_and_clear();
 
+    // a rootfs of a foreign architecture can only run with emulation
+    match crate::qemu::setup_emulation(&dist_dir) {
+        Ok(Some(arch)) => {
+            info!(
+                "Foreign architecture rootfs, set up QEMU user-mode emulation ({}).",
+                arch
+            );
+        }
+        Ok(None) => (),
+        Err(e) => {
+            warn!(
+                "Unable to set up emulation for this foreign architecture rootfs: {}",
+                e
+            );
+        }
+    }
+
     Ok(())
 }
 
 pub fn ciel_init() -> Result<()> {
+    ciel_init_at(Path::new("."))
+}
+
+/// Initialize the workspace in the directory (e.g. the per-architecture workspaces)
+pub fn ciel_init_at(root: &Path) -> Result<()> {
     for dir in SKELETON_DIRS {
-        fs::create_dir_all(dir)?;
+        fs::create_dir_all(root.join(dir))?;
     }
-    let mut f = File::create(".ciel/version")?;
-    f.write_all(CURRENT_CIEL_VERSION_STR.as_bytes())?;
+    fs::write(
+        root.join(CIEL_VERSION_FILE),
+        CURRENT_CIEL_VERSION.to_string(),
+    )?;
 
     Ok(())
 }
@@ -206,19 +588,119 @@ pub fn is_instance_exists(instance: &str) -> bool {
     Path::new(CIEL_INST_DIR).join(instance).is_dir()
 }
 
+fn parse_workspace_version(data: &str) -> Result<usize> {
+    data.trim()
+        .parse()
+        .map_err(|_| anyhow!("Invalid workspace version: {:?}", data.trim()))
+}
+
+/// Version (layout) of the current workspace, as recorded in `.ciel/version`
+pub fn workspace_version() -> Result<usize> {
+    parse_workspace_version(&fs::read_to_string(CIEL_VERSION_FILE)?)
+}
+
+#[inline]
+pub fn write_workspace_version(version: usize) -> Result<()> {
+    fs::write(CIEL_VERSION_FILE, version.to_string())?;
+
+    Ok(())
+}
+
 pub fn is_legacy_workspace() -> Result<bool> {
-    let mut f = fs::File::open(".ciel/version")?;
-    // TODO: use a more robust check
-    let mut buf = [0u8; 1];
-    f.read_exact(&mut buf)?;
+    Ok(workspace_version()? < CURRENT_CIEL_VERSION)
+}
+
+/// Parse a size in bytes with an optional K/M/G/T suffix, e.g. `16G`
+pub fn parse_size(size: &str) -> Result<u64> {
+    let size = size.trim();
+    let (number, shift) = match size.chars().last().map(|x| x.to_ascii_uppercase()) {
+        Some('K') => (&size[..size.len() - 1], 10),
+        Some('M') => (&size[..size.len() - 1], 20),
+        Some('G') => (&size[..size.len() - 1], 30),
+        Some('T') => (&size[..size.len() - 1], 40),
+        _ => (size, 0),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|x| x.checked_mul(1 << shift))
+        .ok_or_else(|| anyhow!("Invalid size: {} (e.g. 500M or 16G)", size))
+}
+
+/// Allocated size of the directory like `du -s`, the hard links in `seen` are not counted again
+pub fn directory_size(path: &Path, seen: &mut HashSet<(u64, u64)>) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|x| x.ok())
+        .filter_map(|x| x.metadata().ok())
+        .filter(|x| x.nlink() < 2 || seen.insert((x.dev(), x.ino())))
+        .map(|x| x.blocks() * 512)
+        .sum()
+}
+
+/// Available space (for unprivileged users) on the filesystem holding the workspace
+pub fn available_space() -> Result<u64> {
+    let stat = nix::sys::statvfs::statvfs(".ciel")?;
 
-    Ok(buf[0] < CURRENT_CIEL_VERSION_STR.as_bytes()[0])
+    Ok(stat.blocks_available() * stat.fragment_size())
+}
+
+#[inline]
+pub fn set_space_check(enabled: bool) {
+    NO_SPACE_CHECK.store(!enabled, Ordering::Relaxed);
+}
+
+/// Abort early if less than `required` bytes are available for the operation
+pub fn ensure_free_space(required: u64, operation: &str) -> Result<()> {
+    if NO_SPACE_CHECK.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    let available = available_space()?;
+    if available < required {
+        bail!(
+            "Not enough disk space for {}: about {} is required, but only {} is available. Free some space (see `ciel du`) or use --no-space-check.",
+            operation,
+            HumanBytes(required),
+            HumanBytes(available)
+        );
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn set_batch_mode(enabled: bool) {
+    BATCH_MODE.store(enabled, Ordering::Relaxed);
+}
+
+#[inline]
+pub fn is_batch_mode() -> bool {
+    BATCH_MODE.load(Ordering::Relaxed)
+}
+
+/// Whether the user can be prompted: not in batch mode, and both stdin and stdout are terminals
+pub fn can_prompt() -> bool {
+    !is_batch_mode() && user_attended() && std::io::stdin().is_terminal()
+}
+
+/// Fail with an error explaining how to provide the input (`hint`) instead of prompting
+pub fn ensure_interactive(hint: &str) -> Result<()> {
+    if can_prompt() {
+        return Ok(());
+    }
+    let reason = if is_batch_mode() {
+        "batch mode is enabled"
+    } else {
+        "not running in a terminal"
+    };
+
+    bail!("Input is required, but {}: {}", reason, hint)
 }
 
 pub fn ask_for_target_arch() -> Result<&'static str> {
     // Collect all supported architectures
     let host_arch = get_host_arch_name();
-    if !user_attended() {
+    if !can_prompt() {
         return match host_arch {
             Some(v) => Ok(v),
             None => Err(anyhow!("Could not determine host architecture")),
@@ -226,6 +708,12 @@ pub fn ask_for_target_arch() -> Result<&'static str> {
     }
     let mut all_archs: Vec<&'static str> = CIEL_MAINLINE_ARCHS.into();
     all_archs.append(&mut CIEL_RETRO_ARCHS.into());
+    // the new ports only known from the release manifest
+    let new_archs = known_archs()
+        .into_keys()
+        .filter(|x| !all_archs.contains(x))
+        .collect::<Vec<_>>();
+    all_archs.extend(new_archs.iter());
     let default_arch_index = match host_arch {
         Some(host_arch) => all_archs.iter().position(|a| *a == host_arch).unwrap(),
         None => 0,
@@ -236,6 +724,7 @@ pub fn ask_for_target_arch() -> Result<&'static str> {
         .iter()
         .map(|x| format!("mainline: {x}"))
         .chain(CIEL_RETRO_ARCHS.iter().map(|x| format!("retro: {x}")))
+        .chain(new_archs.iter().map(|x| format!("new: {x}")))
         .collect::<Vec<_>>();
     let chosen_index = FuzzySelect::with_theme(&theme)
         .with_prompt("Target Architecture")
@@ -245,3 +734,110 @@ pub fn ask_for_target_arch() -> Result<&'static str> {
 
     Ok(all_archs[chosen_index])
 }
+
+#[test]
+fn test_unpack_archive_skip_devices() {
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Char);
+    header.set_mode(0o600);
+    header.set_size(0);
+    header.set_device_major(5).unwrap();
+    header.set_device_minor(1).unwrap();
+    builder
+        .append_data(&mut header, "dev/console", std::io::empty())
+        .unwrap();
+    let mut header = tar::Header::new_gnu();
+    header.set_mode(0o644);
+    header.set_size(5);
+    builder
+        .append_data(&mut header, "etc/hostname", &b"aosc\n"[..])
+        .unwrap();
+    let tarball = builder.into_inner().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    unpack_archive(&mut tar::Archive::new(&tarball[..]), dir.path(), true).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("etc/hostname")).unwrap(),
+        "aosc\n"
+    );
+    assert!(!dir.path().join("dev/console").exists());
+}
+
+#[test]
+fn test_detect_tarball_compression() {
+    assert_eq!(
+        detect_tarball_compression(b"\xfd7zXZ\x00\x00\x04"),
+        TarballCompression::Xz
+    );
+    assert_eq!(
+        detect_tarball_compression(b"\x28\xb5\x2f\xfd\x04"),
+        TarballCompression::Zstd
+    );
+    assert_eq!(
+        detect_tarball_compression(b"\x1f\x8b\x08"),
+        TarballCompression::Gzip
+    );
+    assert_eq!(
+        detect_tarball_compression(b"etc/\x00\x00"),
+        TarballCompression::None
+    );
+}
+
+#[test]
+fn test_chunk_reader() {
+    let (sender, receiver) = sync_channel(PIPELINE_DEPTH);
+    sender.send(Ok(b"etc/".to_vec())).unwrap();
+    sender.send(Ok(b"os-release".to_vec())).unwrap();
+    drop(sender);
+    let mut reader = ChunkReader {
+        receiver,
+        chunk: Vec::new(),
+        offset: 0,
+        unpacked: 0,
+        progress: None,
+    };
+    let mut data = String::new();
+    reader.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "etc/os-release");
+    assert_eq!(reader.unpacked, 14);
+}
+
+#[test]
+fn test_parse_workspace_version() {
+    assert_eq!(parse_workspace_version("3").unwrap(), 3);
+    assert_eq!(parse_workspace_version("2\n").unwrap(), 2);
+    assert_eq!(parse_workspace_version("12").unwrap(), 12);
+    assert!(parse_workspace_version("").is_err());
+}
+
+#[test]
+fn test_parse_size() {
+    assert_eq!(parse_size("512").unwrap(), 512);
+    assert_eq!(parse_size("16G").unwrap(), 16 << 30);
+    assert_eq!(parse_size("500m").unwrap(), 500 << 20);
+    assert!(parse_size("G").is_err());
+    assert!(parse_size("1.5G").is_err());
+}
+
+#[test]
+fn test_shell_quote() {
+    assert_eq!(shell_quote("ls"), "'ls'");
+    assert_eq!(shell_quote("it's"), "'it'\\''s'");
+}
+
+#[test]
+fn test_loongarch_arch_name() {
+    assert_eq!(find_arch_name("loong64"), Some("loongarch64"));
+    assert_eq!(
+        find_arch_name("loongarch64_nosimd"),
+        Some("loongarch64_nosimd")
+    );
+    assert_eq!(
+        loongarch_arch_name("Features\t\t: cpucfg lam ual fpu lsx lasx crc32 complex crypto lvz"),
+        "loongarch64"
+    );
+    assert_eq!(
+        loongarch_arch_name("Features\t\t: cpucfg lam ual fpu crc32"),
+        "loongarch64_nosimd"
+    );
+}
@@ -0,0 +1,121 @@
+//! Shell completion scripts, with instance and package names completed at runtime
+
+use anyhow::{anyhow, Result};
+use ciel_core::{common::find_ciel_dir, machine::list_instances_simple};
+use clap_complete::{generate, Shell};
+use std::{fs, io::Write, path::Path};
+
+use crate::cli::build_cli;
+
+/// Completes instance names and package names by calling `ciel _complete`
+const BASH_DYNAMIC: &str = r#"
+_ciel_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" prev="${COMP_WORDS[COMP_CWORD-1]}"
+    if [[ "$prev" == -i ]]; then
+        COMPREPLY=($(compgen -W "$(ciel _complete instances 2>/dev/null)" -- "$cur"))
+        return 0
+    fi
+    if [[ "${COMP_WORDS[*]}" == *" build "* && "$cur" != -* ]]; then
+        COMPREPLY=($(ciel _complete packages "$cur" 2>/dev/null))
+        return 0
+    fi
+    _ciel "$@"
+}
+
+complete -F _ciel_dynamic -o bashdefault -o default ciel
+"#;
+
+const ZSH_DYNAMIC: &str = r#"
+_ciel_instances() {
+    compadd -- ${(f)"$(ciel _complete instances 2>/dev/null)"}
+}
+
+_ciel_packages() {
+    compadd -- ${(f)"$(ciel _complete packages "$PREFIX" 2>/dev/null)"}
+}
+"#;
+
+const FISH_DYNAMIC: &str = r#"
+complete -c ciel -s i -x -a "(ciel _complete instances 2>/dev/null)"
+complete -c ciel -n "__fish_seen_subcommand_from build" -f -a "(ciel _complete packages (commandline -ct) 2>/dev/null)"
+"#;
+
+/// Print the completion script for the shell
+pub fn print_completion(shell: &str) -> Result<()> {
+    let shell = shell
+        .parse::<Shell>()
+        .map_err(|e| anyhow!("Unsupported shell {}: {}", shell, e))?;
+    let mut script = Vec::new();
+    generate(shell, &mut build_cli(), "ciel", &mut script);
+    let mut script = String::from_utf8(script)?;
+    match shell {
+        Shell::Bash => script.push_str(BASH_DYNAMIC),
+        Shell::Zsh => {
+            script = script
+                .replace(":INSTANCE:_default", ":INSTANCE:_ciel_instances")
+                .replace(":PACKAGES:_default", ":PACKAGES:_ciel_packages");
+            // the helpers must be defined after the `#compdef` line
+            let pos = script.find('\n').map_or(0, |x| x + 1);
+            script.insert_str(pos, ZSH_DYNAMIC);
+        }
+        Shell::Fish => script.push_str(FISH_DYNAMIC),
+        _ => (),
+    }
+    std::io::stdout().write_all(script.as_bytes())?;
+
+    Ok(())
+}
+
+/// List the package names (and package groups) in the tree starting with `prefix`
+fn list_packages(prefix: &str) -> Result<Vec<String>> {
+    let tree = Path::new("TREE");
+    let mut packages = Vec::new();
+    if let Ok(groups) = fs::read_dir(tree.join("groups")) {
+        for entry in groups.flatten() {
+            let name = format!("groups/{}", entry.file_name().to_string_lossy());
+            if entry.path().is_file() && name.starts_with(prefix) {
+                packages.push(name);
+            }
+        }
+    }
+    if prefix.contains('/') {
+        return Ok(packages);
+    }
+    for section in fs::read_dir(tree)?.flatten() {
+        let section_name = section.file_name();
+        if !section.path().is_dir() || section_name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        for entry in fs::read_dir(section.path())?.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with(prefix) && entry.path().join("spec").is_file() {
+                packages.push(name);
+            }
+        }
+    }
+    packages.sort_unstable();
+
+    Ok(packages)
+}
+
+/// Print the completion candidates, used by the completion scripts.
+/// Errors are not reported since this is called on every tab press.
+pub fn print_candidates(directory: &Path, kind: &str, prefix: &str) {
+    let directory = match find_ciel_dir(directory) {
+        Ok(directory) => directory,
+        Err(_) => return,
+    };
+    if std::env::set_current_dir(directory).is_err() {
+        return;
+    }
+    let candidates = match kind {
+        "instances" => list_instances_simple(),
+        "packages" => list_packages(prefix),
+        _ => return,
+    };
+    for candidate in candidates.unwrap_or_default() {
+        if candidate.starts_with(prefix) {
+            println!("{}", candidate);
+        }
+    }
+}
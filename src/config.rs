@@ -1,24 +1,52 @@
 //! This module contains configuration files related APIs
 
-use crate::common::CURRENT_CIEL_VERSION;
-use crate::{get_host_arch_name, info};
-use anyhow::{anyhow, Result};
-use console::{style, user_attended};
-use dialoguer::{theme::ColorfulTheme, Confirm, Editor, Input};
+use crate::backend::BackendKind;
+use crate::common::{can_prompt, ensure_interactive, CIEL_INST_DIR, CURRENT_CIEL_VERSION};
+use crate::common::{get_host_arch_name, parse_size};
+use crate::overlayfs::FsBackendKind;
+use crate::{info, network};
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use dialoguer::{theme::ColorfulTheme, Confirm, Editor, Input, Select};
 use serde::{Deserialize, Serialize};
-use std::{ffi::OsString, path::Path};
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
 use std::{
     fs,
     io::{Read, Write},
 };
 
-const DEFAULT_CONFIG_LOCATION: &str = ".ciel/data/config.toml";
+const DEFAULT_CONFIG_LOCATION: &str = ".ciel/config.toml";
+const INSTANCE_CONFIG_FILE: &str = "config.toml";
+/// Configuration file location used by older versions of Ciel
+const LEGACY_CONFIG_LOCATION: &str = ".ciel/data/config.toml";
 const DEFAULT_APT_SOURCE: &str = "deb https://repo.aosc.io/debs/ stable main";
+const DEFAULT_REPO_URL: &str = "https://repo.aosc.io/debs/";
+const DEFAULT_RELEASES_URL: &str = "https://releases.aosc.io/";
 const DEFAULT_AB4_CONFIG_FILE: &str = "ab4cfg.sh";
 const DEFAULT_AB4_CONFIG_LOCATION: &str = "etc/autobuild/ab4cfg.sh";
 const DEFAULT_APT_LIST_LOCATION: &str = "etc/apt/sources.list";
+const DEFAULT_EXTRA_APT_LIST_LOCATION: &str = "etc/apt/sources.list.d/ciel-extra.list";
+const DEFAULT_TOPIC_APT_LIST_LOCATION: &str = "etc/apt/sources.list.d/ciel-topic.list";
+const DEFAULT_APT_PROXY_LOCATION: &str = "etc/apt/apt.conf.d/99ciel-proxy";
+const DEFAULT_APT_LIMIT_RATE_LOCATION: &str = "etc/apt/apt.conf.d/99ciel-limit-rate";
 const DEFAULT_RESOLV_LOCATION: &str = "etc/systemd/resolved.conf";
+const DEFAULT_RESOLV_CONF_LOCATION: &str = "etc/resolv.conf";
+const DEFAULT_HOSTS_LOCATION: &str = "etc/hosts";
+/// Used when the rootfs (e.g. the configuration layer of an instance) has no hosts file yet
+const DEFAULT_HOSTS: &str = "127.0.0.1\tlocalhost\n::1\t\tlocalhost\n";
+const HOSTS_BEGIN_MARKER: &str = "# BEGIN ciel";
+const HOSTS_END_MARKER: &str = "# END ciel";
 const DEFAULT_ACBS_CONFIG: &str = "etc/acbs/forest.conf";
+const DEFAULT_SCCACHE_CONFIG: &str = "etc/sccache/config";
+const DEFAULT_GIT_CONFIG_LOCATION: &str = "etc/gitconfig";
+const DISTCC_MASQUERADE_DIR: &str = "/usr/lib/distcc";
+/// Location of the shared ccache directory inside the containers
+pub const CCACHE_DIR: &str = "/var/cache/ccache";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CielConfig {
@@ -36,6 +64,675 @@ pub struct CielConfig {
     pub volatile_mount: bool,
     #[serde(default = "CielConfig::default_force_use_apt")]
     pub force_use_apt: bool,
+    #[serde(rename = "container-backend", default)]
+    pub backend: BackendKind,
+    #[serde(default)]
+    pub rootless: bool,
+    #[serde(rename = "filesystem-backend", default)]
+    pub fs_backend: FsBackendKind,
+    #[serde(default)]
+    pub ccache: bool,
+    /// Additional APT repositories (in sources.list format)
+    #[serde(rename = "extra-repos", default)]
+    pub extra_repos: Vec<String>,
+    /// Default branch of the ABBS tree
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Git remote the ABBS tree is cloned from (`ciel tree clone`)
+    #[serde(
+        rename = "tree-remote",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tree_remote: Option<String>,
+    /// Topic being worked on (`ciel topic`), its repository is added to the APT sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    /// Default target architecture when loading the OS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    #[serde(rename = "remote-compile", default)]
+    pub remote_compile: RemoteCompileConfig,
+    #[serde(rename = "repo-signing", default)]
+    pub repo_signing: RepoSigningConfig,
+    #[serde(rename = "release-signing", default)]
+    pub release_signing: ReleaseSigningConfig,
+    /// AOSC OS mirror used for the OS tarballs and the APT sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<String>,
+    #[serde(default)]
+    pub notify: NotifyConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    #[serde(rename = "repo-layout", default)]
+    pub repo_layout: RepoLayoutConfig,
+    #[serde(default)]
+    pub resources: ResourceLimits,
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+    #[serde(default)]
+    pub qa: QaConfig,
+    #[serde(default)]
+    pub provision: ProvisionConfig,
+    /// HTTP(S) proxy for the downloads and inside the containers, e.g. `http://proxy:3128`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Download rate limit (bytes per second, e.g. `2M`), for the downloads and APT in the containers
+    #[serde(
+        rename = "limit-rate",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub limit_rate: Option<String>,
+    /// DNS servers of the containers, instead of the ones of the host
+    #[serde(rename = "dns-servers", default, skip_serializing_if = "Vec::is_empty")]
+    pub dns_servers: Vec<String>,
+    /// Entries added to /etc/hosts in the containers, e.g. `10.0.0.2 mirror.corp.example`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<String>,
+    #[serde(rename = "bind-mounts", default, skip_serializing_if = "Vec::is_empty")]
+    pub bind_mounts: Vec<BindMount>,
+    /// Device nodes (or directories of them) of the host passed into the containers, e.g. `/dev/kvm`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<String>,
+    #[serde(default)]
+    pub network: NetworkMode,
+    #[serde(default)]
+    pub jobs: JobsConfig,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub remotes: BTreeMap<String, RemoteConfig>,
+    #[serde(rename = "free-space", default)]
+    pub free_space: FreeSpaceConfig,
+}
+
+/// Free space required on the filesystem holding the workspace before the operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreeSpaceConfig {
+    /// Required for updating the OS
+    #[serde(default = "FreeSpaceConfig::default_update")]
+    pub update: String,
+    /// Required for building a package without a hint
+    #[serde(default = "FreeSpaceConfig::default_build")]
+    pub build: String,
+    /// Hints for the packages requiring more space, e.g. `llvm = "60G"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub packages: BTreeMap<String, String>,
+}
+
+impl FreeSpaceConfig {
+    fn default_update() -> String {
+        "2G".to_string()
+    }
+
+    fn default_build() -> String {
+        "5G".to_string()
+    }
+
+    /// Space required for updating the OS, in bytes
+    pub fn update_requirement(&self) -> Result<u64> {
+        parse_size(&self.update)
+    }
+
+    /// Space required for building the package (`name` or `section/name`), in bytes
+    pub fn build_requirement(&self, package: &str) -> Result<u64> {
+        let name = package.rsplit('/').next().unwrap_or(package);
+        let hint = self
+            .packages
+            .get(package)
+            .or_else(|| self.packages.get(name));
+
+        parse_size(hint.unwrap_or(&self.build))
+    }
+}
+
+impl Default for FreeSpaceConfig {
+    fn default() -> Self {
+        Self {
+            update: Self::default_update(),
+            build: Self::default_build(),
+            packages: BTreeMap::new(),
+        }
+    }
+}
+
+/// Parallel jobs of the builds (`ABTHREADS`), autobuild decides if not set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobsConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<usize>,
+    /// Overrides for the memory-hungry packages, e.g. `firefox = 4`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub packages: BTreeMap<String, usize>,
+}
+
+impl JobsConfig {
+    /// Parallel jobs overridden for the package (`name` or `section/name`),
+    /// the default ones are written to the autobuild configuration
+    pub fn package_override(&self, package: &str) -> Option<usize> {
+        let name = package.rsplit('/').next().unwrap_or(package);
+
+        self.packages
+            .get(package)
+            .or_else(|| self.packages.get(name))
+            .copied()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.default == Some(0) {
+            bail!("The number of parallel jobs must be positive.");
+        }
+        if let Some((package, _)) = self.packages.iter().find(|x| *x.1 == 0) {
+            bail!(
+                "The number of parallel jobs of {} must be positive.",
+                package
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Remote machine running ciel that the packages can be built on (`ciel build --on`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteConfig {
+    /// SSH destination, e.g. `user@host`
+    pub host: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Workspace on the remote machine, relative to the home directory unless absolute
+    #[serde(default = "RemoteConfig::default_path")]
+    pub path: String,
+    /// Instance to build in, if not specified with `-i`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Architecture of the base OS on the remote machine, the build farm builds the packages
+    /// for each architecture of its builders
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+}
+
+impl RemoteConfig {
+    pub fn default_path() -> String {
+        "ciel".to_string()
+    }
+}
+
+/// Layouts of the local repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepoLayoutKind {
+    /// Flat repository in `debs/` only
+    #[default]
+    Flat,
+    /// Additionally generate a Debian archive layout (`pool/` and `dists/`)
+    Pool,
+}
+
+/// Layout of the local repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoLayoutConfig {
+    #[serde(default)]
+    pub kind: RepoLayoutKind,
+    /// Suite name used in the `dists/` directory
+    #[serde(default = "RepoLayoutConfig::default_suite")]
+    pub suite: String,
+    #[serde(default = "RepoLayoutConfig::default_component")]
+    pub component: String,
+}
+
+impl RepoLayoutConfig {
+    fn default_suite() -> String {
+        "stable".to_string()
+    }
+
+    fn default_component() -> String {
+        "main".to_string()
+    }
+}
+
+impl Default for RepoLayoutConfig {
+    fn default() -> Self {
+        Self {
+            kind: RepoLayoutKind::default(),
+            suite: Self::default_suite(),
+            component: Self::default_component(),
+        }
+    }
+}
+
+/// Retention policy of the packages in the local repository (`ciel repo prune`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RetentionConfig {
+    /// Number of versions to keep for each package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_versions: Option<usize>,
+    /// Remove the packages older than this (except the latest version)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u64>,
+    /// Keep only the latest version of the debug symbol (-dbg) packages
+    #[serde(default)]
+    pub dbg_latest_only: bool,
+    /// Prune the repository before each refresh
+    #[serde(default)]
+    pub auto_prune: bool,
+}
+
+/// systemd resource controls applied to the containers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ResourceLimits {
+    /// CPU time quota, e.g. `800%` for 8 CPUs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_quota: Option<String>,
+    /// Memory limit, e.g. `16G`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_max: Option<String>,
+    /// IO weight (1-10000, defaults to 100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub io_weight: Option<u16>,
+    /// CPUs the container may run on, e.g. `0-7`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_cpus: Option<String>,
+}
+
+impl ResourceLimits {
+    /// Override the limits with the ones set in `other`
+    pub fn merge(&mut self, other: ResourceLimits) {
+        if other.cpu_quota.is_some() {
+            self.cpu_quota = other.cpu_quota;
+        }
+        if other.memory_max.is_some() {
+            self.memory_max = other.memory_max;
+        }
+        if other.io_weight.is_some() {
+            self.io_weight = other.io_weight;
+        }
+        if other.allowed_cpus.is_some() {
+            self.allowed_cpus = other.allowed_cpus;
+        }
+    }
+
+    /// Unit properties of the limits (e.g. `MemoryMax=16G`)
+    pub fn properties(&self) -> Vec<String> {
+        let mut properties = Vec::new();
+        if let Some(quota) = &self.cpu_quota {
+            properties.push(format!("CPUQuota={}", quota));
+        }
+        if let Some(memory) = &self.memory_max {
+            properties.push(format!("MemoryMax={}", memory));
+        }
+        if let Some(weight) = self.io_weight {
+            properties.push(format!("IOWeight={}", weight));
+        }
+        if let Some(cpus) = &self.allowed_cpus {
+            properties.push(format!("AllowedCPUs={}", cpus));
+        }
+
+        properties
+    }
+}
+
+/// Limits after which a build is considered hung and aborted
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WatchdogConfig {
+    /// Minutes without any output from the build
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout: Option<u64>,
+    /// Total minutes the build of a package may take
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_limit: Option<u64>,
+}
+
+/// Checks run on the .deb files produced by the builds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QaCheck {
+    /// Maintainer scripts without the executable permission
+    ScriptPermissions,
+    /// Packages without any file nor dependency
+    EmptyPackage,
+    /// Symbolic links pointing to nothing, neither in the packages nor in the instance
+    DanglingSymlink,
+    /// Binaries with their symbols although a `-dbg` package is produced
+    UnstrippedBinary,
+    /// Shared libraries (sonames) removed since the previous build of the package
+    SonameBump,
+    /// Symbols removed from the shared libraries since the previous build of the package
+    AbiBreak,
+}
+
+/// Quality assurance of the built packages, `ciel build --strict` fails the builds with problems
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QaConfig {
+    /// Checks not to run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip: Vec<QaCheck>,
+}
+
+/// Additional directory of the host bind-mounted into the containers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BindMount {
+    /// Path on the host, relative to the workspace
+    pub source: String,
+    /// Path in the containers
+    pub target: String,
+    #[serde(rename = "read-only", default)]
+    pub read_only: bool,
+}
+
+impl BindMount {
+    /// The systemd-nspawn option (`--bind=` or `--bind-ro=`) of the mount
+    pub fn nspawn_option(&self) -> Result<String> {
+        let source = fs::canonicalize(&self.source)
+            .map_err(|e| anyhow!("Unable to bind-mount {}: {}", self.source, e))?;
+        let source = source.to_string_lossy();
+        if source.contains(':') || self.target.contains(':') || !self.target.starts_with('/') {
+            bail!("Invalid bind mount: {} -> {}", source, self.target);
+        }
+
+        Ok(format!(
+            "--bind{}={}:{}",
+            if self.read_only { "-ro" } else { "" },
+            source,
+            self.target
+        ))
+    }
+}
+
+/// Network of the containers: the one of the host, or their own network identity
+/// on a macvlan interface or an existing bridge of the host
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "mode")]
+pub enum NetworkMode {
+    #[default]
+    Host,
+    Macvlan {
+        /// Interface of the host the macvlan interface is created on
+        interface: String,
+    },
+    Bridge {
+        bridge: String,
+    },
+}
+
+impl NetworkMode {
+    /// Whether the containers have their own network
+    pub fn is_private(&self) -> bool {
+        *self != NetworkMode::Host
+    }
+
+    /// The systemd-nspawn options of the network
+    pub fn nspawn_options(&self) -> Result<Vec<String>> {
+        let (option, interface) = match self {
+            NetworkMode::Host => return Ok(Vec::new()),
+            NetworkMode::Macvlan { interface } => ("--network-macvlan", interface),
+            NetworkMode::Bridge { bridge } => ("--network-bridge", bridge),
+        };
+        if interface.is_empty() || interface.contains(|c: char| c.is_whitespace() || c == '/') {
+            bail!("Invalid network interface: {}", interface);
+        }
+
+        Ok(vec![format!("{}={}", option, interface)])
+    }
+}
+
+/// Build essentials installed into the new instances by `ciel add --provision`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvisionConfig {
+    /// Provision every new instance, without `--provision`
+    #[serde(default)]
+    pub auto: bool,
+    #[serde(default = "ProvisionConfig::default_packages")]
+    pub packages: Vec<String>,
+    /// Editor package, installed and set as `EDITOR` in the instances
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+}
+
+impl ProvisionConfig {
+    fn default_packages() -> Vec<String> {
+        vec![
+            "autobuild3".to_string(),
+            "acbs".to_string(),
+            "git".to_string(),
+        ]
+    }
+}
+
+impl Default for ProvisionConfig {
+    fn default() -> Self {
+        Self {
+            auto: false,
+            packages: Self::default_packages(),
+            editor: None,
+        }
+    }
+}
+
+/// Hooks fired when a build finishes or fails
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotifyConfig {
+    /// Shell command, the build result is passed in the `CIEL_BUILD_*` environment variables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// URL to POST the build result (in JSON) to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+    /// Show a desktop notification (using notify-send)
+    #[serde(default)]
+    pub desktop: bool,
+}
+
+/// Methods for signing the local repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningMethod {
+    #[default]
+    None,
+    Gpg,
+    Minisign,
+}
+
+/// Local repository signing settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoSigningConfig {
+    #[serde(default)]
+    pub method: SigningMethod,
+    /// GPG key ID or path to the minisign secret key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
+/// Verification of the release manifest and the OS tarballs (unless `--insecure` is used)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReleaseSigningConfig {
+    /// OpenPGP keyring holding the release signing keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyring: Option<String>,
+    /// Fingerprints of the keys allowed to sign the releases (any key in the keyring if empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fingerprints: Vec<String>,
+}
+
+/// Tools for offloading the compile jobs to other machines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteCompileKind {
+    #[default]
+    None,
+    Distcc,
+    Sccache,
+}
+
+/// Remote compilation settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemoteCompileConfig {
+    #[serde(default)]
+    pub kind: RemoteCompileKind,
+    /// distcc hosts (e.g. `192.168.1.2/8`) or the sccache scheduler URL
+    #[serde(default)]
+    pub endpoints: Vec<String>,
+    /// Authentication token for the sccache scheduler
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Number of parallel compile jobs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<usize>,
+}
+
+impl RemoteCompileConfig {
+    #[inline]
+    pub fn enabled(&self) -> bool {
+        self.kind != RemoteCompileKind::None && !self.endpoints.is_empty()
+    }
+
+    /// Generate the autobuild environment overrides
+    fn autobuild_overrides(&self) -> String {
+        let mut overrides = String::new();
+        match self.kind {
+            RemoteCompileKind::None => return overrides,
+            RemoteCompileKind::Distcc => {
+                overrides.push_str(&format!(
+                    "\nexport DISTCC_HOSTS=\"{}\"\nexport PATH=\"{}:$PATH\"",
+                    self.endpoints.join(" "),
+                    DISTCC_MASQUERADE_DIR
+                ));
+            }
+            RemoteCompileKind::Sccache => {
+                overrides.push_str(&format!(
+                    "\nexport SCCACHE_CONF=\"/{}\"\nexport RUSTC_WRAPPER=sccache\nexport CC=\"sccache gcc\"\nexport CXX=\"sccache g++\"",
+                    DEFAULT_SCCACHE_CONFIG
+                ));
+            }
+        }
+        if let Some(jobs) = self.jobs {
+            overrides.push_str(&format!("\nABTHREADS={}", jobs));
+        }
+
+        overrides
+    }
+
+    /// Generate the sccache client configuration
+    fn sccache_config(&self) -> String {
+        let mut config = format!(
+            "[dist]\nscheduler_url = \"{}\"\ntoolchains = []\n",
+            self.endpoints
+                .first()
+                .map(|x| x.as_str())
+                .unwrap_or_default()
+        );
+        if let Some(token) = &self.token {
+            config.push_str(&format!(
+                "\n[dist.auth]\ntype = \"token\"\ntoken = \"{}\"\n",
+                token
+            ));
+        }
+
+        config
+    }
+}
+
+/// Upper layer of the instance on tmpfs, discarded when the instance is stopped
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TmpfsConfig {
+    /// Size limit (e.g. `16G` or `50%`), defaults to half of the memory
+    #[serde(default)]
+    pub size: Option<String>,
+}
+
+/// Check the tmpfs size limit (`<number>[k|m|g|%]`)
+pub fn validate_tmpfs_size(size: &str) -> Result<()> {
+    let number = size
+        .strip_suffix(|c: char| "kKmMgG%".contains(c))
+        .unwrap_or(size);
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        bail!("Invalid tmpfs size: {}", size);
+    }
+
+    Ok(())
+}
+
+/// Virtual machine settings of the instances using the `vm` container backend
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VmConfig {
+    /// Kernel image to boot, defaults to the newest `/boot/vmlinuz-*` in the instance
+    #[serde(default)]
+    pub kernel: Option<PathBuf>,
+    /// Initrd image, defaults to the one matching the kernel in the instance (if any)
+    #[serde(default)]
+    pub initrd: Option<PathBuf>,
+    /// Extra kernel command line arguments
+    #[serde(rename = "kernel-cmdline", default)]
+    pub kernel_cmdline: Option<String>,
+}
+
+/// Per-instance overrides of the workspace configuration,
+/// stored in `.ciel/container/instances/<name>/config.toml`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct InstanceConfig {
+    #[serde(default)]
+    maintainer: Option<String>,
+    #[serde(default)]
+    apt_sources: Option<String>,
+    #[serde(rename = "extra-repos", default)]
+    extra_repos: Option<Vec<String>>,
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(default)]
+    resources: Option<ResourceLimits>,
+    #[serde(default)]
+    pub tmpfs: Option<TmpfsConfig>,
+    /// Container backend of the instance (e.g. `vm` for the kernel-dependent builds)
+    #[serde(rename = "container-backend", default)]
+    pub backend: Option<BackendKind>,
+    #[serde(default)]
+    pub vm: Option<VmConfig>,
+    /// Shared layers (in `.ciel/container/layers`) stacked on the base OS, from the bottom to the top
+    #[serde(default)]
+    pub layers: Option<Vec<String>>,
+    /// Bind mounts of the instance, in addition to the ones of the workspace
+    #[serde(rename = "bind-mounts", default)]
+    pub bind_mounts: Option<Vec<BindMount>>,
+    /// Devices of the instance, in addition to the ones of the workspace
+    #[serde(default)]
+    pub devices: Option<Vec<String>>,
+    /// Ports of the host forwarded to the instance (`ciel port`)
+    #[serde(default)]
+    pub ports: Option<Vec<String>>,
+    #[serde(default)]
+    pub network: Option<NetworkMode>,
+}
+
+impl InstanceConfig {
+    /// Override the workspace configuration using the instance configuration
+    fn apply_to(self, config: &mut CielConfig) {
+        if let Some(maintainer) = self.maintainer {
+            config.maintainer = maintainer;
+        }
+        if let Some(apt_sources) = self.apt_sources {
+            config.apt_sources = apt_sources;
+        }
+        if let Some(extra_repos) = self.extra_repos {
+            config.extra_repos = extra_repos;
+        }
+        if self.branch.is_some() {
+            config.branch = self.branch;
+        }
+        if let Some(resources) = self.resources {
+            config.resources.merge(resources);
+        }
+        if let Some(bind_mounts) = self.bind_mounts {
+            config.bind_mounts.extend(bind_mounts);
+        }
+        if let Some(devices) = self.devices {
+            config.devices.extend(devices);
+        }
+        if let Some(network) = self.network {
+            config.network = network;
+        }
+    }
 }
 
 impl CielConfig {
@@ -50,6 +747,40 @@ impl CielConfig {
     pub fn load_config(data: &str) -> Result<CielConfig> {
         Ok(toml::from_str(data)?)
     }
+
+    /// Maintainer identity (`Name <email>`)
+    pub fn maintainer(&self) -> &str {
+        &self.maintainer
+    }
+
+    /// Whether oma is used instead of apt for the package operations (`force-use-apt`),
+    /// never on RISC-V hosts where oma may lock up
+    pub fn use_oma(&self) -> bool {
+        !self.force_use_apt && get_host_arch_name().is_none_or(|x| x != "riscv64")
+    }
+
+    /// URL of the package repository on the selected mirror
+    pub fn repo_url(&self) -> String {
+        match &self.mirror {
+            Some(mirror) => format!("{}debs/", mirror),
+            None => DEFAULT_REPO_URL.to_string(),
+        }
+    }
+
+    /// URL of the OS releases (tarballs) on the selected mirror
+    pub fn releases_url(&self) -> String {
+        match &self.mirror {
+            Some(mirror) => format!("{}aosc-os/", mirror),
+            None => DEFAULT_RELEASES_URL.to_string(),
+        }
+    }
+
+    /// Switch to the mirror (`None` for the origin server), the APT sources are updated accordingly
+    pub fn set_mirror(&mut self, mirror: Option<&str>) {
+        let previous = self.repo_url();
+        self.mirror = mirror.map(|x| format!("{}/", x.trim_end_matches('/')));
+        self.apt_sources = self.apt_sources.replace(&previous, &self.repo_url());
+    }
 }
 
 impl Default for CielConfig {
@@ -65,10 +796,122 @@ impl Default for CielConfig {
             sep_mount: true,
             volatile_mount: false,
             force_use_apt: false,
+            backend: BackendKind::default(),
+            rootless: false,
+            fs_backend: FsBackendKind::default(),
+            ccache: false,
+            extra_repos: Vec::new(),
+            branch: None,
+            tree_remote: None,
+            topic: None,
+            arch: None,
+            remote_compile: RemoteCompileConfig::default(),
+            repo_signing: RepoSigningConfig::default(),
+            release_signing: ReleaseSigningConfig::default(),
+            mirror: None,
+            notify: NotifyConfig::default(),
+            retention: RetentionConfig::default(),
+            repo_layout: RepoLayoutConfig::default(),
+            resources: ResourceLimits::default(),
+            watchdog: WatchdogConfig::default(),
+            qa: QaConfig::default(),
+            provision: ProvisionConfig::default(),
+            proxy: None,
+            limit_rate: None,
+            dns_servers: Vec::new(),
+            hosts: Vec::new(),
+            bind_mounts: Vec::new(),
+            devices: Vec::new(),
+            network: NetworkMode::default(),
+            jobs: JobsConfig::default(),
+            remotes: BTreeMap::new(),
+            free_space: FreeSpaceConfig::default(),
         }
     }
 }
 
+/// Split a maintainer identity (`Name <email>`) into the name and the email
+pub fn split_maintainer(maintainer: &str) -> Option<(&str, &str)> {
+    let (name, email) = maintainer.split_once('<')?;
+    let email = email.trim_end().strip_suffix('>')?;
+    let name = name.trim();
+    if name.is_empty() || email.is_empty() {
+        return None;
+    }
+
+    Some((name, email))
+}
+
+/// Environment variables passing the maintainer identity (`CIEL_MAINTAINER`) into the containers,
+/// for autobuild3, the Debian tools and git
+pub fn maintainer_environment() -> Vec<(&'static str, String)> {
+    let maintainer = match std::env::var("CIEL_MAINTAINER") {
+        Ok(maintainer) => maintainer,
+        Err(_) => return Vec::new(),
+    };
+    let (name, email) = match split_maintainer(&maintainer) {
+        Some(identity) => identity,
+        None => return Vec::new(),
+    };
+
+    vec![
+        ("MTER", maintainer.clone()),
+        ("MAINTAINER", maintainer.clone()),
+        ("DEBFULLNAME", name.to_string()),
+        ("DEBEMAIL", email.to_string()),
+        ("GIT_AUTHOR_NAME", name.to_string()),
+        ("GIT_AUTHOR_EMAIL", email.to_string()),
+        ("GIT_COMMITTER_NAME", name.to_string()),
+        ("GIT_COMMITTER_EMAIL", email.to_string()),
+    ]
+}
+
+/// Check the DNS servers and the hosts entries (`<address> <name>...`)
+fn validate_name_resolution(config: &CielConfig) -> Result<()> {
+    for server in config.dns_servers.iter() {
+        if server.parse::<IpAddr>().is_err() {
+            bail!("Invalid DNS server: {}", server);
+        }
+    }
+    for entry in config.hosts.iter() {
+        let mut fields = entry.split_whitespace();
+        let address = fields.next().is_some_and(|x| x.parse::<IpAddr>().is_ok());
+        if !address || fields.next().is_none() {
+            bail!("Invalid hosts entry: {}", entry);
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace the entries managed by ciel (between the markers) in the hosts file
+fn update_hosts(hosts: &str, entries: &[String]) -> String {
+    let mut result = String::new();
+    let mut managed = false;
+    for line in hosts.lines() {
+        if line == HOSTS_BEGIN_MARKER {
+            managed = true;
+        } else if line == HOSTS_END_MARKER {
+            managed = false;
+        } else if !managed {
+            result.push_str(line);
+            result.push('\n');
+        }
+    }
+    if !entries.is_empty() {
+        result.push_str(HOSTS_BEGIN_MARKER);
+        result.push('\n');
+        for entry in entries {
+            result.push_str(entry);
+            result.push('\n');
+        }
+        result.push_str(HOSTS_END_MARKER);
+        result.push('\n');
+    }
+
+    result
+}
+
 #[allow(clippy::ptr_arg)]
 fn validate_maintainer(maintainer: &String) -> Result<(), String> {
     let mut lt = false; // "<"
@@ -147,7 +990,11 @@ fn get_default_editor() -> OsString {
 /// Shows a series of prompts to let the user select the configurations
 pub fn ask_for_config(config: Option<CielConfig>) -> Result<CielConfig> {
     let mut config = config.unwrap_or_default();
-    if !user_attended() {
+    if config.rootless {
+        // only podman supports running containers without root privileges
+        config.backend = BackendKind::Podman;
+    }
+    if !can_prompt() {
         info!("Not controlled by an user. Default values are used.");
         return Ok(config);
     }
@@ -192,6 +1039,10 @@ pub fn ask_for_config(config: Option<CielConfig>) -> Result<CielConfig> {
         .with_prompt("Use volatile mode for filesystem operations")
         .default(config.volatile_mount)
         .interact()?;
+    config.ccache = Confirm::with_theme(&theme)
+        .with_prompt("Enable ccache (compiler cache shared by all instances)")
+        .default(config.ccache)
+        .interact()?;
 
     // FIXME: RISC-V build hosts is unreliable when using oma: random lock-ups
     // during `oma refresh'. Disabling oma to workaround potential lock-ups.
@@ -204,18 +1055,135 @@ pub fn ask_for_config(config: Option<CielConfig>) -> Result<CielConfig> {
             .interact()?;
     }
 
+    if config.rootless {
+        return Ok(config);
+    }
+    let backends = BackendKind::ALL
+        .iter()
+        .map(|x| x.name())
+        .collect::<Vec<_>>();
+    let backend_index = Select::with_theme(&theme)
+        .with_prompt("Container backend")
+        .default(
+            BackendKind::ALL
+                .iter()
+                .position(|x| *x == config.backend)
+                .unwrap_or(0),
+        )
+        .items(&backends)
+        .interact()?;
+    config.backend = BackendKind::ALL[backend_index];
+
     Ok(config)
 }
 
 /// Reads the configuration file from the current workspace
 pub fn read_config() -> Result<CielConfig> {
-    let mut f = std::fs::File::open(DEFAULT_CONFIG_LOCATION)?;
+    let mut f = match std::fs::File::open(DEFAULT_CONFIG_LOCATION) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            std::fs::File::open(LEGACY_CONFIG_LOCATION)?
+        }
+        f => f?,
+    };
     let mut data = String::new();
     f.read_to_string(&mut data)?;
 
     CielConfig::load_config(&data)
 }
 
+#[inline]
+fn instance_config_path(instance: &str) -> PathBuf {
+    Path::new(CIEL_INST_DIR)
+        .join(instance)
+        .join(INSTANCE_CONFIG_FILE)
+}
+
+/// Returns true if the instance has its own configuration overrides
+#[inline]
+pub fn has_instance_config(instance: &str) -> bool {
+    instance_config_path(instance).is_file()
+}
+
+/// Reads the configuration overrides of the instance (if any)
+pub fn read_instance_overrides(instance: &str) -> Result<Option<InstanceConfig>> {
+    if !has_instance_config(instance) {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(instance_config_path(instance))?;
+    let overrides = toml::from_str(&data)
+        .map_err(|e| anyhow!("Invalid configuration for {}: {}", instance, e))?;
+
+    Ok(Some(overrides))
+}
+
+/// Saves the configuration overrides of the instance
+pub fn write_instance_overrides(instance: &str, overrides: &InstanceConfig) -> Result<()> {
+    fs::write(instance_config_path(instance), toml::to_string(overrides)?)?;
+
+    Ok(())
+}
+
+/// Reads the workspace configuration with the overrides of the instance applied
+pub fn read_instance_config(instance: &str) -> Result<CielConfig> {
+    let mut config = read_config()?;
+    if let Some(overrides) = read_instance_overrides(instance)? {
+        overrides.apply_to(&mut config);
+    }
+
+    Ok(config)
+}
+
+/// Saves the configuration file to the current workspace
+pub fn write_config(config: &CielConfig) -> Result<()> {
+    write_workspace_config(Path::new("."), config)
+}
+
+/// Saves the configuration file to the workspace in the directory
+pub fn write_workspace_config(root: &Path, config: &CielConfig) -> Result<()> {
+    fs::write(root.join(DEFAULT_CONFIG_LOCATION), config.save_config()?)?;
+    // migrate from the legacy location
+    fs::remove_file(root.join(LEGACY_CONFIG_LOCATION)).ok();
+
+    Ok(())
+}
+
+/// Whether the configuration file is in the legacy location or written by an older version of Ciel
+pub fn config_needs_upgrade() -> bool {
+    Path::new(LEGACY_CONFIG_LOCATION).exists()
+        || read_config().is_ok_and(|c| c.version < CURRENT_CIEL_VERSION)
+}
+
+/// Rewrite the configuration file in the current format and location
+pub fn upgrade_config() -> Result<()> {
+    let mut config = read_config()?;
+    config.version = CURRENT_CIEL_VERSION;
+
+    write_config(&config)
+}
+
+/// URL of the OS releases, using the mirror configured in the workspace (if any)
+pub fn releases_url() -> String {
+    read_config()
+        .map(|c| c.releases_url())
+        .unwrap_or_else(|_| DEFAULT_RELEASES_URL.to_string())
+}
+
+/// Opens the configuration file in the editor, returns the edited configuration
+pub fn edit_config() -> Result<CielConfig> {
+    ensure_interactive("please edit .ciel/config.toml directly")?;
+    let current = match read_config() {
+        Ok(c) => c.save_config()?,
+        Err(_) => CielConfig::default().save_config()?,
+    };
+    let edited = Editor::new()
+        .executable(get_default_editor())
+        .extension(".toml")
+        .edit(&current)?
+        .ok_or_else(|| anyhow!("Configuration is not changed."))?;
+
+    CielConfig::load_config(&edited).map_err(|e| anyhow!("Invalid configuration: {}", e))
+}
+
 /// Applies the given configuration (th configuration itself will not be saved to the disk)
 pub fn apply_config<P: AsRef<Path>>(root: P, config: &CielConfig) -> Result<()> {
     // write maintainer information
@@ -226,12 +1194,39 @@ pub fn apply_config<P: AsRef<Path>>(root: P, config: &CielConfig) -> Result<()>
     let mut f = std::fs::File::create(&config_path)?;
     f.write_all(
         format!(
-            "#!/bin/bash\nABMPM=dpkg\nABAPMS=\nABINSTALL=dpkg\nMTER=\"{}\"",
+            "#!/bin/bash\nABMPM=dpkg\nABAPMS=\nABINSTALL=dpkg\nMTER=\"${{MTER:-{}}}\"",
             config.maintainer
         )
         .as_bytes(),
     )?;
+    if config.ccache {
+        f.write_all(format!("\nUSECCACHE=1\nexport CCACHE_DIR=\"{}\"", CCACHE_DIR).as_bytes())?;
+    }
+    config.jobs.validate()?;
+    // the job count of the remote compilation takes precedence
+    if let Some(jobs) = config.jobs.default {
+        f.write_all(format!("\nABTHREADS={}", jobs).as_bytes())?;
+    }
+    if config.remote_compile.enabled() {
+        f.write_all(config.remote_compile.autobuild_overrides().as_bytes())?;
+        if config.remote_compile.kind == RemoteCompileKind::Sccache {
+            let sccache_path = rootfs.join(DEFAULT_SCCACHE_CONFIG);
+            create_parent_dir(&sccache_path)?;
+            fs::write(sccache_path, config.remote_compile.sccache_config())?;
+        }
+    }
+    // per-package jobs, and fewer jobs when retrying the builds killed by the OOM killer
+    f.write_all(b"\nif [ -n \"${CIEL_JOBS}\" ]; then ABTHREADS=\"${CIEL_JOBS}\"; fi")?;
     config_path.set_file_name(DEFAULT_AB4_CONFIG_FILE);
+    // git identity of the commits made in the containers
+    if let Some((name, email)) = split_maintainer(&config.maintainer) {
+        let git_config_path = rootfs.join(DEFAULT_GIT_CONFIG_LOCATION);
+        create_parent_dir(&git_config_path)?;
+        fs::write(
+            git_config_path,
+            format!("[user]\n\tname = {}\n\temail = {}\n", name, email),
+        )?;
+    }
     // write sources.list
     if !config.apt_sources.is_empty() {
         let mut apt_list_path = rootfs.to_owned();
@@ -240,13 +1235,99 @@ pub fn apply_config<P: AsRef<Path>>(root: P, config: &CielConfig) -> Result<()>
         let mut f = std::fs::File::create(apt_list_path)?;
         f.write_all(config.apt_sources.as_bytes())?;
     }
-    // write DNSSEC configuration
-    if !config.dnssec {
+    // write additional repositories
+    let extra_list_path = rootfs.join(DEFAULT_EXTRA_APT_LIST_LOCATION);
+    if config.extra_repos.is_empty() {
+        fs::remove_file(&extra_list_path).ok();
+    } else {
+        create_parent_dir(&extra_list_path)?;
+        fs::write(&extra_list_path, config.extra_repos.join("\n") + "\n")?;
+    }
+    // write the topic repository
+    let topic_list_path = rootfs.join(DEFAULT_TOPIC_APT_LIST_LOCATION);
+    match &config.topic {
+        Some(topic) => {
+            create_parent_dir(&topic_list_path)?;
+            fs::write(
+                &topic_list_path,
+                format!("deb {} {} main\n", config.repo_url(), topic),
+            )?;
+        }
+        None => {
+            fs::remove_file(&topic_list_path).ok();
+        }
+    }
+    // write APT proxy configuration
+    let proxy_path = rootfs.join(DEFAULT_APT_PROXY_LOCATION);
+    match config.proxy.as_deref().filter(|_| !network::is_no_proxy()) {
+        Some(proxy) => {
+            create_parent_dir(&proxy_path)?;
+            fs::write(
+                &proxy_path,
+                format!(
+                    "Acquire::http::Proxy \"{}\";\nAcquire::https::Proxy \"{}\";\n",
+                    proxy, proxy
+                ),
+            )?;
+        }
+        None => {
+            fs::remove_file(&proxy_path).ok();
+        }
+    }
+    // write APT download rate limit (in KiB/s)
+    let limit_rate_path = rootfs.join(DEFAULT_APT_LIMIT_RATE_LOCATION);
+    match network::get_rate_limit() {
+        Some(rate) => {
+            let rate = (rate / 1024).max(1);
+            create_parent_dir(&limit_rate_path)?;
+            fs::write(
+                &limit_rate_path,
+                format!(
+                    "Acquire::http::Dl-Limit \"{}\";\nAcquire::https::Dl-Limit \"{}\";\n",
+                    rate, rate
+                ),
+            )?;
+        }
+        None => {
+            fs::remove_file(&limit_rate_path).ok();
+        }
+    }
+    // write DNS configuration
+    validate_name_resolution(config)?;
+    if !config.dnssec || !config.dns_servers.is_empty() {
         let mut resolv_path = rootfs.to_owned();
         resolv_path.push(DEFAULT_RESOLV_LOCATION);
         create_parent_dir(&resolv_path)?;
         let mut f = std::fs::File::create(resolv_path)?;
-        f.write_all(b"[Resolve]\nDNSSEC=no\n")?;
+        f.write_all(b"[Resolve]\n")?;
+        if !config.dns_servers.is_empty() {
+            f.write_all(format!("DNS={}\n", config.dns_servers.join(" ")).as_bytes())?;
+        }
+        if !config.dnssec {
+            f.write_all(b"DNSSEC=no\n")?;
+        }
+    }
+    if !config.dns_servers.is_empty() {
+        // usually a symlink to the stub resolver, nspawn does not manage it (--resolv-conf=off)
+        let resolv_conf_path = rootfs.join(DEFAULT_RESOLV_CONF_LOCATION);
+        fs::remove_file(&resolv_conf_path).ok();
+        create_parent_dir(&resolv_conf_path)?;
+        fs::write(
+            resolv_conf_path,
+            config
+                .dns_servers
+                .iter()
+                .map(|x| format!("nameserver {}\n", x))
+                .collect::<String>(),
+        )?;
+    }
+    // write hosts entries
+    let hosts_path = rootfs.join(DEFAULT_HOSTS_LOCATION);
+    let hosts = fs::read_to_string(&hosts_path).ok();
+    if !config.hosts.is_empty() || hosts.is_some() {
+        let hosts = hosts.as_deref().unwrap_or(DEFAULT_HOSTS);
+        create_parent_dir(&hosts_path)?;
+        fs::write(&hosts_path, update_hosts(hosts, &config.hosts))?;
     }
     // write acbs configuration
     let mut acbs_path = rootfs.to_owned();
@@ -268,4 +1349,147 @@ fn test_validate_maintainer() {
         validate_maintainer(&"test <aosc@aosc.io;".to_owned()),
         Err("Invalid format.".to_owned())
     );
+    assert_eq!(
+        split_maintainer("Bot <null@aosc.io>"),
+        Some(("Bot", "null@aosc.io"))
+    );
+    assert_eq!(split_maintainer("Bot"), None);
+    assert_eq!(split_maintainer(" <null@aosc.io>"), None);
+}
+
+#[test]
+fn test_bind_mount_option() {
+    let mount = BindMount {
+        source: "/".to_string(),
+        target: "/mnt/host".to_string(),
+        read_only: true,
+    };
+    assert_eq!(mount.nspawn_option().unwrap(), "--bind-ro=/:/mnt/host");
+    let mount = BindMount {
+        target: "mnt".to_string(),
+        ..mount
+    };
+    assert!(mount.nspawn_option().is_err());
+}
+
+#[test]
+fn test_network_mode() {
+    let overrides: InstanceConfig =
+        toml::from_str("[network]\nmode = \"macvlan\"\ninterface = \"eth0\"\n").unwrap();
+    let mut config = CielConfig::default();
+    assert!(config.network.nspawn_options().unwrap().is_empty());
+    overrides.apply_to(&mut config);
+    assert_eq!(
+        config.network.nspawn_options().unwrap(),
+        vec!["--network-macvlan=eth0"]
+    );
+}
+
+#[test]
+fn test_update_hosts() {
+    let hosts = "127.0.0.1 localhost\n# BEGIN ciel\n10.0.0.1 old.example\n# END ciel\n";
+    assert_eq!(
+        update_hosts(hosts, &["10.0.0.2 mirror.example".to_string()]),
+        "127.0.0.1 localhost\n# BEGIN ciel\n10.0.0.2 mirror.example\n# END ciel\n"
+    );
+    assert_eq!(update_hosts(hosts, &[]), "127.0.0.1 localhost\n");
+}
+
+#[test]
+fn test_remote_compile_overrides() {
+    let config = RemoteCompileConfig {
+        kind: RemoteCompileKind::Distcc,
+        endpoints: vec!["192.168.1.2/8".to_owned(), "localhost".to_owned()],
+        token: None,
+        jobs: Some(16),
+    };
+    assert!(config.enabled());
+    assert_eq!(
+        config.autobuild_overrides(),
+        "\nexport DISTCC_HOSTS=\"192.168.1.2/8 localhost\"\nexport PATH=\"/usr/lib/distcc:$PATH\"\nABTHREADS=16"
+    );
+    assert!(!RemoteCompileConfig::default().enabled());
+}
+
+#[test]
+fn test_instance_config_overrides() {
+    let mut config = CielConfig::default();
+    let overrides: InstanceConfig = toml::from_str(
+        "branch = \"stable\"\nextra-repos = [\"deb https://example.com/debs/ testing main\"]\n",
+    )
+    .unwrap();
+    overrides.apply_to(&mut config);
+    assert_eq!(config.branch.as_deref(), Some("stable"));
+    assert_eq!(config.extra_repos.len(), 1);
+    assert_eq!(config.apt_sources, DEFAULT_APT_SOURCE);
+}
+
+#[test]
+fn test_set_mirror() {
+    let mut config = CielConfig::default();
+    config.set_mirror(Some("https://mirrors.example.com/anthon"));
+    assert_eq!(
+        config.apt_sources,
+        "deb https://mirrors.example.com/anthon/debs/ stable main"
+    );
+    assert_eq!(
+        config.releases_url(),
+        "https://mirrors.example.com/anthon/aosc-os/"
+    );
+    config.set_mirror(None);
+    assert_eq!(config.apt_sources, DEFAULT_APT_SOURCE);
+}
+
+#[test]
+fn test_resource_limits_overrides() {
+    let mut config = CielConfig::default();
+    config.resources.cpu_quota = Some("400%".to_string());
+    config.resources.io_weight = Some(50);
+    let overrides: InstanceConfig =
+        toml::from_str("[resources]\nmemory-max = \"16G\"\ncpu-quota = \"800%\"\n").unwrap();
+    overrides.apply_to(&mut config);
+    assert_eq!(
+        config.resources.properties(),
+        vec!["CPUQuota=800%", "MemoryMax=16G", "IOWeight=50"]
+    );
+}
+
+#[test]
+fn test_validate_tmpfs_size() {
+    assert!(validate_tmpfs_size("16G").is_ok());
+    assert!(validate_tmpfs_size("50%").is_ok());
+    assert!(validate_tmpfs_size("1048576").is_ok());
+    assert!(validate_tmpfs_size("G").is_err());
+    assert!(validate_tmpfs_size("16G,exec").is_err());
+}
+
+#[test]
+fn test_free_space_requirements() {
+    let config: FreeSpaceConfig =
+        toml::from_str("build = \"4G\"\n[packages]\nllvm = \"60G\"\n").unwrap();
+    assert_eq!(config.update_requirement().unwrap(), 2 * 1024 * 1024 * 1024);
+    assert_eq!(
+        config.build_requirement("runtime-devel/llvm").unwrap(),
+        60 * 1024 * 1024 * 1024
+    );
+    assert_eq!(
+        config.build_requirement("bash").unwrap(),
+        4 * 1024 * 1024 * 1024
+    );
+}
+
+#[test]
+fn test_jobs_for_package() {
+    let config: JobsConfig = toml::from_str(
+        "default = 8
+[packages]
+firefox = 4
+",
+    )
+    .unwrap();
+    assert_eq!(config.package_override("web-browser/firefox"), Some(4));
+    assert!(config.package_override("bash").is_none());
+    assert!(config.validate().is_ok());
+    let config: JobsConfig = toml::from_str("[packages]\nfirefox = 0\n").unwrap();
+    assert!(config.validate().is_err());
 }
@@ -0,0 +1,305 @@
+//! REST API daemon for controlling Ciel remotely (e.g. from web dashboards)
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
+
+use crate::{common::CIEL_DATA_DIR, error, info, machine};
+
+/// Directory of the build logs of the builds triggered using the API
+const DAEMON_LOG_DIR: &str = "daemon";
+const LOG_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Where the daemon listens on
+pub enum Listen<'a> {
+    Unix(&'a Path),
+    Tcp(&'a str),
+}
+
+#[derive(Debug, Deserialize)]
+struct BuildRequest {
+    instance: String,
+    packages: Vec<String>,
+    #[serde(default)]
+    offline: bool,
+    #[serde(default)]
+    stage2: bool,
+}
+
+/// A build triggered using the API
+#[derive(Debug, Clone, Serialize)]
+struct BuildJob {
+    id: usize,
+    instance: String,
+    packages: Vec<String>,
+    started: u64,
+    /// Exit status of the build, `None` if the build is still running
+    status: Option<i32>,
+    #[serde(skip)]
+    log: PathBuf,
+}
+
+#[derive(Default)]
+struct DaemonState {
+    token: Option<String>,
+    builds: Mutex<Vec<BuildJob>>,
+}
+
+impl DaemonState {
+    fn find_build(&self, id: usize) -> Option<BuildJob> {
+        self.builds
+            .lock()
+            .ok()?
+            .iter()
+            .find(|x| x.id == id)
+            .cloned()
+    }
+
+    fn is_running(&self, id: usize) -> bool {
+        self.find_build(id).is_some_and(|x| x.status.is_none())
+    }
+}
+
+/// Follows the log file of a running build (like `tail -f`) until the build finishes
+struct LogFollower {
+    file: File,
+    id: usize,
+    state: Arc<DaemonState>,
+}
+
+impl Read for LogFollower {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let running = self.state.is_running(self.id);
+            let size = self.file.read(buf)?;
+            if size > 0 || !running {
+                return Ok(size);
+            }
+            thread::sleep(LOG_POLL_INTERVAL);
+        }
+    }
+}
+
+type ApiResponse = Response<Box<dyn Read + Send>>;
+
+fn json_response<T: Serialize>(code: u16, value: &T) -> Result<ApiResponse> {
+    let data = serde_json::to_vec(value)?;
+    let header = Header::from_bytes("Content-Type", "application/json")
+        .map_err(|_| anyhow!("Invalid header"))?;
+
+    Ok(Response::new(
+        StatusCode(code),
+        vec![header],
+        Box::new(io::Cursor::new(data)),
+        None,
+        None,
+    ))
+}
+
+fn error_response(code: u16, message: &str) -> Result<ApiResponse> {
+    json_response(code, &serde_json::json!({ "error": message }))
+}
+
+/// Compare the secrets in constant time, so that the time taken does not reveal
+/// how many leading bytes match
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn is_authorized(state: &DaemonState, request: &Request) -> bool {
+    let token = match &state.token {
+        Some(token) => token,
+        None => return true,
+    };
+    request.headers().iter().any(|x| {
+        x.field.equiv("Authorization")
+            && x.value
+                .as_str()
+                .strip_prefix("Bearer ")
+                .is_some_and(|value| constant_time_eq(value.trim().as_bytes(), token.as_bytes()))
+    })
+}
+
+fn start_build(state: &Arc<DaemonState>, request: BuildRequest) -> Result<ApiResponse> {
+    if request.packages.is_empty() {
+        return error_response(400, "No packages specified");
+    }
+    if !crate::common::is_instance_exists(&request.instance) {
+        return error_response(404, "Instance does not exist");
+    }
+    let mut builds = state
+        .builds
+        .lock()
+        .map_err(|_| anyhow!("Build list lock poisoned"))?;
+    if builds
+        .iter()
+        .any(|x| x.instance == request.instance && x.status.is_none())
+    {
+        return error_response(409, "A build is already running in this instance");
+    }
+    let id = builds.len() + 1;
+    let log_dir = Path::new(CIEL_DATA_DIR).join(DAEMON_LOG_DIR);
+    fs::create_dir_all(&log_dir)?;
+    let log = log_dir.join(format!("{}.log", id));
+    let output = File::create(&log)?;
+    let mut command = Command::new(std::env::current_exe()?);
+    command.args(["build", "-i", &request.instance]);
+    if request.offline {
+        command.arg("--offline");
+    }
+    if request.stage2 {
+        command.arg("--stage2");
+    }
+    let mut child = command
+        .arg("--")
+        .args(&request.packages)
+        .stdin(Stdio::null())
+        .stdout(output.try_clone()?)
+        .stderr(output)
+        .spawn()?;
+    let job = BuildJob {
+        id,
+        instance: request.instance,
+        packages: request.packages,
+        started: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        status: None,
+        log,
+    };
+    info!("Build #{} started in {}", id, job.instance);
+    builds.push(job.clone());
+    drop(builds);
+    let state = state.clone();
+    thread::spawn(move || {
+        let status = child.wait().map_or(-1, |x| x.code().unwrap_or(127));
+        info!("Build #{} finished with status {}", id, status);
+        if let Ok(mut builds) = state.builds.lock() {
+            if let Some(job) = builds.iter_mut().find(|x| x.id == id) {
+                job.status = Some(status);
+            }
+        }
+    });
+
+    json_response(202, &job)
+}
+
+fn route(state: &Arc<DaemonState>, request: &mut Request) -> Result<ApiResponse> {
+    if !is_authorized(state, request) {
+        return error_response(401, "Unauthorized");
+    }
+    let url = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let segments = url.split('/').filter(|x| !x.is_empty()).collect::<Vec<_>>();
+    let method = request.method().clone();
+    match (method, segments.as_slice()) {
+        (Method::Get, ["instances"]) => json_response(200, &machine::list_instances()?),
+        (Method::Get, ["builds"]) => {
+            let builds = state
+                .builds
+                .lock()
+                .map_err(|_| anyhow!("Build list lock poisoned"))?
+                .clone();
+            json_response(200, &builds)
+        }
+        (Method::Post, ["builds"]) => {
+            let build: BuildRequest = match serde_json::from_reader(request.as_reader()) {
+                Ok(build) => build,
+                Err(e) => return error_response(400, &e.to_string()),
+            };
+            start_build(state, build)
+        }
+        (Method::Get, ["builds", id]) => match id.parse().ok().and_then(|x| state.find_build(x)) {
+            Some(job) => json_response(200, &job),
+            None => error_response(404, "No such build"),
+        },
+        (Method::Get, ["builds", id, "log"]) => {
+            let job = match id.parse().ok().and_then(|x| state.find_build(x)) {
+                Some(job) => job,
+                None => return error_response(404, "No such build"),
+            };
+            let header = Header::from_bytes("Content-Type", "text/plain; charset=utf-8")
+                .map_err(|_| anyhow!("Invalid header"))?;
+            let follower = LogFollower {
+                file: File::open(&job.log)?,
+                id: job.id,
+                state: state.clone(),
+            };
+            // the length is unknown, so the log is streamed using chunked encoding
+            Ok(Response::new(
+                StatusCode(200),
+                vec![header],
+                Box::new(follower),
+                None,
+                None,
+            ))
+        }
+        _ => error_response(404, "Not found"),
+    }
+}
+
+fn handle_request(state: &Arc<DaemonState>, mut request: Request) -> Result<()> {
+    let response = match route(state, &mut request) {
+        Ok(response) => response,
+        Err(e) => error_response(500, &e.to_string())?,
+    };
+
+    Ok(request.respond(response)?)
+}
+
+/// Serve the REST API until interrupted
+pub fn run_daemon(listen: Listen, token: Option<String>) -> Result<()> {
+    let server = match listen {
+        Listen::Tcp(addr) => {
+            if token.is_none() {
+                bail!("A token is required when listening on TCP.");
+            }
+            info!("Listening on http://{}/", addr);
+            Server::http(addr).map_err(|e| anyhow!("Unable to listen on {}: {}", addr, e))?
+        }
+        Listen::Unix(path) => {
+            fs::remove_file(path).ok();
+            info!("Listening on {}", path.display());
+            Server::http_unix(path)
+                .map_err(|e| anyhow!("Unable to listen on {}: {}", path.display(), e))?
+        }
+    };
+    let state = Arc::new(DaemonState {
+        token,
+        ..Default::default()
+    });
+    for request in server.incoming_requests() {
+        let state = state.clone();
+        thread::spawn(move || {
+            if let Err(e) = handle_request(&state, request) {
+                error!("Failed to handle the request: {}", e);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_constant_time_eq() {
+    assert!(constant_time_eq(b"secret", b"secret"));
+    assert!(!constant_time_eq(b"secret", b"secreT"));
+    assert!(!constant_time_eq(b"secret", b"secret2"));
+    assert!(!constant_time_eq(b"", b"secret"));
+}
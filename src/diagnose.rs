@@ -2,11 +2,19 @@ use anyhow::{anyhow, bail, Result};
 use console::style;
 use fs3::statvfs;
 use indicatif::HumanBytes;
+use nix::mount::{umount2, MntFlags};
+use serde::Serialize;
 use std::env;
+use std::process::Command;
 use std::sync::mpsc::channel;
-use std::{fs::File, io::BufRead, time::Duration};
 use std::{
-    io::{BufReader, Write},
+    fs::{self, File},
+    io::BufRead,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use std::{
+    io::{BufReader, Read, Seek, Write},
     thread,
 };
 use tempfile::tempfile_in;
@@ -14,21 +22,71 @@ use which::which;
 use zbus::blocking::Connection;
 use zbus::proxy;
 
+use crate::backend::{get_backend, BackendKind};
+use crate::config::read_config;
+use crate::dbus_machine1::ManagerProxyBlocking;
+use crate::dbus_machine1_machine::MachineProxyBlocking;
 use crate::error;
+use crate::logging::{json_output, print_json};
+use crate::machine::{
+    execute_container_command_with_output, list_instances, list_instances_simple,
+};
+use crate::overlayfs::fuse_unmount;
 
 const TEST_TEXT: &[u8] = b"An-An was born a rabbit, but found herself a girl with bunny ears and tails when she woke up one day. She couldn't seem to remember why.";
-const TEST_PROGRAMS: &[&str] = &["systemd-nspawn", "systemd-run", "unsquashfs"];
-const TEST_CASES: &[&dyn Fn() -> Result<String>] = &[
-    &test_sd_bus,
-    &test_io_simple,
-    &test_required_binaries,
-    &test_fs_support,
-    &test_vm_container,
-    &test_disk_io,
-    &test_disk_space,
-    &test_editor,
+const TEST_PROGRAMS: &[&str] = &["unsquashfs"];
+const TEST_NSPAWN_PROGRAMS: &[&str] = &["systemd-nspawn", "systemd-run"];
+const TEST_PODMAN_PROGRAMS: &[&str] = &["podman"];
+const TEST_VM_PROGRAMS: &[&str] = &["systemd-vmspawn", "ssh", "ssh-keygen"];
+const TEST_ROOTLESS_PROGRAMS: &[&str] = &["podman", "fuse-overlayfs", "fusermount3"];
+/// Oldest systemd version known to work with Ciel
+const MIN_SYSTEMD_VERSION: usize = 240;
+const BINFMT_MISC_DIR: &str = "/proc/sys/fs/binfmt_misc";
+/// IFF_UP flag of the network interfaces
+const IFF_UP: u32 = 0x1;
+const CHECKS: &[Check] = &[
+    Check::new("systemd", &test_sd_bus),
+    Check::new("io", &test_io_simple),
+    Check::new("binaries", &test_required_binaries),
+    Check::new("overlayfs", &test_fs_support).with_fix(&fix_fs_support),
+    Check::new("environment", &test_vm_container),
+    Check::new("binfmt", &test_binfmt).with_fix(&fix_binfmt),
+    Check::new("disk-io", &test_disk_io),
+    Check::new("disk-space", &test_disk_space),
+    Check::new("stale-mounts", &test_stale_mounts).with_fix(&fix_stale_mounts),
+    Check::new("orphaned-machines", &test_orphaned_machines).with_fix(&fix_orphaned_machines),
+    Check::new("leftover-scopes", &test_leftover_scopes).with_fix(&fix_leftover_scopes),
+    Check::new("loopback", &test_container_loopback).with_fix(&fix_container_loopback),
+    Check::new("editor", &test_editor),
 ];
 
+type CheckFn = dyn Fn() -> Result<String> + Sync;
+
+/// A diagnostic check, with an optional remediation for the issues it may find
+struct Check {
+    name: &'static str,
+    test: &'static CheckFn,
+    /// Only safe remediations are provided
+    fix: Option<&'static CheckFn>,
+}
+
+impl Check {
+    const fn new(name: &'static str, test: &'static CheckFn) -> Self {
+        Check {
+            name,
+            test,
+            fix: None,
+        }
+    }
+
+    const fn with_fix(self, fix: &'static CheckFn) -> Self {
+        Check {
+            fix: Some(fix),
+            ..self
+        }
+    }
+}
+
 #[proxy(
     interface = "org.freedesktop.systemd1.Manager",
     default_service = "org.freedesktop.systemd1",
@@ -45,9 +103,25 @@ trait Systemd1Manager {
 }
 
 fn test_sd_bus() -> Result<String> {
+    if read_config().is_ok_and(|c| c.backend != BackendKind::Nspawn) {
+        return Ok(
+            "!Systemd D-Bus test skipped (not required by the container backend)".to_string(),
+        );
+    }
     let conn = Connection::system()?;
     let proxy = Systemd1ManagerProxyBlocking::new(&conn)?;
     let version: String = proxy.version()?;
+    let major = version
+        .split(|c: char| !c.is_ascii_digit())
+        .next()
+        .and_then(|x| x.parse::<usize>().ok())
+        .unwrap_or_default();
+    if major < MIN_SYSTEMD_VERSION {
+        return Ok(format!(
+            "!systemd {} is too old, at least systemd {} is recommended",
+            version, MIN_SYSTEMD_VERSION
+        ));
+    }
     Ok(format!(
         "Systemd D-Bus (systemd {}) seems to be working",
         version
@@ -60,7 +134,13 @@ fn test_io_simple() -> Result<String> {
 }
 
 fn test_required_binaries() -> Result<String> {
-    for binary in TEST_PROGRAMS {
+    let backend_programs = match read_config() {
+        Ok(c) if c.rootless => TEST_ROOTLESS_PROGRAMS,
+        Ok(c) if c.backend == BackendKind::Podman => TEST_PODMAN_PROGRAMS,
+        Ok(c) if c.backend == BackendKind::Vm => TEST_VM_PROGRAMS,
+        _ => TEST_NSPAWN_PROGRAMS,
+    };
+    for binary in TEST_PROGRAMS.iter().chain(backend_programs) {
         if which(binary).is_err() {
             return Err(anyhow!("Required program `{}` is not found", binary));
         }
@@ -86,7 +166,15 @@ fn test_fs_support() -> Result<String> {
     ))
 }
 
+fn fix_fs_support() -> Result<String> {
+    run_command(Command::new("modprobe").arg("overlay"))?;
+    Ok("Loaded the overlay kernel module".to_string())
+}
+
 fn test_vm_container() -> Result<String> {
+    if read_config().is_ok_and(|c| c.backend != BackendKind::Nspawn) {
+        return Ok("Environment check skipped (not required by the container backend)".to_string());
+    }
     let conn = Connection::system()?;
     let proxy = Systemd1ManagerProxyBlocking::new(&conn)?;
     let virt: String = proxy.virtualization()?;
@@ -135,6 +223,300 @@ fn test_disk_space() -> Result<String> {
     }
 }
 
+fn test_binfmt() -> Result<String> {
+    let status = Path::new(BINFMT_MISC_DIR).join("status");
+    if !status.exists() {
+        return Ok(
+            "!binfmt_misc is not mounted, foreign architecture instances will not work".to_string(),
+        );
+    }
+    if fs::read_to_string(&status)?.trim() != "enabled" {
+        return Ok(
+            "!binfmt_misc is disabled, foreign architecture instances will not work".to_string(),
+        );
+    }
+    let registered = fs::read_dir(BINFMT_MISC_DIR)?
+        .filter_map(|x| x.ok())
+        .filter(|x| x.file_name() != "register" && x.file_name() != "status")
+        .count();
+    Ok(format!(
+        "binfmt_misc is enabled ({} interpreters registered)",
+        registered
+    ))
+}
+
+fn fix_binfmt() -> Result<String> {
+    let status = Path::new(BINFMT_MISC_DIR).join("status");
+    if !status.exists() {
+        run_command(Command::new("mount").args([
+            "-t",
+            "binfmt_misc",
+            "binfmt_misc",
+            BINFMT_MISC_DIR,
+        ]))?;
+    }
+    fs::write(&status, "1")?;
+    Ok("Mounted and enabled binfmt_misc".to_string())
+}
+
+/// Decode the octal escapes in /proc/self/mountinfo (e.g. `\040` for spaces)
+fn decode_mount_path(path: &str) -> PathBuf {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 3 < bytes.len() {
+            let octal = std::str::from_utf8(&bytes[i + 1..i + 4]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(octal, 8) {
+                decoded.push(byte);
+                i += 4;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    PathBuf::from(String::from_utf8_lossy(&decoded).to_string())
+}
+
+/// Mount points inside the workspace that do not belong to any instance or are inaccessible
+pub(crate) fn find_stale_mounts() -> Result<Vec<PathBuf>> {
+    let workspace = env::current_dir()?;
+    let instances = list_instances_simple()?;
+    let mountinfo = fs::read_to_string("/proc/self/mountinfo")?;
+    let mut stale = mountinfo
+        .lines()
+        .filter_map(|x| x.split(' ').nth(4))
+        .map(decode_mount_path)
+        .filter(|x| {
+            let relative = match x.strip_prefix(&workspace) {
+                Ok(relative) => relative,
+                Err(_) => return false,
+            };
+            // the bind mounts inside the instances are checked along with the instances
+            let top = match relative.components().next() {
+                Some(top) => top.as_os_str().to_string_lossy().to_string(),
+                None => return false,
+            };
+            // the filesystem backends may mount the layers inside the workspace directory
+            if top == ".ciel" {
+                return false;
+            }
+            !instances.contains(&top) || fs::metadata(x).is_err()
+        })
+        .collect::<Vec<_>>();
+    // unmount the innermost ones first
+    stale.sort_by_key(|x| std::cmp::Reverse(x.components().count()));
+    stale.dedup();
+
+    Ok(stale)
+}
+
+fn test_stale_mounts() -> Result<String> {
+    let stale = find_stale_mounts()?;
+    if stale.is_empty() {
+        return Ok("No stale mounts found".to_string());
+    }
+    Ok(format!(
+        "!Found {} stale mount(s): {}",
+        stale.len(),
+        stale
+            .iter()
+            .map(|x| x.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+pub(crate) fn fix_stale_mounts() -> Result<String> {
+    let stale = find_stale_mounts()?;
+    for mount in stale.iter() {
+        // FUSE mounts (rootless mode) can't be unmounted by unprivileged users otherwise
+        if umount2(mount, MntFlags::MNT_DETACH).is_err() {
+            fuse_unmount(mount)?;
+        }
+    }
+    Ok(format!("Unmounted {} stale mount(s)", stale.len()))
+}
+
+/// Machines running from the workspace directory that do not belong to any instance,
+/// or whose leader process is gone (half-registered machines)
+pub(crate) fn find_orphaned_machines() -> Result<Vec<String>> {
+    if read_config().is_ok_and(|c| c.rootless || c.backend != BackendKind::Nspawn) {
+        return Ok(Vec::new());
+    }
+    let workspace = env::current_dir()?;
+    let instances = list_instances_simple()?;
+    let conn = Connection::system()?;
+    let proxy = ManagerProxyBlocking::new(&conn)?;
+    let mut orphaned = Vec::new();
+    for (name, _, _, path) in proxy.list_machines()? {
+        let machine = MachineProxyBlocking::builder(&conn).path(&path)?.build()?;
+        let root = PathBuf::from(machine.root_directory().unwrap_or_default());
+        if root.parent() != Some(workspace.as_path()) {
+            continue;
+        }
+        let instance = root
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let alive = machine
+            .leader()
+            .is_ok_and(|x| Path::new(&format!("/proc/{}", x)).exists());
+        if !instances.contains(&instance) || !alive {
+            orphaned.push(name);
+        }
+    }
+
+    Ok(orphaned)
+}
+
+fn test_orphaned_machines() -> Result<String> {
+    let orphaned = find_orphaned_machines()?;
+    if orphaned.is_empty() {
+        return Ok("No orphaned containers found".to_string());
+    }
+    Ok(format!(
+        "!Found {} orphaned container(s): {}",
+        orphaned.len(),
+        orphaned.join(", ")
+    ))
+}
+
+pub(crate) fn fix_orphaned_machines() -> Result<String> {
+    let orphaned = find_orphaned_machines()?;
+    let backend = get_backend();
+    for machine in orphaned.iter() {
+        if backend.terminate(machine).is_err() {
+            // the machine is dead already, just unregister it
+            let conn = Connection::system()?;
+            ManagerProxyBlocking::new(&conn)?.unregister_machine(machine)?;
+        }
+    }
+    Ok(format!(
+        "Terminated {} orphaned container(s)",
+        orphaned.len()
+    ))
+}
+
+/// Failed machine scopes left behind by the crashed containers
+fn find_leftover_scopes() -> Result<Vec<String>> {
+    if read_config().is_ok_and(|c| c.rootless || c.backend != BackendKind::Nspawn) {
+        return Ok(Vec::new());
+    }
+    let output = Command::new("systemctl")
+        .args([
+            "list-units",
+            "--type=scope",
+            "--state=failed,abandoned",
+            "--all",
+            "--plain",
+            "--no-legend",
+            "machine-*.scope",
+        ])
+        .output()?;
+    if !output.status.success() {
+        bail!("systemctl exited with error: {}", output.status);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|x| x.split_whitespace().next())
+        .map(|x| x.to_string())
+        .collect())
+}
+
+fn test_leftover_scopes() -> Result<String> {
+    let scopes = find_leftover_scopes()?;
+    if scopes.is_empty() {
+        return Ok("No leftover container scopes found".to_string());
+    }
+    Ok(format!(
+        "!Found {} leftover container scope(s): {}",
+        scopes.len(),
+        scopes.join(", ")
+    ))
+}
+
+pub(crate) fn fix_leftover_scopes() -> Result<String> {
+    let scopes = find_leftover_scopes()?;
+    for scope in scopes.iter() {
+        // abandoned scopes need to be stopped before they can be cleared
+        Command::new("systemctl").args(["stop", scope]).status()?;
+        Command::new("systemctl")
+            .args(["reset-failed", scope])
+            .status()?;
+    }
+    Ok(format!(
+        "Cleared {} leftover container scope(s)",
+        scopes.len()
+    ))
+}
+
+/// Running instances whose loopback interface is down
+fn find_loopback_down() -> Result<Vec<String>> {
+    let mut down = Vec::new();
+    for instance in list_instances()?.into_iter().filter(|x| x.started) {
+        let mut output = tempfile_in(".")?;
+        let status = execute_container_command_with_output(
+            &instance.ns_name,
+            &["cat", "/sys/class/net/lo/flags"],
+            Some(&output),
+        )?;
+        let mut flags = String::new();
+        output.rewind()?;
+        output.read_to_string(&mut flags)?;
+        let flags = u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16).unwrap_or(0);
+        if status != 0 || flags & IFF_UP == 0 {
+            down.push(instance.ns_name);
+        }
+    }
+
+    Ok(down)
+}
+
+fn test_container_loopback() -> Result<String> {
+    let down = find_loopback_down()?;
+    if down.is_empty() {
+        return Ok("Loopback networking in the running containers seems to be working".to_string());
+    }
+    Ok(format!(
+        "!Loopback interface is down in {}, builds that use localhost may fail",
+        down.join(", ")
+    ))
+}
+
+fn fix_container_loopback() -> Result<String> {
+    let down = find_loopback_down()?;
+    for ns_name in down.iter() {
+        let status = execute_container_command_with_output(
+            ns_name,
+            &["ip", "link", "set", "lo", "up"],
+            None,
+        )?;
+        if status != 0 {
+            bail!("Unable to bring up the loopback interface in {}", ns_name);
+        }
+    }
+    Ok(format!(
+        "Brought up the loopback interface in {} container(s)",
+        down.len()
+    ))
+}
+
+fn run_command(command: &mut Command) -> Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let status = command
+        .status()
+        .map_err(|e| anyhow!("Unable to execute {}: {}", program, e))?;
+    if !status.success() {
+        bail!("{} exited with error: {}", program, status);
+    }
+
+    Ok(())
+}
+
 fn test_editor() -> Result<String> {
     let editor_env = env::var("EDITOR");
     let editor_path = which::which("editor");
@@ -148,37 +530,91 @@ fn test_editor() -> Result<String> {
     }
 }
 
-/// Carry out the diagnostic tests
-pub fn run_diagnose() -> Result<()> {
-    let mut lines = vec![];
-    let mut has_error = false;
-    for test in TEST_CASES {
-        match test() {
-            Ok(msg) => {
-                if msg.starts_with('!') {
-                    lines.push(format!(
-                        "{} {}",
-                        style("!").yellow(),
-                        style(msg.strip_prefix('!').unwrap()).yellow().bold()
-                    ));
-                    continue;
+/// Outcome of a diagnostic test
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum TestStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Serialize)]
+struct TestResult {
+    name: &'static str,
+    status: TestStatus,
+    message: String,
+    /// Remediation applied (`--fix`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<String>,
+}
+
+impl TestResult {
+    fn from_result(name: &'static str, result: Result<String>) -> Self {
+        let (status, message) = match result {
+            Ok(msg) => match msg.strip_prefix('!') {
+                Some(msg) => (TestStatus::Warning, msg.to_string()),
+                None => (TestStatus::Ok, msg),
+            },
+            Err(err) => (TestStatus::Error, err.to_string()),
+        };
+
+        TestResult {
+            name,
+            status,
+            message,
+            fix: None,
+        }
+    }
+}
+
+/// Carry out the diagnostic tests, remediate the issues found if `fix` is true
+pub fn run_diagnose(fix: bool) -> Result<()> {
+    let mut results = vec![];
+    for check in CHECKS {
+        let mut result = TestResult::from_result(check.name, (check.test)());
+        let failed = !matches!(result.status, TestStatus::Ok);
+        if let Some(remediate) = check.fix.filter(|_| fix && failed) {
+            match remediate() {
+                Ok(msg) => {
+                    // check again to see if the issue is resolved
+                    result = TestResult::from_result(check.name, (check.test)());
+                    result.fix = Some(msg);
                 }
-                lines.push(format!(
-                    "{} {}",
-                    style("✓").green(),
-                    style(msg).green().bold()
-                ))
-            }
-            Err(err) => {
-                has_error = true;
-                lines.push(format!("{} {}", style("x").red(), style(err).red().bold()));
-                break;
+                Err(err) => result.fix = Some(format!("Failed to fix the issue: {}", err)),
             }
         }
+        results.push(result);
     }
+    let has_error = results
+        .iter()
+        .any(|x| matches!(x.status, TestStatus::Error));
 
-    for line in lines {
-        println!("{}", line);
+    if json_output() {
+        print_json(&results)?;
+    } else {
+        for result in results {
+            if let Some(fix) = &result.fix {
+                println!("{} {}", style("*").cyan(), style(fix).cyan());
+            }
+            match result.status {
+                TestStatus::Ok => println!(
+                    "{} {}",
+                    style("✓").green(),
+                    style(result.message).green().bold()
+                ),
+                TestStatus::Warning => println!(
+                    "{} {}",
+                    style("!").yellow(),
+                    style(result.message).yellow().bold()
+                ),
+                TestStatus::Error => println!(
+                    "{} {}",
+                    style("x").red(),
+                    style(result.message).red().bold()
+                ),
+            }
+        }
     }
     if has_error {
         return Err(anyhow!("Test error detected"));
@@ -186,3 +622,12 @@ pub fn run_diagnose() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_decode_mount_path() {
+    assert_eq!(
+        decode_mount_path("/home/user/my\\040workspace/alpine"),
+        PathBuf::from("/home/user/my workspace/alpine")
+    );
+    assert_eq!(decode_mount_path("/tmp\\"), PathBuf::from("/tmp\\"));
+}
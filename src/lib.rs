@@ -0,0 +1,23 @@
+//! Ciel core library: workspace, instance, build and repository management.
+//!
+//! The `ciel` command line tool is a thin wrapper around this crate,
+//! other tools (e.g. buildbots) can use the same operations without spawning `ciel`.
+//! All the operations expect the current directory to be the workspace root.
+
+pub mod actions;
+pub mod backend;
+pub mod common;
+pub mod config;
+pub mod daemon;
+mod dbus_machine1;
+mod dbus_machine1_machine;
+pub mod diagnose;
+pub mod lock;
+pub mod logging;
+pub mod machine;
+pub mod mirror;
+pub mod network;
+pub mod overlayfs;
+mod qemu;
+pub mod repo;
+pub mod tui;
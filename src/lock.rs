@@ -0,0 +1,141 @@
+//! Workspace and instance locks, so that concurrent ciel invocations (e.g. a scheduled
+//! `update-os` and a manual build) do not modify the same data at the same time
+
+use anyhow::{bail, Result};
+use console::style;
+use nix::{
+    errno::Errno,
+    fcntl::{Flock, FlockArg},
+};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::info;
+
+/// Held by every command using the workspace, exclusively by the ones modifying the base OS
+const WORKSPACE_LOCK: &str = ".ciel/lock";
+/// `<instance>.lock`, held exclusively by the commands modifying the instance
+const INSTANCE_LOCK_DIR: &str = ".ciel/locks";
+/// Set for the processes started while holding the locks (e.g. hooks calling ciel), they use the locks
+/// of their parent instead of waiting for them
+const LOCK_HELD_ENV: &str = "CIEL_LOCK_HELD";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    Shared,
+    Exclusive,
+}
+
+/// Locks held until dropped (or until the process exits)
+pub struct WorkspaceLocks {
+    _workspace: Flock<File>,
+    _instance: Option<Flock<File>>,
+}
+
+#[inline]
+fn instance_lock_path(instance: &str) -> PathBuf {
+    Path::new(INSTANCE_LOCK_DIR).join(format!("{}.lock", instance))
+}
+
+/// The process holding the lock as recorded in the lock file (`<pid> <command line>`),
+/// `None` if it is not running anymore
+fn lock_holder(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let (pid, command) = content.trim().split_once(' ')?;
+    if !Path::new("/proc").join(pid).exists() {
+        return None;
+    }
+
+    Some(format!("PID {}: {}", pid, command))
+}
+
+/// Record this process as the holder of the lock
+fn record_holder(lock: &Flock<File>) -> Result<()> {
+    let command = std::env::args().collect::<Vec<_>>().join(" ");
+    let mut file: &File = lock;
+    file.set_len(0)?;
+    file.write_all(format!("{} {}\n", std::process::id(), command).as_bytes())?;
+
+    Ok(())
+}
+
+/// Acquire the lock, waiting (and telling who holds it) if it is busy
+fn acquire(path: &Path, mode: LockMode, what: &str) -> Result<Flock<File>> {
+    // the holder recorded in the lock file is kept until the lock is acquired
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .read(true)
+        .write(true)
+        .open(path)?;
+    let (nonblocking, blocking) = match mode {
+        LockMode::Shared => (FlockArg::LockSharedNonblock, FlockArg::LockShared),
+        LockMode::Exclusive => (FlockArg::LockExclusiveNonblock, FlockArg::LockExclusive),
+    };
+    let file = match Flock::lock(file, nonblocking) {
+        Ok(lock) => {
+            record_holder(&lock)?;
+            return Ok(lock);
+        }
+        Err((file, Errno::EWOULDBLOCK)) => file,
+        Err((_, e)) => bail!("Unable to lock {}: {}", what, e),
+    };
+    match lock_holder(path) {
+        Some(holder) => {
+            info!("Waiting for {} to be released ({})...", what, holder);
+        }
+        None => {
+            info!("Waiting for {} to be released...", what);
+        }
+    }
+    let lock = match Flock::lock(file, blocking) {
+        Ok(lock) => lock,
+        Err((_, e)) => bail!("Unable to lock {}: {}", what, e),
+    };
+    record_holder(&lock)?;
+
+    Ok(lock)
+}
+
+/// Lock the workspace (`mode`) and the instance (exclusively) for the duration of a command,
+/// `None` if the locks are held by the parent process already
+pub fn lock_workspace(mode: LockMode, instance: Option<&str>) -> Result<Option<WorkspaceLocks>> {
+    if std::env::var_os(LOCK_HELD_ENV).is_some() {
+        return Ok(None);
+    }
+    let workspace = acquire(Path::new(WORKSPACE_LOCK), mode, "the workspace")?;
+    let instance = match instance {
+        Some(instance) => {
+            fs::create_dir_all(INSTANCE_LOCK_DIR)?;
+            Some(acquire(
+                &instance_lock_path(instance),
+                LockMode::Exclusive,
+                &format!("instance {}", instance),
+            )?)
+        }
+        None => None,
+    };
+
+    std::env::set_var(LOCK_HELD_ENV, "1");
+
+    Ok(Some(WorkspaceLocks {
+        _workspace: workspace,
+        _instance: instance,
+    }))
+}
+
+#[test]
+fn test_lock_holder() {
+    let path = std::env::temp_dir().join(format!("ciel-lock-test-{}", std::process::id()));
+    fs::write(&path, format!("{} ciel build foo\n", std::process::id())).unwrap();
+    assert_eq!(
+        lock_holder(&path),
+        Some(format!("PID {}: ciel build foo", std::process::id()))
+    );
+    fs::write(&path, "").unwrap();
+    assert_eq!(lock_holder(&path), None);
+    fs::remove_file(&path).ok();
+}
@@ -1,3 +1,45 @@
+use anyhow::Result;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Whether the informational commands should print machine-readable JSON
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+/// Log format of the CI system (`ciel ci`)
+static CI_FORMAT: AtomicU8 = AtomicU8::new(CiFormat::Plain as u8);
+
+lazy_static! {
+    /// Name of the collapsible section currently open (GitLab needs it to close the section)
+    static ref CURRENT_GROUP: Mutex<Option<String>> = Mutex::new(None);
+}
+
+/// Log formats of the supported CI systems
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiFormat {
+    Plain = 0,
+    GitHub = 1,
+    GitLab = 2,
+}
+
+impl CiFormat {
+    /// Detect the CI system from the environment variables set by the runners
+    pub fn detect() -> Self {
+        if std::env::var("GITHUB_ACTIONS").is_ok_and(|x| x == "true") {
+            CiFormat::GitHub
+        } else if std::env::var("GITLAB_CI").is_ok() {
+            CiFormat::GitLab
+        } else {
+            CiFormat::Plain
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! info {
     ($($arg:tt)+) => {
@@ -30,3 +72,105 @@ pub fn color_bool(pred: bool) -> &'static str {
         "\x1b[34mNo\x1b[0m"
     }
 }
+
+#[inline]
+pub fn set_json_output(enabled: bool) {
+    JSON_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+#[inline]
+pub fn json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Print the value as JSON to stdout
+pub fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+
+    Ok(())
+}
+
+#[inline]
+pub fn set_ci_format(format: CiFormat) {
+    CI_FORMAT.store(format as u8, Ordering::Relaxed);
+}
+
+#[inline]
+pub fn ci_format() -> CiFormat {
+    match CI_FORMAT.load(Ordering::Relaxed) {
+        1 => CiFormat::GitHub,
+        2 => CiFormat::GitLab,
+        _ => CiFormat::Plain,
+    }
+}
+
+/// Start a collapsible section in the CI log, closing the previous one (no-op outside CI)
+pub fn begin_group(title: &str) {
+    end_group();
+    let mut current = match CURRENT_GROUP.lock() {
+        Ok(current) => current,
+        Err(_) => return,
+    };
+    match ci_format() {
+        CiFormat::Plain => return,
+        CiFormat::GitHub => eprintln!("::group::{}", title),
+        CiFormat::GitLab => {
+            let name = title
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect::<String>()
+                .to_ascii_lowercase();
+            eprintln!(
+                "\x1b[0Ksection_start:{}:{}[collapsed=true]\r\x1b[0K{}",
+                unix_time(),
+                name,
+                title
+            );
+            *current = Some(name);
+            return;
+        }
+    }
+    *current = Some(title.to_string());
+}
+
+/// Close the current collapsible section in the CI log (if any)
+pub fn end_group() {
+    let name = match CURRENT_GROUP.lock().ok().and_then(|mut x| x.take()) {
+        Some(name) => name,
+        None => return,
+    };
+    match ci_format() {
+        CiFormat::Plain => (),
+        CiFormat::GitHub => eprintln!("::endgroup::"),
+        CiFormat::GitLab => eprintln!("\x1b[0Ksection_end:{}:{}\r\x1b[0K", unix_time(), name),
+    }
+}
+
+/// Report an error as a problem annotation of the CI system,
+/// `file` is the path (relative to the repository) the problem is attributed to
+pub fn annotate_error(title: &str, message: &str, file: Option<&str>) {
+    match ci_format() {
+        CiFormat::GitHub => {
+            // newlines must be escaped in the workflow commands
+            let message = message
+                .replace('%', "%25")
+                .replace('\r', "%0D")
+                .replace('\n', "%0A");
+            match file {
+                Some(file) => eprintln!("::error file={},title={}::{}", file, title, message),
+                None => eprintln!("::error title={}::{}", title, message),
+            }
+        }
+        // GitLab collects the problems from the Code Quality report instead
+        CiFormat::GitLab | CiFormat::Plain => {
+            eprintln!("\x1b[1;31merror:\x1b[0m {}: {}", title, message)
+        }
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or_default()
+}
@@ -1,41 +1,42 @@
-//! This module contains systemd machined related APIs
+//! This module contains container (machine) management related APIs
 
+use crate::backend::get_container_backend;
 use crate::common::{is_legacy_workspace, CIEL_INST_DIR};
-use crate::dbus_machine1::ManagerProxyBlocking;
-use crate::dbus_machine1_machine::MachineProxyBlocking;
-use crate::overlayfs::is_mounted;
-use crate::{info, overlayfs::LayerManager, warn};
+use crate::overlayfs::get_layer_manager;
+use crate::{overlayfs::LayerManager, warn};
 use adler32::adler32;
 use anyhow::{anyhow, Result};
 use console::style;
 use libc::{c_char, ftok, waitpid, WNOHANG};
-use libsystemd_sys::bus::{sd_bus_flush_close_unref, sd_bus_open_system_machine};
-use std::{
-    ffi::{CString, OsStr},
-    mem::MaybeUninit,
-    process::Command,
-};
-use std::{fs, time::Duration};
-use std::{os::unix::ffi::OsStrExt, process::Child};
-use std::{path::Path, process::Stdio, thread::sleep};
-use zbus::blocking::Connection;
+use serde::Serialize;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
 
-const DEFAULT_NSPAWN_OPTIONS: &[&str] = &[
-    "-qb",
-    "--capability=CAP_IPC_LOCK",
-    "--system-call-filter=swapcontext",
-];
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
 
 /// Instance status information
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct CielInstance {
-    name: String,
+    pub name: String,
     // namespace name (in the form of `$name-$id`)
     pub ns_name: String,
     pub mounted: bool,
-    running: bool,
+    pub running: bool,
     pub started: bool,
-    booted: Option<bool>,
+    pub booted: Option<bool>,
+}
+
+/// Resource usage of a container, read from its cgroup
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResourceUsage {
+    /// CPU time in microseconds
+    pub cpu_time: u64,
+    /// Peak memory usage in bytes (requires Linux 5.19+)
+    pub peak_memory: Option<u64>,
+    /// Number of the processes killed by the OOM killer
+    pub oom_kills: Option<u64>,
 }
 
 /// Used for getting the instance name from Ciel 1/2
@@ -80,64 +81,6 @@ fn new_container_name(path: &Path) -> Result<String> {
     ))
 }
 
-fn try_open_container_bus(ns_name: &str) -> Result<()> {
-    // There are bunch of trickeries happening here
-    // First we initialize an empty pointer
-    let mut buf = MaybeUninit::uninit();
-    // Convert the ns_name to C-style `const char*` (NUL-terminated)
-    let ns_name = CString::new(ns_name)?;
-    // unsafe: these functions are from libsystemd, which involving FFI calls
-    unsafe {
-        // Try opening a connection to the container
-        if sd_bus_open_system_machine(buf.as_mut_ptr(), ns_name.as_ptr()) >= 0 {
-            // If successful, just close the connection and drop the pointer
-            sd_bus_flush_close_unref(buf.assume_init());
-            return Ok(());
-        }
-    }
-
-    Err(anyhow!("Could not open container bus"))
-}
-
-fn wait_for_container(child: &mut Child, ns_name: &str, retry: usize) -> Result<()> {
-    for i in 0..retry {
-        let exited = child.try_wait()?;
-        if let Some(status) = exited {
-            return Err(anyhow!("nspawn exited too early! (Status: {})", status));
-        }
-        // why this is used: because PTY spawning can happen before the systemd in the container
-        // is fully initialized. To spawn a new process in the container, we need the systemd
-        // in the container to be fully initialized and listening for connections.
-        // One way to resolve this issue is to test the connection to the container's systemd.
-        if try_open_container_bus(ns_name).is_ok() {
-            return Ok(());
-        }
-        // wait for a while, sleep time follows a natural-logarithm distribution
-        sleep(Duration::from_secs_f32(((i + 1) as f32).ln().ceil()));
-    }
-
-    Err(anyhow!("Timeout waiting for container {}", ns_name))
-}
-
-/// Setting up cross-namespace bind-mounts for the container using systemd
-fn setup_bind_mounts(ns_name: &str, mounts: &[(String, &str)]) -> Result<()> {
-    let conn = Connection::system()?;
-    let proxy = ManagerProxyBlocking::new(&conn)?;
-    for mount in mounts {
-        fs::create_dir_all(&mount.0)?;
-        let source_path = fs::canonicalize(&mount.0)?;
-        proxy.bind_mount_machine(
-            ns_name,
-            &source_path.to_string_lossy(),
-            mount.1,
-            false,
-            true,
-        )?;
-    }
-
-    Ok(())
-}
-
 /// Get the container name (ns_name) of the instance
 pub fn get_container_ns_name<P: AsRef<Path>>(path: P, legacy: bool) -> Result<String> {
     let current_dir = std::env::current_dir()?;
@@ -151,53 +94,25 @@ pub fn get_container_ns_name<P: AsRef<Path>>(path: P, legacy: bool) -> Result<St
     new_container_name(&path)
 }
 
-/// Spawn a new container using nspawn
+/// Spawn a new container using the configured backend
 pub fn spawn_container<P: AsRef<Path>>(
     ns_name: &str,
     path: P,
     extra_options: &[String],
     mounts: &[(String, &str)],
 ) -> Result<()> {
-    let path = path
-        .as_ref()
-        .to_str()
-        .ok_or_else(|| anyhow!("Path contains invalid Unicode characters."))?;
-    let mut child = Command::new("systemd-nspawn")
-        .args(DEFAULT_NSPAWN_OPTIONS)
-        .args(extra_options)
-        .args(["-D", path, "-M", ns_name, "--"])
-        .env("SYSTEMD_NSPAWN_TMPFS_TMP", "0")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
-
-    info!("{}: waiting for container to start...", ns_name);
-    wait_for_container(&mut child, ns_name, 10)?;
-    info!("{}: setting up mounts...", ns_name);
-    if let Err(e) = setup_bind_mounts(ns_name, mounts) {
-        warn!("Failed to setup bind mounts: {:?}", e);
-    }
-
-    Ok(())
+    get_container_backend(ns_name).spawn(ns_name, path.as_ref(), extra_options, mounts)
 }
 
-/// Execute a command in the container
-pub fn execute_container_command<S: AsRef<OsStr>>(ns_name: &str, args: &[S]) -> Result<i32> {
-    let mut extra_options = vec!["--setenv=HOME=/root".to_string()];
-    if std::env::var("CIEL_STAGE2").is_ok() {
-        extra_options.push("--setenv=ABSTAGE2=1".to_string());
-    }
-    // TODO: maybe replace with systemd API cross-namespace call?
-    let exit_code = Command::new("systemd-run")
-        .args(extra_options)
-        .args(["-M", ns_name, "-qt", "--"])
-        .args(args)
-        .spawn()?
-        .wait()?
-        .code()
-        .unwrap_or(127);
+/// Execute a command in the container, redirecting its output to the specified file (if any)
+pub fn execute_container_command_with_output<S: AsRef<OsStr>>(
+    ns_name: &str,
+    args: &[S],
+    output: Option<&File>,
+) -> Result<i32> {
+    let args = args.iter().map(|x| x.as_ref()).collect::<Vec<_>>();
 
-    Ok(exit_code)
+    get_container_backend(ns_name).execute(ns_name, &args, output)
 }
 
 /// Reap all the exited child processes
@@ -206,97 +121,47 @@ pub(crate) fn clean_child_process() {
     unsafe { waitpid(-1, &mut status, WNOHANG) };
 }
 
-fn kill_container(proxy: &MachineProxyBlocking) -> Result<()> {
-    proxy.kill("all", libc::SIGKILL)?;
-    proxy.terminate()?;
-
-    Ok(())
-}
-
-fn execute_poweroff(ns_name: &str) -> Result<()> {
-    // TODO: maybe replace with systemd API cross-namespace call?
-    let exit_code = Command::new("systemd-run")
-        .args(["-M", ns_name, "-q", "--no-block", "--", "poweroff"])
-        .spawn()?
-        .wait()?
-        .code()
-        .unwrap_or(127);
-
-    if exit_code != 0 {
-        Err(anyhow!("Could not execute shutdown command: {}", exit_code))
-    } else {
-        Ok(())
-    }
-}
-
-fn wait_for_poweroff(proxy: &ManagerProxyBlocking, ns_name: &str) -> Result<()> {
-    for _ in 0..10 {
-        if proxy.get_machine(ns_name).is_err() {
-            // machine object no longer exists
-            return Ok(());
-        }
-        sleep(Duration::from_secs(1));
-    }
-
-    Err(anyhow!("shutdown failed"))
-}
-
-fn is_booted(proxy: &MachineProxyBlocking) -> Result<bool> {
-    let leader_pid = proxy.leader()?;
-    // let's inspect the cmdline of the PID 1 in the container
-    let f = std::fs::read(format!("/proc/{}/cmdline", leader_pid))?;
-    // take until the first null byte
-    let pos: usize = f
-        .iter()
-        .position(|c| *c == 0u8)
-        .ok_or_else(|| anyhow!("Unable to parse the process cmdline of PID 1 in the container"))?;
-    // ... well, of course it's a path
-    let path = Path::new(OsStr::from_bytes(&f[..pos]));
-    let exe_name = path.file_name();
-    // if PID 1 is systemd or init (System V init) then it should be a "booted" container
-    if let Some(exe_name) = exe_name {
-        return Ok(exe_name == "systemd" || exe_name == "init");
-    }
-
-    Ok(false)
-}
-
-fn terminate_container(
-    proxy: &ManagerProxyBlocking,
-    machine_proxy: &MachineProxyBlocking,
-    ns_name: &str,
-) -> Result<()> {
-    let _ = machine_proxy.receive_state_changed();
-    if execute_poweroff(ns_name).is_ok() {
-        // Successfully passed poweroff command to the container, wait for it
-        if wait_for_poweroff(proxy, ns_name).is_ok() {
-            return Ok(());
-        }
-        // still did not poweroff?
-        warn!("Container did not respond to the poweroff command correctly...");
-        warn!("Killing the container by sending SIGKILL...");
-        // fall back to nuke
-    }
-
-    // violently kill everything inside the container
-    kill_container(machine_proxy)?;
-    machine_proxy.terminate().ok();
-    // status re-check, in the event of I/O problems, the container may still be running (stuck)
-    if wait_for_poweroff(proxy, ns_name).is_ok() {
-        return Ok(());
-    }
-
-    Err(anyhow!("Failed to kill the container! This may indicate a problem with your I/O, see dmesg or journalctl for more details."))
-}
-
 /// Terminate the container (Use graceful method if possible)
 pub fn terminate_container_by_name(ns_name: &str) -> Result<()> {
-    let conn = Connection::system()?;
-    let proxy = ManagerProxyBlocking::new(&conn)?;
-    let path = proxy.get_machine(ns_name)?;
-    let machine_proxy = MachineProxyBlocking::builder(&conn).path(&path)?.build()?;
+    get_container_backend(ns_name).terminate(ns_name)
+}
 
-    terminate_container(&proxy, &machine_proxy, ns_name)
+/// Read the resource usage of the running container from the cgroup (v2) accounting
+pub fn container_resource_usage(ns_name: &str) -> Result<ResourceUsage> {
+    let leader = get_container_backend(ns_name)
+        .inspect(ns_name)?
+        .leader
+        .ok_or_else(|| anyhow!("Container {} is not running", ns_name))?;
+    let cgroup = fs::read_to_string(format!("/proc/{}/cgroup", leader))?;
+    let path = cgroup
+        .lines()
+        .find_map(|x| x.strip_prefix("0::"))
+        .ok_or_else(|| anyhow!("Resource accounting requires cgroup v2"))?
+        .trim();
+    // the init system in the container is moved into a sub-cgroup
+    let path = path.strip_suffix("/init.scope").unwrap_or(path);
+    let root = Path::new(CGROUP_ROOT).join(path.trim_start_matches('/'));
+    let cpu_time = fs::read_to_string(root.join("cpu.stat"))?
+        .lines()
+        .find_map(|x| x.strip_prefix("usage_usec "))
+        .and_then(|x| x.trim().parse().ok())
+        .ok_or_else(|| anyhow!("Unable to read the CPU usage of {}", ns_name))?;
+    let peak_memory = fs::read_to_string(root.join("memory.peak"))
+        .ok()
+        .and_then(|x| x.trim().parse().ok());
+    let oom_kills = fs::read_to_string(root.join("memory.events"))
+        .ok()
+        .and_then(|x| {
+            x.lines()
+                .find_map(|x| x.strip_prefix("oom_kill "))
+                .and_then(|x| x.trim().parse().ok())
+        });
+
+    Ok(ResourceUsage {
+        cpu_time,
+        peak_memory,
+        oom_kills,
+    })
 }
 
 /// Mount the filesystem layers using the specified layer manager and the instance name
@@ -313,40 +178,16 @@ pub fn mount_layers(manager: &mut dyn LayerManager, name: &str) -> Result<()> {
 /// Get the information of the container specified
 pub fn inspect_instance(name: &str, ns_name: &str) -> Result<CielInstance> {
     let full_path = std::env::current_dir()?.join(name);
-    let mounted = is_mounted(&full_path, OsStr::new("overlay"))?;
-    let conn = Connection::system()?;
-    let proxy = ManagerProxyBlocking::new(&conn)?;
-    let path = proxy.get_machine(ns_name);
-    if let Err(e) = path {
-        if let zbus::Error::MethodError(ref err_name, _, _) = e {
-            if err_name.as_ref() == "org.freedesktop.machine1.NoSuchMachine" {
-                return Ok(CielInstance {
-                    name: name.to_owned(),
-                    ns_name: ns_name.to_owned(),
-                    started: false,
-                    running: false,
-                    mounted,
-                    booted: None,
-                });
-            }
-        }
-        // For all other errors, just return the original error object
-        return Err(anyhow!("{}", e));
-    }
-    let path = path?;
-    let proxy = MachineProxyBlocking::builder(&conn).path(&path)?.build()?;
-    let state = proxy.state()?;
-    // Sometimes the system in the container is misconfigured, so we also accept "degraded" status as "running"
-    let running = state == "running" || state == "degraded";
-    let booted = is_booted(&proxy)?;
+    let mounted = get_layer_manager(name)?.is_mounted(&full_path)?;
+    let state = get_container_backend(ns_name).inspect(ns_name)?;
 
     Ok(CielInstance {
         name: name.to_owned(),
         ns_name: ns_name.to_owned(),
-        started: true,
-        running,
+        started: state.started,
+        running: state.running,
         mounted,
-        booted: Some(booted),
+        booted: state.booted,
     })
 }
 
@@ -370,8 +211,10 @@ pub fn list_instances() -> Result<Vec<CielInstance>> {
 pub fn list_instances_simple() -> Result<Vec<String>> {
     let mut instances: Vec<String> = Vec::new();
     for entry in (fs::read_dir(CIEL_INST_DIR)?).flatten() {
-        if entry.file_type().map(|e| e.is_dir())? {
-            instances.push(entry.file_name().to_string_lossy().to_string());
+        let name = entry.file_name().to_string_lossy().to_string();
+        // skip the hidden directories (e.g. the temporary directories of `ciel import`)
+        if !name.starts_with('.') && entry.file_type().map(|e| e.is_dir())? {
+            instances.push(name);
         }
     }
 
@@ -380,11 +223,14 @@ pub fn list_instances_simple() -> Result<Vec<String>> {
 
 /// Print all the instances under the current directory
 pub fn print_instances() -> Result<()> {
-    use crate::logging::color_bool;
+    use crate::logging::{color_bool, json_output, print_json};
     use std::io::Write;
     use tabwriter::TabWriter;
 
     let instances = list_instances()?;
+    if json_output() {
+        return print_json(&instances);
+    }
     let mut formatter = TabWriter::new(std::io::stderr());
     writeln!(&mut formatter, "NAME\tMOUNTED\tRUNNING\tBOOTED")?;
     for instance in instances {
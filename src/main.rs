@@ -1,26 +1,25 @@
-mod actions;
+mod ci;
 mod cli;
-mod common;
-mod config;
-mod dbus_machine1;
-mod dbus_machine1_machine;
-mod diagnose;
-mod logging;
-mod machine;
-mod network;
-mod overlayfs;
-mod repo;
+mod completion;
 
 use anyhow::{anyhow, bail, Context, Result};
 use clap::ArgMatches;
 use config::read_config;
-use console::{style, user_attended};
+use console::style;
 use dotenvy::dotenv;
 use std::process;
-use std::{path::Path, process::Command};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
 
-use crate::actions::BuildSettings;
-use crate::common::*;
+use ciel_core::actions::BuildSettings;
+use ciel_core::common::*;
+use ciel_core::{
+    actions, backend, common, config, daemon, diagnose, error, info,
+    lock::{self, LockMode},
+    logging, machine, mirror, network, overlayfs, repo, tui, warn,
+};
 
 macro_rules! print_error {
     ($input:block) => {
@@ -45,19 +44,38 @@ fn unsupported_target_architecture(arch: &str) -> ! {
     error!("Unknown target architecture {}", arch);
     info!("Supported target architectures:");
     eprintln!(
-        "{}\n{}",
-        CIEL_MAINLINE_ARCHS.join("\n\t"),
-        CIEL_RETRO_ARCHS.join("\n\t")
+        "\t{}",
+        known_archs().into_keys().collect::<Vec<_>>().join("\n\t")
     );
     info!("If you do want to load an OS unsupported by Ciel, specify a tarball to initialize this workspace.");
     process::exit(1);
 }
 
+/// Look up the target architecture, refreshing the architecture registry
+/// from the release manifest if it is unknown (e.g. a new port)
+fn resolve_target_arch(arch: &str) -> &'static str {
+    find_arch_name(arch)
+        .or_else(|| {
+            network::refresh_arch_registry().ok()?;
+            find_arch_name(arch)
+        })
+        .unwrap_or_else(|| unsupported_target_architecture(arch))
+}
+
 fn get_output_dir() -> String {
-    if let Ok(c) = config::read_config() {
-        return actions::get_output_directory(c.sep_mount);
+    actions::get_output_directory(config::read_config().is_ok_and(|c| c.sep_mount))
+}
+
+/// Select the output repository if `--branch` is specified for the (sub)command
+fn select_output_branch(args: &ArgMatches) -> Result<()> {
+    if let Ok(Some(branch)) = args.try_get_one::<String>("OUTPUT_BRANCH") {
+        actions::set_output_branch(branch)?;
     }
-    "OUTPUT".to_string()
+    if let Some((_, args)) = args.subcommand() {
+        select_output_branch(args)?;
+    }
+
+    Ok(())
 }
 
 #[inline]
@@ -70,25 +88,99 @@ fn get_instance_option(args: &ArgMatches) -> Result<String> {
     Ok(option_instance.expect("Internal error").to_string())
 }
 
+/// Locks held during the command: the workspace lock (exclusive for the commands modifying the base OS
+/// or all the instances) and the lock of the instance being modified. `None` for the read-only commands.
+fn command_locks(name: &str, args: &ArgMatches) -> Option<(LockMode, Option<String>)> {
+    let nested = args.subcommand();
+    let instance = nested
+        .map_or(args, |x| x.1)
+        .try_get_one::<String>("INSTANCE")
+        .ok()
+        .flatten()
+        .cloned();
+    let exclusive = match (name, nested.map(|x| x.0)) {
+        ("init", _)
+        | ("new", _)
+        | ("version", _)
+        | ("archs", _)
+        | ("list", _)
+        | ("du", _)
+        | ("search", _)
+        | ("log", _)
+        | ("logs", _)
+        | ("list-builds", _)
+        | ("status", _)
+        | ("doctor", _)
+        | ("tui", _)
+        | ("daemon", _)
+        | ("diff", _)
+        | ("verify-dist", _)
+        | ("", _) => return None,
+        ("farewell", _)
+        | ("load-os", _)
+        | ("unpack-os", _)
+        | ("commit", _)
+        | ("gc", _)
+        | ("recover", _)
+        | ("clean", _)
+        | ("topic", _) => true,
+        ("mirror", Some("set")) | ("mirror", Some("auto")) | ("cache", Some("clear")) => true,
+        ("layer", Some("create")) | ("layer", Some("remove")) => true,
+        ("remote", Some("add")) | ("remote", Some("remove")) => true,
+        ("config", _) => args.get_flag("g"),
+        // all the instances when none is specified
+        ("update-os", _) | ("mount", _) | ("down", _) | ("stop", _) | ("rollback", _) => {
+            instance.is_none()
+        }
+        _ => false,
+    };
+    // the commands only using the instance
+    let instance = match name {
+        "shell" | "run" | "export" | "cache" | "remote" | "farm" => None,
+        // the instance is on the remote machine
+        "build" if args.try_get_one::<String>("ON").ok().flatten().is_some() => None,
+        // the builds run in other processes, which lock the instances themselves
+        "build" if args.contains_id("ARCHS") => None,
+        _ => instance,
+    };
+
+    Some((
+        if exclusive {
+            LockMode::Exclusive
+        } else {
+            LockMode::Shared
+        },
+        instance,
+    ))
+}
+
+/// Ring the terminal bell, stdout is kept clean in JSON output mode
+#[inline]
+fn ring_bell() {
+    if logging::json_output() {
+        eprintln!("\x07");
+    } else {
+        println!("\x07");
+    }
+}
+
+/// Verify the newly loaded base OS if requested with `load-os --verify`
+fn verify_loaded_dist(args: &ArgMatches) {
+    if args.get_flag("VERIFY") {
+        print_error!({ actions::verify_dist() });
+    }
+}
+
 #[inline]
 fn is_root() -> bool {
     nix::unistd::geteuid().is_root()
 }
 
 fn update_tree(path: &Path, branch: Option<&String>, rebase_from: Option<&String>) -> Result<()> {
-    let mut repo = network::fetch_repo(path)?;
     if let Some(branch) = branch {
-        if repo.state() != git2::RepositoryState::Clean {
-            bail!(
-                "Cannot switch branches, because your tree seems to have an operation in progress."
-            );
-        }
-        let result = network::git_switch_branch(&mut repo, branch, rebase_from.map(|x| x.as_str()));
-        if let Err(e) = result {
-            bail!("Failed to switch branches: {}\nNote that you can still use `git stash pop` to retrieve your previous changes.`", e);
-        }
-        info!("Successfully updated the tree and switched to {}.", branch);
+        actions::switch_tree_branch(branch, rebase_from.map(|x| x.as_str()))?;
     } else {
+        network::fetch_repo(path)?;
         if rebase_from.is_some() {
             bail!("You need to specify a branch to switch to when requesting a rebase.");
         }
@@ -105,11 +197,38 @@ fn main() -> Result<()> {
     let build_cli = cli::build_cli();
     let version_string = build_cli.render_version();
     let args = build_cli.get_matches();
-    if !is_root() {
-        println!("Please run me as root!");
-        process::exit(1);
+    logging::set_json_output(args.get_flag("json"));
+    network::set_offline(args.get_flag("offline"));
+    network::set_no_proxy(args.get_flag("no_proxy"));
+    network::set_insecure(args.get_flag("insecure"));
+    // Ctrl-C stops the commands running in the containers (the onboarding guide handles it itself)
+    if args.subcommand_name() != Some("new") {
+        backend::forward_signals()?;
+    }
+    common::set_batch_mode(args.get_flag("batch"));
+    common::set_space_check(!args.get_flag("no_space_check"));
+    if let Some(rate) = args.get_one::<String>("limit_rate") {
+        network::set_rate_limit(rate)?;
     }
     let mut directory = Path::new(args.get_one::<String>("C").unwrap()).to_path_buf();
+    // shell completions need neither a workspace nor root privileges
+    match args.subcommand() {
+        Some(("completion", args)) => {
+            print_error!({
+                completion::print_completion(args.get_one::<String>("SHELL").unwrap())
+            });
+            return Ok(());
+        }
+        Some(("_complete", args)) => {
+            completion::print_candidates(
+                &directory,
+                args.get_one::<String>("KIND").unwrap(),
+                args.get_one::<String>("PREFIX").unwrap(),
+            );
+            return Ok(());
+        }
+        _ => (),
+    }
     let host_arch = get_host_arch_name();
     // Switch to the target directory
     std::env::set_current_dir(&directory).unwrap();
@@ -117,7 +236,7 @@ fn main() -> Result<()> {
     let subcmd = args.subcommand();
     // check if the workspace exists, except when the command is `init` or `new`
     match subcmd {
-        Some(("init", _)) | Some(("new", _)) | Some(("version", _)) => (),
+        Some(("init", _)) | Some(("new", _)) | Some(("version", _)) | Some(("archs", _)) => (),
         _ if !Path::new("./.ciel").is_dir() => {
             if directory == Path::new(".") {
                 directory =
@@ -134,33 +253,99 @@ fn main() -> Result<()> {
         }
         _ => (),
     }
+    // rootless workspaces do not require root privileges
+    let rootless = match subcmd {
+        Some(("new", args)) => args.get_flag("rootless"),
+        _ => read_config().is_ok_and(|c| c.rootless),
+    };
+    if !is_root() && !rootless {
+        println!("Please run me as root!");
+        process::exit(1);
+    }
+    // recover from an unclean shutdown automatically
+    match subcmd {
+        Some(("init", _))
+        | Some(("new", _))
+        | Some(("version", _))
+        | Some(("farewell", _))
+        | Some(("recover", _)) => (),
+        _ if actions::needs_recovery() => {
+            warn!("This workspace was not shut down cleanly, recovering...");
+            print_error!({ actions::recover() });
+        }
+        _ => (),
+    }
+    print_error!({ select_output_branch(&args) });
     // list instances if no command is specified
     if subcmd.is_none() {
         machine::print_instances()?;
         return Ok(());
     }
     let subcmd = subcmd.unwrap();
+    let _locks = match command_locks(subcmd.0, subcmd.1) {
+        Some((mode, instance)) => match lock::lock_workspace(mode, instance.as_deref()) {
+            Ok(locks) => locks,
+            Err(e) => {
+                error!("{:?}", e);
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
     // Switch table
     match subcmd {
-        ("farewell", _) => {
-            actions::farewell(&directory).unwrap();
+        ("farewell", args) => {
+            print_error!({ actions::farewell(&directory, args.get_flag("yes")) });
         }
         ("init", args) => {
             if args.get_flag("upgrade") {
-                info!("Upgrading workspace...");
-                info!("First, shutting down all the instances...");
-                print_error!({ actions::for_each_instance(&actions::container_down) });
-            } else {
-                warn!("Please do not use this command manually ...");
-                warn!("... try `ciel new` instead.");
+                print_error!({ actions::upgrade_workspace(args.get_flag("dry_run")) });
+                return Ok(());
             }
+            warn!("Please do not use this command manually ...");
+            warn!("... try `ciel new` instead.");
             print_error!({ common::ciel_init() });
             info!("Initialized working directory at {}", directory.display());
         }
         ("load-tree", args) => {
-            info!("Cloning abbs tree...");
-            network::download_git(args.get_one::<String>("url").unwrap(), Path::new("TREE"))?;
+            print_error!({
+                actions::clone_tree(
+                    args.get_one::<String>("url").map(|x| x.as_str()),
+                    args.get_one::<String>("branch").map(|x| x.as_str()),
+                )
+            });
+        }
+        ("topic", args) => {
+            if args.get_flag("OFF") {
+                print_error!({ actions::switch_topic(None) });
+            } else if let Some(topic) = args.get_one::<String>("NAME") {
+                print_error!({ actions::switch_topic(Some(topic)) });
+            } else {
+                print_error!({ actions::print_topic() });
+            }
         }
+        ("tree", args) => match args.subcommand() {
+            Some(("clone", args)) => {
+                print_error!({
+                    actions::clone_tree(
+                        args.get_one::<String>("URL").map(|x| x.as_str()),
+                        args.get_one::<String>("BRANCH").map(|x| x.as_str()),
+                    )
+                });
+            }
+            Some(("update", _)) => {
+                print_error!({ actions::update_tree() });
+            }
+            Some(("switch-branch", args)) => {
+                print_error!({
+                    actions::switch_tree_branch(
+                        args.get_one::<String>("BRANCH").unwrap(),
+                        args.get_one::<String>("REBASE").map(|x| x.as_str()),
+                    )
+                });
+            }
+            _ => unreachable!(),
+        },
         ("update-tree", args) => {
             let tree = Path::new("TREE");
             info!("Updating tree...");
@@ -169,10 +354,21 @@ fn main() -> Result<()> {
         ("load-os", args) => {
             let url = args.get_one::<String>("url");
             if let Some(url) = url {
-                let use_tarball = !url.ends_with(".squashfs");
+                // incremental update using rsync
+                if url.starts_with("rsync://") || Path::new(url).is_dir() {
+                    print_error!({ actions::sync_os(url) });
+                    verify_loaded_dist(args);
+                    return Ok(());
+                }
+                let use_tarball = !network::strip_multi_source_suffix(url)
+                    .unwrap_or(url)
+                    .ends_with(".squashfs");
                 // load from network using specified url
                 if url.starts_with("https://") || url.starts_with("http://") {
-                    print_error!({ actions::load_os(url, None, use_tarball) });
+                    print_error!({
+                        actions::load_os(url, None, use_tarball, args.get_flag("STREAM"))
+                    });
+                    verify_loaded_dist(args);
                     return Ok(());
                 }
                 // load from file
@@ -184,17 +380,18 @@ fn main() -> Result<()> {
                 print_error!({
                     common::extract_system_rootfs(tarball, tarball.metadata()?.len(), use_tarball)
                 });
+                print_error!({ actions::run_hook(actions::Hook::PostLoadOs, None, &[]) });
+                verify_loaded_dist(args);
 
                 return Ok(());
             }
             // load from network using auto picked url
-            let specified_arch = args.get_one::<String>("arch");
+            print_error!({ network::ensure_online("Picking the latest OS tarball") });
+            let config_arch = read_config().ok().and_then(|c| c.arch);
+            let specified_arch = args.get_one::<String>("arch").or(config_arch.as_ref());
             let arch = if let Some(specified_arch) = specified_arch {
-                if !check_arch_name(specified_arch.as_str()) {
-                    unsupported_target_architecture(specified_arch.as_str());
-                }
-                specified_arch
-            } else if !user_attended() {
+                resolve_target_arch(specified_arch)
+            } else if !can_prompt() {
                 host_arch
                     .ok_or_else(|| anyhow!("Ciel does not support this CPU architecture."))
                     .unwrap()
@@ -210,13 +407,28 @@ fn main() -> Result<()> {
             }
 
             let rootfs = rootfs.unwrap();
+            if let Some(inst_size) = rootfs.inst_size {
+                // the tarball is downloaded into the workspace before the extraction
+                let required = inst_size + rootfs.download_size.unwrap_or_default();
+                print_error!({ ensure_free_space(required, "loading the OS") });
+            }
+            let suffix = match args.get_one::<String>("source").map(|x| x.as_str()) {
+                Some("metalink") => ".meta4",
+                Some("torrent") => ".torrent",
+                _ => "",
+            };
             print_error!({
                 actions::load_os(
-                    &format!("https://releases.aosc.io/{}", rootfs.path),
+                    &format!("{}{}{}", config::releases_url(), rootfs.path, suffix),
                     Some(rootfs.sha256sum),
                     false,
+                    args.get_flag("STREAM"),
                 )
             });
+            verify_loaded_dist(args);
+        }
+        ("verify-dist", _) => {
+            print_error!({ actions::verify_dist() });
         }
         ("update-os", args) => {
             let force_use_apt = if get_host_arch_name().is_some_and(|x| x == "riscv64") {
@@ -225,9 +437,31 @@ fn main() -> Result<()> {
                 args.get_flag("force_use_apt") || read_config().is_ok_and(|x| x.force_use_apt)
             };
 
+            if let Some(instance) = args.get_one::<String>("INSTANCE") {
+                print_error!({ actions::update_instance(instance) });
+                return Ok(());
+            }
+            if args.get_flag("DIST_ONLY") {
+                print_error!({ actions::update_dist(force_use_apt) });
+                return Ok(());
+            }
             print_error!({ actions::update_os(force_use_apt,) });
         }
+        ("pack-os", args) => {
+            let format = args
+                .get_one::<String>("FORMAT")
+                .and_then(|x| overlayfs::DistImageFormat::from_name(x))
+                .unwrap();
+            print_error!({ actions::pack_os(format) });
+        }
+        ("unpack-os", _) => {
+            print_error!({ actions::unpack_os() });
+        }
         ("config", args) => {
+            if let Some(("edit", _)) = args.subcommand() {
+                print_error!({ actions::edit_config() });
+                return Ok(());
+            }
             if args.get_flag("g") {
                 print_error!({ actions::config_os(None) });
                 return Ok(());
@@ -239,14 +473,18 @@ fn main() -> Result<()> {
             print_error!({ one_or_all_instance!(args, &actions::mount_fs) });
         }
         ("new", args) => {
-            let arch = args.get_one::<String>("arch").map(|val| {
-                if !check_arch_name(val) {
-                    unsupported_target_architecture(val.as_str());
-                }
-                val.as_str()
-            });
+            let arch = args
+                .get_one::<String>("arch")
+                .map(|val| resolve_target_arch(val));
             let tarball = args.get_one::<String>("tarball");
-            if let Err(e) = actions::onboarding(tarball, arch) {
+            let instance = args.get_one::<String>("instance").map(|x| x.as_str());
+            if let Err(e) = actions::onboarding(
+                tarball,
+                arch,
+                args.get_flag("rootless"),
+                instance,
+                actions::DEFAULT_TREE_REMOTE,
+            ) {
                 error!("{}", e);
                 process::exit(1);
             }
@@ -256,30 +494,77 @@ fn main() -> Result<()> {
             let args = args.get_many::<String>("COMMANDS").unwrap();
             let status =
                 actions::run_in_container(&instance, &args.into_iter().collect::<Vec<_>>())?;
-            process::exit(status);
+            backend::exit_with_status(status);
+        }
+        ("run-ephemeral", args) => {
+            let command = args
+                .get_many::<String>("COMMANDS")
+                .unwrap()
+                .cloned()
+                .collect::<Vec<_>>();
+            let output = args.get_one::<String>("OUTPUT").map(Path::new);
+            let status = actions::run_ephemeral(&command, output)?;
+            backend::exit_with_status(status);
         }
         ("shell", args) => {
             let instance = get_instance_option(args)?;
-            if let Some(cmd) = args.get_many::<String>("COMMANDS") {
-                let command = cmd
-                    .into_iter()
-                    .fold(String::with_capacity(1024), |acc, x| acc + " " + x);
-                let status = actions::run_in_container(&instance, &["/bin/bash", "-ec", &command])?;
-                process::exit(status);
-            }
-            let status = actions::run_in_container(&instance, &["/bin/bash"])?;
-            process::exit(status);
+            let command = args
+                .get_many::<String>("COMMANDS")
+                .map(|x| x.cloned().collect::<Vec<_>>())
+                .unwrap_or_default();
+            let status = actions::shell_in_container(
+                &instance,
+                args.get_one::<String>("WORKDIR").map(|x| x.as_str()),
+                args.get_one::<String>("USER").map(|x| x.as_str()),
+                &command,
+            )?;
+            backend::exit_with_status(status);
         }
         ("stop", args) => {
             let instance = get_instance_option(args)?;
             print_error!({ actions::stop_container(&instance) });
         }
+        ("checkpoint", args) => {
+            let instance = get_instance_option(args)?;
+            print_error!({ actions::checkpoint_container(&instance) });
+        }
+        ("restore", args) => {
+            let instance = get_instance_option(args)?;
+            if args.get_flag("DISCARD") {
+                print_error!({ actions::discard_checkpoint(&instance) });
+            } else {
+                print_error!({ actions::restore_container(&instance) });
+            }
+        }
         ("down", args) => {
             print_error!({ one_or_all_instance!(args, &actions::container_down) });
         }
         ("commit", args) => {
             let instance = get_instance_option(args)?;
-            print_error!({ actions::commit_container(&instance) });
+            let paths = args
+                .get_many::<String>("PATHS")
+                .map(|x| x.map(PathBuf::from).collect::<Vec<_>>())
+                .unwrap_or_default();
+            let interactive = args.get_flag("INTERACTIVE");
+            if args.get_flag("LIST") {
+                print_error!({ actions::print_instance_diff(&instance, &[], false) });
+            } else if interactive || !paths.is_empty() {
+                print_error!({
+                    actions::commit_container_selected(&instance, &paths, interactive)
+                });
+            } else {
+                print_error!({ actions::commit_container(&instance) });
+            }
+        }
+        ("diff", args) => {
+            let instance = get_instance_option(args)?;
+            let paths = args
+                .get_many::<String>("PATHS")
+                .map(|x| x.map(PathBuf::from).collect::<Vec<_>>())
+                .unwrap_or_default();
+            print_error!({
+                actions::print_instance_diff(&instance, &paths, args.get_flag("CONTENT"))
+            });
         }
         ("rollback", args) => {
             print_error!({ one_or_all_instance!(args, &actions::rollback_container) });
@@ -288,55 +573,203 @@ fn main() -> Result<()> {
             let instance = args.get_one::<String>("INSTANCE").unwrap();
             print_error!({ actions::remove_instance(instance) });
         }
+        ("clone", args) => {
+            let source = args.get_one::<String>("SOURCE").unwrap();
+            let target = args.get_one::<String>("TARGET").unwrap();
+            print_error!({ actions::clone_instance(source, target) });
+        }
+        ("export", args) => {
+            let instance = args.get_one::<String>("INSTANCE").unwrap();
+            let file = args.get_one::<String>("FILE").unwrap();
+            print_error!({ actions::export_instance(instance, Path::new(file)) });
+        }
+        ("oci", args) => match args.subcommand() {
+            Some(("export", args)) => {
+                let instance = args.get_one::<String>("INSTANCE").unwrap();
+                let output = args
+                    .get_one::<String>("OUTPUT")
+                    .cloned()
+                    .unwrap_or_else(|| format!("oci-{}", instance));
+                print_error!({
+                    actions::export_oci_image(
+                        instance,
+                        Path::new(&output),
+                        args.get_one::<String>("TAG").unwrap(),
+                        args.get_one::<String>("PUSH").map(|x| x.as_str()),
+                    )
+                });
+            }
+            _ => unreachable!(),
+        },
+        ("import", args) => {
+            let file = args.get_one::<String>("FILE").unwrap();
+            let name = args.get_one::<String>("NAME").map(|x| x.as_str());
+            print_error!({ actions::import_instance(Path::new(file), name) });
+        }
         ("add", args) => {
             let instance = args.get_one::<String>("INSTANCE").unwrap();
-            print_error!({ actions::add_instance(instance) });
+            if let Some(template) = args.get_one::<String>("TEMPLATE") {
+                print_error!({ actions::add_instance_from_template(instance, template) });
+            } else if args.get_flag("TMPFS") {
+                let size = args.get_one::<String>("TMPFS_SIZE").map(|x| x.as_str());
+                print_error!({ actions::add_tmpfs_instance(instance, size) });
+            } else if args.get_flag("VM") {
+                let kernel = args.get_one::<String>("KERNEL").map(Path::new);
+                print_error!({ actions::add_vm_instance(instance, kernel) });
+            } else {
+                print_error!({ actions::add_instance(instance) });
+            }
+            let provision =
+                args.get_flag("PROVISION") || read_config().is_ok_and(|x| x.provision.auto);
+            if provision {
+                print_error!({ actions::provision_instance(instance) });
+            }
         }
         ("build", args) => {
+            if let Some(remote) = args.get_one::<String>("ON") {
+                let status = actions::remote_build(
+                    remote,
+                    args.get_one::<String>("INSTANCE").map(|x| x.as_str()),
+                    &std::env::args().collect::<Vec<_>>(),
+                )?;
+                process::exit(status);
+            }
+            if let Some(archs) = args.get_many::<String>("ARCHS") {
+                let status = actions::build_archs(
+                    &archs.cloned().collect::<Vec<_>>(),
+                    &get_instance_option(args)?,
+                    &std::env::args().collect::<Vec<_>>(),
+                )?;
+                process::exit(status);
+            }
             let instance = get_instance_option(args)?;
+            let cross = args.get_one::<String>("CROSS").map(|arch| {
+                find_arch_name(arch).unwrap_or_else(|| unsupported_target_architecture(arch))
+            });
+            actions::set_resource_limits(
+                args.get_one::<u32>("CPUS").copied(),
+                args.get_one::<String>("MEMORY").map(|x| x.as_str()),
+            )?;
             let settings = BuildSettings {
                 offline: args.get_flag("OFFLINE"),
                 stage2: args.get_flag("STAGE2"),
+                html_report: args.get_flag("HTML_REPORT"),
+                with_deps: args.get_flag("WITH_DEPS"),
+                revdeps: args.get_flag("REVDEPS"),
+                isolate_network: args.get_flag("ISOLATE_NETWORK"),
+                retries: *args.get_one::<usize>("RETRIES").unwrap(),
+                strict: args.get_flag("STRICT"),
+                cross,
             };
+            if let Some(package) = args.get_one::<String>("VERIFY_REPRODUCIBLE") {
+                let status = actions::verify_reproducible(&instance, package, settings)?;
+                process::exit(status);
+            }
             let mut state = None;
-            if let Some(cont) = args.get_one::<String>("CONTINUE") {
-                state = Some(actions::load_build_checkpoint(cont)?);
+            if args.contains_id("CONTINUE") {
+                // continue from the last failure if no checkpoint is specified
+                state = Some(match args.get_one::<String>("CONTINUE") {
+                    Some(cont) => actions::load_build_checkpoint(cont)?,
+                    None => actions::load_build_state()?,
+                });
                 let empty: Vec<&str> = Vec::new();
                 let status = actions::package_build(&instance, empty.into_iter(), state, settings)?;
-                println!("\x07"); // bell character
-                process::exit(status);
+                ring_bell();
+                backend::exit_with_status(status);
             }
-            let packages = args.get_many::<String>("PACKAGES");
-            if packages.is_none() {
-                error!("Please specify a list of packages to build!");
-                process::exit(1);
-            }
-            let packages = packages.unwrap();
+            let packages = match args.get_one::<String>("CHANGED_SINCE") {
+                Some(rev) => {
+                    let packages = actions::changed_packages(rev)?;
+                    if packages.is_empty() {
+                        info!("No packages changed since {}, nothing to build.", rev);
+                        process::exit(0);
+                    }
+                    packages
+                }
+                None => match args.get_many::<String>("PACKAGES") {
+                    Some(packages) => packages.cloned().collect::<Vec<_>>(),
+                    None => {
+                        error!("Please specify a list of packages to build!");
+                        process::exit(1);
+                    }
+                },
+            };
             if args.contains_id("SELECT") {
                 let start_package = args.get_one::<String>("SELECT");
-                let status =
-                    actions::packages_stage_select(&instance, packages, settings, start_package)?;
+                let status = actions::packages_stage_select(
+                    &instance,
+                    packages.iter(),
+                    settings,
+                    start_package,
+                )?;
                 process::exit(status);
             }
+            if let Some(jobs) = args.get_one::<usize>("PARALLEL") {
+                let status =
+                    actions::package_build_parallel(&instance, &packages, settings, *jobs)?;
+                ring_bell();
+                backend::exit_with_status(status);
+            }
             if args.get_flag("FETCH") {
-                let packages = packages.into_iter().collect::<Vec<_>>();
                 let status = actions::package_fetch(&instance, &packages)?;
                 process::exit(status);
             }
-            let status = actions::package_build(&instance, packages, state, settings)?;
-            println!("\x07"); // bell character
+            let status = actions::package_build(&instance, packages.iter(), state, settings)?;
+            ring_bell();
+            backend::exit_with_status(status);
+        }
+        ("ci", args) => {
+            process::exit(ci::run_ci(args));
+        }
+        ("du", _) => {
+            print_error!({ actions::print_disk_usage() });
+        }
+        ("gc", args) => {
+            let log_age = *args.get_one::<u64>("LOG_AGE").unwrap();
+            print_error!({ actions::gc(log_age, args.get_flag("DRY_RUN")) });
+        }
+        ("list-builds", args) => {
+            let package = args.get_one::<String>("PACKAGE").map(|x| x.as_str());
+            let limit = *args.get_one::<usize>("LIMIT").unwrap();
+            print_error!({ actions::list_builds(package, limit) });
+        }
+        ("log", args) => {
+            let package = args.get_one::<String>("PACKAGE").unwrap();
+            print_error!({ actions::show_package_log(package) });
+        }
+        ("fetch", args) => {
+            let packages = args.get_many::<String>("PACKAGES").unwrap();
+            let instance = args.get_one::<String>("INSTANCE").map(|x| x.as_str());
+            let status = actions::fetch_sources(packages, instance)?;
             process::exit(status);
         }
+        ("status", args) => {
+            print_error!({ actions::print_package_status(args.get_flag("OUTDATED")) });
+        }
+        ("search", args) => {
+            let pattern = args.get_one::<String>("PATTERN").unwrap();
+            print_error!({ actions::print_search_results(pattern, args.get_flag("OUTDATED")) });
+        }
+        ("logs", args) => {
+            let instance = args.get_one::<String>("INSTANCE").unwrap();
+            print_error!({ actions::show_instance_log(instance, args.get_flag("follow")) });
+        }
         ("", _) => {
             machine::print_instances()?;
         }
         ("list", _) => {
             machine::print_instances()?;
         }
-        ("doctor", _) => {
-            print_error!({ diagnose::run_diagnose() });
+        ("recover", _) => {
+            print_error!({ actions::recover() });
+        }
+        ("doctor", args) => {
+            print_error!({ diagnose::run_diagnose(args.get_flag("fix")) });
         }
         ("repo", args) => match args.subcommand() {
+            Some(("list", _)) => {
+                print_error!({ repo::print_output_repos(&get_output_dir()) });
+            }
             Some(("refresh", _)) => {
                 info!("Refreshing repository...");
                 print_error!({
@@ -352,6 +785,55 @@ fn main() -> Result<()> {
                 print_error!({ repo::init_repo(&cwd.join(get_output_dir()), &cwd.join(instance)) });
                 info!("Repository has been initialized and refreshed.");
             }
+            Some(("prune", args)) => {
+                let retention = read_config().map(|c| c.retention).unwrap_or_default();
+                let mut policy = repo::PrunePolicy::from_config(&retention);
+                if let Some(keep) = args.get_one::<usize>("KEEP") {
+                    policy.keep_versions = Some(*keep);
+                }
+                if let Some(days) = args.get_one::<u64>("MAX_AGE") {
+                    policy.max_age = Some(std::time::Duration::from_secs(days * 24 * 60 * 60));
+                }
+                policy.dbg_latest_only |= args.get_flag("DBG_LATEST_ONLY");
+                if policy.is_empty() {
+                    error!("No retention policy specified, please set `retention` in the workspace configuration or use the command line options.");
+                    process::exit(1);
+                }
+                let root = std::env::current_dir().unwrap().join(get_output_dir());
+                let debs = root.join("debs");
+                if args.get_flag("DRY_RUN") {
+                    let prunable = repo::find_prunable_packages(&debs, &policy)?;
+                    for path in prunable.iter() {
+                        println!("{}", path.display());
+                    }
+                    info!("{} package(s) would be removed.", prunable.len());
+                    return Ok(());
+                }
+                let pruned = repo::prune_packages(&debs, &policy)?;
+                info!(
+                    "Removed {} package(s), refreshing repository...",
+                    pruned.len()
+                );
+                print_error!({ repo::refresh_repo(&root) });
+            }
+            Some(("sign", _)) => {
+                info!("Signing repository...");
+                print_error!({
+                    repo::sign_repo(&std::env::current_dir().unwrap().join(get_output_dir()))
+                });
+                info!("Repository has been signed.");
+            }
+            Some(("serve", args)) => {
+                let port = *args.get_one::<u16>("PORT").unwrap();
+                let bind = args.get_one::<String>("BIND").unwrap();
+                print_error!({
+                    repo::serve_repo(
+                        &std::env::current_dir().unwrap().join(get_output_dir()),
+                        bind,
+                        port,
+                    )
+                });
+            }
             Some(("deinit", args)) => {
                 info!("Disabling local repository...");
                 let instance = get_instance_option(args)?;
@@ -362,11 +844,215 @@ fn main() -> Result<()> {
             }
             _ => unreachable!(),
         },
+        ("template", args) => match args.subcommand() {
+            Some(("save", args)) => {
+                let strings = |id: &str| -> Vec<String> {
+                    args.get_many::<String>(id)
+                        .map(|x| x.cloned().collect())
+                        .unwrap_or_default()
+                };
+                print_error!({
+                    actions::save_template(
+                        args.get_one::<String>("INSTANCE").unwrap(),
+                        args.get_one::<String>("NAME").unwrap(),
+                        strings("PACKAGES"),
+                        strings("USERS"),
+                        args.get_one::<String>("SCRIPT").map(Path::new),
+                    )
+                });
+            }
+            Some(("list", _)) => {
+                print_error!({ actions::list_templates() });
+            }
+            Some(("remove", args)) => {
+                print_error!({ actions::remove_template(args.get_one::<String>("NAME").unwrap()) });
+            }
+            _ => unreachable!(),
+        },
+        ("layer", args) => {
+            let get = |args: &ArgMatches, id: &str| args.get_one::<String>(id).unwrap().clone();
+            match args.subcommand() {
+                Some(("create", args)) => {
+                    let instance = args.get_one::<String>("INSTANCE").map(|x| x.as_str());
+                    print_error!({ actions::create_layer(&get(args, "NAME"), instance) });
+                }
+                Some(("attach", args)) => {
+                    print_error!({
+                        actions::attach_layer(&get(args, "INSTANCE"), &get(args, "NAME"))
+                    });
+                }
+                Some(("detach", args)) => {
+                    print_error!({
+                        actions::detach_layer(&get(args, "INSTANCE"), &get(args, "NAME"))
+                    });
+                }
+                Some(("list", _)) => {
+                    print_error!({ actions::list_layers() });
+                }
+                Some(("remove", args)) => {
+                    print_error!({ actions::remove_layer(&get(args, "NAME")) });
+                }
+                _ => unreachable!(),
+            }
+        }
+        ("remote", args) => {
+            let name = |args: &ArgMatches| args.get_one::<String>("NAME").unwrap().clone();
+            match args.subcommand() {
+                Some(("add", args)) => {
+                    print_error!({
+                        actions::add_remote(
+                            &name(args),
+                            args.get_one::<String>("HOST").unwrap(),
+                            args.get_one::<u16>("PORT").copied(),
+                            args.get_one::<String>("PATH").map(|x| x.as_str()),
+                            args.get_one::<String>("INSTANCE").map(|x| x.as_str()),
+                            args.get_one::<String>("ARCH").map(|x| x.as_str()),
+                        )
+                    });
+                }
+                Some(("remove", args)) => {
+                    print_error!({ actions::remove_remote(&name(args)) });
+                }
+                Some(("list", _)) => {
+                    print_error!({ actions::list_remotes() });
+                }
+                _ => unreachable!(),
+            }
+        }
+        ("farm", args) => {
+            let packages = args.get_many::<String>("PACKAGES").unwrap();
+            let builders = args
+                .get_many::<String>("BUILDERS")
+                .map(|x| x.cloned().collect());
+            let settings = BuildSettings {
+                offline: false,
+                stage2: false,
+                html_report: false,
+                with_deps: args.get_flag("WITH_DEPS"),
+                revdeps: args.get_flag("REVDEPS"),
+                isolate_network: false,
+                retries: 0,
+                strict: false,
+                cross: None,
+            };
+            let status = actions::farm_build(packages, builders, settings)?;
+            process::exit(status);
+        }
+        ("port", args) => {
+            let get = |args: &ArgMatches, id: &str| args.get_one::<String>(id).unwrap().clone();
+            match args.subcommand() {
+                Some(("add", args)) => {
+                    print_error!({ actions::add_port(&get(args, "INSTANCE"), &get(args, "PORT")) });
+                }
+                Some(("remove", args)) => {
+                    print_error!({
+                        actions::remove_port(&get(args, "INSTANCE"), &get(args, "PORT"))
+                    });
+                }
+                Some(("list", _)) => {
+                    print_error!({ actions::list_ports() });
+                }
+                _ => unreachable!(),
+            }
+        }
+        ("snapshot", args) => {
+            let name = |args: &ArgMatches| args.get_one::<String>("NAME").unwrap().clone();
+            match args.subcommand() {
+                Some(("create", args)) => {
+                    let instance = args.get_one::<String>("INSTANCE").unwrap();
+                    print_error!({ actions::create_snapshot(instance, &name(args)) });
+                }
+                Some(("restore", args)) => {
+                    let instance = args.get_one::<String>("INSTANCE").unwrap();
+                    print_error!({ actions::restore_snapshot(instance, &name(args)) });
+                }
+                Some(("list", args)) => {
+                    let instance = args.get_one::<String>("INSTANCE");
+                    print_error!({ actions::list_snapshots(instance.map(|x| x.as_str())) });
+                }
+                Some(("remove", args)) => {
+                    let instance = args.get_one::<String>("INSTANCE").unwrap();
+                    print_error!({ actions::remove_snapshot(instance, &name(args)) });
+                }
+                _ => unreachable!(),
+            }
+        }
+        ("cache", args) => match args.subcommand() {
+            Some(("stats", args)) => {
+                let instance = get_instance_option(args)?;
+                let status = actions::cache_stats(&instance)?;
+                process::exit(status);
+            }
+            Some(("clear", _)) => {
+                print_error!({ actions::cache_clear() });
+            }
+            Some(("prune", args)) => {
+                print_error!({
+                    actions::apt_cache_prune(
+                        *args.get_one::<usize>("KEEP").unwrap(),
+                        args.get_one::<u64>("MAX_AGE").copied(),
+                        args.get_flag("ALL"),
+                        args.get_flag("DRY_RUN"),
+                    )
+                });
+            }
+            _ => unreachable!(),
+        },
+        ("mirror", args) => match args.subcommand() {
+            Some(("list", _)) => {
+                print_error!({ mirror::list_mirrors() });
+            }
+            Some(("bench", _)) => {
+                print_error!({
+                    mirror::benchmark_mirrors().and_then(|x| mirror::print_benchmark(&x))
+                });
+            }
+            Some(("set", args)) => {
+                print_error!({ mirror::set_mirror(args.get_one::<String>("MIRROR").unwrap()) });
+            }
+            Some(("auto", _)) => {
+                print_error!({ mirror::auto_select_mirror() });
+            }
+            _ => unreachable!(),
+        },
+        ("tui", _) => {
+            print_error!({ tui::run_dashboard() });
+        }
+        ("daemon", args) => {
+            let listen = match args.get_one::<String>("LISTEN") {
+                Some(addr) => daemon::Listen::Tcp(addr),
+                None => daemon::Listen::Unix(Path::new(args.get_one::<String>("SOCKET").unwrap())),
+            };
+            print_error!({ daemon::run_daemon(listen, args.get_one::<String>("TOKEN").cloned()) });
+        }
         ("clean", _) => {
             print_error!({ actions::cleanup_outputs() });
         }
+        ("archs", args) => {
+            if args.get_flag("REFRESH") {
+                print_error!({ network::refresh_arch_registry() });
+            }
+            for (arch, variants) in known_archs() {
+                println!(
+                    "{:<20}{}",
+                    arch,
+                    if variants.is_empty() {
+                        "-".to_string()
+                    } else {
+                        variants.join(", ")
+                    }
+                );
+            }
+        }
         ("version", _) => {
-            println!("{}", version_string);
+            if logging::json_output() {
+                logging::print_json(&serde_json::json!({
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                }))?;
+            } else {
+                println!("{}", version_string);
+            }
         }
         // catch all other conditions
         (_, options) => {
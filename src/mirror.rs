@@ -0,0 +1,194 @@
+//! AOSC OS mirror selection and benchmarking
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use rayon::prelude::*;
+use reqwest::{blocking::Client, header::RANGE};
+use serde::Serialize;
+use std::{
+    io::{Read, Write},
+    time::{Duration, Instant},
+};
+use tabwriter::TabWriter;
+
+use crate::{
+    actions::{container_down, for_each_instance},
+    common::CIEL_DIST_DIR,
+    config, info,
+    logging::{json_output, print_json},
+    network, warn,
+};
+
+/// Known mirrors (name, base URL), the first one is the origin server
+const MIRRORS: &[(&str, &str)] = &[
+    ("origin", "https://repo.aosc.io/"),
+    ("tuna", "https://mirrors.tuna.tsinghua.edu.cn/anthon/"),
+    ("ustc", "https://mirrors.ustc.edu.cn/anthon/"),
+    ("bfsu", "https://mirrors.bfsu.edu.cn/anthon/"),
+    ("nju", "https://mirrors.nju.edu.cn/anthon/"),
+    ("sjtu", "https://mirror.sjtu.edu.cn/anthon/"),
+];
+/// Small file used for measuring the latency
+const LATENCY_PROBE: &str = "debs/dists/stable/InRelease";
+/// Large file used for measuring the throughput (only the first few megabytes are downloaded)
+const THROUGHPUT_PROBE: &str = "debs/dists/stable/main/binary-amd64/Packages";
+const THROUGHPUT_PROBE_SIZE: u64 = 4 * 1024 * 1024;
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Benchmark result of a mirror
+#[derive(Debug, Serialize)]
+pub struct MirrorResult {
+    pub name: String,
+    pub url: String,
+    /// Round-trip latency in milliseconds
+    pub latency: Option<u64>,
+    /// Throughput in bytes per second
+    pub speed: Option<u64>,
+}
+
+fn measure_latency(client: &Client, url: &str) -> Result<u64> {
+    let start = Instant::now();
+    client
+        .head(format!("{}{}", url, LATENCY_PROBE))
+        .send()?
+        .error_for_status()?;
+
+    Ok(start.elapsed().as_millis() as u64)
+}
+
+fn measure_speed(client: &Client, url: &str) -> Result<u64> {
+    let start = Instant::now();
+    let resp = client
+        .get(format!("{}{}", url, THROUGHPUT_PROBE))
+        .header(RANGE, format!("bytes=0-{}", THROUGHPUT_PROBE_SIZE - 1))
+        .send()?
+        .error_for_status()?;
+    // the server may ignore the range request
+    let received = std::io::copy(&mut resp.take(THROUGHPUT_PROBE_SIZE), &mut std::io::sink())?;
+    let elapsed = start.elapsed().as_secs_f64().max(0.001);
+
+    Ok((received as f64 / elapsed) as u64)
+}
+
+fn probe_mirror(client: &Client, name: &str, url: &str) -> MirrorResult {
+    let latency = measure_latency(client, url).ok();
+    let speed = latency.and_then(|_| measure_speed(client, url).ok());
+
+    MirrorResult {
+        name: name.to_string(),
+        url: url.to_string(),
+        latency,
+        speed,
+    }
+}
+
+/// Probe all the known mirrors, returns the results ranked by throughput and then latency
+pub fn benchmark_mirrors() -> Result<Vec<MirrorResult>> {
+    network::ensure_online("Benchmarking the mirrors")?;
+    let client = network::http_client_builder()?
+        .timeout(PROBE_TIMEOUT)
+        .build()?;
+    info!("Probing {} mirrors...", MIRRORS.len());
+    let mut results = MIRRORS
+        .par_iter()
+        .map(|(name, url)| probe_mirror(&client, name, url))
+        .collect::<Vec<_>>();
+    results.sort_by_key(|x| {
+        (
+            x.speed.is_none(),
+            std::cmp::Reverse(x.speed),
+            x.latency.unwrap_or(u64::MAX),
+        )
+    });
+
+    Ok(results)
+}
+
+/// Print the benchmark results
+pub fn print_benchmark(results: &[MirrorResult]) -> Result<()> {
+    if json_output() {
+        return print_json(&results);
+    }
+    let mut formatter = TabWriter::new(std::io::stderr());
+    writeln!(&mut formatter, "NAME\tLATENCY\tSPEED\tURL")?;
+    for result in results {
+        let latency = result
+            .latency
+            .map(|x| format!("{} ms", x))
+            .unwrap_or_else(|| "\x1b[2m-\x1b[0m".to_string());
+        let speed = result
+            .speed
+            .map(|x| format!("{}/s", indicatif::HumanBytes(x)))
+            .unwrap_or_else(|| "\x1b[2m-\x1b[0m".to_string());
+        writeln!(
+            &mut formatter,
+            "{}\t{}\t{}\t{}",
+            result.name, latency, speed, result.url
+        )?;
+    }
+    formatter.flush()?;
+
+    Ok(())
+}
+
+/// List the known mirrors, marking the currently selected one
+pub fn list_mirrors() -> Result<()> {
+    let current = config::read_config()
+        .ok()
+        .and_then(|c| c.mirror)
+        .unwrap_or_else(|| MIRRORS[0].1.to_string());
+    if json_output() {
+        let mirrors = MIRRORS
+            .iter()
+            .map(|(name, url)| (name.to_string(), url.to_string()))
+            .collect::<std::collections::BTreeMap<_, _>>();
+        return print_json(&mirrors);
+    }
+    let mut formatter = TabWriter::new(std::io::stderr());
+    writeln!(&mut formatter, "NAME\tSELECTED\tURL")?;
+    for (name, url) in MIRRORS {
+        let selected = crate::logging::color_bool(*url == current);
+        writeln!(&mut formatter, "{}\t{}\t{}", name, selected, url)?;
+    }
+    formatter.flush()?;
+
+    Ok(())
+}
+
+/// Switch the workspace to the mirror (a known mirror name or URL)
+pub fn set_mirror(mirror: &str) -> Result<()> {
+    let url = if mirror.starts_with("https://") || mirror.starts_with("http://") {
+        mirror
+    } else {
+        MIRRORS
+            .iter()
+            .find(|(name, _)| *name == mirror)
+            .map(|(_, url)| *url)
+            .ok_or_else(|| anyhow!("Unknown mirror: {}", mirror))?
+    };
+    crate::overlayfs::ensure_dist_writable("changing the mirror")?;
+    let mut c =
+        config::read_config().map_err(|_| anyhow!("Please configure this workspace first!"))?;
+    // the origin server is the default
+    c.set_mirror(Some(url).filter(|x| *x != MIRRORS[0].1));
+    info!("Shutting down instance(s) before applying config...");
+    for_each_instance(&container_down)?;
+    config::apply_config(CIEL_DIST_DIR, &c)?;
+    config::write_config(&c)?;
+    info!("Now using mirror {}.", url);
+    warn!("Please rollback all your instances for the new config to take effect!");
+
+    Ok(())
+}
+
+/// Benchmark the mirrors and then switch to the fastest one
+pub fn auto_select_mirror() -> Result<()> {
+    let results = benchmark_mirrors()?;
+    print_benchmark(&results)?;
+    let best = match results.iter().find(|x| x.speed.is_some()) {
+        Some(best) => best,
+        None => bail!("None of the mirrors are reachable."),
+    };
+
+    set_mirror(&best.url)
+}
@@ -1,20 +1,43 @@
-use crate::make_progress_bar;
-use anyhow::{anyhow, Result};
-use fs3::FileExt;
+use crate::common::{register_archs, sha256sum, CIEL_DATA_DIR};
+use crate::{config, info, make_progress_bar, warn};
+use anyhow::{anyhow, bail, Result};
+use console::style;
 use lazy_static::lazy_static;
-use reqwest::blocking::{Client, Response};
-use serde::Deserialize;
-use std::path::Path;
+use reqwest::blocking::{Client, ClientBuilder};
+use reqwest::{header::RANGE, Proxy, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
 use std::{
+    collections::BTreeMap,
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
     thread::{self, sleep},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 const MANIFEST_URL: &str = "https://releases.aosc.io/manifest/recipe.json";
+/// Suffixes of the multi-source download descriptors (metalink and BitTorrent)
+const MULTI_SOURCE_SUFFIXES: &[&str] = &[".meta4", ".metalink", ".torrent"];
+/// Location of the global tarball cache (relative to the user cache directory)
+const TARBALL_CACHE_DIR: &str = "ciel/tarballs";
+
+/// Environment variables specifying the proxy, in the order of precedence
+const PROXY_VARIABLES: &[&str] = &["https_proxy", "HTTPS_PROXY", "http_proxy", "HTTP_PROXY"];
+
+/// Detached signatures are published next to the signed files
+const SIGNATURE_SUFFIX: &str = ".asc";
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+static NO_PROXY: AtomicBool = AtomicBool::new(false);
+/// Skip the signature verification of the releases (`--insecure`)
+static INSECURE: AtomicBool = AtomicBool::new(false);
+/// Download rate limit in bytes per second set on the command line (0: not set)
+static RATE_LIMIT: AtomicU64 = AtomicU64::new(0);
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct RootFs {
@@ -22,6 +45,12 @@ pub struct RootFs {
     pub date: String,
     pub path: String,
     pub sha256sum: String,
+    /// Size of the tarball in bytes
+    #[serde(rename = "downloadSize", default)]
+    pub download_size: Option<u64>,
+    /// Size of the extracted OS in bytes
+    #[serde(rename = "instSize", default)]
+    pub inst_size: Option<u64>,
 }
 
 #[derive(Deserialize)]
@@ -37,50 +66,641 @@ pub struct Recipe {
     variants: Vec<Variant>,
 }
 
+/// Information about a partially downloaded file, stored next to the `.part` file
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct PartialDownload {
+    url: String,
+    sha256: Option<String>,
+}
+
 lazy_static! {
     static ref GIT_PROGRESS: indicatif::ProgressStyle = indicatif::ProgressStyle::default_bar()
         .template("[{bar:25.cyan/blue}] {pos}/{len} {msg} ({eta})")
         .unwrap();
 }
 
-/// Download a file from the web
-pub fn download_file(url: &str) -> Result<Response> {
-    let client = Client::new().get(url).send()?;
+/// Forbid any network access
+#[inline]
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::SeqCst);
+}
+
+#[inline]
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::SeqCst)
+}
+
+/// Ignore the proxy settings (`--no-proxy`)
+#[inline]
+pub fn set_no_proxy(no_proxy: bool) {
+    NO_PROXY.store(no_proxy, Ordering::SeqCst);
+}
+
+#[inline]
+pub fn is_no_proxy() -> bool {
+    NO_PROXY.load(Ordering::SeqCst)
+}
+
+/// Do not verify the signatures of the release manifest and the OS tarballs
+#[inline]
+pub fn set_insecure(insecure: bool) {
+    INSECURE.store(insecure, Ordering::SeqCst);
+}
+
+#[inline]
+pub fn is_insecure() -> bool {
+    INSECURE.load(Ordering::SeqCst)
+}
+
+/// Proxy used for the network operations: the `proxy` configuration of the workspace,
+/// or the HTTP(S)_PROXY environment variables
+pub fn get_proxy() -> Option<String> {
+    if is_no_proxy() {
+        return None;
+    }
+    if let Some(proxy) = config::read_config().ok().and_then(|c| c.proxy) {
+        return Some(proxy);
+    }
+
+    PROXY_VARIABLES
+        .iter()
+        .find_map(|x| std::env::var(x).ok())
+        .filter(|x| !x.is_empty())
+}
+
+/// Environment variables passing the proxy settings into the containers
+pub fn proxy_environment() -> Vec<(&'static str, String)> {
+    match get_proxy() {
+        Some(proxy) => PROXY_VARIABLES
+            .iter()
+            .map(|x| (*x, proxy.clone()))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// HTTP client builder honoring the proxy settings
+pub fn http_client_builder() -> Result<ClientBuilder> {
+    let builder = Client::builder();
+
+    Ok(match get_proxy() {
+        Some(proxy) => builder.proxy(Proxy::all(&proxy)?),
+        None => builder.no_proxy(),
+    })
+}
+
+/// Proxy options for the Git operations
+fn git_proxy_options() -> git2::ProxyOptions<'static> {
+    let mut options = git2::ProxyOptions::new();
+    if let Some(proxy) = get_proxy() {
+        options.url(&proxy);
+    }
 
-    Ok(client)
+    options
+}
+
+/// Parse a download rate (bytes per second) with an optional K/M/G suffix, e.g. `500K`
+pub fn parse_rate(rate: &str) -> Result<u64> {
+    let rate = rate.trim();
+    let (number, multiplier) = match rate.chars().last().map(|x| x.to_ascii_uppercase()) {
+        Some('K') => (&rate[..rate.len() - 1], 1024),
+        Some('M') => (&rate[..rate.len() - 1], 1024 * 1024),
+        Some('G') => (&rate[..rate.len() - 1], 1024 * 1024 * 1024),
+        _ => (rate, 1),
+    };
+    match number.parse::<u64>() {
+        Ok(number) if number > 0 => Ok(number * multiplier),
+        _ => Err(anyhow!("Invalid download rate: {} (e.g. 500K or 2M)", rate)),
+    }
+}
+
+/// Limit the download rate (`--limit-rate`), overriding the configuration
+pub fn set_rate_limit(rate: &str) -> Result<()> {
+    RATE_LIMIT.store(parse_rate(rate)?, Ordering::SeqCst);
+
+    Ok(())
+}
+
+/// Download rate limit in bytes per second, from the command line or the `limit-rate` configuration
+pub fn get_rate_limit() -> Option<u64> {
+    match RATE_LIMIT.load(Ordering::SeqCst) {
+        0 => config::read_config()
+            .ok()
+            .and_then(|c| c.limit_rate)
+            .and_then(|x| parse_rate(&x).ok()),
+        rate => Some(rate),
+    }
+}
+
+/// Reader throttled to the specified rate (bytes per second)
+struct RateLimitedReader<R> {
+    inner: R,
+    rate: u64,
+    started: Instant,
+    transferred: u64,
+}
+
+impl<R: Read> Read for RateLimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        // read in small chunks, so the rate stays smooth
+        let chunk = (self.rate as usize / 10).max(1).min(buf.len());
+        let size = self.inner.read(&mut buf[..chunk])?;
+        self.transferred += size as u64;
+        let expected = Duration::from_secs_f64(self.transferred as f64 / self.rate as f64);
+        let elapsed = self.started.elapsed();
+        if expected > elapsed {
+            sleep(expected - elapsed);
+        }
+
+        Ok(size)
+    }
+}
+
+/// Returns an error if the network access is forbidden (`--offline`)
+pub fn ensure_online(action: &str) -> Result<()> {
+    if is_offline() {
+        bail!(
+            "{} requires network access, which is not allowed in offline mode.",
+            action
+        );
+    }
+
+    Ok(())
+}
+
+/// The user cache directory (`$XDG_CACHE_HOME` or `~/.cache`)
+pub fn user_cache_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|x| x.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|x| Path::new(&x).join(".cache")))
+}
+
+/// Directory of the global tarball cache (shared between workspaces)
+pub fn tarball_cache_dir() -> Option<PathBuf> {
+    user_cache_dir().map(|x| x.join(TARBALL_CACHE_DIR))
+}
+
+/// Find the tarball with the specified checksum in the global tarball cache
+pub fn find_cached_tarball(sha256: &str) -> Option<PathBuf> {
+    fs::read_dir(tarball_cache_dir()?.join(sha256))
+        .ok()?
+        .filter_map(|x| x.ok())
+        .map(|x| x.path())
+        .find(|x| x.is_file() && x.extension().is_none_or(|e| e != "part"))
+}
+
+/// Save the (verified) tarball into the global tarball cache
+pub fn cache_tarball(path: &Path, sha256: &str) -> Result<()> {
+    let cache_dir = tarball_cache_dir()
+        .ok_or_else(|| anyhow!("Unable to determine the cache directory"))?
+        .join(sha256);
+    let filename = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid tarball path"))?;
+    fs::create_dir_all(&cache_dir)?;
+    let dest = cache_dir.join(filename);
+    if fs::hard_link(path, &dest).is_err() {
+        // the cache may reside on a different filesystem
+        let part = dest.with_extension("part");
+        fs::copy(path, &part)?;
+        fs::rename(&part, &dest)?;
+    }
+
+    Ok(())
 }
 
 /// Download a file with progress indicator
-pub fn download_file_progress(url: &str, file: &str) -> Result<u64> {
-    let mut output = std::fs::File::create(file)?;
-    let resp = download_file(url)?;
-    let mut total: u64 = 0;
-    if let Some(length) = resp.headers().get("content-length") {
-        total = length.to_str().unwrap_or("0").parse::<u64>().unwrap_or(0);
-    }
-    if total > 0 {
-        // pre-allocate all the required disk space,
-        // fails early when there is insufficient disk space available
-        output.allocate(total)?;
-    }
-    let progress_bar = indicatif::ProgressBar::new(total);
-    progress_bar.set_style(
-        indicatif::ProgressStyle::default_bar()
-            .template(make_progress_bar!("{bytes}/{total_bytes}"))
-            .unwrap(),
-    );
-    progress_bar.set_draw_target(indicatif::ProgressDrawTarget::stderr_with_hz(5));
-    let mut reader = progress_bar.wrap_read(resp);
-    std::io::copy(&mut reader, &mut output)?;
-    progress_bar.finish_and_clear();
+///
+/// The file is first downloaded to a `.part` file in the workspace data directory,
+/// an interrupted download will be resumed (using HTTP range requests) if the URL
+/// and the expected checksum have not changed.
+/// The checksum (if provided) is verified before moving the file to its destination.
+pub fn download_file_progress(url: &str, file: &str, sha256: Option<&str>) -> Result<u64> {
+    ensure_online("Downloading files")?;
+    let data_dir = Path::new(CIEL_DATA_DIR);
+    fs::create_dir_all(data_dir)?;
+    let part_path = data_dir.join(format!("{}.part", file));
+    let state_path = data_dir.join(format!("{}.part.toml", file));
+    let state = PartialDownload {
+        url: url.to_string(),
+        sha256: sha256.map(|x| x.to_string()),
+    };
+    let resumable = fs::read_to_string(&state_path)
+        .ok()
+        .and_then(|x| toml::from_str::<PartialDownload>(&x).ok())
+        .is_some_and(|x| x == state);
+    let mut offset = if resumable {
+        fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0)
+    } else {
+        0
+    };
+    fs::write(&state_path, toml::to_string(&state)?)?;
+
+    let mut request = http_client_builder()?.build()?.get(url);
+    if offset > 0 {
+        info!(
+            "Resuming download from {}...",
+            indicatif::HumanBytes(offset)
+        );
+        request = request.header(RANGE, format!("bytes={}-", offset));
+    }
+    let resp = request.send()?;
+    // the downloaded part is kept for resuming, it is truncated to the offset below
+    let mut output = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&part_path)?;
+    let total = if offset > 0 && resp.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        // the previous download has already been completed
+        offset
+    } else {
+        let resp = resp.error_for_status()?;
+        if offset > 0 && resp.status() != StatusCode::PARTIAL_CONTENT {
+            info!("Server does not support resuming downloads, starting over...");
+            offset = 0;
+        }
+        let remaining = resp.content_length().unwrap_or(0);
+        if remaining > fs3::available_space(data_dir)? {
+            // fails early when there is insufficient disk space available
+            bail!(
+                "Insufficient disk space: {} is required",
+                indicatif::HumanBytes(remaining)
+            );
+        }
+        output.set_len(offset)?;
+        output.seek(SeekFrom::Start(offset))?;
+        let progress_bar = indicatif::ProgressBar::new(offset + remaining);
+        progress_bar.set_style(
+            indicatif::ProgressStyle::default_bar()
+                .template(make_progress_bar!("{bytes}/{total_bytes}"))
+                .unwrap(),
+        );
+        progress_bar.set_draw_target(indicatif::ProgressDrawTarget::stderr_with_hz(5));
+        progress_bar.set_position(offset);
+        let mut reader: Box<dyn Read> = match get_rate_limit() {
+            Some(rate) => Box::new(RateLimitedReader {
+                inner: resp,
+                rate,
+                started: Instant::now(),
+                transferred: 0,
+            }),
+            None => Box::new(resp),
+        };
+        std::io::copy(&mut progress_bar.wrap_read(&mut reader), &mut output)?;
+        progress_bar.finish_and_clear();
+
+        offset + remaining
+    };
+    drop(output);
+
+    if let Some(sha256) = sha256 {
+        info!("Verifying tarball checksum...");
+        let checksum = sha256sum(fs::File::open(&part_path)?)?;
+        if sha256 != checksum {
+            // the partial file is useless now, start over next time
+            fs::remove_file(&part_path).ok();
+            fs::remove_file(&state_path).ok();
+            bail!(
+                "Checksum mismatch: expected {} but got {}",
+                sha256,
+                checksum
+            );
+        }
+        info!("Checksum verified.");
+    }
+    fs::rename(&part_path, file)?;
+    fs::remove_file(&state_path).ok();
+
+    Ok(total)
+}
+
+/// Open the file at the URL for reading (e.g. to extract it while downloading),
+/// returns the reader and the size of the file (0 if unknown)
+pub fn open_download_stream(url: &str) -> Result<(Box<dyn Read + Send>, u64)> {
+    ensure_online("Downloading files")?;
+    let resp = http_client_builder()?
+        .build()?
+        .get(url)
+        .send()?
+        .error_for_status()?;
+    let total = resp.content_length().unwrap_or(0);
+    let reader: Box<dyn Read + Send> = match get_rate_limit() {
+        Some(rate) => Box::new(RateLimitedReader {
+            inner: resp,
+            rate,
+            started: Instant::now(),
+            transferred: 0,
+        }),
+        None => Box::new(resp),
+    };
+
+    Ok((reader, total))
+}
+
+/// Returns the URL of the file described by the metalink or torrent URL,
+/// or `None` if this is not a multi-source URL
+pub fn strip_multi_source_suffix(url: &str) -> Option<&str> {
+    MULTI_SOURCE_SUFFIXES
+        .iter()
+        .find_map(|suffix| url.strip_suffix(suffix))
+}
+
+/// Download a file using a metalink or torrent descriptor (via aria2c),
+/// which fetches from multiple mirrors (or peers) at once and verifies the chunks incrementally.
+/// Falls back to plain HTTP download from the URL without the descriptor suffix.
+pub fn download_file_multi_source(url: &str, file: &str, sha256: Option<&str>) -> Result<u64> {
+    let fallback = strip_multi_source_suffix(url)
+        .ok_or_else(|| anyhow!("{} is not a metalink or torrent URL", url))?;
+    ensure_online("Downloading files")?;
+    if which::which("aria2c").is_err() {
+        info!("aria2c is not installed, falling back to HTTP download...");
+        return download_file_progress(fallback, file, sha256);
+    }
+    let download_dir = Path::new(CIEL_DATA_DIR).join(format!("{}.aria2-dl", file));
+    fs::create_dir_all(&download_dir)?;
+    let mut command = Command::new("aria2c");
+    match get_proxy() {
+        Some(proxy) => {
+            command.arg(format!("--all-proxy={}", proxy));
+        }
+        None => {
+            for variable in PROXY_VARIABLES {
+                command.env_remove(variable);
+            }
+        }
+    }
+    if let Some(rate) = get_rate_limit() {
+        command.arg(format!("--max-overall-download-limit={}", rate));
+    }
+    let status = command
+        .args([
+            "--follow-metalink=mem",
+            "--follow-torrent=mem",
+            "--check-integrity=true",
+            "--continue=true",
+            "--seed-time=0",
+            "--summary-interval=0",
+            "--console-log-level=warn",
+            "--dir",
+        ])
+        .arg(&download_dir)
+        .arg(url)
+        .status();
+    let downloaded = match status {
+        Ok(status) if status.success() => fs::read_dir(&download_dir)?
+            .filter_map(|x| x.ok())
+            .map(|x| x.path())
+            .find(|x| x.is_file() && x.extension().is_none_or(|e| e != "aria2")),
+        Ok(status) => {
+            info!("aria2c exited with error: {}", status);
+            None
+        }
+        Err(e) => {
+            info!("Unable to execute aria2c: {}", e);
+            None
+        }
+    };
+    let downloaded = match downloaded {
+        Some(downloaded) => downloaded,
+        None => {
+            info!("Falling back to HTTP download...");
+            return download_file_progress(fallback, file, sha256);
+        }
+    };
+    if let Some(sha256) = sha256 {
+        info!("Verifying tarball checksum...");
+        let checksum = sha256sum(fs::File::open(&downloaded)?)?;
+        if sha256 != checksum {
+            fs::remove_dir_all(&download_dir).ok();
+            bail!(
+                "Checksum mismatch: expected {} but got {}",
+                sha256,
+                checksum
+            );
+        }
+        info!("Checksum verified.");
+    }
+    let total = fs::metadata(&downloaded)?.len();
+    fs::rename(&downloaded, file)?;
+    fs::remove_dir_all(&download_dir).ok();
 
     Ok(total)
 }
 
+/// Fingerprints of the valid signatures (signing key and primary key) in the gpgv status output
+fn parse_valid_signatures(status: &str) -> Vec<String> {
+    status
+        .lines()
+        .filter_map(|x| x.strip_prefix("[GNUPG:] VALIDSIG "))
+        .flat_map(|x| {
+            let fields = x.split_whitespace().collect::<Vec<_>>();
+            // the fingerprint of the primary key is the 10th field
+            [fields.first(), fields.get(9)]
+                .iter()
+                .flatten()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// gpgv verifying a detached signature using the configured keyring,
+/// the signature and the signed data are appended by the caller
+fn gpgv_command() -> Result<Command> {
+    let keyring = config::read_config()
+        .ok()
+        .and_then(|c| c.release_signing.keyring)
+        .ok_or_else(|| {
+            anyhow!("No release signing keyring configured, please set `release-signing.keyring` in the workspace configuration or use --insecure.")
+        })?;
+    let mut command = Command::new("gpgv");
+    command.args(["--status-fd", "1", "--keyring"]).arg(keyring);
+
+    Ok(command)
+}
+
+/// Check the result of gpgv, the signing key must be one of the pinned fingerprints (if any)
+fn check_gpgv_output(name: &str, output: &Output) -> Result<()> {
+    let valid = parse_valid_signatures(&String::from_utf8_lossy(&output.stdout));
+    if !output.status.success() || valid.is_empty() {
+        bail!(
+            "Invalid signature of {}: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let normalize = |x: &str| x.replace(' ', "").to_ascii_uppercase();
+    let pinned = config::read_config()
+        .map(|c| c.release_signing.fingerprints)
+        .unwrap_or_default()
+        .iter()
+        .map(|x| normalize(x))
+        .collect::<Vec<_>>();
+    if !pinned.is_empty() && !valid.iter().any(|x| pinned.contains(&normalize(x))) {
+        bail!(
+            "{} is signed by an unexpected key ({}).",
+            name,
+            valid.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Verify the detached signature of the file using the configured keyring
+pub fn verify_signature(file: &Path, signature: &Path) -> Result<()> {
+    let output = gpgv_command()?
+        .arg(signature)
+        .arg(file)
+        .output()
+        .map_err(|e| anyhow!("Unable to execute gpgv: {}", e))?;
+
+    check_gpgv_output(&file.display().to_string(), &output)
+}
+
+/// Download the detached signature of the file at the URL from `<url>.asc`
+fn download_signature(url: &str) -> Result<tempfile::NamedTempFile> {
+    let signature = http_client_builder()?
+        .build()?
+        .get(format!("{}{}", url, SIGNATURE_SUFFIX))
+        .send()?
+        .error_for_status()
+        .map_err(|e| anyhow!("Unable to download the signature: {}", e))?
+        .bytes()?;
+    let signature_file = tempfile::NamedTempFile::new()?;
+    fs::write(signature_file.path(), &signature)?;
+
+    Ok(signature_file)
+}
+
+/// Download the detached signature of the file from `<url>.asc` and verify the file
+pub fn verify_downloaded_file(url: &str, file: &Path) -> Result<()> {
+    if is_insecure() {
+        warn!("Signature verification skipped (--insecure).");
+        return Ok(());
+    }
+    info!("Verifying signature of {}...", file.display());
+    let signature = download_signature(url)?;
+    verify_signature(file, signature.path())?;
+    info!("Signature verified.");
+
+    Ok(())
+}
+
+/// Copies the data passing through into the writer
+struct TeeReader<R, W> {
+    inner: R,
+    output: W,
+}
+
+impl<R: Read, W: Write> Read for TeeReader<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let size = self.inner.read(buf)?;
+        self.output.write_all(&buf[..size])?;
+
+        Ok(size)
+    }
+}
+
+/// Verification of the signature of a file while it is being read
+pub struct StreamVerifier {
+    name: String,
+    gpgv: Child,
+    _signature: tempfile::NamedTempFile,
+}
+
+impl StreamVerifier {
+    /// Wait for the result, after the reader returned by `verify_stream` is dropped
+    pub fn finish(self) -> Result<()> {
+        let output = self.gpgv.wait_with_output()?;
+
+        check_gpgv_output(&self.name, &output)
+    }
+}
+
+/// Verify the detached signature (`<url>.asc`) of the file at the URL while it is being read
+/// from the returned reader, the result is available from the verifier
+pub fn verify_stream(
+    url: &str,
+    reader: Box<dyn Read + Send>,
+) -> Result<(Box<dyn Read + Send>, StreamVerifier)> {
+    let signature = download_signature(url)?;
+    let mut gpgv = gpgv_command()?
+        .arg(signature.path())
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Unable to execute gpgv: {}", e))?;
+    let stdin = gpgv
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("Unable to open the input of gpgv"))?;
+    let reader = Box::new(TeeReader {
+        inner: reader,
+        output: stdin,
+    });
+
+    Ok((
+        reader,
+        StreamVerifier {
+            name: url.to_string(),
+            gpgv,
+            _signature: signature,
+        },
+    ))
+}
+
+/// The architectures in the recipe, with the names of the variants published for them
+fn recipe_archs(recipe: &Recipe) -> BTreeMap<String, Vec<String>> {
+    let mut archs: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for variant in recipe.variants.iter() {
+        for rootfs in variant.squashfs.iter() {
+            let variants = archs.entry(rootfs.arch.clone()).or_default();
+            if !variants.contains(&variant.name) {
+                variants.push(variant.name.clone());
+            }
+        }
+    }
+
+    archs
+}
+
+/// Fetch and verify the release manifest, the architectures in it are recorded
+/// in the architecture registry
+fn fetch_recipe() -> Result<Recipe> {
+    let resp = http_client_builder()?
+        .build()?
+        .get(MANIFEST_URL)
+        .send()?
+        .error_for_status()?;
+    let manifest = tempfile::NamedTempFile::new()?;
+    fs::write(manifest.path(), resp.bytes()?)?;
+    // the checksums of the tarballs come from the manifest
+    verify_downloaded_file(MANIFEST_URL, manifest.path())?;
+    let recipe: Recipe = serde_json::from_slice(&fs::read(manifest.path())?)?;
+    if let Err(e) = register_archs(recipe_archs(&recipe)) {
+        warn!("Unable to update the architecture registry: {}", e);
+    }
+
+    Ok(recipe)
+}
+
+/// Update the architecture registry from the release manifest
+pub fn refresh_arch_registry() -> Result<()> {
+    ensure_online("Fetching the release manifest")?;
+    fetch_recipe()?;
+
+    Ok(())
+}
+
 /// Pick the latest buildkit rootfs according to the recipe
 pub fn pick_latest_rootfs(arch: &str) -> Result<RootFs> {
-    let resp = Client::new().get(MANIFEST_URL).send()?;
-    let recipe: Recipe = resp.json()?;
+    ensure_online("Picking the latest OS tarball")?;
+    let recipe = fetch_recipe()?;
     let buildkit = recipe
         .variants
         .into_iter()
@@ -103,6 +723,7 @@ pub fn pick_latest_rootfs(arch: &str) -> Result<RootFs> {
 
 /// Clone the Git repository to `root`
 pub fn download_git(uri: &str, root: &Path) -> Result<()> {
+    ensure_online("Cloning the Git repository")?;
     let mut callbacks = git2::RemoteCallbacks::new();
     let mut co_callback = git2::build::CheckoutBuilder::new();
     let current: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0usize));
@@ -141,6 +762,7 @@ pub fn download_git(uri: &str, root: &Path) -> Result<()> {
     });
     let mut options = git2::FetchOptions::new();
     options.remote_callbacks(callbacks);
+    options.proxy_options(git_proxy_options());
     // drawing progress bar in a separate thread
     let bar = thread::spawn(move || {
         let progress = indicatif::ProgressBar::new(1);
@@ -193,12 +815,14 @@ fn find_branch<'a>(repo: &'a git2::Repository, name: &str) -> Result<git2::Branc
 }
 
 pub fn fetch_repo<P: AsRef<Path>>(path: P) -> Result<git2::Repository> {
+    ensure_online("Fetching the Git repository")?;
     let repo = git2::Repository::open(path.as_ref())?;
     let mut remote = repo.find_remote("origin")?;
     let refs = remote.fetch_refspecs()?;
     let refspecs = refs.into_iter().flatten().collect::<Vec<_>>();
     let mut opts = git2::FetchOptions::new();
     opts.prune(git2::FetchPrune::On);
+    opts.proxy_options(git_proxy_options());
     remote.fetch(&refspecs, Some(&mut opts), None)?;
     drop(remote); // dis-own the variable `repo`
 
@@ -251,3 +875,46 @@ pub fn git_switch_branch(
     // returns whether a stash was made
     Ok(is_tree_dirty)
 }
+
+#[test]
+fn test_parse_valid_signatures() {
+    let status = "[GNUPG:] NEWSIG\n[GNUPG:] GOODSIG 1234ABCD AOSC OS\n[GNUPG:] VALIDSIG SUBKEYFPR 2024-01-01 1704067200 0 4 0 22 8 00 PRIMARYFPR\n";
+    assert_eq!(
+        parse_valid_signatures(status),
+        vec!["SUBKEYFPR".to_string(), "PRIMARYFPR".to_string()]
+    );
+    assert!(parse_valid_signatures("[GNUPG:] BADSIG 1234ABCD\n").is_empty());
+}
+
+#[test]
+fn test_recipe_archs() {
+    let rootfs = |arch: &str| {
+        format!(
+            r#"{{"arch": "{}", "date": "20240101", "path": "os-{}.tar.xz", "sha256sum": "0"}}"#,
+            arch, arch
+        )
+    };
+    let recipe: Recipe = serde_json::from_str(&format!(
+        r#"{{"version": 1, "variants": [
+            {{"name": "BuildKit", "squashfs": [{}, {}]}},
+            {{"name": "Base", "squashfs": [{}]}}
+        ]}}"#,
+        rootfs("amd64"),
+        rootfs("loongarch64_nosimd"),
+        rootfs("amd64")
+    ))
+    .unwrap();
+    assert_eq!(
+        recipe_archs(&recipe),
+        BTreeMap::from([
+            (
+                "amd64".to_string(),
+                vec!["BuildKit".to_string(), "Base".to_string()]
+            ),
+            (
+                "loongarch64_nosimd".to_string(),
+                vec!["BuildKit".to_string()]
+            ),
+        ])
+    );
+}
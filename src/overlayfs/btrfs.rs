@@ -0,0 +1,293 @@
+//! Btrfs snapshot based layer manager
+
+use anyhow::{anyhow, bail, Result};
+use nix::mount::{mount, umount2, MntFlags, MsFlags};
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+use crate::config::TmpfsConfig;
+
+use super::{copy_tree, is_mounted, Change, LayerManager};
+
+/// Inode number of the root directory of a btrfs subvolume
+const BTRFS_SUBVOLUME_INODE: u64 = 256;
+
+pub(crate) struct Btrfs {
+    inst: PathBuf,
+    base: PathBuf,
+    config: PathBuf,
+    snapshot: PathBuf,
+}
+
+fn btrfs_command<S: AsRef<OsStr>>(args: &[S]) -> Result<()> {
+    let status = Command::new("btrfs")
+        .args(args)
+        .status()
+        .map_err(|e| anyhow!("Unable to execute btrfs: {}", e))?;
+    if !status.success() {
+        bail!("btrfs exited with error: {}", status);
+    }
+
+    Ok(())
+}
+
+#[inline]
+fn is_subvolume(path: &Path) -> bool {
+    fs::metadata(path)
+        .map(|m| m.ino() == BTRFS_SUBVOLUME_INODE)
+        .unwrap_or(false)
+}
+
+/// Make sure that `path` is a btrfs subvolume, converting the existing directory if needed
+fn ensure_subvolume(path: &Path) -> Result<()> {
+    if is_subvolume(path) {
+        return Ok(());
+    }
+    let temp = path.with_extension("new");
+    btrfs_command(&[
+        OsStr::new("subvolume"),
+        OsStr::new("create"),
+        temp.as_os_str(),
+    ])?;
+    if path.is_dir() {
+        copy_tree(&path.join("."), &temp)?;
+        fs::remove_dir_all(path)?;
+    }
+    fs::rename(&temp, path)?;
+
+    Ok(())
+}
+
+fn delete_subvolume(path: &Path) -> Result<()> {
+    if path.exists() {
+        btrfs_command(&[
+            OsStr::new("subvolume"),
+            OsStr::new("delete"),
+            path.as_os_str(),
+        ])?;
+    }
+
+    Ok(())
+}
+
+fn create_snapshot(from: &Path, to: &Path) -> Result<()> {
+    btrfs_command(&[
+        OsStr::new("subvolume"),
+        OsStr::new("snapshot"),
+        from.as_os_str(),
+        to.as_os_str(),
+    ])
+}
+
+impl Btrfs {
+    /// Create the instance snapshot from the distribution and apply the configuration layer on top
+    fn create_instance_snapshot(&self) -> Result<()> {
+        ensure_subvolume(&self.base)?;
+        fs::create_dir_all(&self.config)?;
+        if let Some(parent) = self.snapshot.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        create_snapshot(&self.base, &self.snapshot)?;
+        copy_tree(&self.config.join("."), &self.snapshot)?;
+
+        Ok(())
+    }
+}
+
+impl LayerManager for Btrfs {
+    fn name() -> String
+    where
+        Self: Sized,
+    {
+        "btrfs".to_owned()
+    }
+    // The btrfs layout looks like this:
+    // |- snapshot: .ciel/container/instances/<inst_name>/layers/snapshot/ (subvolume)
+    // |- config: .ciel/container/instances/<inst_name>/layers/local/
+    // ||- base: .ciel/container/dist/ (subvolume)
+    fn from_inst_dir<P: AsRef<Path>>(
+        dist_path: P,
+        inst_path: P,
+        inst_name: P,
+    ) -> Result<Box<dyn LayerManager>>
+    where
+        Self: Sized,
+    {
+        let dist = dist_path.as_ref();
+        let inst = inst_path.as_ref().join(inst_name.as_ref());
+        Ok(Box::new(Btrfs {
+            inst: inst.to_owned(),
+            base: dist.to_owned(),
+            config: inst.join("layers/local"),
+            snapshot: inst.join("layers/snapshot"),
+        }))
+    }
+
+    fn mount(&mut self, to: &Path) -> Result<()> {
+        if !self.snapshot.exists() {
+            self.create_instance_snapshot()?;
+        }
+        mount(
+            Some(&self.snapshot),
+            to,
+            None::<&str>,
+            MsFlags::MS_BIND,
+            None::<&str>,
+        )?;
+
+        Ok(())
+    }
+
+    fn is_mounted(&self, target: &Path) -> Result<bool> {
+        is_mounted(target, OsStr::new("btrfs"))
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        delete_subvolume(&self.snapshot)?;
+        self.create_instance_snapshot()
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        if !self.snapshot.exists() {
+            return Ok(());
+        }
+        let old_base = self.base.with_extension("old");
+        delete_subvolume(&old_base)?;
+        fs::rename(&self.base, &old_base)?;
+        create_snapshot(&self.snapshot, &self.base)?;
+        // the configuration layer belongs to the instance, restore the original files
+        for entry in WalkDir::new(&self.config).min_depth(1) {
+            let entry = entry?;
+            if entry.file_type().is_dir() {
+                continue;
+            }
+            let rel_path = entry.path().strip_prefix(&self.config)?;
+            let new_path = self.base.join(rel_path);
+            let old_path = old_base.join(rel_path);
+            if fs::symlink_metadata(&new_path).is_ok() {
+                fs::remove_file(&new_path)?;
+            }
+            if fs::symlink_metadata(&old_path).is_ok() {
+                copy_tree(&old_path, &new_path)?;
+            }
+        }
+        delete_subvolume(&old_base)?;
+        self.rollback()
+    }
+
+    fn unmount(&mut self, target: &Path) -> Result<()> {
+        umount2(target, MntFlags::MNT_DETACH)?;
+
+        Ok(())
+    }
+
+    fn get_config_layer(&mut self) -> Result<PathBuf> {
+        Ok(self.config.clone())
+    }
+
+    fn get_base_layer(&mut self) -> Result<PathBuf> {
+        Ok(self.base.clone())
+    }
+
+    fn get_upper_layer(&mut self) -> Result<PathBuf> {
+        bail!("The btrfs backend does not have an upper layer.")
+    }
+
+    fn set_volatile(&mut self, _volatile: bool) -> Result<()> {
+        // snapshots do not have a volatile mode
+        Ok(())
+    }
+
+    fn set_rootless(&mut self, rootless: bool) -> Result<()> {
+        if rootless {
+            bail!("Btrfs filesystem backend does not support rootless mode");
+        }
+
+        Ok(())
+    }
+
+    fn set_extra_layers(&mut self, layers: &[PathBuf]) -> Result<()> {
+        if !layers.is_empty() {
+            bail!("Btrfs filesystem backend does not support shared layers");
+        }
+
+        Ok(())
+    }
+
+    fn set_tmpfs(&mut self, tmpfs: Option<&TmpfsConfig>) -> Result<()> {
+        if tmpfs.is_some() {
+            bail!("Btrfs filesystem backend does not support tmpfs upper layers");
+        }
+
+        Ok(())
+    }
+
+    fn clone_to(&mut self, inst_name: &str) -> Result<()> {
+        let target = self.inst.with_file_name(inst_name).join("layers");
+        fs::create_dir_all(&target)?;
+        if self.config.is_dir() {
+            copy_tree(&self.config, &target)?;
+        }
+        if self.snapshot.exists() {
+            create_snapshot(&self.snapshot, &target.join("snapshot"))?;
+        }
+
+        Ok(())
+    }
+
+    fn destroy(&mut self) -> Result<()> {
+        delete_subvolume(&self.snapshot)?;
+        fs::remove_dir_all(&self.inst)?;
+
+        Ok(())
+    }
+
+    fn save_snapshot(&mut self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        if self.config.is_dir() {
+            copy_tree(&self.config, dir)?;
+        }
+        if self.snapshot.exists() {
+            create_snapshot(&self.snapshot, &dir.join("snapshot"))?;
+        }
+
+        Ok(())
+    }
+
+    fn restore_snapshot(&mut self, dir: &Path) -> Result<()> {
+        delete_subvolume(&self.snapshot)?;
+        let saved = dir.join("snapshot");
+        if saved.exists() {
+            create_snapshot(&saved, &self.snapshot)?;
+        }
+        if self.config.exists() {
+            fs::remove_dir_all(&self.config)?;
+        }
+        fs::create_dir_all(&self.config)?;
+        let saved = dir.join("local");
+        if saved.is_dir() {
+            copy_tree(&saved.join("."), &self.config)?;
+        }
+
+        Ok(())
+    }
+
+    fn delete_snapshot(&mut self, dir: &Path) -> Result<()> {
+        delete_subvolume(&dir.join("snapshot"))?;
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    fn list_changes(&mut self) -> Result<Vec<Change>> {
+        bail!("Listing the changes is not supported by the btrfs backend.")
+    }
+
+    fn commit_selected(&mut self, _paths: &[PathBuf]) -> Result<()> {
+        bail!("Selective commit is not supported by the btrfs backend.")
+    }
+}
@@ -0,0 +1,171 @@
+//! Read-only images (erofs or squashfs) of the base OS, mounted as the lowest overlay layer
+
+use anyhow::{anyhow, bail, Result};
+use nix::mount::{umount2, MntFlags};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::common::CIEL_DIST_DIR;
+
+use super::{copy_tree, is_mounted};
+
+/// Formats of the base OS image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DistImageFormat {
+    Erofs,
+    Squashfs,
+}
+
+impl DistImageFormat {
+    pub const ALL: &'static [DistImageFormat] =
+        &[DistImageFormat::Erofs, DistImageFormat::Squashfs];
+
+    /// Name of the format, also the filesystem type and the extension of the image
+    pub fn name(self) -> &'static str {
+        match self {
+            DistImageFormat::Erofs => "erofs",
+            DistImageFormat::Squashfs => "squashfs",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|x| x.name() == name)
+    }
+
+    /// The image is placed next to the dist directory, e.g. `.ciel/container/dist.erofs`
+    fn image_path(self) -> PathBuf {
+        PathBuf::from(format!("{}.{}", CIEL_DIST_DIR, self.name()))
+    }
+}
+
+/// Return the format and the path of the base OS image, `None` if the base OS is a plain directory
+pub fn dist_image() -> Option<(DistImageFormat, PathBuf)> {
+    DistImageFormat::ALL
+        .iter()
+        .map(|x| (*x, x.image_path()))
+        .find(|x| x.1.is_file())
+}
+
+/// Fail if the base OS is packed into a (read-only) image, `operation` is used in the error message
+pub fn ensure_dist_writable(operation: &str) -> Result<()> {
+    if let Some((format, _)) = dist_image() {
+        bail!(
+            "The base OS is packed into a read-only {} image, {} requires `ciel unpack-os` first.",
+            format.name(),
+            operation
+        );
+    }
+
+    Ok(())
+}
+
+/// Mount the base OS image (if any) read-only on the dist directory
+pub fn mount_dist_image() -> Result<()> {
+    let (format, image) = match dist_image() {
+        Some(image) => image,
+        None => return Ok(()),
+    };
+    let target = std::env::current_dir()?.join(CIEL_DIST_DIR);
+    if is_mounted(&target, OsStr::new(format.name()))? {
+        return Ok(());
+    }
+    fs::create_dir_all(&target)?;
+    let status = Command::new("mount")
+        .args(["-t", format.name(), "-o", "loop,ro"])
+        .arg(&image)
+        .arg(&target)
+        .status()
+        .map_err(|e| anyhow!("Unable to execute mount: {}", e))?;
+    if !status.success() {
+        bail!(
+            "Unable to mount the base OS image {}: {}",
+            image.display(),
+            status
+        );
+    }
+
+    Ok(())
+}
+
+/// Un-mount the base OS image (if mounted)
+pub fn unmount_dist_image() -> Result<()> {
+    if let Some((format, _)) = dist_image() {
+        let target = std::env::current_dir()?.join(CIEL_DIST_DIR);
+        if is_mounted(&target, OsStr::new(format.name()))? {
+            umount2(&target, MntFlags::MNT_DETACH)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the base OS image (e.g. when a new base OS is loaded)
+pub fn remove_dist_image() -> Result<()> {
+    unmount_dist_image()?;
+    if let Some((_, image)) = dist_image() {
+        fs::remove_file(image)?;
+    }
+
+    Ok(())
+}
+
+/// Pack the base OS directory into an image and mount it in place of the directory.
+/// All the instances must be un-mounted first.
+pub fn pack_dist(format: DistImageFormat) -> Result<()> {
+    if dist_image().is_some() {
+        bail!("The base OS is already packed into an image.");
+    }
+    let dist = Path::new(CIEL_DIST_DIR);
+    if fs::read_dir(dist).map_or(true, |mut x| x.next().is_none()) {
+        bail!("No base OS is loaded.");
+    }
+    let image = format.image_path();
+    let temp = PathBuf::from(format!("{}.tmp", image.display()));
+    let status = match format {
+        DistImageFormat::Erofs => Command::new("mkfs.erofs")
+            .args(["--quiet", "-zlz4hc"])
+            .arg(&temp)
+            .arg(dist)
+            .status(),
+        DistImageFormat::Squashfs => Command::new("mksquashfs")
+            .arg(dist)
+            .arg(&temp)
+            .args(["-comp", "zstd", "-noappend", "-quiet"])
+            .status(),
+    }
+    .map_err(|e| anyhow!("Unable to create the {} image: {}", format.name(), e))?;
+    if !status.success() {
+        fs::remove_file(&temp).ok();
+        bail!("Unable to create the {} image: {}", format.name(), status);
+    }
+    fs::rename(&temp, &image)?;
+    // the directory becomes the mount point of the image
+    fs::remove_dir_all(dist)?;
+    fs::create_dir_all(dist)?;
+
+    mount_dist_image()
+}
+
+/// Unpack the base OS image into the dist directory, so that it can be modified again.
+/// All the instances must be un-mounted first.
+pub fn unpack_dist() -> Result<()> {
+    let (_, image) =
+        dist_image().ok_or_else(|| anyhow!("The base OS is not packed into an image."))?;
+    mount_dist_image()?;
+    let temp = PathBuf::from(format!("{}.unpacked", CIEL_DIST_DIR));
+    if temp.exists() {
+        fs::remove_dir_all(&temp)?;
+    }
+    fs::create_dir_all(&temp)?;
+    copy_tree(&Path::new(CIEL_DIST_DIR).join("."), &temp)?;
+    unmount_dist_image()?;
+    fs::remove_dir(CIEL_DIST_DIR)?;
+    fs::rename(&temp, CIEL_DIST_DIR)?;
+    fs::remove_file(image)?;
+
+    Ok(())
+}
@@ -0,0 +1,881 @@
+use crate::{
+    common,
+    config::{self, TmpfsConfig},
+};
+use anyhow::{anyhow, bail, Context, Result};
+use libmount::{mountinfo::Parser, Overlay};
+use nix::mount::{mount, umount2, MntFlags, MsFlags};
+use nix::sys::statfs::{statfs, FsType, BTRFS_SUPER_MAGIC};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{
+    ffi::OsStr,
+    io::{BufRead, BufReader},
+};
+
+mod btrfs;
+mod image;
+mod zfs;
+
+pub use self::image::{
+    dist_image, ensure_dist_writable, mount_dist_image, pack_dist, remove_dist_image,
+    unmount_dist_image, unpack_dist, DistImageFormat,
+};
+
+/// ZFS does not have a magic number defined in nix
+const ZFS_SUPER_MAGIC: FsType = FsType(0x2fc12fc1);
+
+/// Filesystem backends for the instances
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FsBackendKind {
+    /// Detect the backend using the filesystem where the workspace is located
+    #[default]
+    Auto,
+    Overlay,
+    Btrfs,
+    Zfs,
+}
+
+pub trait LayerManager {
+    /// Return the name of the layer manager, e.g. "overlay".
+    /// This name should be the same as the fs_type listed in the /proc/<>/mountinfo file
+    fn name() -> String
+    where
+        Self: Sized;
+    /// Create a new layer manager from the given distribution directory
+    /// dist: distribution directory, inst: instance name (not directory)
+    fn from_inst_dir<P: AsRef<Path>>(
+        dist_path: P,
+        inst_path: P,
+        inst_name: P,
+    ) -> Result<Box<dyn LayerManager>>
+    where
+        Self: Sized;
+    /// Mount the filesystem to the given path
+    fn mount(&mut self, to: &Path) -> Result<()>;
+    /// Return if the filesystem is mounted
+    fn is_mounted(&self, target: &Path) -> Result<bool>;
+    /// Rollback the filesystem to the distribution state
+    fn rollback(&mut self) -> Result<()>;
+    /// Commit the current state of the instance filesystem to the distribution state
+    fn commit(&mut self) -> Result<()>;
+    /// Un-mount the filesystem
+    fn unmount(&mut self, target: &Path) -> Result<()>;
+    /// Return the directory where the configuration layer is located
+    /// You may temporary mount this directory if your backend does not expose this directory directly
+    fn get_config_layer(&mut self) -> Result<PathBuf>;
+    /// Return the directory where the base layer is located
+    fn get_base_layer(&mut self) -> Result<PathBuf>;
+    /// Return the directory holding the changes of the instance (the upper layer)
+    fn get_upper_layer(&mut self) -> Result<PathBuf>;
+    /// Set the volatile state of the instance filesystem
+    fn set_volatile(&mut self, volatile: bool) -> Result<()>;
+    /// Set whether the filesystem should be handled without root privileges
+    fn set_rootless(&mut self, rootless: bool) -> Result<()>;
+    /// Place the upper layer on tmpfs (`None` to keep it on the disk)
+    fn set_tmpfs(&mut self, tmpfs: Option<&TmpfsConfig>) -> Result<()>;
+    /// Stack the shared read-only layers (from the bottom to the top) on the base layer
+    fn set_extra_layers(&mut self, layers: &[PathBuf]) -> Result<()>;
+    /// Duplicate the filesystem (including the configuration layer) of the current instance into a new instance
+    fn clone_to(&mut self, inst_name: &str) -> Result<()>;
+    /// Destroy the filesystem of the current instance
+    fn destroy(&mut self) -> Result<()>;
+    /// Save the state of the instance (everything above the base layer) into the given directory
+    fn save_snapshot(&mut self, dir: &Path) -> Result<()>;
+    /// Replace the state of the instance with the one saved by `save_snapshot`
+    fn restore_snapshot(&mut self, dir: &Path) -> Result<()>;
+    /// Remove the snapshot saved by `save_snapshot`
+    fn delete_snapshot(&mut self, dir: &Path) -> Result<()>;
+    /// List the changes made in the instance filesystem (relative to the distribution state)
+    fn list_changes(&mut self) -> Result<Vec<Change>>;
+    /// Commit only the changes at the given paths (as returned by `list_changes`) to the distribution state
+    fn commit_selected(&mut self, paths: &[PathBuf]) -> Result<()>;
+}
+
+/// Kind of the change made in the instance filesystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl ChangeKind {
+    /// Single letter status, as in `git status --short`
+    pub fn symbol(self) -> char {
+        match self {
+            ChangeKind::Added => 'A',
+            ChangeKind::Modified => 'M',
+            ChangeKind::Deleted => 'D',
+        }
+    }
+}
+
+/// A change made in the instance filesystem, `path` is relative to the root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub path: PathBuf,
+}
+
+struct OverlayFS {
+    inst: PathBuf,
+    base: PathBuf,
+    lower: PathBuf,
+    upper: PathBuf,
+    work: PathBuf,
+    volatile: bool,
+    rootless: bool,
+    tmpfs: Option<TmpfsConfig>,
+    /// Shared layers between the base and the lower layer, from the bottom to the top
+    layers: Vec<PathBuf>,
+}
+
+/// Whiteout marker prefix used by fuse-overlayfs when running unprivileged (same as in the OCI layers)
+pub(crate) const WHITEOUT_PREFIX: &str = ".wh.";
+/// Opaque directory marker used by fuse-overlayfs when running unprivileged (same as in the OCI layers)
+pub(crate) const OPAQUE_MARKER: &str = ".wh..wh..opq";
+
+/// Create a new overlay filesystem on the host system
+pub fn create_new_instance_fs<P: AsRef<Path>>(inst_path: P, inst_name: P) -> Result<()> {
+    let inst = inst_path.as_ref().join(inst_name.as_ref());
+    fs::create_dir_all(inst)?;
+    Ok(())
+}
+
+/// OverlayFS operations
+#[derive(Debug)]
+enum Diff {
+    Symlink(PathBuf),
+    OverrideDir(PathBuf),
+    RenamedDir(PathBuf, PathBuf),
+    NewDir(PathBuf),
+    ModifiedDir(PathBuf),    // Modify permission only
+    WhiteoutFile(PathBuf),   // Dir or File
+    WhiteoutMarker(PathBuf), // `.wh.` prefixed file (fuse-overlayfs)
+    File(PathBuf),           // Simple modified or new file
+}
+
+impl Diff {
+    /// Describe the operation as a change, renamed directories are reported at their new path
+    fn change(&self, base: &Path) -> Change {
+        let (kind, path) = match self {
+            Diff::Symlink(path) | Diff::File(path) => {
+                if fs::symlink_metadata(base.join(path)).is_ok() {
+                    (ChangeKind::Modified, path.clone())
+                } else {
+                    (ChangeKind::Added, path.clone())
+                }
+            }
+            Diff::NewDir(path) | Diff::RenamedDir(_, path) => (ChangeKind::Added, path.clone()),
+            Diff::OverrideDir(path) | Diff::ModifiedDir(path) => {
+                (ChangeKind::Modified, path.clone())
+            }
+            Diff::WhiteoutFile(path) => (ChangeKind::Deleted, path.clone()),
+            Diff::WhiteoutMarker(path) => {
+                let name = path
+                    .file_name()
+                    .and_then(|x| x.as_bytes().strip_prefix(WHITEOUT_PREFIX.as_bytes()))
+                    .unwrap_or_default();
+                (
+                    ChangeKind::Deleted,
+                    path.with_file_name(OsStr::from_bytes(name)),
+                )
+            }
+        };
+
+        Change { kind, path }
+    }
+}
+
+impl OverlayFS {
+    /// Apply the operations to the base layer, deletions first
+    fn apply_diff(&self, mods: &[Diff]) -> Result<()> {
+        ensure_dist_writable("committing")?;
+        // FIXME: use drain_filter in the future
+        // first pass to execute all the deletion actions
+        for i in mods.iter() {
+            match i {
+                Diff::WhiteoutFile(_) | Diff::WhiteoutMarker(_) => overlay_exec_action(i, self)?,
+                _ => continue,
+            }
+        }
+        // second pass for everything else
+        for i in mods.iter() {
+            match i {
+                Diff::WhiteoutFile(_) | Diff::WhiteoutMarker(_) => continue,
+                _ => overlay_exec_action(i, self)
+                    .with_context(|| format!("when processing {:?}", i))?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Generate a list of changes made in the upper layer
+    fn diff(&self) -> Result<Vec<Diff>> {
+        let mut mods: Vec<Diff> = Vec::new();
+        let mut processed_dirs: Vec<PathBuf> = Vec::new();
+
+        for entry in walkdir::WalkDir::new(&self.upper).into_iter().skip(1) {
+            // SKip the root
+            let path: PathBuf = entry?.path().to_path_buf();
+            let rel_path = path.strip_prefix(&self.upper)?.to_path_buf();
+            let lower_path = self.lower.join(&rel_path).to_path_buf();
+
+            if has_prefix(&rel_path, &processed_dirs) {
+                continue; // We already dealt with it
+            }
+            let meta = fs::symlink_metadata(&path)?;
+            let file_type = meta.file_type();
+
+            if file_type.is_symlink() {
+                // Just move the symlink
+                mods.push(Diff::Symlink(rel_path.clone()));
+            } else if meta.is_dir() {
+                // Deal with dirs
+                let opaque = xattr::get(&path, "trusted.overlay.opaque")?
+                    .or(xattr::get(&path, "user.fuseoverlayfs.opaque")?)
+                    .or_else(|| {
+                        if path.join(OPAQUE_MARKER).exists() {
+                            Some(b"y".to_vec())
+                        } else {
+                            None
+                        }
+                    });
+                let redirect = xattr::get(&path, "trusted.overlay.redirect")?;
+                let metacopy = xattr::get(&path, "trusted.overlay.metacopy")?;
+
+                if let Some(_data) = metacopy {
+                    bail!("Unsupported filesystem feature: metacopy");
+                }
+                if let Some(text) = opaque {
+                    // the new dir (completely) replace the old one
+                    if text == b"y" {
+                        // Delete corresponding dir
+                        mods.push(Diff::OverrideDir(rel_path.clone()));
+                        processed_dirs.push(rel_path.clone());
+                    }
+                } else if let Some(from_utf8) = redirect {
+                    // Renamed
+                    let mut from_rel_path = PathBuf::from(OsStr::from_bytes(&from_utf8));
+                    if from_rel_path.is_absolute() {
+                        // abs path from root of OverlayFS
+                        from_rel_path = from_rel_path.strip_prefix("/")?.to_path_buf();
+                    } else {
+                        // rel path, same parent dir as the origin
+                        let mut from_path = path.clone();
+                        from_path.pop();
+                        from_path.push(PathBuf::from(&from_rel_path));
+                        from_rel_path = from_path.strip_prefix(&self.upper)?.to_path_buf();
+                    }
+                    mods.push(Diff::RenamedDir(from_rel_path, rel_path));
+                } else if !lower_path.is_dir() {
+                    // New dir
+                    mods.push(Diff::NewDir(rel_path.clone()));
+                } else {
+                    // Modified
+                    mods.push(Diff::ModifiedDir(rel_path.clone()));
+                }
+            } else {
+                // Deal with files
+                let is_marker = rel_path
+                    .file_name()
+                    .map(|x| x.as_bytes().starts_with(WHITEOUT_PREFIX.as_bytes()))
+                    .unwrap_or(false);
+                if file_type.is_char_device() && meta.rdev() == 0 {
+                    // Whiteout file!
+                    mods.push(Diff::WhiteoutFile(rel_path.clone()));
+                } else if is_marker {
+                    // Whiteout marker file (or opaque marker) created by fuse-overlayfs
+                    if rel_path.file_name() != Some(OsStr::new(OPAQUE_MARKER)) {
+                        mods.push(Diff::WhiteoutMarker(rel_path.clone()));
+                    }
+                } else if lower_path.is_dir() {
+                    // A new file overrides an old directory
+                    mods.push(Diff::OverrideDir(rel_path.clone()));
+                } else {
+                    mods.push(Diff::File(rel_path.clone()));
+                }
+            }
+        }
+
+        Ok(mods)
+    }
+
+    /// Mount the filesystem using fuse-overlayfs (does not require root privileges)
+    /// Mount point of the tmpfs holding the upper layer
+    fn tmpfs_dir(&self) -> PathBuf {
+        self.inst.join("layers/tmpfs")
+    }
+
+    fn mount_tmpfs(&self, tmpfs: &TmpfsConfig) -> Result<()> {
+        let target = std::env::current_dir()?.join(self.tmpfs_dir());
+        fs::create_dir_all(&target)?;
+        if is_mounted(&target, OsStr::new("tmpfs"))? {
+            return Ok(());
+        }
+        let mut options = "mode=0755".to_string();
+        if let Some(size) = &tmpfs.size {
+            config::validate_tmpfs_size(size)?;
+            options.push_str(&format!(",size={}", size));
+        }
+        mount(
+            Some("tmpfs"),
+            &target,
+            Some("tmpfs"),
+            MsFlags::empty(),
+            Some(options.as_str()),
+        )
+        .map_err(|e| anyhow!("Unable to mount tmpfs: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Read-only layers of the overlay, from the top to the bottom
+    fn lower_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = vec![self.lower.clone()];
+        dirs.extend(self.layers.iter().rev().cloned());
+        dirs.push(self.base.clone());
+
+        dirs
+    }
+
+    fn mount_fuse(&self, to: &Path) -> Result<()> {
+        let mut options = OsString::from("lowerdir=");
+        for (i, dir) in self.lower_dirs().iter().enumerate() {
+            if i > 0 {
+                options.push(":");
+            }
+            options.push(dir);
+        }
+        options.push(",upperdir=");
+        options.push(&self.upper);
+        options.push(",workdir=");
+        options.push(&self.work);
+        let status = Command::new("fuse-overlayfs")
+            .arg("-o")
+            .arg(options)
+            .arg(to)
+            .status()
+            .map_err(|e| anyhow!("Unable to execute fuse-overlayfs: {}", e))?;
+        if !status.success() {
+            bail!("fuse-overlayfs exited with error: {}", status);
+        }
+
+        Ok(())
+    }
+}
+
+impl LayerManager for OverlayFS {
+    fn name() -> String
+    where
+        Self: Sized,
+    {
+        "overlay".to_owned()
+    }
+    // The overlayfs structure inherited from older CIEL looks like this:
+    // |- work: .ciel/container/instances/<inst_name>/diff.tmp/
+    // |- upper: .ciel/container/instances/<inst_name>/diff/
+    // |- lower: .ciel/container/instances/<inst_name>/local/
+    // ||- lower (base): .ciel/container/dist/
+    fn from_inst_dir<P: AsRef<Path>>(
+        dist_path: P,
+        inst_path: P,
+        inst_name: P,
+    ) -> Result<Box<dyn LayerManager>>
+    where
+        Self: Sized,
+    {
+        let dist = dist_path.as_ref();
+        let inst = inst_path.as_ref().join(inst_name.as_ref());
+        Ok(Box::new(OverlayFS {
+            inst: inst.to_owned(),
+            base: dist.to_owned(),
+            lower: inst.join("layers/local"),
+            upper: inst.join("layers/diff"),
+            work: inst.join("layers/diff.tmp"),
+            volatile: false,
+            rootless: false,
+            tmpfs: None,
+            layers: Vec::new(),
+        }))
+    }
+    fn mount(&mut self, to: &Path) -> Result<()> {
+        let base_dirs = self.lower_dirs();
+        let mut overlay = Overlay::writable(
+            // base_dirs variable contains the lower, the shared and the base directories
+            base_dirs.iter().map(|x| x.as_ref()),
+            self.upper.clone(),
+            self.work.clone(),
+            to,
+        );
+        if let Some(tmpfs) = &self.tmpfs {
+            if self.rootless {
+                bail!("The upper layer can not be placed on tmpfs in rootless mode");
+            }
+            self.mount_tmpfs(tmpfs)?;
+        }
+        if dist_image().is_some() {
+            if self.rootless {
+                bail!("The base OS image can not be mounted in rootless mode, please run `ciel unpack-os`.");
+            }
+            mount_dist_image()?;
+        }
+        // create the directories if they don't exist (work directory may be missing)
+        fs::create_dir_all(&self.work)?;
+        fs::create_dir_all(&self.upper)?;
+        fs::create_dir_all(&self.lower)?;
+        let dirty_flag = self.work.join("work/incompat");
+        if dirty_flag.exists() {
+            return Err(anyhow!(
+                "This container filesystem can't be used anymore. Please rollback."
+            ));
+        }
+        if self.rootless {
+            return self.mount_fuse(to);
+        }
+        // check overlay usability
+        load_overlayfs_support()?;
+        if self.volatile {
+            overlay.set_options(b"volatile".to_vec());
+        }
+        // let's mount them
+        overlay.mount().map_err(|e| anyhow!("{}", e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// is_mounted: check if a path is a mountpoint with corresponding fs_type
+    fn is_mounted(&self, target: &Path) -> Result<bool> {
+        if self.rootless {
+            return is_mounted(target, OsStr::new("fuse.fuse-overlayfs"));
+        }
+        is_mounted(target, OsStr::new("overlay"))
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        for dir in [&self.upper, &self.work] {
+            // the layers on tmpfs are gone after un-mounting
+            if dir.exists() {
+                fs::remove_dir_all(dir)?;
+            }
+            fs::create_dir_all(dir)?;
+        }
+
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        if self.volatile {
+            // for safety reasons
+            nix::unistd::sync();
+        }
+        let mods = self.diff()?;
+        self.apply_diff(&mods)?;
+        // clear all the remnant items in the upper layer
+        self.rollback()?;
+
+        Ok(())
+    }
+
+    fn list_changes(&mut self) -> Result<Vec<Change>> {
+        if !self.upper.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        Ok(self.diff()?.iter().map(|x| x.change(&self.base)).collect())
+    }
+
+    fn commit_selected(&mut self, paths: &[PathBuf]) -> Result<()> {
+        if self.volatile {
+            nix::unistd::sync();
+        }
+        let mods = self
+            .diff()?
+            .into_iter()
+            .filter(|x| {
+                let path = x.change(&self.base).path;
+                // the new parent directories are created together with their contents
+                paths.contains(&path)
+                    || matches!(x, Diff::NewDir(_)) && paths.iter().any(|p| p.starts_with(&path))
+            })
+            .collect::<Vec<_>>();
+        // the changes left out stay in the upper layer
+        self.apply_diff(&mods)
+    }
+
+    fn unmount(&mut self, target: &Path) -> Result<()> {
+        if self.rootless {
+            return fuse_unmount(target);
+        }
+        umount2(target, MntFlags::MNT_DETACH)?;
+        if self.tmpfs.is_some() {
+            let tmpfs_dir = std::env::current_dir()?.join(self.tmpfs_dir());
+            if is_mounted(&tmpfs_dir, OsStr::new("tmpfs"))? {
+                umount2(&tmpfs_dir, MntFlags::MNT_DETACH)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_config_layer(&mut self) -> Result<PathBuf> {
+        Ok(self.lower.clone())
+    }
+
+    fn get_base_layer(&mut self) -> Result<PathBuf> {
+        Ok(self.base.clone())
+    }
+
+    fn get_upper_layer(&mut self) -> Result<PathBuf> {
+        Ok(self.upper.clone())
+    }
+
+    fn clone_to(&mut self, inst_name: &str) -> Result<()> {
+        let target = self.inst.with_file_name(inst_name).join("layers");
+        fs::create_dir_all(&target)?;
+        // the work directory is not needed, it will be re-created when mounting
+        for layer in [&self.lower, &self.upper] {
+            if layer.is_dir() {
+                copy_tree(layer, &target)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn destroy(&mut self) -> Result<()> {
+        fs::remove_dir_all(&self.inst)?;
+
+        Ok(())
+    }
+
+    fn save_snapshot(&mut self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        for layer in [&self.lower, &self.upper] {
+            if layer.is_dir() {
+                copy_tree(layer, dir)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn restore_snapshot(&mut self, dir: &Path) -> Result<()> {
+        for (layer, name) in [(&self.lower, "local"), (&self.upper, "diff")] {
+            if layer.exists() {
+                fs::remove_dir_all(layer)?;
+            }
+            fs::create_dir_all(layer)?;
+            let saved = dir.join(name);
+            if saved.is_dir() {
+                copy_tree(&saved.join("."), layer)?;
+            }
+        }
+        // the work directory must be empty when mounting
+        if self.work.exists() {
+            fs::remove_dir_all(&self.work)?;
+        }
+        fs::create_dir_all(&self.work)?;
+
+        Ok(())
+    }
+
+    fn delete_snapshot(&mut self, dir: &Path) -> Result<()> {
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    fn set_volatile(&mut self, volatile: bool) -> Result<()> {
+        self.volatile = volatile;
+
+        Ok(())
+    }
+
+    fn set_rootless(&mut self, rootless: bool) -> Result<()> {
+        self.rootless = rootless;
+
+        Ok(())
+    }
+
+    fn set_extra_layers(&mut self, layers: &[PathBuf]) -> Result<()> {
+        self.layers = layers.to_vec();
+
+        Ok(())
+    }
+
+    fn set_tmpfs(&mut self, tmpfs: Option<&TmpfsConfig>) -> Result<()> {
+        self.tmpfs = tmpfs.cloned();
+        if self.tmpfs.is_some() {
+            // the upper and work directories must be on the same filesystem
+            self.upper = self.tmpfs_dir().join("diff");
+            self.work = self.tmpfs_dir().join("diff.tmp");
+        }
+
+        Ok(())
+    }
+}
+
+/// is_mounted: check if a path is a mountpoint with corresponding fs_type
+pub(crate) fn is_mounted(mountpoint: &Path, fs_type: &OsStr) -> Result<bool> {
+    let mountinfo_content: Vec<u8> = fs::read("/proc/self/mountinfo")?;
+    let parser = Parser::new(&mountinfo_content);
+
+    for mount in parser {
+        let mount = mount?;
+        if mount.mount_point == mountpoint && mount.fstype == fs_type {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Detect the filesystem backend to use for the given instance
+fn detect_fs_backend(inst_name: &str) -> Result<FsBackendKind> {
+    let layers = Path::new(common::CIEL_INST_DIR)
+        .join(inst_name)
+        .join("layers");
+    // existing instances keep using the backend they were created with
+    if layers.join("diff").exists() || layers.join("tmpfs").exists() {
+        return Ok(FsBackendKind::Overlay);
+    } else if layers.join("snapshot").exists() {
+        return Ok(FsBackendKind::Btrfs);
+    } else if layers.join("clone").exists() {
+        return Ok(FsBackendKind::Zfs);
+    }
+    let fs_type = statfs(".ciel")?.filesystem_type();
+    if fs_type == BTRFS_SUPER_MAGIC {
+        return Ok(FsBackendKind::Btrfs);
+    } else if fs_type == ZFS_SUPER_MAGIC {
+        return Ok(FsBackendKind::Zfs);
+    }
+
+    Ok(FsBackendKind::Overlay)
+}
+
+/// A convenience function for getting the LayerManager of the instance
+/// (using the filesystem backend specified in the configuration, or detected automatically)
+pub(crate) fn get_layer_manager(inst_name: &str) -> Result<Box<dyn LayerManager>> {
+    let config = config::read_config().ok();
+    let rootless = config.as_ref().is_some_and(|c| c.rootless);
+    let overrides = config::read_instance_overrides(inst_name)
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    let tmpfs = overrides.tmpfs;
+    let layers = overrides
+        .layers
+        .unwrap_or_default()
+        .iter()
+        .map(|x| shared_layer_path(x))
+        .collect::<Result<Vec<_>>>()?;
+    let mut kind = config.map(|c| c.fs_backend).unwrap_or_default();
+    if kind == FsBackendKind::Auto {
+        // only overlay (fuse-overlayfs) works without root privileges, with tmpfs,
+        // with the base OS image or with the shared layers
+        kind = if rootless || tmpfs.is_some() || dist_image().is_some() || !layers.is_empty() {
+            FsBackendKind::Overlay
+        } else {
+            detect_fs_backend(inst_name)?
+        };
+    }
+    if kind != FsBackendKind::Overlay && dist_image().is_some() {
+        bail!("The base OS image can only be used with the overlay filesystem backend, please run `ciel unpack-os`.");
+    }
+    let (dist, inst) = (common::CIEL_DIST_DIR, common::CIEL_INST_DIR);
+    let mut man = match kind {
+        FsBackendKind::Btrfs => btrfs::Btrfs::from_inst_dir(dist, inst, inst_name)?,
+        FsBackendKind::Zfs => zfs::Zfs::from_inst_dir(dist, inst, inst_name)?,
+        _ => OverlayFS::from_inst_dir(dist, inst, inst_name)?,
+    };
+    man.set_rootless(rootless)?;
+    man.set_tmpfs(tmpfs.as_ref())?;
+    man.set_extra_layers(&layers)?;
+
+    Ok(man)
+}
+
+/// Path of the shared layer, the layer must exist
+pub fn shared_layer_path(name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains('/') || name.starts_with('.') {
+        bail!("Invalid layer name: {}", name);
+    }
+    let path = Path::new(common::CIEL_LAYERS_DIR).join(name);
+    if !path.is_dir() {
+        bail!("Shared layer {} does not exist.", name);
+    }
+
+    Ok(path)
+}
+
+/// Copy the contents of `from` into `to`, using reflinks whenever possible
+pub(crate) fn copy_tree(from: &Path, to: &Path) -> Result<()> {
+    let status = Command::new("cp")
+        .arg("-a")
+        .arg("--reflink=auto")
+        .arg(from)
+        .arg(to)
+        .status()?;
+    if !status.success() {
+        bail!("Unable to copy {} to {}", from.display(), to.display());
+    }
+
+    Ok(())
+}
+
+/// Un-mount a FUSE filesystem without root privileges
+pub(crate) fn fuse_unmount(target: &Path) -> Result<()> {
+    let fusermount = which::which("fusermount3").or_else(|_| which::which("fusermount"))?;
+    let status = Command::new(fusermount).arg("-uz").arg(target).status()?;
+    if !status.success() {
+        bail!("Unable to un-mount {}: {}", target.display(), status);
+    }
+
+    Ok(())
+}
+
+/// Check if path have all specified prefixes (with order)
+#[inline]
+fn has_prefix(path: &Path, prefixes: &[PathBuf]) -> bool {
+    prefixes
+        .iter()
+        .any(|prefix| path.strip_prefix(prefix).is_ok())
+}
+
+fn load_overlayfs_support() -> Result<()> {
+    if test_overlay_usability().is_err() {
+        Command::new("modprobe")
+            .arg("overlay")
+            .status()
+            .map_err(|e| anyhow!("Unable to load overlay kernel module: {}", e))?;
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn test_overlay_usability() -> Result<()> {
+    let f = fs::File::open("/proc/filesystems")?;
+    let reader = BufReader::new(f);
+    for line in reader.lines() {
+        let line = line?;
+        let mut fs_type = line.splitn(2, '\t');
+        if let Some(fs_type) = fs_type.nth(1) {
+            if fs_type == "overlay" {
+                return Ok(());
+            }
+        }
+    }
+
+    Err(anyhow!("No overlayfs support detected"))
+}
+
+/// Set permission of to according to from
+#[inline]
+fn sync_permission(from: &Path, to: &Path) -> Result<()> {
+    let from_meta = fs::metadata(from)?;
+    let to_meta = fs::metadata(to)?;
+
+    if from_meta.mode() != to_meta.mode() {
+        to_meta.permissions().set_mode(to_meta.mode());
+    }
+
+    Ok(())
+}
+
+#[inline]
+fn overlay_exec_action(action: &Diff, overlay: &OverlayFS) -> Result<()> {
+    match action {
+        Diff::Symlink(path) => {
+            let upper_path = overlay.upper.join(path);
+            let lower_path = overlay.base.join(path);
+            // Replace lower dir with upper
+            fs::rename(upper_path, lower_path)?;
+        }
+        Diff::OverrideDir(path) => {
+            let upper_path = overlay.upper.join(path);
+            let lower_path = overlay.base.join(path);
+            // Replace lower dir with upper
+            if lower_path.is_dir() {
+                // If exists and was not removed already, then remove it
+                fs::remove_dir_all(&lower_path)?;
+            } else if lower_path.is_file() {
+                // If it's a file, then remove it as well
+                fs::remove_file(&lower_path)?;
+            }
+            fs::rename(upper_path, &lower_path)?;
+            // drop the opaque marker left by fuse-overlayfs
+            let marker = lower_path.join(OPAQUE_MARKER);
+            if marker.is_file() {
+                fs::remove_file(marker)?;
+            }
+        }
+        Diff::RenamedDir(from, to) => {
+            // TODO: Implement copy down
+            // Such dir will include diff files, so this
+            // section need more testing
+            let from_path = overlay.base.join(from);
+            let to_path = overlay.base.join(to);
+            // TODO: Merge files from upper to lower
+            // Replace lower dir with upper
+            fs::rename(from_path, to_path)?;
+            // the upper directory now merges with the renamed one
+            xattr::remove(overlay.upper.join(to), "trusted.overlay.redirect").ok();
+        }
+        Diff::NewDir(path) => {
+            let lower_path = overlay.base.join(path);
+            // Construct lower path
+            fs::create_dir_all(lower_path)?;
+        }
+        Diff::ModifiedDir(path) => {
+            // Do nothing, just sync permission
+            let upper_path = overlay.upper.join(path);
+            let lower_path = overlay.base.join(path);
+            sync_permission(&upper_path, &lower_path)?;
+        }
+        Diff::WhiteoutFile(path) => {
+            let lower_path = overlay.base.join(path);
+            if lower_path.is_dir() {
+                fs::remove_dir_all(&lower_path)?;
+            } else if lower_path.is_file() {
+                fs::remove_file(&lower_path)?;
+            }
+            // remove the whiteout in the upper layer
+            fs::remove_file(overlay.upper.join(path))?;
+        }
+        Diff::WhiteoutMarker(path) => {
+            let name = path
+                .file_name()
+                .and_then(|x| x.as_bytes().strip_prefix(WHITEOUT_PREFIX.as_bytes()))
+                .ok_or_else(|| anyhow!("Invalid whiteout marker: {:?}", path))?;
+            let lower_path = overlay
+                .base
+                .join(path.with_file_name(OsStr::from_bytes(name)));
+            if lower_path.is_dir() {
+                fs::remove_dir_all(&lower_path)?;
+            } else if lower_path.is_file() {
+                fs::remove_file(&lower_path)?;
+            }
+            // remove the whiteout marker in the upper layer
+            fs::remove_file(overlay.upper.join(path))?;
+        }
+        Diff::File(path) => {
+            let upper_path = overlay.upper.join(path);
+            let lower_path = overlay.base.join(path);
+            // Move upper file to overwrite the lower
+            fs::rename(upper_path, lower_path)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,476 @@
+//! ZFS clone based layer manager
+
+use adler32::adler32;
+use anyhow::{anyhow, bail, Result};
+use nix::mount::{mount, umount2, MntFlags, MsFlags};
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+use walkdir::WalkDir;
+
+use crate::config::TmpfsConfig;
+
+use super::{copy_tree, is_mounted, Change, LayerManager};
+
+pub(crate) struct Zfs {
+    inst: PathBuf,
+    inst_name: String,
+    base: PathBuf,
+    config: PathBuf,
+    clone: PathBuf,
+    /// Parent dataset holding all the datasets of this workspace
+    root: String,
+}
+
+fn zfs_command<S: AsRef<OsStr>>(args: &[S]) -> Result<String> {
+    let output = Command::new("zfs")
+        .args(args)
+        .output()
+        .map_err(|e| anyhow!("Unable to execute zfs: {}", e))?;
+    if !output.status.success() {
+        bail!(
+            "zfs exited with error: {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[inline]
+fn dataset_exists(name: &str) -> bool {
+    zfs_command(&["list", "-H", "-o", "name", name]).is_ok()
+}
+
+#[inline]
+fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn mountpoint_option(path: &Path) -> std::ffi::OsString {
+    let mut option = std::ffi::OsString::from("mountpoint=");
+    option.push(path);
+
+    option
+}
+
+/// Copy the snapshot into a new independent dataset
+fn send_dataset(snapshot: &str, dataset: &str, mountpoint: &Path) -> Result<()> {
+    let mut send = Command::new("zfs")
+        .args(["send", snapshot])
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Unable to execute zfs: {}", e))?;
+    let stream = send
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("Unable to read from zfs send"))?;
+    let status = Command::new("zfs")
+        .arg("recv")
+        .arg("-o")
+        .arg(mountpoint_option(mountpoint))
+        .arg(dataset)
+        .stdin(stream)
+        .status()?;
+    let send_status = send.wait()?;
+    if !status.success() || !send_status.success() {
+        bail!("Unable to copy {} to {}", snapshot, dataset);
+    }
+
+    Ok(())
+}
+
+/// Name of the temporary snapshot used to save the instance state into a file
+const SAVED_SNAPSHOT: &str = "ciel-saved";
+/// File holding the saved instance dataset
+const SAVED_STREAM: &str = "clone.zfs";
+
+/// Write the snapshot as a stream into the file
+fn send_to_file(snapshot: &str, file: &Path) -> Result<()> {
+    let status = Command::new("zfs")
+        .args(["send", snapshot])
+        .stdout(fs::File::create(file)?)
+        .status()
+        .map_err(|e| anyhow!("Unable to execute zfs: {}", e))?;
+    if !status.success() {
+        bail!("Unable to save {} to {}", snapshot, file.display());
+    }
+
+    Ok(())
+}
+
+/// Create the dataset from the stream written by `send_to_file`
+fn receive_from_file(file: &Path, dataset: &str, mountpoint: &Path) -> Result<()> {
+    let status = Command::new("zfs")
+        .arg("recv")
+        .arg("-o")
+        .arg(mountpoint_option(mountpoint))
+        .arg(dataset)
+        .stdin(fs::File::open(file)?)
+        .status()
+        .map_err(|e| anyhow!("Unable to execute zfs: {}", e))?;
+    if !status.success() {
+        bail!("Unable to restore {} from {}", dataset, file.display());
+    }
+
+    Ok(())
+}
+
+impl Zfs {
+    #[inline]
+    fn dist_dataset(&self) -> String {
+        format!("{}/dist", self.root)
+    }
+
+    #[inline]
+    fn clone_dataset(&self) -> String {
+        format!("{}/{}", self.root, self.inst_name)
+    }
+
+    /// Make sure that the distribution is a ZFS dataset, converting the existing directory if needed
+    fn ensure_dist_dataset(&self) -> Result<()> {
+        if !dataset_exists(&self.root) {
+            zfs_command(&[
+                "create",
+                "-p",
+                "-o",
+                "canmount=off",
+                "-o",
+                "mountpoint=none",
+                &self.root,
+            ])?;
+        }
+        let dist = self.dist_dataset();
+        if dataset_exists(&dist) {
+            return Ok(());
+        }
+        let temp = self.base.with_extension("new");
+        zfs_command(&[
+            OsStr::new("create"),
+            OsStr::new("-o"),
+            &mountpoint_option(&temp),
+            OsStr::new(&dist),
+        ])?;
+        if self.base.is_dir() {
+            copy_tree(&self.base.join("."), &temp)?;
+            fs::remove_dir_all(&self.base)?;
+        }
+        zfs_command(&[
+            OsStr::new("set"),
+            &mountpoint_option(&self.base),
+            OsStr::new(&dist),
+        ])?;
+        fs::remove_dir(&temp).ok();
+
+        Ok(())
+    }
+
+    /// Clone the distribution and apply the configuration layer on top
+    fn create_instance_clone(&self) -> Result<()> {
+        self.ensure_dist_dataset()?;
+        fs::create_dir_all(&self.config)?;
+        let snapshot = format!("{}@{}-{}", self.dist_dataset(), self.inst_name, timestamp());
+        zfs_command(&["snapshot", &snapshot])?;
+        zfs_command(&[
+            OsStr::new("clone"),
+            OsStr::new("-o"),
+            &mountpoint_option(&self.clone),
+            OsStr::new(&snapshot),
+            OsStr::new(&self.clone_dataset()),
+        ])?;
+        copy_tree(&self.config.join("."), &self.clone)?;
+
+        Ok(())
+    }
+
+    /// Destroy the instance clone together with the snapshot it originates from
+    fn destroy_instance_clone(&self) -> Result<()> {
+        let clone = self.clone_dataset();
+        if dataset_exists(&clone) {
+            let origin = zfs_command(&["get", "-H", "-o", "value", "origin", &clone])?;
+            zfs_command(&["destroy", &clone])?;
+            if origin != "-" {
+                zfs_command(&["destroy", &origin]).ok();
+            }
+        }
+        fs::remove_dir(&self.clone).ok();
+        self.cleanup_datasets();
+
+        Ok(())
+    }
+
+    /// Remove the replaced distributions and snapshots no longer used by any instance.
+    /// Datasets still referenced by other instances are kept until those are rolled back.
+    fn cleanup_datasets(&self) {
+        let children =
+            zfs_command(&["list", "-H", "-o", "name", "-d", "1", &self.root]).unwrap_or_default();
+        for child in children.lines() {
+            if child.starts_with(&format!("{}/dist-old-", self.root)) {
+                zfs_command(&["destroy", child]).ok();
+            }
+        }
+        let snapshots = zfs_command(&[
+            "list",
+            "-H",
+            "-t",
+            "snapshot",
+            "-o",
+            "name,clones",
+            "-d",
+            "1",
+            &self.dist_dataset(),
+        ])
+        .unwrap_or_default();
+        for line in snapshots.lines() {
+            if let Some((name, "")) | Some((name, "-")) = line.split_once('\t') {
+                zfs_command(&["destroy", name]).ok();
+            }
+        }
+    }
+}
+
+impl LayerManager for Zfs {
+    fn name() -> String
+    where
+        Self: Sized,
+    {
+        "zfs".to_owned()
+    }
+    // The ZFS layout looks like this:
+    // |- clone: .ciel/container/instances/<inst_name>/layers/clone/ (<pool>/ciel-<hash>/<inst_name>)
+    // |- config: .ciel/container/instances/<inst_name>/layers/local/
+    // ||- base: .ciel/container/dist/ (<pool>/ciel-<hash>/dist)
+    fn from_inst_dir<P: AsRef<Path>>(
+        dist_path: P,
+        inst_path: P,
+        inst_name: P,
+    ) -> Result<Box<dyn LayerManager>>
+    where
+        Self: Sized,
+    {
+        let pwd = std::env::current_dir()?;
+        let dist = pwd.join(dist_path.as_ref());
+        let inst = pwd.join(inst_path.as_ref()).join(inst_name.as_ref());
+        let inst_name = inst_name
+            .as_ref()
+            .to_str()
+            .ok_or_else(|| anyhow!("Instance name is not valid unicode."))?;
+        // datasets of different workspaces on the same pool are separated by $PWD's checksum
+        let parent = zfs_command(&["list", "-H", "-o", "name", ".ciel"])?;
+        let hash = adler32(pwd.as_os_str().as_bytes())?;
+
+        Ok(Box::new(Zfs {
+            inst_name: inst_name.to_owned(),
+            base: dist,
+            config: inst.join("layers/local"),
+            clone: inst.join("layers/clone"),
+            inst,
+            root: format!("{}/ciel-{:x}", parent, hash),
+        }))
+    }
+
+    fn mount(&mut self, to: &Path) -> Result<()> {
+        if !dataset_exists(&self.clone_dataset()) {
+            self.create_instance_clone()?;
+        }
+        mount(
+            Some(&self.clone),
+            to,
+            None::<&str>,
+            MsFlags::MS_BIND,
+            None::<&str>,
+        )?;
+
+        Ok(())
+    }
+
+    fn is_mounted(&self, target: &Path) -> Result<bool> {
+        is_mounted(target, OsStr::new("zfs"))
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        self.destroy_instance_clone()?;
+        self.create_instance_clone()
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        let clone = self.clone_dataset();
+        if !dataset_exists(&clone) {
+            return Ok(());
+        }
+        let dist = self.dist_dataset();
+        let old_dist = format!("{}/dist-old-{}", self.root, timestamp());
+        let old_base = self.base.with_extension("old");
+        // the instance takes over the snapshots, the old distribution becomes its clone
+        // (cloned instances are independent datasets and don't need to be promoted)
+        if zfs_command(&["get", "-H", "-o", "value", "origin", &clone])? != "-" {
+            zfs_command(&["promote", &clone])?;
+        }
+        zfs_command(&[
+            OsStr::new("set"),
+            &mountpoint_option(&old_base),
+            OsStr::new(&dist),
+        ])?;
+        zfs_command(&["rename", &dist, &old_dist])?;
+        zfs_command(&[
+            OsStr::new("set"),
+            &mountpoint_option(&self.base),
+            OsStr::new(&clone),
+        ])?;
+        zfs_command(&["rename", &clone, &dist])?;
+        // the configuration layer belongs to the instance, restore the original files
+        for entry in WalkDir::new(&self.config).min_depth(1) {
+            let entry = entry?;
+            if entry.file_type().is_dir() {
+                continue;
+            }
+            let rel_path = entry.path().strip_prefix(&self.config)?;
+            let new_path = self.base.join(rel_path);
+            let old_path = old_base.join(rel_path);
+            if fs::symlink_metadata(&new_path).is_ok() {
+                fs::remove_file(&new_path)?;
+            }
+            if fs::symlink_metadata(&old_path).is_ok() {
+                copy_tree(&old_path, &new_path)?;
+            }
+        }
+        zfs_command(&["set", "mountpoint=none", &old_dist]).ok();
+        fs::remove_dir(&old_base).ok();
+        self.rollback()
+    }
+
+    fn unmount(&mut self, target: &Path) -> Result<()> {
+        umount2(target, MntFlags::MNT_DETACH)?;
+
+        Ok(())
+    }
+
+    fn get_config_layer(&mut self) -> Result<PathBuf> {
+        Ok(self.config.clone())
+    }
+
+    fn get_base_layer(&mut self) -> Result<PathBuf> {
+        Ok(self.base.clone())
+    }
+
+    fn get_upper_layer(&mut self) -> Result<PathBuf> {
+        bail!("The ZFS backend does not have an upper layer.")
+    }
+
+    fn set_volatile(&mut self, _volatile: bool) -> Result<()> {
+        // clones do not have a volatile mode
+        Ok(())
+    }
+
+    fn set_rootless(&mut self, rootless: bool) -> Result<()> {
+        if rootless {
+            bail!("ZFS filesystem backend does not support rootless mode");
+        }
+
+        Ok(())
+    }
+
+    fn set_extra_layers(&mut self, layers: &[PathBuf]) -> Result<()> {
+        if !layers.is_empty() {
+            bail!("ZFS filesystem backend does not support shared layers");
+        }
+
+        Ok(())
+    }
+
+    fn set_tmpfs(&mut self, tmpfs: Option<&TmpfsConfig>) -> Result<()> {
+        if tmpfs.is_some() {
+            bail!("ZFS filesystem backend does not support tmpfs upper layers");
+        }
+
+        Ok(())
+    }
+
+    fn clone_to(&mut self, inst_name: &str) -> Result<()> {
+        let target = self.inst.with_file_name(inst_name).join("layers");
+        fs::create_dir_all(&target)?;
+        if self.config.is_dir() {
+            copy_tree(&self.config, &target)?;
+        }
+        let source = self.clone_dataset();
+        if !dataset_exists(&source) {
+            return Ok(());
+        }
+        // make an independent copy, so that the source instance can still be rolled back
+        let snapshot_name = format!("clone-{}-{}", inst_name, timestamp());
+        let snapshot = format!("{}@{}", source, snapshot_name);
+        let dataset = format!("{}/{}", self.root, inst_name);
+        zfs_command(&["snapshot", &snapshot])?;
+        let result = send_dataset(&snapshot, &dataset, &target.join("clone"));
+        zfs_command(&["destroy", &snapshot]).ok();
+        zfs_command(&["destroy", &format!("{}@{}", dataset, snapshot_name)]).ok();
+
+        result
+    }
+
+    fn destroy(&mut self) -> Result<()> {
+        self.destroy_instance_clone()?;
+        fs::remove_dir_all(&self.inst)?;
+
+        Ok(())
+    }
+
+    fn save_snapshot(&mut self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        if self.config.is_dir() {
+            copy_tree(&self.config, dir)?;
+        }
+        let clone = self.clone_dataset();
+        if !dataset_exists(&clone) {
+            return Ok(());
+        }
+        // a stream file instead of a dataset snapshot, so that it survives the rollbacks
+        let snapshot = format!("{}@{}", clone, SAVED_SNAPSHOT);
+        zfs_command(&["snapshot", &snapshot])?;
+        let result = send_to_file(&snapshot, &dir.join(SAVED_STREAM));
+        zfs_command(&["destroy", &snapshot]).ok();
+
+        result
+    }
+
+    fn restore_snapshot(&mut self, dir: &Path) -> Result<()> {
+        self.destroy_instance_clone()?;
+        let stream = dir.join(SAVED_STREAM);
+        if stream.is_file() {
+            let clone = self.clone_dataset();
+            receive_from_file(&stream, &clone, &self.clone)?;
+            zfs_command(&["destroy", &format!("{}@{}", clone, SAVED_SNAPSHOT)]).ok();
+        }
+        if self.config.exists() {
+            fs::remove_dir_all(&self.config)?;
+        }
+        fs::create_dir_all(&self.config)?;
+        let saved = dir.join("local");
+        if saved.is_dir() {
+            copy_tree(&saved.join("."), &self.config)?;
+        }
+
+        Ok(())
+    }
+
+    fn delete_snapshot(&mut self, dir: &Path) -> Result<()> {
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    fn list_changes(&mut self) -> Result<Vec<Change>> {
+        bail!("Listing the changes is not supported by the ZFS backend.")
+    }
+
+    fn commit_selected(&mut self, _paths: &[PathBuf]) -> Result<()> {
+        bail!("Selective commit is not supported by the ZFS backend.")
+    }
+}
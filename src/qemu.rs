@@ -0,0 +1,159 @@
+//! QEMU user-mode emulation for running foreign architecture instances
+
+use anyhow::{anyhow, Result};
+use std::{
+    fs,
+    io::Read,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+use which::which;
+
+const BINFMT_MISC_DIR: &str = "/proc/sys/fs/binfmt_misc";
+const BINFMT_CONFIG_DIRS: &[&str] = &["/etc/binfmt.d", "/usr/lib/binfmt.d", "/run/binfmt.d"];
+/// A binary that exists in every AOSC OS rootfs
+const PROBE_BINARY: &str = "usr/bin/bash";
+
+const EM_386: u16 = 3;
+const EM_68K: u16 = 4;
+const EM_MIPS: u16 = 8;
+const EM_PPC: u16 = 20;
+const EM_PPC64: u16 = 21;
+const EM_ARM: u16 = 40;
+const EM_X86_64: u16 = 62;
+const EM_AARCH64: u16 = 183;
+const EM_RISCV: u16 = 243;
+const EM_LOONGARCH: u16 = 258;
+
+/// Determine the QEMU architecture name from the ELF header
+fn elf_qemu_arch(header: &[u8]) -> Option<&'static str> {
+    if header.len() < 20 || &header[..4] != b"\x7fELF" {
+        return None;
+    }
+    let is_64 = header[4] == 2;
+    let little_endian = header[5] == 1;
+    let machine = if little_endian {
+        u16::from_le_bytes([header[18], header[19]])
+    } else {
+        u16::from_be_bytes([header[18], header[19]])
+    };
+    match machine {
+        EM_386 => Some("i386"),
+        EM_68K => Some("m68k"),
+        EM_MIPS if is_64 && little_endian => Some("mips64el"),
+        EM_MIPS if is_64 => Some("mips64"),
+        EM_MIPS if little_endian => Some("mipsel"),
+        EM_MIPS => Some("mips"),
+        EM_PPC => Some("ppc"),
+        EM_PPC64 if little_endian => Some("ppc64le"),
+        EM_PPC64 => Some("ppc64"),
+        EM_ARM => Some("arm"),
+        EM_X86_64 => Some("x86_64"),
+        EM_AARCH64 => Some("aarch64"),
+        EM_RISCV if is_64 => Some("riscv64"),
+        EM_LOONGARCH => Some("loongarch64"),
+        _ => None,
+    }
+}
+
+/// Determine the QEMU architecture name of the host
+fn host_qemu_arch() -> Option<&'static str> {
+    let mut header = [0u8; 20];
+    fs::File::open("/proc/self/exe")
+        .and_then(|mut f| f.read_exact(&mut header))
+        .ok()?;
+
+    elf_qemu_arch(&header)
+}
+
+/// Determine the QEMU architecture name of the rootfs
+fn rootfs_qemu_arch(rootfs: &Path) -> Result<Option<&'static str>> {
+    let mut header = [0u8; 20];
+    // the probe binary may be a symlink pointing to an absolute path
+    let mut probe = rootfs.join(PROBE_BINARY);
+    if let Ok(target) = fs::read_link(&probe) {
+        probe = rootfs.join(target.strip_prefix("/").unwrap_or(&target));
+    }
+    fs::File::open(&probe)?.read_exact(&mut header)?;
+
+    Ok(elf_qemu_arch(&header))
+}
+
+/// Whether the host can run the binaries of the given architecture natively
+fn is_native(host: &str, target: &str) -> bool {
+    host == target || (host == "x86_64" && target == "i386")
+}
+
+/// Find the binfmt_misc registration for the architecture, registering it if needed.
+/// Returns the path of the interpreter.
+fn ensure_binfmt(arch: &str) -> Result<PathBuf> {
+    let entry = Path::new(BINFMT_MISC_DIR).join(format!("qemu-{}", arch));
+    if !entry.exists() {
+        let config = BINFMT_CONFIG_DIRS
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+            .flatten()
+            .filter(|x| {
+                let name = x.file_name().to_string_lossy().to_string();
+                name == format!("qemu-{}.conf", arch) || name == format!("qemu-{}-static.conf", arch)
+            })
+            .find_map(|x| fs::read_to_string(x.path()).ok())
+            .ok_or_else(|| {
+                anyhow!(
+                    "binfmt_misc is not configured for {}, please install qemu-user-static (and its binfmt configuration).",
+                    arch
+                )
+            })?;
+        for line in config.lines().filter(|x| x.starts_with(':')) {
+            fs::write(Path::new(BINFMT_MISC_DIR).join("register"), line)?;
+        }
+    }
+    let registration = fs::read_to_string(&entry)
+        .map_err(|e| anyhow!("Unable to register binfmt_misc for {}: {}", arch, e))?;
+    let interpreter = registration
+        .lines()
+        .find_map(|x| x.strip_prefix("interpreter "))
+        .ok_or_else(|| anyhow!("Unable to determine the interpreter for {}", arch))?;
+
+    Ok(PathBuf::from(interpreter))
+}
+
+/// Set up QEMU user-mode emulation if the rootfs is of a foreign architecture.
+/// The interpreter is copied into the rootfs, so it works regardless of the binfmt_misc flags.
+pub fn setup_emulation(rootfs: &Path) -> Result<Option<&'static str>> {
+    let target = match rootfs_qemu_arch(rootfs)? {
+        Some(target) => target,
+        None => return Ok(None),
+    };
+    match host_qemu_arch() {
+        Some(host) if !is_native(host, target) => (),
+        _ => return Ok(None),
+    }
+    let interpreter = ensure_binfmt(target)?;
+    let source = if interpreter.is_file() {
+        interpreter.clone()
+    } else {
+        which(format!("qemu-{}-static", target))
+            .map_err(|_| anyhow!("qemu-{}-static is not found.", target))?
+    };
+    let dest = rootfs.join(interpreter.strip_prefix("/")?);
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(&source, &dest)?;
+    fs::set_permissions(&dest, fs::Permissions::from_mode(0o755))?;
+
+    Ok(Some(target))
+}
+
+#[test]
+fn test_elf_qemu_arch() {
+    let mut header = [0u8; 20];
+    header[..6].copy_from_slice(b"\x7fELF\x02\x01");
+    header[18..20].copy_from_slice(&EM_RISCV.to_le_bytes());
+    assert_eq!(elf_qemu_arch(&header), Some("riscv64"));
+    header[18..20].copy_from_slice(&EM_PPC64.to_le_bytes());
+    assert_eq!(elf_qemu_arch(&header), Some("ppc64le"));
+    assert_eq!(elf_qemu_arch(b"#!/bin/sh"), None);
+}
@@ -1,17 +1,37 @@
 //! Local repository
 
-use crate::info;
-use anyhow::Result;
+use crate::{
+    config, info,
+    logging::{color_bool, json_output, print_json},
+};
+use anyhow::{anyhow, bail, Result};
 use console::style;
+use lazy_static::lazy_static;
+use serde::Serialize;
 use sha2::{Digest, Sha256};
 use std::io::Write;
-use std::{fs, io, path::Path};
+use std::{fs, io, path::Path, sync::Mutex};
+use tabwriter::TabWriter;
 use time::{format_description::FormatItem, macros::format_description, OffsetDateTime};
 
 mod monitor;
+mod pool;
+mod prune;
 mod scan;
+mod serve;
+mod sign;
 
 pub use monitor::start_monitor;
+pub use prune::{
+    compare_versions, find_prunable_packages, latest_package_versions, prune_packages, PrunePolicy,
+};
+pub use scan::collect_all_packages;
+pub use serve::serve_repo;
+
+lazy_static! {
+    /// Serializes the refresh of the local repository (e.g. when building in parallel)
+    static ref REFRESH_LOCK: Mutex<()> = Mutex::new(());
+}
 
 /// Debian 822 date: "%a, %d %b %Y %H:%M:%S %z"
 const DEB822_DATE: &[FormatItem] = format_description!("[weekday repr:short], [day] [month repr:short] [year] [hour repr:24]:[minute]:[second] [offset_hour sign:mandatory][offset_minute]");
@@ -34,8 +54,21 @@ fn generate_release(path: &Path) -> Result<String> {
 
 /// Refresh the local repository (Update Packages file)
 pub fn refresh_repo(root: &Path) -> Result<()> {
+    let _guard = REFRESH_LOCK
+        .lock()
+        .map_err(|_| anyhow!("Repository refresh lock poisoned"))?;
     let path = root.join("debs");
     fs::create_dir_all(&path)?;
+    let conf = config::read_config().ok();
+    if let Some(retention) = conf.as_ref().map(|c| &c.retention) {
+        let policy = PrunePolicy::from_config(retention);
+        if retention.auto_prune && !policy.is_empty() {
+            let pruned = prune_packages(&path, &policy)?;
+            if !pruned.is_empty() {
+                info!("Pruned {} old package(s).", pruned.len());
+            }
+        }
+    }
     let mut output = fs::File::create(path.join("Packages"))?;
     let entries = scan::collect_all_packages(&path)?;
     info!("Scanning {} packages...", entries.len());
@@ -43,8 +76,98 @@ pub fn refresh_repo(root: &Path) -> Result<()> {
     println!();
 
     let release = generate_release(&path)?;
+    // stale signatures would not match the new Release file
+    for signature in sign::SIGNATURE_FILES {
+        fs::remove_file(path.join(signature)).ok();
+    }
     let mut release_file = fs::File::create(path.join("Release"))?;
     release_file.write_all(release.as_bytes())?;
+    if let Some(c) = conf {
+        sign::sign_release(&path, &c.repo_signing)?;
+        if c.repo_layout.kind == config::RepoLayoutKind::Pool {
+            info!("Generating the pool layout...");
+            pool::generate_pool(root, &c.repo_layout, &c.repo_signing)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Sign the local repository using the configured key
+pub fn sign_repo(root: &Path) -> Result<()> {
+    let c = config::read_config().map_err(|_| anyhow!("Please configure this workspace first!"))?;
+    if c.repo_signing.method == config::SigningMethod::None {
+        bail!("Repository signing is not configured, please set `repo-signing` in the workspace configuration.");
+    }
+    let path = root.join("debs");
+    if !path.join("Release").is_file() {
+        bail!("Repository has not been refreshed yet.");
+    }
+
+    sign::sign_release(&path, &c.repo_signing)
+}
+
+/// An output repository in the workspace
+#[derive(Debug, Serialize)]
+pub struct OutputRepo {
+    pub name: String,
+    pub directory: String,
+    pub packages: usize,
+    /// Whether the Packages index has been generated
+    pub indexed: bool,
+    /// Whether the repository is used by the current build configuration
+    pub current: bool,
+}
+
+/// Find all the output repositories (`OUTPUT` and `OUTPUT-<name>`) in the workspace
+pub fn find_output_repos(current: &str) -> Result<Vec<OutputRepo>> {
+    let mut repos = Vec::new();
+    for entry in fs::read_dir(".")?.flatten() {
+        let directory = entry.file_name().to_string_lossy().to_string();
+        let name = match directory.as_str() {
+            "OUTPUT" => "default",
+            _ => match directory.strip_prefix("OUTPUT-") {
+                Some(name) => name,
+                None => continue,
+            },
+        };
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let debs = entry.path().join("debs");
+        repos.push(OutputRepo {
+            name: name.to_string(),
+            packages: scan::collect_all_packages(&debs).map_or(0, |x| x.len()),
+            indexed: debs.join("Packages").is_file(),
+            current: directory == current,
+            directory,
+        });
+    }
+    repos.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(repos)
+}
+
+/// Print the output repositories in the workspace
+pub fn print_output_repos(current: &str) -> Result<()> {
+    let repos = find_output_repos(current)?;
+    if json_output() {
+        return print_json(&repos);
+    }
+    let mut formatter = TabWriter::new(std::io::stderr());
+    writeln!(&mut formatter, "NAME	PACKAGES	INDEXED	CURRENT	DIRECTORY")?;
+    for repo in repos {
+        writeln!(
+            &mut formatter,
+            "{}	{}	{}	{}	{}",
+            repo.name,
+            repo.packages,
+            color_bool(repo.indexed),
+            color_bool(repo.current),
+            repo.directory
+        )?;
+    }
+    formatter.flush()?;
 
     Ok(())
 }
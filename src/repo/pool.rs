@@ -0,0 +1,221 @@
+//! Debian archive layout (`pool/` and `dists/`) of the local repository,
+//! compatible with the repositories managed by aptly or reprepro
+
+use anyhow::{anyhow, Result};
+use flate2::{write::GzEncoder, Compression};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs::{self, File},
+    io::Write,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+use time::OffsetDateTime;
+use walkdir::WalkDir;
+
+use crate::config::{RepoLayoutConfig, RepoSigningConfig};
+
+use super::{
+    scan::{collect_all_packages, read_control, scan_single_deb_simple, sha256sum},
+    sign, DEB822_DATE,
+};
+
+const POOL_DIR: &str = "pool";
+const DISTS_DIR: &str = "dists";
+const ARCH_ALL: &str = "all";
+
+/// Read the value of the field from the control file
+fn control_field<'a>(control: &'a str, name: &str) -> Option<&'a str> {
+    control.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        if key.eq_ignore_ascii_case(name) {
+            Some(value.trim())
+        } else {
+            None
+        }
+    })
+}
+
+/// Directory of the source package in the pool (e.g. `pool/main/libf/libfoo`)
+fn pool_directory(component: &str, source: &str) -> PathBuf {
+    let prefix = if source.starts_with("lib") && source.len() > 3 {
+        &source[..4]
+    } else {
+        &source[..1.min(source.len())]
+    };
+
+    Path::new(POOL_DIR)
+        .join(component)
+        .join(prefix)
+        .join(source)
+}
+
+/// Hard link the file into the pool, falling back to copying
+fn link_into_pool(source: &Path, target: &Path) -> Result<()> {
+    if let (Ok(a), Ok(b)) = (fs::metadata(source), fs::metadata(target)) {
+        if a.dev() == b.dev() && a.ino() == b.ino() {
+            return Ok(());
+        }
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::remove_file(target).ok();
+    if fs::hard_link(source, target).is_err() {
+        fs::copy(source, target)?;
+    }
+
+    Ok(())
+}
+
+/// Write the Packages index (and its gzip-compressed variant) of the architecture
+fn write_index(directory: &Path, stanzas: &[&Vec<u8>]) -> Result<()> {
+    fs::create_dir_all(directory)?;
+    let mut index = File::create(directory.join("Packages"))?;
+    let mut compressed = GzEncoder::new(
+        File::create(directory.join("Packages.gz"))?,
+        Compression::default(),
+    );
+    for stanza in stanzas {
+        index.write_all(stanza)?;
+        compressed.write_all(stanza)?;
+    }
+    compressed.finish()?;
+
+    Ok(())
+}
+
+/// Generate the Release file of the suite, listing the checksums of the indices
+fn generate_suite_release(
+    suite_dir: &Path,
+    layout: &RepoLayoutConfig,
+    architectures: &BTreeSet<String>,
+) -> Result<String> {
+    let mut checksums = String::new();
+    for entry in WalkDir::new(suite_dir)
+        .into_iter()
+        .filter_map(|x| x.ok())
+        .filter(|x| x.file_type().is_file())
+    {
+        let name = entry.file_name().to_string_lossy();
+        if name != "Packages" && name != "Packages.gz" {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(suite_dir)?;
+        checksums.push_str(&format!(
+            " {} {} {}\n",
+            sha256sum(File::open(entry.path())?)?,
+            entry.metadata()?.len(),
+            relative.display()
+        ));
+    }
+
+    Ok(format!(
+        "Origin: Ciel\nLabel: Ciel\nSuite: {suite}\nCodename: {suite}\nDate: {}\nArchitectures: {}\nComponents: {}\nSHA256:\n{}",
+        OffsetDateTime::now_utc().format(&DEB822_DATE)?,
+        architectures
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" "),
+        layout.component,
+        checksums,
+        suite = layout.suite
+    ))
+}
+
+/// Generate the `pool/` and `dists/` directories from the packages in `debs/`,
+/// so the repository can be synchronized to the main repository infrastructure directly
+pub fn generate_pool(
+    root: &Path,
+    layout: &RepoLayoutConfig,
+    signing: &RepoSigningConfig,
+) -> Result<()> {
+    let debs = root.join("debs");
+    let mut stanzas: BTreeMap<String, Vec<Vec<u8>>> = BTreeMap::new();
+    let mut pooled = HashSet::new();
+    for entry in collect_all_packages(&debs)? {
+        let control = read_control(entry.path())?;
+        let control = String::from_utf8_lossy(&control);
+        let package = control_field(&control, "Package")
+            .ok_or_else(|| anyhow!("{} has no package name", entry.path().display()))?;
+        let arch = control_field(&control, "Architecture").unwrap_or(ARCH_ALL);
+        // the source field may contain a version (e.g. `foo (1.0)`)
+        let source = control_field(&control, "Source")
+            .and_then(|x| x.split_whitespace().next())
+            .unwrap_or(package);
+        let relative = pool_directory(&layout.component, source).join(entry.file_name());
+        let target = root.join(&relative);
+        link_into_pool(entry.path(), &target)?;
+        stanzas
+            .entry(arch.to_string())
+            .or_default()
+            .push(scan_single_deb_simple(target.as_path(), root)?);
+        pooled.insert(target);
+    }
+    // remove the packages no longer in the repository
+    for entry in WalkDir::new(root.join(POOL_DIR))
+        .into_iter()
+        .filter_map(|x| x.ok())
+        .filter(|x| x.file_type().is_file())
+    {
+        if !pooled.contains(entry.path()) {
+            fs::remove_file(entry.path())?;
+        }
+    }
+
+    let suite_dir = root.join(DISTS_DIR).join(&layout.suite);
+    if suite_dir.exists() {
+        fs::remove_dir_all(&suite_dir)?;
+    }
+    let mut architectures = stanzas
+        .keys()
+        .filter(|x| *x != ARCH_ALL)
+        .cloned()
+        .collect::<BTreeSet<_>>();
+    if architectures.is_empty() {
+        architectures.insert(ARCH_ALL.to_string());
+    }
+    let arch_all = stanzas.get(ARCH_ALL);
+    for arch in architectures.iter() {
+        // architecture-independent packages are listed in every architecture
+        let mut index = stanzas
+            .get(arch)
+            .map(|x| x.iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+        if arch != ARCH_ALL {
+            index.extend(arch_all.into_iter().flatten());
+        }
+        write_index(
+            &suite_dir
+                .join(&layout.component)
+                .join(format!("binary-{}", arch)),
+            &index,
+        )?;
+    }
+    let release = generate_suite_release(&suite_dir, layout, &architectures)?;
+    fs::write(suite_dir.join("Release"), release)?;
+    sign::sign_release(&suite_dir, signing)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_pool_directory() {
+    assert_eq!(
+        pool_directory("main", "libfoo"),
+        Path::new("pool/main/libf/libfoo")
+    );
+    assert_eq!(
+        pool_directory("main", "bash"),
+        Path::new("pool/main/b/bash")
+    );
+}
+
+#[test]
+fn test_control_field() {
+    let control = "Package: foo\nSource: bar (1.0)\nArchitecture: amd64\n";
+    assert_eq!(control_field(control, "Package"), Some("foo"));
+    assert_eq!(control_field(control, "architecture"), Some("amd64"));
+    assert_eq!(control_field(control, "Depends"), None);
+}
@@ -0,0 +1,248 @@
+//! Retention policies for the packages in the local repository
+
+use anyhow::Result;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::config::RetentionConfig;
+
+use super::scan::collect_all_packages;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Which packages to remove from the repository
+#[derive(Debug, Clone, Default)]
+pub struct PrunePolicy {
+    /// Number of versions to keep for each package
+    pub keep_versions: Option<usize>,
+    /// Remove the packages older than this (the latest version is always kept)
+    pub max_age: Option<Duration>,
+    /// Keep only the latest version of the debug symbol packages
+    pub dbg_latest_only: bool,
+}
+
+impl PrunePolicy {
+    pub fn from_config(config: &RetentionConfig) -> Self {
+        Self {
+            keep_versions: config.keep_versions,
+            max_age: config
+                .max_age_days
+                .map(|x| Duration::from_secs(x * SECONDS_PER_DAY)),
+            dbg_latest_only: config.dbg_latest_only,
+        }
+    }
+
+    /// Whether the policy does not remove anything
+    pub fn is_empty(&self) -> bool {
+        self.keep_versions.is_none() && self.max_age.is_none() && !self.dbg_latest_only
+    }
+}
+
+/// A .deb file in the repository
+struct DebEntry {
+    path: PathBuf,
+    name: String,
+    version: String,
+    modified: SystemTime,
+}
+
+/// Parse the name, version and architecture from `<name>_<version>_<arch>.deb`
+fn parse_deb_name(file_name: &str) -> Option<(String, String, String)> {
+    let stem = file_name.strip_suffix(".deb")?;
+    let mut parts = stem.splitn(3, '_');
+    let name = parts.next()?;
+    let version = parts.next()?;
+    let arch = parts.next()?;
+
+    // epochs are escaped in the file names
+    Some((
+        name.to_string(),
+        version.replace("%3a", ":").replace("%3A", ":"),
+        arch.to_string(),
+    ))
+}
+
+/// Order of a character in a Debian version string
+fn char_order(c: Option<u8>) -> i32 {
+    match c {
+        Some(b'~') => -1,
+        None => 0,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => c as i32,
+        Some(c) => c as i32 + 256,
+    }
+}
+
+/// Compare the upstream version or revision part (`verrevcmp` in dpkg)
+fn compare_fragment(a: &[u8], b: &[u8]) -> Ordering {
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        // non-digit prefix
+        while (i < a.len() && !a[i].is_ascii_digit()) || (j < b.len() && !b[j].is_ascii_digit()) {
+            let ac = char_order(a.get(i).copied().filter(|x| !x.is_ascii_digit()));
+            let bc = char_order(b.get(j).copied().filter(|x| !x.is_ascii_digit()));
+            if ac != bc {
+                return ac.cmp(&bc);
+            }
+            if i < a.len() && !a[i].is_ascii_digit() {
+                i += 1;
+            }
+            if j < b.len() && !b[j].is_ascii_digit() {
+                j += 1;
+            }
+        }
+        // numeric part
+        while i < a.len() && a[i] == b'0' {
+            i += 1;
+        }
+        while j < b.len() && b[j] == b'0' {
+            j += 1;
+        }
+        let mut first_diff = Ordering::Equal;
+        while i < a.len() && a[i].is_ascii_digit() && j < b.len() && b[j].is_ascii_digit() {
+            if first_diff == Ordering::Equal {
+                first_diff = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if i < a.len() && a[i].is_ascii_digit() {
+            return Ordering::Greater;
+        }
+        if j < b.len() && b[j].is_ascii_digit() {
+            return Ordering::Less;
+        }
+        if first_diff != Ordering::Equal {
+            return first_diff;
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// Compare two Debian package versions (`[epoch:]upstream[-revision]`)
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let split = |v: &str| -> (u64, String, String) {
+        let (epoch, rest) = match v.split_once(':') {
+            Some((epoch, rest)) => (epoch.parse().unwrap_or(0), rest),
+            None => (0, v),
+        };
+        let (upstream, revision) = rest.rsplit_once('-').unwrap_or((rest, ""));
+        (epoch, upstream.to_string(), revision.to_string())
+    };
+    let (a_epoch, a_upstream, a_revision) = split(a);
+    let (b_epoch, b_upstream, b_revision) = split(b);
+
+    a_epoch
+        .cmp(&b_epoch)
+        .then_with(|| compare_fragment(a_upstream.as_bytes(), b_upstream.as_bytes()))
+        .then_with(|| compare_fragment(a_revision.as_bytes(), b_revision.as_bytes()))
+}
+
+/// Latest version of each package (of any architecture) in the repository
+pub fn latest_package_versions(repo: &Path) -> Result<HashMap<String, String>> {
+    let mut latest: HashMap<String, String> = HashMap::new();
+    for entry in collect_all_packages(repo)? {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let (name, version, _) = match parse_deb_name(&file_name) {
+            Some(parsed) => parsed,
+            None => continue,
+        };
+        match latest.get(&name) {
+            Some(current) if compare_versions(current, &version) != Ordering::Less => (),
+            _ => {
+                latest.insert(name, version);
+            }
+        }
+    }
+
+    Ok(latest)
+}
+
+/// Determine the .deb files in the repository to be removed according to the policy
+pub fn find_prunable_packages(repo: &Path, policy: &PrunePolicy) -> Result<Vec<PathBuf>> {
+    let mut groups: HashMap<(String, String), Vec<DebEntry>> = HashMap::new();
+    for entry in collect_all_packages(repo)? {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let (name, version, arch) = match parse_deb_name(&file_name) {
+            Some(parsed) => parsed,
+            None => continue,
+        };
+        let modified = entry.metadata()?.modified()?;
+        groups
+            .entry((name.clone(), arch))
+            .or_default()
+            .push(DebEntry {
+                path: entry.into_path(),
+                name,
+                version,
+                modified,
+            });
+    }
+    let mut prunable = Vec::new();
+    for debs in groups.values_mut() {
+        // newest first
+        debs.sort_by(|a, b| compare_versions(&b.version, &a.version));
+        let latest = &debs[0];
+        let keep = if policy.dbg_latest_only && latest.name.ends_with("-dbg") {
+            Some(1)
+        } else {
+            policy.keep_versions.map(|x| x.max(1))
+        };
+        for (i, deb) in debs.iter().enumerate() {
+            // the latest version is always kept
+            if i == 0 || deb.version == latest.version {
+                continue;
+            }
+            let too_many = keep.is_some_and(|x| i >= x);
+            let too_old = policy
+                .max_age
+                .is_some_and(|max_age| deb.modified.elapsed().is_ok_and(|x| x > max_age));
+            if too_many || too_old {
+                prunable.push(deb.path.clone());
+            }
+        }
+    }
+    prunable.sort();
+
+    Ok(prunable)
+}
+
+/// Remove the .deb files according to the policy, returns the removed files.
+/// The repository needs to be refreshed afterwards.
+pub fn prune_packages(repo: &Path, policy: &PrunePolicy) -> Result<Vec<PathBuf>> {
+    let prunable = find_prunable_packages(repo, policy)?;
+    for path in prunable.iter() {
+        fs::remove_file(path)?;
+    }
+
+    Ok(prunable)
+}
+
+#[test]
+fn test_compare_versions() {
+    assert_eq!(compare_versions("1.2.3-1", "1.2.3-1"), Ordering::Equal);
+    assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+    assert_eq!(compare_versions("1.0~rc1", "1.0"), Ordering::Less);
+    assert_eq!(compare_versions("1:0.1", "2.0"), Ordering::Greater);
+    assert_eq!(compare_versions("2.0-2", "2.0-10"), Ordering::Less);
+    assert_eq!(compare_versions("1.0a", "1.0"), Ordering::Greater);
+}
+
+#[test]
+fn test_parse_deb_name() {
+    assert_eq!(
+        parse_deb_name("openssl_1%3a3.1.0-1_amd64.deb"),
+        Some((
+            "openssl".to_string(),
+            "1:3.1.0-1".to_string(),
+            "amd64".to_string()
+        ))
+    );
+    assert_eq!(parse_deb_name("Packages"), None);
+}
@@ -74,7 +74,12 @@ fn open_deb_simple<R: Read>(reader: R) -> Result<Vec<u8>> {
     Err(anyhow!("data archive not found or format unsupported"))
 }
 
-fn scan_single_deb_simple<P: AsRef<Path>>(path: P, root: P) -> Result<Vec<u8>> {
+/// Read the control file of the .deb file
+pub(super) fn read_control(path: &Path) -> Result<Vec<u8>> {
+    open_deb_simple(File::open(path)?)
+}
+
+pub(super) fn scan_single_deb_simple<P: AsRef<Path>>(path: P, root: P) -> Result<Vec<u8>> {
     let mut f = File::open(path.as_ref())?;
     let sha256 = sha256sum(&mut f)?;
     let actual_size = f.stream_position()?;
@@ -0,0 +1,172 @@
+use anyhow::{anyhow, Result};
+use console::style;
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+    thread,
+};
+use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
+
+use crate::{config, error, info};
+
+/// Decode the percent-encoded URL path (e.g. `%3a` used by APT for the epoch separator)
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Map the request URL to a path inside the repository, refusing to escape the repository
+fn resolve_path(root: &Path, url: &str) -> Option<PathBuf> {
+    let path = percent_decode(url.split(['?', '#']).next().unwrap_or_default());
+    let mut resolved = root.to_path_buf();
+    for component in Path::new(&path).components() {
+        match component {
+            Component::Normal(name) => resolved.push(name),
+            Component::RootDir | Component::CurDir => (),
+            _ => return None,
+        }
+    }
+
+    Some(resolved)
+}
+
+/// Escape the special characters of HTML
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn directory_listing(path: &Path, url: &str) -> Result<String> {
+    let mut entries = fs::read_dir(path)?
+        .filter_map(|x| x.ok())
+        .map(|x| {
+            let mut name = x.file_name().to_string_lossy().to_string();
+            if x.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                name.push('/');
+            }
+            name
+        })
+        .collect::<Vec<_>>();
+    entries.sort();
+    let base = html_escape(url.trim_end_matches('/'));
+    let mut html = format!(
+        "<html><head><title>Index of {0}/</title></head><body><h1>Index of {0}/</h1><ul>",
+        base
+    );
+    for entry in entries.iter().map(|x| html_escape(x)) {
+        html.push_str(&format!(
+            "<li><a href=\"{}/{}\">{}</a></li>",
+            base, entry, entry
+        ));
+    }
+    html.push_str("</ul></body></html>");
+
+    Ok(html)
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|x| x.to_str()) {
+        Some("deb") => "application/vnd.debian.binary-package",
+        Some("gpg") | Some("asc") => "application/pgp-signature",
+        _ => "text/plain; charset=utf-8",
+    }
+}
+
+fn handle_request(root: &Path, request: Request) -> Result<()> {
+    let method = request.method().clone();
+    if method != Method::Get && method != Method::Head {
+        return Ok(request.respond(Response::empty(StatusCode(405)))?);
+    }
+    let url = request.url().to_string();
+    let path = match resolve_path(root, &url) {
+        Some(path) if path.exists() => path,
+        _ => return Ok(request.respond(Response::empty(StatusCode(404)))?),
+    };
+    if path.is_dir() {
+        let listing = directory_listing(&path, &url)?;
+        let header = Header::from_bytes("Content-Type", "text/html; charset=utf-8")
+            .map_err(|_| anyhow!("Invalid header"))?;
+        return Ok(request.respond(Response::from_string(listing).with_header(header))?);
+    }
+    let header = Header::from_bytes("Content-Type", content_type(&path))
+        .map_err(|_| anyhow!("Invalid header"))?;
+    // tiny_http omits the body for HEAD requests by itself
+    let file = fs::File::open(&path)?;
+
+    Ok(request.respond(Response::from_file(file).with_header(header))?)
+}
+
+/// Serve the local repository over HTTP
+pub fn serve_repo(root: &Path, bind: &str, port: u16) -> Result<()> {
+    super::refresh_repo(root)?;
+    let root = root.join("debs");
+    let server = Server::http((bind, port))
+        .map_err(|e| anyhow!("Unable to listen on {}:{}: {}", bind, port, e))?;
+    let signed =
+        config::read_config().is_ok_and(|c| c.repo_signing.method != config::SigningMethod::None);
+    info!(
+        "Serving {} at http://{}:{}/ (press Ctrl-C to stop)",
+        root.display(),
+        bind,
+        port
+    );
+    info!(
+        "Add the following line to sources.list on the client machines:\n\tdeb {}http://<this host>:{}/ /",
+        if signed { "" } else { "[trusted=yes] " },
+        port
+    );
+    for request in server.incoming_requests() {
+        let root = root.clone();
+        thread::spawn(move || {
+            if let Err(e) = handle_request(&root, request) {
+                error!("Failed to serve the request: {}", e);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_resolve_path() {
+    let root = Path::new("/srv/debs");
+    assert_eq!(
+        resolve_path(root, "/amd64/foo_1%3a1.0_amd64.deb?x=1"),
+        Some(PathBuf::from("/srv/debs/amd64/foo_1:1.0_amd64.deb"))
+    );
+    assert_eq!(resolve_path(root, "/../etc/passwd"), None);
+}
+
+#[test]
+fn test_html_escape() {
+    assert_eq!(
+        html_escape("<a href=\"x\">Tom & Jerry's</a>"),
+        "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+    );
+}
@@ -0,0 +1,83 @@
+use anyhow::{anyhow, bail, Result};
+use std::{path::Path, process::Command};
+
+use crate::config::{RepoSigningConfig, SigningMethod};
+
+/// Files generated when signing the repository
+pub const SIGNATURE_FILES: &[&str] = &["InRelease", "Release.gpg", "Release.minisig"];
+/// Public key exported along with the signed repository (GPG only)
+const PUBLIC_KEY_FILE: &str = "ciel-local.asc";
+
+fn run_signer(command: &mut Command) -> Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let output = command
+        .output()
+        .map_err(|e| anyhow!("Unable to execute {}: {}", program, e))?;
+    if !output.status.success() {
+        bail!(
+            "{} exited with error: {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+#[inline]
+fn gpg_command(key: &str) -> Command {
+    let mut command = Command::new("gpg");
+    command.args(["--batch", "--yes", "--local-user", key]);
+
+    command
+}
+
+fn sign_gpg(path: &Path, key: &str) -> Result<()> {
+    let release = path.join("Release");
+    run_signer(
+        gpg_command(key)
+            .args(["--clearsign", "--output"])
+            .arg(path.join("InRelease"))
+            .arg(&release),
+    )?;
+    run_signer(
+        gpg_command(key)
+            .args(["--armor", "--detach-sign", "--output"])
+            .arg(path.join("Release.gpg"))
+            .arg(&release),
+    )?;
+    // clients can import the key instead of using `trusted=yes`
+    run_signer(
+        Command::new("gpg")
+            .args(["--batch", "--yes", "--armor", "--export", "--output"])
+            .arg(path.join(PUBLIC_KEY_FILE))
+            .arg(key),
+    )
+}
+
+fn sign_minisign(path: &Path, key: &str) -> Result<()> {
+    run_signer(
+        Command::new("minisign")
+            .args(["-S", "-s", key, "-m"])
+            .arg(path.join("Release"))
+            .arg("-x")
+            .arg(path.join("Release.minisig")),
+    )
+}
+
+/// Sign the Release file in the repository using the configured method
+pub fn sign_release(path: &Path, config: &RepoSigningConfig) -> Result<()> {
+    if config.method == SigningMethod::None {
+        return Ok(());
+    }
+    let key = config
+        .key
+        .as_deref()
+        .ok_or_else(|| anyhow!("No signing key is configured for the local repository."))?;
+    match config.method {
+        SigningMethod::None => Ok(()),
+        SigningMethod::Gpg => sign_gpg(path, key),
+        SigningMethod::Minisign => sign_minisign(path, key),
+    }
+}
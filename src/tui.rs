@@ -0,0 +1,284 @@
+//! Interactive dashboard showing the instances and the builds in progress
+
+use anyhow::Result;
+use console::{style, Key, Term};
+use indicatif::HumanBytes;
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+use walkdir::WalkDir;
+
+use crate::{
+    actions,
+    common::{ensure_interactive, CIEL_INST_DIR},
+    machine::{self, CielInstance},
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+/// Computing the disk usage is expensive, so it is refreshed less frequently
+const DISK_USAGE_INTERVAL: Duration = Duration::from_secs(10);
+/// Builds whose logs have not been updated for this long are considered interrupted
+const ACTIVE_BUILD_WINDOW: Duration = Duration::from_secs(600);
+const LOG_TAIL_LINES: usize = 10;
+const LOG_TAIL_BYTES: u64 = 16 * 1024;
+const KEY_HELP: &str = "q: quit  ↑/↓: select  s: start  t: stop  d: down  m: mount  r: rollback";
+
+#[derive(Default)]
+struct Dashboard {
+    instances: Vec<CielInstance>,
+    /// Size of the upper layer of each instance
+    disk_usage: Vec<(String, Option<u64>)>,
+    disk_usage_updated: Option<Instant>,
+    builds: Vec<PathBuf>,
+    selected: usize,
+    /// Rollback of the selected instance waiting for confirmation
+    pending_rollback: bool,
+    message: String,
+}
+
+/// Total size of the files in the upper layer of the instance (overlay backend only)
+fn upper_dir_usage(instance: &str) -> Option<u64> {
+    let upper = Path::new(CIEL_INST_DIR).join(instance).join("layers/diff");
+    if !upper.is_dir() {
+        return None;
+    }
+
+    Some(
+        WalkDir::new(upper)
+            .into_iter()
+            .filter_map(|x| x.ok())
+            .filter_map(|x| x.metadata().ok())
+            .filter(|x| x.is_file())
+            .map(|x| x.len())
+            .sum(),
+    )
+}
+
+/// Read the last few lines of the log
+fn tail_log(path: &Path, lines: usize) -> Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    file.seek(SeekFrom::Start(size.saturating_sub(LOG_TAIL_BYTES)))?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+    let content = String::from_utf8_lossy(&buffer);
+    let mut tail = content
+        .lines()
+        .rev()
+        // progress bars rewrite the same line using carriage returns
+        .map(|x| x.rsplit('\r').next().unwrap_or_default().to_string())
+        .take(lines)
+        .collect::<Vec<_>>();
+    tail.reverse();
+
+    Ok(tail)
+}
+
+/// Truncate the line to fit in the terminal
+fn fit(line: &str, width: usize) -> String {
+    console::truncate_str(line, width, "…").to_string()
+}
+
+impl Dashboard {
+    fn refresh(&mut self) -> Result<()> {
+        self.instances = machine::list_instances()?;
+        self.selected = self.selected.min(self.instances.len().saturating_sub(1));
+        self.builds = actions::active_package_logs(ACTIVE_BUILD_WINDOW)?;
+        if self
+            .disk_usage_updated
+            .is_none_or(|x| x.elapsed() >= DISK_USAGE_INTERVAL)
+        {
+            self.disk_usage = self
+                .instances
+                .iter()
+                .map(|x| (x.name.clone(), upper_dir_usage(&x.name)))
+                .collect();
+            self.disk_usage_updated = Some(Instant::now());
+        }
+
+        Ok(())
+    }
+
+    fn render(&self, term: &Term) -> Result<()> {
+        let (rows, columns) = term.size();
+        let width = columns as usize;
+        let mut lines = vec![
+            style("Ciel Dashboard").bold().to_string(),
+            style(KEY_HELP).dim().to_string(),
+            String::new(),
+        ];
+        let name_width = self
+            .instances
+            .iter()
+            .map(|x| x.name.len())
+            .max()
+            .unwrap_or_default()
+            .max(4);
+        lines.push(
+            style(format!(
+                "  {:name_width$}  {:7}  {:7}  {:6}  UPPERDIR",
+                "NAME",
+                "MOUNTED",
+                "RUNNING",
+                "BOOTED",
+                name_width = name_width
+            ))
+            .bold()
+            .to_string(),
+        );
+        let yes_no = |x: bool| if x { "Yes" } else { "No" };
+        for (i, instance) in self.instances.iter().enumerate() {
+            let usage = self
+                .disk_usage
+                .iter()
+                .find(|x| x.0 == instance.name)
+                .and_then(|x| x.1)
+                .map(|x| HumanBytes(x).to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let row = format!(
+                "{} {:name_width$}  {:7}  {:7}  {:6}  {}",
+                if i == self.selected { ">" } else { " " },
+                instance.name,
+                yes_no(instance.mounted),
+                yes_no(instance.running),
+                instance.booted.map_or("-", yes_no),
+                usage,
+                name_width = name_width
+            );
+            if i == self.selected {
+                lines.push(style(fit(&row, width)).reverse().to_string());
+            } else {
+                lines.push(fit(&row, width));
+            }
+        }
+        if self.instances.is_empty() {
+            lines.push(style("  (no instances)").dim().to_string());
+        }
+        lines.push(String::new());
+        lines.push(style("Builds in progress").bold().to_string());
+        if self.builds.is_empty() {
+            lines.push(style("  (none)").dim().to_string());
+        }
+        for log in self.builds.iter() {
+            let name = log.file_stem().unwrap_or_default().to_string_lossy();
+            lines.push(fit(&format!("  {}", name), width));
+        }
+        // only the most recently updated build is followed
+        if let Some(log) = self.builds.first() {
+            lines.push(String::new());
+            let tail_lines = (rows as usize)
+                .saturating_sub(lines.len() + 3)
+                .min(LOG_TAIL_LINES);
+            for line in tail_log(log, tail_lines).unwrap_or_default() {
+                lines.push(style(fit(&line, width)).dim().to_string());
+            }
+        }
+        lines.push(String::new());
+        lines.push(fit(&self.message, width));
+        term.clear_screen()?;
+        term.write_str(&lines.join("\n"))?;
+
+        Ok(())
+    }
+
+    /// Run the action on the selected instance, showing the result in the status line
+    fn run_action<F: FnOnce(&str) -> Result<()>>(
+        &mut self,
+        term: &Term,
+        verb: &str,
+        action: F,
+    ) -> Result<()> {
+        let instance = match self.instances.get(self.selected) {
+            Some(instance) => instance.name.clone(),
+            None => return Ok(()),
+        };
+        self.message = format!("{} {}...", verb, instance);
+        self.render(term)?;
+        self.message = match action(&instance) {
+            Ok(()) => format!("{} {}: done.", verb, instance),
+            Err(e) => style(format!("{} {}: {}", verb, instance, e))
+                .red()
+                .to_string(),
+        };
+
+        Ok(())
+    }
+
+    /// Handle the key press, returns false if the user wants to quit
+    fn handle_key(&mut self, term: &Term, key: Key) -> Result<bool> {
+        if self.pending_rollback {
+            self.pending_rollback = false;
+            if key == Key::Char('y') {
+                self.run_action(term, "Rolling back", actions::rollback_container)?;
+            } else {
+                self.message = "Rollback cancelled.".to_string();
+            }
+            return Ok(true);
+        }
+        match key {
+            Key::Char('q') | Key::Escape => return Ok(false),
+            Key::ArrowUp | Key::Char('k') => self.selected = self.selected.saturating_sub(1),
+            Key::ArrowDown | Key::Char('j') => {
+                self.selected = (self.selected + 1).min(self.instances.len().saturating_sub(1))
+            }
+            Key::Char('s') => self.run_action(term, "Starting", |x| {
+                actions::start_container(x).map(|_| ())
+            })?,
+            Key::Char('t') => self.run_action(term, "Stopping", actions::stop_container)?,
+            Key::Char('d') => self.run_action(term, "Shutting down", actions::container_down)?,
+            Key::Char('m') => self.run_action(term, "Mounting", actions::mount_fs)?,
+            Key::Char('r') if !self.instances.is_empty() => {
+                self.pending_rollback = true;
+                self.message =
+                    style("Roll back the selected instance? All changes will be lost. [y/N]")
+                        .yellow()
+                        .to_string();
+            }
+            _ => (),
+        }
+
+        Ok(true)
+    }
+}
+
+/// Show the interactive dashboard until the user quits
+pub fn run_dashboard() -> Result<()> {
+    ensure_interactive("the dashboard requires an interactive terminal")?;
+    let term = Term::stdout();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let term = Term::stdout();
+        // read_key fails when Ctrl-C is pressed, which closes the channel
+        while let Ok(key) = term.read_key() {
+            if sender.send(key).is_err() {
+                break;
+            }
+        }
+    });
+    let mut dashboard = Dashboard::default();
+    term.hide_cursor()?;
+    let result = (|| -> Result<()> {
+        loop {
+            dashboard.refresh()?;
+            dashboard.render(&term)?;
+            match receiver.recv_timeout(REFRESH_INTERVAL) {
+                Ok(key) => {
+                    if !dashboard.handle_key(&term, key)? {
+                        return Ok(());
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+    })();
+    term.clear_screen()?;
+    term.show_cursor()?;
+
+    result
+}